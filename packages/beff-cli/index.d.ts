@@ -179,6 +179,28 @@ export interface JSONSchema7 {
 
 export type Header<T> = T;
 export type StringFormat<Tag extends string> = string & { __customType: Tag };
+export type NumberFormat<Tag extends string> = number & { __customNumberType: Tag };
+
+// `V` is used by the parsers when the property is missing
+export type Default<T, V extends T> = T;
+
+// `T` on the wire, a distinct type at compile time
+export type Brand<T, B extends string> = T & { readonly __brand: B };
+
+// Codecs, parsed from their ISO 8601 strings
+export type PlainDate = { year: number; month: number; day: number };
+export type PlainTime = { hour: number; minute: number; second: number };
+export type IsoDuration = {
+  years: number;
+  months: number;
+  weeks: number;
+  days: number;
+  hours: number;
+  minutes: number;
+  seconds: number;
+};
+// bytes like `Uint8Array`, encoded with the url safe alphabet and no padding
+export type Base64Url = Uint8Array;
 
 export type RegularDecodeError = {
   message: string;
@@ -193,8 +215,11 @@ export type UnionDecodeError = {
 };
 export type DecodeError = RegularDecodeError | UnionDecodeError;
 
+export type UnknownKeys = "strict" | "strip" | "passthrough";
+
 export type ParseOptions = {
   disallowExtraProperties?: boolean;
+  unknownKeys?: UnknownKeys;
 };
 
 export type BeffParser<T> = {
@@ -203,6 +228,11 @@ export type BeffParser<T> = {
     input: any,
     options?: ParseOptions
   ) => { success: true; data: T } | { success: false; errors: DecodeError[] };
+  encode: (value: T, options?: ParseOptions) => unknown;
+  safeEncode: (
+    value: T,
+    options?: ParseOptions
+  ) => { success: true; data: unknown } | { success: false; errors: DecodeError[] };
   zod: () => ZodType<T>;
 };
 type Parsers<T> = {
@@ -212,7 +242,11 @@ type Parsers<T> = {
 export type TagOfFormat<T extends StringFormat<string>> = T extends StringFormat<infer Tag> ? Tag : never;
 
 export type BuildParserFunction = <T>(args?: {
+  id?: string;
   customFormats?: { [key: string]: (input: string) => boolean };
+  customNumberFormats?: { [key: string]: (input: number) => boolean };
+  brands?: { [brand: string]: (input: any) => boolean };
+  unknownKeys?: UnknownKeys;
 }) => Parsers<T>;
 
 type Schemas<T> = {
@@ -221,4 +255,22 @@ type Schemas<T> = {
 
 export type BuildSchemaFunction = <T>() => Schemas<T>;
 
+export type RouteDefinition = {
+  params?: unknown;
+  query?: unknown;
+  body?: unknown;
+  response: unknown;
+  status?: number;
+};
+
+export type RouteParsers<T extends RouteDefinition> = {
+  [K in Exclude<keyof T, "status">]-?: BeffParser<NonNullable<T[K]>>;
+};
+
+type Routes<T> = {
+  [K in keyof T]: T[K] extends RouteDefinition ? RouteParsers<T[K]> : never;
+};
+
+export type BuildRoutesFunction = <T>() => Routes<T>;
+
 export type TypeOf<T> = T extends BeffParser<infer U> ? U : never;
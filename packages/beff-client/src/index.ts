@@ -82,11 +82,15 @@ export const printErrors = (it: DecodeError[], parentPath: string[] = []): strin
     .join(" | ");
 };
 
+type SafeParse<T> = (
+  input: any,
+  options?: ParseOptions
+) => { success: true; data: T } | { success: false; errors: DecodeError[] };
+
+// values without codecs are encoded by checking them
 const buildParserFromSafeParser = <T>(
-  safeParse: (
-    input: any,
-    options?: ParseOptions
-  ) => { success: true; data: T } | { success: false; errors: DecodeError[] }
+  safeParse: SafeParse<T>,
+  safeEncode: SafeParse<unknown> = safeParse
 ): BeffParser<T> => {
   const parse = (input: any, options?: ParseOptions) => {
     const safe = safeParse(input, options);
@@ -98,6 +102,16 @@ const buildParserFromSafeParser = <T>(
     error.errors = safe.errors;
     throw error;
   };
+  const encode = (value: T, options?: ParseOptions) => {
+    const safe = safeEncode(value, options);
+    if (safe.success) {
+      return safe.data;
+    }
+    const error = new Error(`Failed to encode`);
+    //@ts-ignore
+    error.errors = safe.errors;
+    throw error;
+  };
   const zod = () => {
     //@ts-ignore
     return z.custom(
@@ -113,6 +127,8 @@ const buildParserFromSafeParser = <T>(
   return {
     safeParse,
     parse,
+    safeEncode,
+    encode,
     zod,
   };
 };
@@ -121,39 +137,50 @@ const Object_ = <T extends Record<string, BeffParser<any>>>(
   fields: T
 ): BeffParser<{
   [K in keyof T]: T[K] extends BeffParser<infer U> ? U : never;
-}> =>
-  buildParserFromSafeParser((input: any, options?: ParseOptions) => {
-    const disallowExtraProperties = options?.disallowExtraProperties ?? false;
-
-    const errors: DecodeError[] = [];
-    const result = {} as any;
-
-    for (const key in fields) {
-      const field = fields[key];
-      const res = field.safeParse(input[key]);
-      if (res.success) {
-        result[key] = res.data;
-      } else {
-        errors.push(...res.errors.map((it) => ({ ...it, path: [key, ...it.path] })));
+}> => {
+  const run =
+    (encode: boolean): SafeParse<any> =>
+    (input: any, options?: ParseOptions) => {
+      const unknownKeys = options?.unknownKeys ?? (options?.disallowExtraProperties ? "strict" : "strip");
+
+      const errors: DecodeError[] = [];
+      const result = {} as any;
+
+      for (const key in fields) {
+        const field = fields[key];
+        const res = encode ? field.safeEncode(input[key]) : field.safeParse(input[key]);
+        if (res.success) {
+          result[key] = res.data;
+        } else {
+          errors.push(...res.errors.map((it) => ({ ...it, path: [key, ...it.path] })));
+        }
       }
-    }
 
-    if (disallowExtraProperties) {
-      for (const key in input) {
-        if (!fields[key]) {
-          errors.push({
-            message: "Extra property",
-            path: [key],
-            received: input[key],
-          });
+      if (unknownKeys === "strict") {
+        for (const key in input) {
+          if (!fields[key]) {
+            errors.push({
+              message: "Extra property",
+              path: [key],
+              received: input[key],
+            });
+          }
         }
       }
-    }
-    if (errors.length > 0) {
-      return { success: false, errors };
-    }
-    return { success: true, data: result };
-  });
+      if (unknownKeys === "passthrough") {
+        for (const key in input) {
+          if (!fields[key]) {
+            result[key] = input[key];
+          }
+        }
+      }
+      if (errors.length > 0) {
+        return { success: false, errors };
+      }
+      return { success: true, data: result };
+    };
+  return buildParserFromSafeParser(run(false), run(true));
+};
 
 const String_ = (): BeffParser<string> =>
   buildParserFromSafeParser((input: any) => {
@@ -213,29 +240,33 @@ const Unknown_ = (): BeffParser<unknown> =>
     return { success: true, data: input };
   });
 
-const Array_ = <T>(parser: BeffParser<T>): BeffParser<T[]> =>
-  buildParserFromSafeParser((input: any) => {
-    if (!Array.isArray(input)) {
-      return {
-        success: false,
-        errors: [{ message: "Expected array", path: [], received: input }],
-      };
-    }
-    const errors: DecodeError[] = [];
-    const results: T[] = [];
-    for (let i = 0; i < input.length; i++) {
-      const res = parser.safeParse(input[i]);
-      if (res.success) {
-        results.push(res.data);
-      } else {
-        errors.push(...res.errors.map((it) => ({ ...it, path: [i.toString(), ...it.path] })));
+const Array_ = <T>(parser: BeffParser<T>): BeffParser<T[]> => {
+  const run =
+    (encode: boolean): SafeParse<any> =>
+    (input: any) => {
+      if (!Array.isArray(input)) {
+        return {
+          success: false,
+          errors: [{ message: "Expected array", path: [], received: input }],
+        };
       }
-    }
-    if (errors.length > 0) {
-      return { success: false, errors };
-    }
-    return { success: true, data: results };
-  });
+      const errors: DecodeError[] = [];
+      const results: unknown[] = [];
+      for (let i = 0; i < input.length; i++) {
+        const res = encode ? parser.safeEncode(input[i]) : parser.safeParse(input[i]);
+        if (res.success) {
+          results.push(res.data);
+        } else {
+          errors.push(...res.errors.map((it) => ({ ...it, path: [i.toString(), ...it.path] })));
+        }
+      }
+      if (errors.length > 0) {
+        return { success: false, errors };
+      }
+      return { success: true, data: results };
+    };
+  return buildParserFromSafeParser(run(false), run(true));
+};
 
 export const b = {
   Object: Object_,
@@ -6,6 +6,7 @@ use crate::ast::json::Json;
 use crate::subtyping::semtype::SemTypeContext;
 use crate::subtyping::semtype::SemTypeOps;
 use crate::subtyping::ToSemType;
+use crate::UnknownKeys;
 use crate::Validator;
 use anyhow::anyhow;
 use anyhow::Result;
@@ -24,6 +25,7 @@ use swc_ecma_ast::TsKeywordType;
 use swc_ecma_ast::TsKeywordTypeKind;
 use swc_ecma_ast::TsLit;
 use swc_ecma_ast::TsLitType;
+use swc_ecma_ast::TsOptionalType;
 use swc_ecma_ast::TsParenthesizedType;
 use swc_ecma_ast::TsPropertySignature;
 use swc_ecma_ast::TsRestType;
@@ -45,28 +47,36 @@ use super::json::N;
 pub enum Optionality<T> {
     Optional(T),
     Required(T),
+    // optional, decoders use the value when the key is absent
+    Default(T, Json),
 }
 
 impl<T> Optionality<T> {
     pub fn inner(&self) -> &T {
         match self {
-            Optionality::Optional(t) | Optionality::Required(t) => t,
+            Optionality::Optional(t) | Optionality::Required(t) | Optionality::Default(t, _) => t,
         }
     }
     pub fn inner_move(self) -> T {
         match self {
-            Optionality::Optional(t) | Optionality::Required(t) => t,
+            Optionality::Optional(t) | Optionality::Required(t) | Optionality::Default(t, _) => t,
         }
     }
     pub fn is_required(&self) -> bool {
         match self {
-            Optionality::Optional(_) => false,
+            Optionality::Optional(_) | Optionality::Default(_, _) => false,
             Optionality::Required(_) => true,
         }
     }
+    pub fn default_value(&self) -> Option<&Json> {
+        match self {
+            Optionality::Default(_, it) => Some(it),
+            Optionality::Optional(_) | Optionality::Required(_) => None,
+        }
+    }
     pub fn to_required(self) -> Optionality<T> {
         match self {
-            Optionality::Optional(t) => Optionality::Required(t),
+            Optionality::Optional(t) | Optionality::Default(t, _) => Optionality::Required(t),
             Optionality::Required(t) => Optionality::Required(t),
         }
     }
@@ -74,6 +84,14 @@ impl<T> Optionality<T> {
         match self {
             Optionality::Optional(t) => Optionality::Optional(t),
             Optionality::Required(t) => Optionality::Optional(t),
+            Optionality::Default(t, it) => Optionality::Default(t, it),
+        }
+    }
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Optionality<U> {
+        match self {
+            Optionality::Optional(t) => Optionality::Optional(f(t)),
+            Optionality::Required(t) => Optionality::Required(f(t)),
+            Optionality::Default(t, it) => Optionality::Default(f(t), it),
         }
     }
 }
@@ -81,7 +99,9 @@ impl<T> Optionality<T> {
 impl Optionality<JsonSchema> {
     pub fn negated(self) -> Optionality<JsonSchema> {
         match self {
-            Optionality::Optional(it) => JsonSchema::StNot(it.into()).optional(),
+            Optionality::Optional(it) | Optionality::Default(it, _) => {
+                JsonSchema::StNot(it.into()).optional()
+            }
             Optionality::Required(it) => JsonSchema::StNot(it.into()).required(),
         }
     }
@@ -121,6 +141,59 @@ impl JsonSchemaConst {
     }
 }
 
+pub struct BuiltInStringFormat {
+    // valid in both Rust and JavaScript
+    pub regex: String,
+    pub example: &'static str,
+}
+
+const DATE_REGEX: &str = "[0-9]{4}-(0[1-9]|1[0-2])-(0[1-9]|[12][0-9]|3[01])";
+const TIME_REGEX: &str =
+    "([01][0-9]|2[0-3]):[0-5][0-9]:([0-5][0-9]|60)(\\.[0-9]+)?([Zz]|[+-]([01][0-9]|2[0-3]):[0-5][0-9])";
+pub const PLAIN_TIME_PATTERN: &str = "^([01][0-9]|2[0-3]):[0-5][0-9]:[0-5][0-9](\\.[0-9]+)?$";
+const IPV4_OCTET_REGEX: &str = "(25[0-5]|2[0-4][0-9]|1[0-9][0-9]|[1-9]?[0-9])";
+const IPV6_REGEX: &str = "(([0-9a-fA-F]{1,4}:){7}[0-9a-fA-F]{1,4}|([0-9a-fA-F]{1,4}:){1,7}:|([0-9a-fA-F]{1,4}:){1,6}:[0-9a-fA-F]{1,4}|([0-9a-fA-F]{1,4}:){1,5}(:[0-9a-fA-F]{1,4}){1,2}|([0-9a-fA-F]{1,4}:){1,4}(:[0-9a-fA-F]{1,4}){1,3}|([0-9a-fA-F]{1,4}:){1,3}(:[0-9a-fA-F]{1,4}){1,4}|([0-9a-fA-F]{1,4}:){1,2}(:[0-9a-fA-F]{1,4}){1,5}|[0-9a-fA-F]{1,4}:(:[0-9a-fA-F]{1,4}){1,6}|:((:[0-9a-fA-F]{1,4}){1,7}|:))";
+const HOSTNAME_LABEL_REGEX: &str = "[a-zA-Z0-9]([a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?";
+
+// Standard JSON Schema formats, they do not need to be registered.
+pub fn built_in_string_format(name: &str) -> Option<BuiltInStringFormat> {
+    let (regex, example) = match name {
+        "email" => (
+            "[^\\s@]+@[^\\s@]+\\.[^\\s@]+".to_string(),
+            "alpha@example.com",
+        ),
+        "uuid" => (
+            "[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}"
+                .to_string(),
+            "123e4567-e89b-12d3-a456-426614174000",
+        ),
+        "uri" => (
+            "[a-zA-Z][a-zA-Z0-9+.-]*:[^\\s]*".to_string(),
+            "https://example.com",
+        ),
+        "date" => (DATE_REGEX.to_string(), "2020-01-01"),
+        "date-time" => (
+            format!("{DATE_REGEX}[Tt ]{TIME_REGEX}"),
+            "2020-01-01T00:00:00.000Z",
+        ),
+        "time" => (TIME_REGEX.to_string(), "12:00:00Z"),
+        "ipv4" => (
+            format!("({IPV4_OCTET_REGEX}\\.){{3}}{IPV4_OCTET_REGEX}"),
+            "127.0.0.1",
+        ),
+        "ipv6" => (IPV6_REGEX.to_string(), "::1"),
+        "hostname" => (
+            format!("{HOSTNAME_LABEL_REGEX}(\\.{HOSTNAME_LABEL_REGEX})*"),
+            "example.com",
+        ),
+        _ => return None,
+    };
+    Some(BuiltInStringFormat {
+        regex: format!("^({regex})$"),
+        example,
+    })
+}
+
 #[derive(PartialEq, Eq, Hash, Debug, Ord, PartialOrd, Clone)]
 pub enum TplLitTypeItem {
     String,
@@ -164,7 +237,7 @@ impl TplLitTypeItem {
     pub fn regex_expr(&self) -> String {
         match self {
             TplLitTypeItem::String => "(.*)".to_string(),
-            TplLitTypeItem::Number => r"(\d+(\.\d+)?)".to_string(),
+            TplLitTypeItem::Number => r"([+-]?(\d+(\.\d*)?|\.\d+)([eE][+-]?\d+)?)".to_string(),
             TplLitTypeItem::Boolean => "(true|false)".to_string(),
             TplLitTypeItem::Quasis(lit) => {
                 if lit.is_empty() {
@@ -194,6 +267,20 @@ impl TplLitTypeItem {
         }
     }
 
+    pub fn const_vec(vs: &[Self]) -> Option<String> {
+        vs.iter()
+            .map(|it| match it {
+                TplLitTypeItem::StringConst(v) | TplLitTypeItem::Quasis(v) => Some(v.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn pattern_vec(vs: &[Self]) -> String {
+        let inner = vs.iter().map(|it| it.regex_expr()).collect::<String>();
+        format!("^{}$", inner)
+    }
+
     pub fn describe_vec(vs: &[Self]) -> String {
         vs.iter()
             .map(|it| match it {
@@ -209,7 +296,7 @@ impl TplLitTypeItem {
                     values.sort();
                     let values = values
                         .into_iter()
-                        .map(|it| Self::describe_vec(&[it.clone()]))
+                        .map(|it| Self::describe_vec(std::slice::from_ref(it)))
                         .collect::<Vec<_>>()
                         .join(" | ");
                     format!("({})", values)
@@ -228,15 +315,23 @@ pub enum JsonSchema {
     Any,
     AnyArrayLike,
     StringWithFormat(String),
+    NumberWithFormat(String),
     TplLitType(Vec<TplLitTypeItem>),
+    // a nominal type, it is the first type on the wire
+    Brand(Box<JsonSchema>, String),
     Object {
         vs: BTreeMap<String, Optionality<JsonSchema>>,
         rest: Option<Box<JsonSchema>>,
+        pattern_vs: BTreeMap<Vec<TplLitTypeItem>, JsonSchema>,
     },
     Array(Box<JsonSchema>),
+    // [...prefix_items, ...optional_items?, ...items[], ...suffix_items]
+    // suffix_items follow a rest, they are not used together with optional_items
     Tuple {
         prefix_items: Vec<JsonSchema>,
+        optional_items: Vec<JsonSchema>,
         items: Option<Box<JsonSchema>>,
+        suffix_items: Vec<JsonSchema>,
     },
     Ref(String),
 
@@ -253,6 +348,11 @@ pub enum JsonSchema {
 pub enum CodecName {
     ISO8061,
     BigInt,
+    PlainDate,
+    PlainTime,
+    Duration,
+    Base64,
+    Base64Url,
 }
 
 impl fmt::Display for CodecName {
@@ -260,6 +360,11 @@ impl fmt::Display for CodecName {
         let extra = match self {
             CodecName::ISO8061 => "ISO8061",
             CodecName::BigInt => "BigInt",
+            CodecName::PlainDate => "PlainDate",
+            CodecName::PlainTime => "PlainTime",
+            CodecName::Duration => "Duration",
+            CodecName::Base64 => "Base64",
+            CodecName::Base64Url => "Base64Url",
         };
         let e = "Codec::".to_string() + extra;
         write!(f, "{}", e)
@@ -298,6 +403,16 @@ impl JsonSchema {
         Self::Object {
             vs: vs.into_iter().collect(),
             rest,
+            pattern_vs: BTreeMap::new(),
+        }
+    }
+
+    pub fn tuple(prefix_items: Vec<JsonSchema>, items: Option<Box<JsonSchema>>) -> Self {
+        Self::Tuple {
+            prefix_items,
+            optional_items: vec![],
+            items,
+            suffix_items: vec![],
         }
     }
 
@@ -319,17 +434,29 @@ impl JsonSchema {
             1 => vs.into_iter().next().expect("we just checked len"),
             _ => {
                 let mut obj_kvs: Vec<(String, Optionality<JsonSchema>)> = vec![];
+                let mut obj_patterns: BTreeMap<Vec<TplLitTypeItem>, JsonSchema> = BTreeMap::new();
                 let mut all_objects = true;
                 let mut rest_is_none = true;
 
                 for v in vs.iter() {
                     match v {
-                        JsonSchema::Object { vs, rest } => {
+                        JsonSchema::Object {
+                            vs,
+                            rest,
+                            pattern_vs,
+                        } => {
                             if rest.is_some() {
                                 rest_is_none = false;
                                 break;
                             }
                             obj_kvs.extend(vs.iter().map(|it| (it.0.clone(), it.1.clone())));
+                            for (k, v) in pattern_vs {
+                                let v = match obj_patterns.remove(k) {
+                                    Some(prev) => JsonSchema::all_of(vec![prev, v.clone()]),
+                                    None => v.clone(),
+                                };
+                                obj_patterns.insert(k.clone(), v);
+                            }
                         }
                         _ => {
                             all_objects = false;
@@ -339,7 +466,11 @@ impl JsonSchema {
                 }
 
                 if rest_is_none && all_objects && vs.len() > 1 {
-                    JsonSchema::object(obj_kvs, None)
+                    JsonSchema::Object {
+                        vs: obj_kvs.into_iter().collect(),
+                        rest: None,
+                        pattern_vs: obj_patterns,
+                    }
                 } else {
                     Self::AllOf(BTreeSet::from_iter(vs))
                 }
@@ -399,13 +530,28 @@ impl JsonSchema {
 pub struct JsonFlatConverter<'a> {
     seen_refs: BTreeSet<String>,
     validators: &'a [Validator],
+    unknown_keys: UnknownKeys,
 }
 
 impl<'a> JsonFlatConverter<'a> {
-    pub fn new(validators: &'a [Validator]) -> Self {
+    pub fn new(validators: &'a [Validator], unknown_keys: UnknownKeys) -> Self {
         Self {
             seen_refs: BTreeSet::new(),
             validators,
+            unknown_keys,
+        }
+    }
+
+    // the schema of a property, with its default value
+    pub fn property(&mut self, property: Optionality<JsonSchema>) -> Json {
+        let default = property.default_value().cloned();
+        let json = self.to_json_flat(property.inner_move());
+        match (json, default) {
+            (Json::Object(mut vs), Some(default)) => {
+                vs.insert("default".into(), default);
+                Json::Object(vs)
+            }
+            (json, _) => json,
         }
     }
 
@@ -419,19 +565,53 @@ impl<'a> JsonFlatConverter<'a> {
                 ("type".into(), Json::String("string".into())),
                 ("format".into(), Json::String(format)),
             ]),
-            JsonSchema::Codec(format) => Json::object(vec![
+            JsonSchema::NumberWithFormat(format) => Json::object(vec![
+                ("type".into(), Json::String("number".into())),
+                ("format".into(), Json::String(format)),
+            ]),
+            JsonSchema::Brand(ty, _) => self.to_json_flat(*ty),
+            JsonSchema::Codec(CodecName::ISO8061) => Json::object(vec![
+                ("type".into(), Json::String("string".into())),
+                ("format".into(), Json::String("date-time".into())),
+            ]),
+            JsonSchema::Codec(CodecName::BigInt) => Json::object(vec![
+                ("type".into(), Json::String("string".into())),
+                ("pattern".into(), Json::String("^-?[0-9]+$".into())),
+            ]),
+            JsonSchema::Codec(CodecName::PlainDate) => Json::object(vec![
+                ("type".into(), Json::String("string".into())),
+                ("format".into(), Json::String("date".into())),
+            ]),
+            // the `time` format requires an offset, a time of day has none
+            JsonSchema::Codec(CodecName::PlainTime) => Json::object(vec![
+                ("type".into(), Json::String("string".into())),
+                ("pattern".into(), Json::String(PLAIN_TIME_PATTERN.into())),
+            ]),
+            JsonSchema::Codec(CodecName::Duration) => Json::object(vec![
+                ("type".into(), Json::String("string".into())),
+                ("format".into(), Json::String("duration".into())),
+            ]),
+            JsonSchema::Codec(CodecName::Base64) => Json::object(vec![
+                ("type".into(), Json::String("string".into())),
+                ("contentEncoding".into(), Json::String("base64".into())),
+            ]),
+            JsonSchema::Codec(CodecName::Base64Url) => Json::object(vec![
                 ("type".into(), Json::String("string".into())),
-                ("format".into(), Json::String(format.to_string())),
+                ("contentEncoding".into(), Json::String("base64url".into())),
             ]),
             JsonSchema::TplLitType(items) => Json::object(vec![
                 ("type".into(), Json::String("string".into())),
                 (
-                    "format".into(),
-                    Json::String(TplLitTypeItem::describe_vec(&items)),
+                    "pattern".into(),
+                    Json::String(TplLitTypeItem::pattern_vec(&items)),
                 ),
             ]),
 
-            JsonSchema::Object { vs: values, rest } => {
+            JsonSchema::Object {
+                vs: values,
+                rest,
+                pattern_vs,
+            } => {
                 let mut vs = vec![
                     //
                     ("type".into(), Json::String("object".into())),
@@ -452,16 +632,35 @@ impl<'a> JsonFlatConverter<'a> {
                         Json::Object(
                             values
                                 .into_iter()
-                                .map(|(k, v)| (k, self.to_json_flat(v.inner_move())))
+                                .map(|(k, v)| (k, self.property(v)))
                                 .collect(),
                         ),
                     ),
                 ];
 
-                if let Some(rest) = rest {
-                    vs.push(("additionalProperties".into(), self.to_json_flat(*rest)));
-                } else {
-                    vs.push(("additionalProperties".into(), Json::Bool(false)));
+                if !pattern_vs.is_empty() {
+                    vs.push((
+                        "patternProperties".into(),
+                        Json::Object(
+                            pattern_vs
+                                .into_iter()
+                                .map(|(k, v)| {
+                                    (TplLitTypeItem::pattern_vec(&k), self.to_json_flat(v))
+                                })
+                                .collect(),
+                        ),
+                    ));
+                }
+
+                match rest {
+                    Some(rest) => {
+                        vs.push(("additionalProperties".into(), self.to_json_flat(*rest)));
+                    }
+                    // decoded values of strict and stripped objects never have other keys
+                    None if self.unknown_keys != UnknownKeys::Passthrough => {
+                        vs.push(("additionalProperties".into(), Json::Bool(false)));
+                    }
+                    None => {}
                 }
                 Json::object(vs)
             }
@@ -529,29 +728,43 @@ impl<'a> JsonFlatConverter<'a> {
 
             JsonSchema::Tuple {
                 prefix_items,
+                optional_items,
                 items,
+                suffix_items,
             } => {
                 let mut v = vec![
                     //
                     ("type".into(), Json::String("array".into())),
                 ];
-                let len_f = prefix_items.len();
-                if !prefix_items.is_empty() {
+                let min_items = prefix_items.len() + suffix_items.len();
+                let max_items = prefix_items.len() + optional_items.len();
+                let positional: Vec<_> = prefix_items.into_iter().chain(optional_items).collect();
+                if !positional.is_empty() {
                     v.push((
                         "prefixItems".into(),
                         Json::Array(
-                            prefix_items
+                            positional
                                 .into_iter()
                                 .map(|it| self.to_json_flat(it))
                                 .collect(),
                         ),
                     ));
                 }
-                if let Some(ty) = items {
-                    v.push(("items".into(), self.to_json_flat(*ty)));
-                } else {
-                    v.push(("minItems".into(), Json::parse_int(len_f as i64)));
-                    v.push(("maxItems".into(), Json::parse_int(len_f as i64)));
+                match items {
+                    Some(ty) => {
+                        // the position of the suffix depends on the length, it cannot be
+                        // described by prefixItems, so the rest accepts it too
+                        let ty =
+                            JsonSchema::any_of(std::iter::once(*ty).chain(suffix_items).collect());
+                        v.push(("items".into(), self.to_json_flat(ty)));
+                        if min_items > 0 {
+                            v.push(("minItems".into(), Json::parse_int(min_items as i64)));
+                        }
+                    }
+                    None => {
+                        v.push(("minItems".into(), Json::parse_int(min_items as i64)));
+                        v.push(("maxItems".into(), Json::parse_int(max_items as i64)));
+                    }
                 }
                 Json::object(v)
             }
@@ -566,22 +779,59 @@ impl<'a> JsonFlatConverter<'a> {
     }
 }
 
-fn ts_brand(brand: &str) -> TsType {
-    // string & { __brand: "brand" }
+fn ts_index_signature(key: TsType, value: TsType) -> TsTypeElement {
+    TsTypeElement::TsIndexSignature(TsIndexSignature {
+        span: DUMMY_SP,
+        readonly: false,
+        params: vec![TsFnParam::Ident(BindingIdent {
+            id: Ident {
+                span: DUMMY_SP,
+                sym: "key".into(),
+                optional: false,
+            },
+            type_ann: Some(
+                TsTypeAnn {
+                    span: DUMMY_SP,
+                    type_ann: key.into(),
+                }
+                .into(),
+            ),
+        })],
+        type_ann: Some(
+            TsTypeAnn {
+                span: DUMMY_SP,
+                type_ann: value.into(),
+            }
+            .into(),
+        ),
+        is_static: false,
+    })
+}
+
+// the runtime value of the temporal codecs, ie: { year: number; month: number; day: number }
+fn number_fields(keys: &[&str]) -> TsType {
+    JsonSchema::object(
+        keys.iter()
+            .map(|k| (k.to_string(), JsonSchema::Number.required()))
+            .collect(),
+        None,
+    )
+    .to_ts_type()
+}
+
+fn ts_brand(ty: TsType, key: &str, brand: &str) -> TsType {
+    // ty & { key: "brand" }
     TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsIntersectionType(
         TsIntersectionType {
             span: DUMMY_SP,
             types: vec![
-                Box::new(TsType::TsKeywordType(TsKeywordType {
-                    span: DUMMY_SP,
-                    kind: TsKeywordTypeKind::TsStringKeyword,
-                })),
+                Box::new(ty),
                 Box::new(TsType::TsTypeLit(TsTypeLit {
                     span: DUMMY_SP,
                     members: vec![TsTypeElement::TsPropertySignature(TsPropertySignature {
                         span: DUMMY_SP,
                         readonly: false,
-                        key: "brand".into(),
+                        key: key.into(),
                         computed: false,
                         optional: false,
                         init: None,
@@ -628,7 +878,11 @@ impl JsonSchema {
                 span: DUMMY_SP,
                 kind: TsKeywordTypeKind::TsAnyKeyword,
             }),
-            JsonSchema::Object { vs, rest } => {
+            JsonSchema::Object {
+                vs,
+                rest,
+                pattern_vs,
+            } => {
                 let mut members: Vec<TsTypeElement> = vs
                     .iter()
                     .map(|(k, v)| {
@@ -649,39 +903,24 @@ impl JsonSchema {
                     })
                     .collect();
 
+                for (k, v) in pattern_vs {
+                    let key = match k.as_slice() {
+                        [TplLitTypeItem::Number] => TsType::TsKeywordType(TsKeywordType {
+                            span: DUMMY_SP,
+                            kind: TsKeywordTypeKind::TsNumberKeyword,
+                        }),
+                        _ => JsonSchema::TplLitType(k.clone()).to_ts_type(),
+                    };
+                    members.push(ts_index_signature(key, v.to_ts_type()));
+                }
+
                 if let Some(rest) = rest {
-                    let rest = TsTypeElement::TsIndexSignature(TsIndexSignature {
+                    // string type always
+                    let key = TsType::TsKeywordType(TsKeywordType {
                         span: DUMMY_SP,
-                        readonly: false,
-                        params: vec![TsFnParam::Ident(BindingIdent {
-                            id: Ident {
-                                span: DUMMY_SP,
-                                sym: "key".into(),
-                                optional: false,
-                            },
-                            // string type always
-                            type_ann: Some(
-                                TsTypeAnn {
-                                    span: DUMMY_SP,
-                                    type_ann: TsType::TsKeywordType(TsKeywordType {
-                                        span: DUMMY_SP,
-                                        kind: TsKeywordTypeKind::TsStringKeyword,
-                                    })
-                                    .into(),
-                                }
-                                .into(),
-                            ),
-                        })],
-                        type_ann: Some(
-                            TsTypeAnn {
-                                span: DUMMY_SP,
-                                type_ann: rest.to_ts_type().into(),
-                            }
-                            .into(),
-                        ),
-                        is_static: false,
+                        kind: TsKeywordTypeKind::TsStringKeyword,
                     });
-                    members.push(rest);
+                    members.push(ts_index_signature(key, rest.to_ts_type()));
                 }
 
                 TsType::TsTypeLit(TsTypeLit {
@@ -707,34 +946,37 @@ impl JsonSchema {
             }
             JsonSchema::Tuple {
                 prefix_items,
+                optional_items,
                 items,
+                suffix_items,
             } => {
-                let mut elem_types: Vec<TsTupleElement> = vec![];
-                for it in prefix_items {
-                    let ty = it.to_ts_type();
-                    let ty = TsTupleElement {
+                let optional = optional_items.iter().map(|it| {
+                    TsType::TsOptionalType(TsOptionalType {
                         span: DUMMY_SP,
-                        label: None,
-                        ty: ty.into(),
-                    };
-                    elem_types.push(ty);
-                }
-                if let Some(items) = items {
-                    let ty = items.to_ts_type();
-                    let ty = TsType::TsRestType(TsRestType {
+                        type_ann: Box::new(it.to_ts_type()),
+                    })
+                });
+                let rest = items.iter().map(|items| {
+                    TsType::TsRestType(TsRestType {
                         span: DUMMY_SP,
                         type_ann: Box::new(TsType::TsArrayType(TsArrayType {
                             span: DUMMY_SP,
-                            elem_type: Box::new(ty),
+                            elem_type: Box::new(items.to_ts_type()),
                         })),
-                    });
-                    let ty = TsTupleElement {
+                    })
+                });
+                let elem_types: Vec<TsTupleElement> = prefix_items
+                    .iter()
+                    .map(|it| it.to_ts_type())
+                    .chain(optional)
+                    .chain(rest)
+                    .chain(suffix_items.iter().map(|it| it.to_ts_type()))
+                    .map(|ty| TsTupleElement {
                         span: DUMMY_SP,
                         label: None,
                         ty: ty.into(),
-                    };
-                    elem_types.push(ty);
-                }
+                    })
+                    .collect();
                 TsType::TsTupleType(TsTupleType {
                     span: DUMMY_SP,
                     elem_types,
@@ -749,7 +991,23 @@ impl JsonSchema {
                 }),
                 type_params: None,
             }),
-            JsonSchema::StringWithFormat(fmt) => ts_brand(fmt),
+            JsonSchema::StringWithFormat(fmt) => ts_brand(
+                TsType::TsKeywordType(TsKeywordType {
+                    span: DUMMY_SP,
+                    kind: TsKeywordTypeKind::TsStringKeyword,
+                }),
+                "__customType",
+                fmt,
+            ),
+            JsonSchema::NumberWithFormat(fmt) => ts_brand(
+                TsType::TsKeywordType(TsKeywordType {
+                    span: DUMMY_SP,
+                    kind: TsKeywordTypeKind::TsNumberKeyword,
+                }),
+                "__customNumberType",
+                fmt,
+            ),
+            JsonSchema::Brand(ty, brand) => ts_brand(ty.to_ts_type(), "__brand", brand),
             JsonSchema::Codec(c) => match c {
                 CodecName::ISO8061 => TsType::TsTypeRef(TsTypeRef {
                     span: DUMMY_SP,
@@ -764,6 +1022,20 @@ impl JsonSchema {
                     span: DUMMY_SP,
                     kind: TsKeywordTypeKind::TsBigIntKeyword,
                 }),
+                CodecName::PlainDate => number_fields(&["year", "month", "day"]),
+                CodecName::PlainTime => number_fields(&["hour", "minute", "second"]),
+                CodecName::Duration => number_fields(&[
+                    "years", "months", "weeks", "days", "hours", "minutes", "seconds",
+                ]),
+                CodecName::Base64 | CodecName::Base64Url => TsType::TsTypeRef(TsTypeRef {
+                    span: DUMMY_SP,
+                    type_name: TsEntityName::Ident(Ident {
+                        span: DUMMY_SP,
+                        sym: "Uint8Array".into(),
+                        optional: false,
+                    }),
+                    type_params: None,
+                }),
             },
 
             JsonSchema::AnyOf(vs) =>
@@ -8,6 +8,20 @@ use crate::{BffFileName, ParsedModule};
 #[derive(Debug, Clone)]
 pub enum DiagnosticInfoMessage {
     TwoCallsToBuildSchemas,
+    TwoCallsToBuildRoutes,
+    RoutesShouldBeObjectWithRouteKeys,
+    RouteKeyShouldBeMethodAndPath,
+    InvalidRouteMethod(String),
+    RouteShouldBeObjectLiteral,
+    InvalidRouteProperty,
+    RouteMustHaveResponse,
+    InvalidRouteStatus,
+    RouteParamsShouldBeObject,
+    RouteQueryShouldBeObject,
+    RouteParamNotDeclared(String),
+    RouteParamNotInPath(String),
+    ComplexRouteParam(String),
+    OptionalRouteParam(String),
     CannotResolveRefInJsonSchemaToTplLit,
     TypeOfJSXTextNotSupported,
     TypeOfRegexNotSupported,
@@ -16,14 +30,14 @@ pub enum DiagnosticInfoMessage {
     TypeofObjectUnsupportedPropBigInt,
     TypeofObjectUnsupportedSpread,
     TypeofObjectUnsupportedProp,
+    TypeofCallWithoutReturnType,
+    TypeofCallOfNonFunction,
     TypeofPrivateNameNotSupported,
     FoundTypeExpectedValueInSymbolExport,
-    TypeOfStarNotSupported,
     TypeOfSomethingOfOtherFileNotSupported,
     CannotUseDefaultAsStar,
     CannotUseNamedAsStar,
     TypeOfTsBuiltinNotSupported,
-    TypeofTsEnumNotSupported,
     TplLitTypeNonStringNonNumberNonBoolean,
     ExcludeShouldHaveTwoTypeArguments,
     MissingArgumentsOnExclude,
@@ -43,6 +57,17 @@ pub enum DiagnosticInfoMessage {
     CannotResolveRefInExtractUnion,
     PartialShouldHaveObjectAsTypeArgument,
     MissingArgumentsOnPartial,
+    MissingArgumentsOnParameters,
+    MissingArgumentsOnReturnType,
+    MissingArgumentsOnAwaited,
+    ParametersShouldHaveOneTypeArgument,
+    ReturnTypeShouldHaveOneTypeArgument,
+    AwaitedShouldHaveOneTypeArgument,
+    StringIntrinsicShouldHaveOneTypeArgument(String),
+    StringIntrinsicShouldHaveStringLiteralArgument(String),
+    ExpectedFunctionType,
+    FunctionParamShouldHaveTypeAnnotation,
+    FunctionShouldDeclareReturnType,
     PickShouldHaveStringAsTypeArgument,
     PickShouldHaveStringOrStringArrayAsTypeArgument,
     MissingArgumentsOnOmit,
@@ -62,7 +87,8 @@ pub enum DiagnosticInfoMessage {
     CannotResolveKey(String),
     CouldNotFindSomethingOfOtherFile(String),
     EnumMemberNoInit,
-    TypeofImportNotSupported,
+    EnumMemberNotFound(String),
+    CannotResolveImportType(String),
     NoArgumentInTypeApplication,
     ExportDefaultNotFound,
     PathMustStartWithDash,
@@ -71,6 +97,7 @@ pub enum DiagnosticInfoMessage {
     FoundValueExpectedType,
     FoundTypeExpectedValue,
     CustomFormatIsNotRegistered,
+    CustomNumberFormatIsNotRegistered,
     GetMustNotHaveBody,
     InvalidIdentifierInPatternNoExplodeAllowed,
     CloseBlockMustEndPattern,
@@ -82,9 +109,11 @@ pub enum DiagnosticInfoMessage {
     DecoderMustHaveTypeAnnotation,
     CannotGetQualifiedTypeFromFile(String),
     CannotGetQualifiedTypeFromFileRec(String),
-    TwoCallsToBuildParsers,
     CannotResolveSomethingOfOtherFile(String),
     InvalidUsageOfStringFormatTypeParameter,
+    InvalidUsageOfNumberFormatTypeParameter,
+    InvalidDefaultType,
+    InvalidJsDocDefault(String),
     CannotResolveNamespaceType,
     ShouldNotResolveTsInterfaceDeclAsNamespace,
     ShouldNotResolveTsTypeAsNamespace,
@@ -109,11 +138,17 @@ pub enum DiagnosticInfoMessage {
     TsTypePredicateNonSerializableToJsonSchema,
     TsImportTypeNonSerializableToJsonSchema,
     OptionalTypeIsNotSupported,
+    InvalidOptionalTupleElement,
     PropShouldHaveTypeAnnotation,
     PropKeyShouldBeIdent,
+    ComputedPropKeyShouldBeConst,
     CannotResolveTypeReferenceOnExtracting(String),
     TsInterfaceExtendsNotSupported,
     TwoDifferentTypesWithTheSameName(String),
+    InvalidUnknownKeys,
+    InvalidBuildParsersId,
+    BuildParsersShouldHaveId,
+    DuplicatedBuildParsersId(String),
     CannotFindFileWhenConvertingToSchema(BffFileName),
     ThisRefersToSomethingThatCannotBeSerialized(String),
     CannotResolveLocalSymbol(String),
@@ -121,7 +156,11 @@ pub enum DiagnosticInfoMessage {
     NonStringKeyInMappedType,
     NoTypeAnnotationInMappedType,
     CannotConvertExprToSchema,
-    MappedTypeMinusNotSupported,
+    InvalidJsonSchema(String, String),
+    UnsupportedJsonSchemaKeyword(String, String),
+    UnsupportedJsonSchemaInteger(String),
+    MixedJsonSchemaAdditionalProperties(String),
+    CannotResolveJsonSchemaRef(String),
 }
 
 #[allow(clippy::inherent_to_string)]
@@ -150,18 +189,37 @@ impl DiagnosticInfoMessage {
             DiagnosticInfoMessage::OptionalTypeIsNotSupported => {
                 "Optional types are not supported at this position".to_string()
             }
+            DiagnosticInfoMessage::InvalidOptionalTupleElement => {
+                "Optional tuple elements must follow the required elements and precede a rest element"
+                    .to_string()
+            }
             DiagnosticInfoMessage::PropShouldHaveTypeAnnotation => {
                 "Property should have a type annotation".to_string()
             }
             DiagnosticInfoMessage::PropKeyShouldBeIdent => {
                 "Property name should be an identifier".to_string()
             }
+            DiagnosticInfoMessage::ComputedPropKeyShouldBeConst => {
+                "Computed property name should be a string or number constant".to_string()
+            }
             DiagnosticInfoMessage::CannotResolveTypeReferenceOnExtracting(name) => {
                 format!("Failed to resolve type reference '{name}' when extracting")
             }
             DiagnosticInfoMessage::TwoDifferentTypesWithTheSameName(name) => {
                 format!("This includes two different types with the same name '{name}'")
             }
+            DiagnosticInfoMessage::InvalidUnknownKeys => {
+                "unknownKeys must be \"strict\", \"strip\" or \"passthrough\"".to_string()
+            }
+            DiagnosticInfoMessage::InvalidBuildParsersId => {
+                "The id of a buildParsers call must be a string literal".to_string()
+            }
+            DiagnosticInfoMessage::BuildParsersShouldHaveId => {
+                "buildParsers is called more than once, each call should have an id".to_string()
+            }
+            DiagnosticInfoMessage::DuplicatedBuildParsersId(id) => {
+                format!("Another buildParsers call has the id '{id}'")
+            }
             DiagnosticInfoMessage::CannotFindFileWhenConvertingToSchema(f) => {
                 let name = &f.0;
                 format!("Cannot find file '{name}' when converting to schema")
@@ -188,15 +246,21 @@ impl DiagnosticInfoMessage {
             DiagnosticInfoMessage::CannotGetQualifiedTypeFromFile(name) => {
                 format!("Cannot find CannotGetQualifiedTypeFromFile type '{name}'")
             }
-            DiagnosticInfoMessage::TwoCallsToBuildParsers => {
-                "buildParser can only be called once".to_string()
-            }
             DiagnosticInfoMessage::CannotResolveSomethingOfOtherFile(name) => {
                 format!("Cannot find CannotResolveSomethingOfOtherFile type '{name}'")
             }
             DiagnosticInfoMessage::InvalidUsageOfStringFormatTypeParameter => {
                 "Invalid usage of string format type parameter".to_string()
             }
+            DiagnosticInfoMessage::InvalidUsageOfNumberFormatTypeParameter => {
+                "Invalid usage of number format type parameter".to_string()
+            }
+            DiagnosticInfoMessage::InvalidDefaultType => {
+                "Default should have a type and a literal type, ie: Default<number, 20>".to_string()
+            }
+            DiagnosticInfoMessage::InvalidJsDocDefault(it) => {
+                format!("Cannot read the @default value '{it}' as JSON")
+            }
             DiagnosticInfoMessage::CannotResolveNamespaceType => {
                 "Cannot resolve namespace type".to_string()
             }
@@ -226,6 +290,9 @@ impl DiagnosticInfoMessage {
             DiagnosticInfoMessage::CustomFormatIsNotRegistered => {
                 "Custom format is not registered".to_string()
             }
+            DiagnosticInfoMessage::CustomNumberFormatIsNotRegistered => {
+                "Custom number format is not registered".to_string()
+            }
             DiagnosticInfoMessage::FoundTypeExpectedValue => {
                 "Found type, expected value".to_string()
             }
@@ -245,8 +312,8 @@ impl DiagnosticInfoMessage {
             DiagnosticInfoMessage::NoArgumentInTypeApplication => {
                 "Missing this type argument".to_string()
             }
-            DiagnosticInfoMessage::TypeofImportNotSupported => {
-                "typeof import is not supported".to_string()
+            DiagnosticInfoMessage::CannotResolveImportType(specifier) => {
+                format!("Cannot resolve import type of module '{specifier}'")
             }
             DiagnosticInfoMessage::CouldNotFindSomethingOfOtherFile(something) => {
                 format!("Could not find '{something}' of other file")
@@ -254,7 +321,12 @@ impl DiagnosticInfoMessage {
             DiagnosticInfoMessage::CannotResolveKey(key) => {
                 format!("Cannot resolve key '{key}' of non-object")
             }
-            DiagnosticInfoMessage::EnumMemberNoInit => "Enum must have initializer".to_string(),
+            DiagnosticInfoMessage::EnumMemberNoInit => {
+                "Enum member must have initializer when it follows a non-numeric member".to_string()
+            }
+            DiagnosticInfoMessage::EnumMemberNotFound(name) => {
+                format!("Enum member '{name}' not found")
+            }
             DiagnosticInfoMessage::CannotUseTsEnumAsQualified => {
                 "Cannot use TS enum as qualified".to_string()
             }
@@ -322,6 +394,39 @@ impl DiagnosticInfoMessage {
             DiagnosticInfoMessage::MissingArgumentsOnPartial => {
                 "Missing arguments on partial".to_string()
             }
+            DiagnosticInfoMessage::MissingArgumentsOnParameters => {
+                "Missing arguments on Parameters".to_string()
+            }
+            DiagnosticInfoMessage::MissingArgumentsOnReturnType => {
+                "Missing arguments on ReturnType".to_string()
+            }
+            DiagnosticInfoMessage::MissingArgumentsOnAwaited => {
+                "Missing arguments on Awaited".to_string()
+            }
+            DiagnosticInfoMessage::ParametersShouldHaveOneTypeArgument => {
+                "Parameters should have one type argument".to_string()
+            }
+            DiagnosticInfoMessage::ReturnTypeShouldHaveOneTypeArgument => {
+                "ReturnType should have one type argument".to_string()
+            }
+            DiagnosticInfoMessage::AwaitedShouldHaveOneTypeArgument => {
+                "Awaited should have one type argument".to_string()
+            }
+            DiagnosticInfoMessage::StringIntrinsicShouldHaveOneTypeArgument(name) => {
+                format!("{name} should have one type argument")
+            }
+            DiagnosticInfoMessage::StringIntrinsicShouldHaveStringLiteralArgument(name) => {
+                format!("{name} can only be applied to string literals")
+            }
+            DiagnosticInfoMessage::ExpectedFunctionType => {
+                "Expected a function type, a function declaration or an arrow function".to_string()
+            }
+            DiagnosticInfoMessage::FunctionParamShouldHaveTypeAnnotation => {
+                "Function parameter should have a type annotation".to_string()
+            }
+            DiagnosticInfoMessage::FunctionShouldDeclareReturnType => {
+                "Function must declare a return type to be used in ReturnType".to_string()
+            }
             DiagnosticInfoMessage::UniqueNonSerializableToJsonSchema => {
                 "Unique cannot be converted to JSON schema".to_string()
             }
@@ -358,8 +463,20 @@ impl DiagnosticInfoMessage {
             DiagnosticInfoMessage::CannotConvertExprToSchema => {
                 "Cannot convert expression to JSON schema".to_string()
             }
-            DiagnosticInfoMessage::MappedTypeMinusNotSupported => {
-                "Mapped type minus is not supported".to_string()
+            DiagnosticInfoMessage::InvalidJsonSchema(pointer, reason) => {
+                format!("Invalid JSON schema at '{pointer}': {reason}")
+            }
+            DiagnosticInfoMessage::UnsupportedJsonSchemaKeyword(keyword, pointer) => {
+                format!("Keyword '{keyword}' at '{pointer}' cannot be represented as a type")
+            }
+            DiagnosticInfoMessage::UnsupportedJsonSchemaInteger(pointer) => {
+                format!("Type 'integer' at '{pointer}' cannot be represented as a type, use 'number'")
+            }
+            DiagnosticInfoMessage::MixedJsonSchemaAdditionalProperties(pointer) => {
+                format!("'additionalProperties: false' at '{pointer}' requires every object of the document to forbid additional properties, the decoders share one unknown keys policy")
+            }
+            DiagnosticInfoMessage::CannotResolveJsonSchemaRef(r) => {
+                format!("Cannot resolve JSON schema reference '{r}'")
             }
             DiagnosticInfoMessage::CannotResolveRefInExtractUnion => {
                 "Cannot resolve ref in extract union".to_string()
@@ -406,9 +523,6 @@ impl DiagnosticInfoMessage {
             DiagnosticInfoMessage::TplLitTypeNonStringNonNumberNonBoolean => {
                 "Template literal type must be a string, number, or boolean".to_string()
             }
-            DiagnosticInfoMessage::TypeofTsEnumNotSupported => {
-                "typeof on TS enum is not supported".to_string()
-            }
             DiagnosticInfoMessage::TypeOfTsBuiltinNotSupported => {
                 "typeof on TS builtin is not supported".to_string()
             }
@@ -419,15 +533,18 @@ impl DiagnosticInfoMessage {
             DiagnosticInfoMessage::TypeOfSomethingOfOtherFileNotSupported => {
                 "typeof something of other file is not supported".to_string()
             }
-            DiagnosticInfoMessage::TypeOfStarNotSupported => {
-                "typeof * is not supported".to_string()
-            }
             DiagnosticInfoMessage::FoundTypeExpectedValueInSymbolExport => {
                 "Found type, expected value in symbol export".to_string()
             }
             DiagnosticInfoMessage::TypeofPrivateNameNotSupported => {
                 "typeof on private name is not supported".to_string()
             }
+            DiagnosticInfoMessage::TypeofCallWithoutReturnType => {
+                "Function must declare a return type to be used in typeof".to_string()
+            }
+            DiagnosticInfoMessage::TypeofCallOfNonFunction => {
+                "typeof of call expression is only supported for function declarations".to_string()
+            }
             DiagnosticInfoMessage::TypeofObjectUnsupportedProp => {
                 "typeof on object unsupported prop".to_string()
             }
@@ -455,6 +572,51 @@ impl DiagnosticInfoMessage {
             DiagnosticInfoMessage::TwoCallsToBuildSchemas => {
                 "buildSchemas can only be called once".to_string()
             }
+            DiagnosticInfoMessage::TwoCallsToBuildRoutes => {
+                "buildRoutes can only be called once".to_string()
+            }
+            DiagnosticInfoMessage::RoutesShouldBeObjectWithRouteKeys => {
+                "Routes should be an object whose keys are routes, ie: \"GET /users/{id}\""
+                    .to_string()
+            }
+            DiagnosticInfoMessage::RouteKeyShouldBeMethodAndPath => {
+                "Route key should be a string with a method and a path, ie: \"GET /users/{id}\""
+                    .to_string()
+            }
+            DiagnosticInfoMessage::InvalidRouteMethod(method) => {
+                format!("Invalid HTTP method '{method}'")
+            }
+            DiagnosticInfoMessage::RouteShouldBeObjectLiteral => {
+                "Route should be an object literal with params, query, body, response and status"
+                    .to_string()
+            }
+            DiagnosticInfoMessage::InvalidRouteProperty => {
+                "Route properties can only be params, query, body, response and status".to_string()
+            }
+            DiagnosticInfoMessage::RouteMustHaveResponse => {
+                "Route must have a response".to_string()
+            }
+            DiagnosticInfoMessage::InvalidRouteStatus => {
+                "Route status should be a number literal between 100 and 599, ie: 201".to_string()
+            }
+            DiagnosticInfoMessage::RouteParamsShouldBeObject => {
+                "Route params should be an object".to_string()
+            }
+            DiagnosticInfoMessage::RouteQueryShouldBeObject => {
+                "Route query should be an object".to_string()
+            }
+            DiagnosticInfoMessage::RouteParamNotDeclared(name) => {
+                format!("Path parameter '{name}' is not declared in params")
+            }
+            DiagnosticInfoMessage::RouteParamNotInPath(name) => {
+                format!("Param '{name}' is not in the route path")
+            }
+            DiagnosticInfoMessage::ComplexRouteParam(name) => {
+                format!("Parameter '{name}' should be a string, number or boolean")
+            }
+            DiagnosticInfoMessage::OptionalRouteParam(name) => {
+                format!("Path parameter '{name}' cannot be optional")
+            }
         }
     }
 }
@@ -462,7 +624,6 @@ impl DiagnosticInfoMessage {
 pub enum DiagnosticParentMessage {
     CannotConvertToSchema,
     ComplexPathParam,
-    InvalidContextPosition,
 }
 
 impl fmt::Display for DiagnosticParentMessage {
@@ -474,9 +635,6 @@ impl fmt::Display for DiagnosticParentMessage {
             DiagnosticParentMessage::ComplexPathParam => {
                 write!(f, "Complex path parameter")
             }
-            DiagnosticParentMessage::InvalidContextPosition => {
-                write!(f, "Invalid context usage")
-            }
         }
     }
 }
@@ -0,0 +1,164 @@
+use std::rc::Rc;
+
+use crate::ast::json_schema::JsonSchema;
+use crate::diag::{Diagnostic, DiagnosticInfoMessage, DiagnosticInformation, Location};
+use crate::qualified_name::type_name;
+use crate::type_to_schema::TypeToSchema;
+use crate::{BeffUserSettings, BffFileName, FileManager, ParsedModule, Validator};
+use anyhow::anyhow;
+use anyhow::Result;
+use swc_common::{Span, DUMMY_SP};
+use swc_ecma_ast::{CallExpr, Callee, Expr, Ident, MemberExpr, MemberProp, TsType};
+use swc_ecma_visit::Visit;
+
+// the state shared by the visitors that look for the calls of an entry file
+pub struct ExtractorState<'a, R: FileManager> {
+    pub files: &'a mut R,
+    pub current_file: BffFileName,
+    pub validators: Vec<Validator>,
+    pub errors: Vec<Diagnostic>,
+    pub settings: &'a BeffUserSettings,
+    pub counter: usize,
+}
+
+impl<'a, R: FileManager> ExtractorState<'a, R> {
+    pub fn new(
+        files: &'a mut R,
+        current_file: BffFileName,
+        settings: &'a BeffUserSettings,
+    ) -> ExtractorState<'a, R> {
+        ExtractorState {
+            files,
+            current_file,
+            validators: vec![],
+            errors: vec![],
+            settings,
+            counter: 0,
+        }
+    }
+
+    pub fn build_error(&self, span: &Span, msg: DiagnosticInfoMessage) -> DiagnosticInformation {
+        let file = self.files.get_existing_file(&self.current_file);
+        Location::build(file, span, &self.current_file).to_info(msg)
+    }
+    pub fn push_error(&mut self, span: &Span, msg: DiagnosticInfoMessage) {
+        self.errors.push(self.build_error(span, msg).to_diag(None));
+    }
+
+    pub fn error<T>(&mut self, span: &Span, msg: DiagnosticInfoMessage) -> Result<T> {
+        let e = anyhow!("{:?}", &msg);
+        self.errors.push(self.build_error(span, msg).to_diag(None));
+        Err(e)
+    }
+
+    pub fn get_current_file(&mut self) -> Result<Rc<ParsedModule>> {
+        let res = self.files.get_or_fetch_file(&self.current_file);
+
+        match res {
+            Some(it) => Ok(it),
+            None => {
+                self.errors.push(
+                    self.build_error(
+                        &DUMMY_SP,
+                        DiagnosticInfoMessage::CannotFindFileWhenConvertingToSchema(
+                            self.current_file.clone(),
+                        ),
+                    )
+                    .to_diag(None),
+                );
+                Err(anyhow!("cannot find file: {}", self.current_file.0))
+            }
+        }
+    }
+
+    fn extend_components(&mut self, defs: Vec<Validator>, span: &Span) {
+        for d in defs {
+            let found = self.validators.iter_mut().find(|x| x.name == d.name);
+            if let Some(found) = found {
+                if found.schema != d.schema {
+                    self.push_error(
+                        span,
+                        DiagnosticInfoMessage::TwoDifferentTypesWithTheSameName(
+                            type_name(&d.name).to_string(),
+                        ),
+                    );
+                }
+            } else {
+                self.validators.push(d);
+            }
+        }
+    }
+    pub fn convert_to_json_schema(&mut self, ty: &TsType, span: &Span) -> JsonSchema {
+        let mut to_schema = TypeToSchema::new(
+            self.files,
+            self.current_file.clone(),
+            self.settings,
+            &mut self.counter,
+        );
+        let res = to_schema.convert_ts_type(ty);
+        match res {
+            Ok(res) => {
+                let mut kvs = vec![];
+                for (k, v) in to_schema.components {
+                    // We store type in an Option to support self-recursion.
+                    // When we encounter the type while transforming it we return string with the type name.
+                    // And we need the option to allow a type to refer to itself before it has been resolved.
+                    match v {
+                        Some(s) => kvs.push((k, s)),
+                        None => self.push_error(
+                            span,
+                            DiagnosticInfoMessage::CannotResolveTypeReferenceOnExtracting(
+                                type_name(&k).to_string(),
+                            ),
+                        ),
+                    }
+                }
+
+                kvs.sort_by(|(ka, _), (kb, _)| ka.cmp(kb));
+                let ext: Vec<Validator> = kvs.into_iter().map(|(_k, v)| v).collect();
+                self.extend_components(ext, span);
+
+                res
+            }
+            Err(diag) => {
+                self.errors.push(*diag);
+                JsonSchema::Any
+            }
+        }
+    }
+}
+
+// a visitor of the entry file that handles calls like `parse.buildParsers<...>()`
+pub trait SpecialCallVisitor<'a, R: FileManager + 'a>: Visit {
+    fn state(&mut self) -> &mut ExtractorState<'a, R>;
+
+    // called with the name of the function, ie: `buildParsers`
+    fn extract_special_calls(&mut self, id: &Ident, n: &CallExpr);
+
+    fn visit_current_file(&mut self) -> Result<()> {
+        let file = self.state().get_current_file()?;
+        let module = file.module.module.clone();
+        self.visit_module(&module);
+        Ok(())
+    }
+
+    fn visit_special_call(&mut self, n: &CallExpr) {
+        match n.callee {
+            Callee::Super(_) => {}
+            Callee::Import(_) => {}
+            Callee::Expr(ref expr) => {
+                if let Expr::Ident(id) = &**expr {
+                    self.extract_special_calls(id, n)
+                }
+
+                if let Expr::Member(MemberExpr { prop, .. }) = &**expr {
+                    match prop {
+                        MemberProp::Ident(id) => self.extract_special_calls(id, n),
+                        MemberProp::PrivateName(_) => {}
+                        MemberProp::Computed(_) => {}
+                    }
+                }
+            }
+        }
+    }
+}
@@ -8,11 +8,13 @@ use crate::SymbolExport;
 use crate::SymbolExportDefault;
 use crate::SymbolsExportsModule;
 use crate::UnresolvedExport;
+use crate::{class_to_interface_decl, merge_interface_decls};
 use anyhow::Result;
 use std::collections::HashMap;
 use std::rc::Rc;
 use swc_atoms::JsWord;
 use swc_common::SourceMap;
+use swc_common::Span;
 use swc_common::Spanned;
 use swc_common::{FileName, SyntaxContext};
 use swc_ecma_ast::Decl;
@@ -22,24 +24,57 @@ use swc_ecma_ast::ExportDefaultExpr;
 use swc_ecma_ast::ExportNamedSpecifier;
 use swc_ecma_ast::ExportNamespaceSpecifier;
 use swc_ecma_ast::ExportSpecifier;
+use swc_ecma_ast::Expr;
+use swc_ecma_ast::FnExpr;
 use swc_ecma_ast::Ident;
 use swc_ecma_ast::ImportDefaultSpecifier;
 use swc_ecma_ast::ImportStarAsSpecifier;
+use swc_ecma_ast::ModuleDecl;
 use swc_ecma_ast::ModuleExportName;
+use swc_ecma_ast::ModuleItem;
 use swc_ecma_ast::NamedExport;
 use swc_ecma_ast::Pat;
+use swc_ecma_ast::Stmt;
 use swc_ecma_ast::TsEnumDecl;
+use swc_ecma_ast::TsExportAssignment;
+use swc_ecma_ast::TsImportEqualsDecl;
+use swc_ecma_ast::TsModuleDecl;
+use swc_ecma_ast::TsModuleName;
+use swc_ecma_ast::TsModuleRef;
+use swc_ecma_ast::TsNamespaceBody;
 use swc_ecma_ast::{
-    ImportDecl, ImportNamedSpecifier, ImportSpecifier, TsInterfaceDecl, TsTypeAliasDecl,
+    ImportDecl, ImportNamedSpecifier, ImportSpecifier, TsImportType, TsInterfaceDecl,
+    TsTypeAliasDecl,
 };
-use swc_ecma_visit::Visit;
+use swc_ecma_visit::{Visit, VisitWith};
 
 pub trait FsModuleResolver {
     fn resolve_import(&mut self, module_specifier: &str) -> Option<BffFileName>;
 }
+// an import that the resolver could not find, it might refer to a `declare module` block
+pub struct AmbientImport {
+    pub local: Ident,
+    pub module_specifier: JsWord,
+    // `None` for star imports
+    pub orig: Option<JsWord>,
+}
+// the blocks of a namespace that is declared more than once
+struct NamespaceGroup {
+    id: Ident,
+    exported: bool,
+    ambient: bool,
+    body: Vec<ModuleItem>,
+    spans: Vec<Span>,
+}
 pub struct ImportsVisitor<'a, R: FsModuleResolver> {
     pub resolver: &'a mut R,
     pub imports: HashMap<(JsWord, SyntaxContext), Rc<ImportReference>>,
+    pub import_types: HashMap<JsWord, BffFileName>,
+    pub namespaces: HashMap<(JsWord, SyntaxContext), Rc<ImportReference>>,
+    pub namespace_scopes: Vec<(Span, Rc<ImportReference>)>,
+    pub ambient_modules: HashMap<JsWord, Rc<ImportReference>>,
+    pub ambient_imports: Vec<AmbientImport>,
+    pub export_assignment: Option<Ident>,
     pub symbol_exports: SymbolsExportsModule,
     pub current_file: BffFileName,
     pub unresolved_exports: Vec<UnresolvedExport>,
@@ -49,6 +84,12 @@ impl<'a, R: FsModuleResolver> ImportsVisitor<'a, R> {
     pub fn from_file(current_file: BffFileName, resolver: &'a mut R) -> ImportsVisitor<'a, R> {
         ImportsVisitor {
             imports: HashMap::new(),
+            import_types: HashMap::new(),
+            namespaces: HashMap::new(),
+            namespace_scopes: Vec::new(),
+            ambient_modules: HashMap::new(),
+            ambient_imports: Vec::new(),
+            export_assignment: None,
             symbol_exports: SymbolsExportsModule::new(),
             current_file,
             unresolved_exports: Vec::new(),
@@ -64,15 +105,22 @@ impl<'a, R: FsModuleResolver> ImportsVisitor<'a, R> {
         let k = (local.sym.clone(), local.span.ctxt);
         let v = self.resolve_import(module_specifier);
 
-        if let Some(v) = v {
-            self.imports.insert(
-                k,
-                Rc::new(ImportReference::Named {
-                    orig: Rc::new(orig.clone()),
-                    file_name: v,
-                    span: local.span,
-                }),
-            );
+        match v {
+            Some(v) => {
+                self.imports.insert(
+                    k,
+                    Rc::new(ImportReference::Named {
+                        orig: Rc::new(orig.clone()),
+                        file_name: v,
+                        span: local.span,
+                    }),
+                );
+            }
+            None => self.ambient_imports.push(AmbientImport {
+                local: local.clone(),
+                module_specifier: module_specifier.into(),
+                orig: Some(orig.clone()),
+            }),
         }
     }
     fn insert_import_default(&mut self, local: &Ident, module_specifier: &str) {
@@ -88,15 +136,220 @@ impl<'a, R: FsModuleResolver> ImportsVisitor<'a, R> {
         let k = (local.sym.clone(), local.span.ctxt);
         let v = self.resolve_import(module_specifier);
 
-        if let Some(v) = v {
-            self.imports.insert(
-                k,
-                Rc::new(ImportReference::Star {
-                    file_name: v,
-                    span: local.span,
+        match v {
+            Some(v) => {
+                self.imports.insert(
+                    k,
+                    Rc::new(ImportReference::Star {
+                        file_name: v,
+                        span: local.span,
+                    }),
+                );
+            }
+            None => self.ambient_imports.push(AmbientImport {
+                local: local.clone(),
+                module_specifier: module_specifier.into(),
+                orig: None,
+            }),
+        }
+    }
+
+    // namespaces and `declare module` blocks are indexed as export tables of their own
+    pub fn index_namespaces(&mut self, items: &[ModuleItem], implicit_exports: bool) {
+        let mut groups: Vec<NamespaceGroup> = vec![];
+        for item in items {
+            let (decl, exported) = match item {
+                ModuleItem::Stmt(Stmt::Decl(Decl::TsModule(decl))) => (decl, implicit_exports),
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                    decl: Decl::TsModule(decl),
+                    ..
+                })) => (decl, true),
+                _ => continue,
+            };
+            if decl.global {
+                continue;
+            }
+            let body = decl
+                .body
+                .as_ref()
+                .map(namespace_body_items)
+                .unwrap_or_default();
+            match &decl.id {
+                TsModuleName::Ident(id) => {
+                    // declarations of ambient namespaces are implicitly exported
+                    let ambient = decl.declare || implicit_exports;
+                    let found = groups
+                        .iter_mut()
+                        .find(|it| it.id.sym == id.sym && it.id.span.ctxt == id.span.ctxt);
+                    match found {
+                        Some(group) => {
+                            group.exported |= exported;
+                            group.body.extend(body);
+                            group.spans.push(decl.span);
+                        }
+                        None => groups.push(NamespaceGroup {
+                            id: id.clone(),
+                            exported,
+                            ambient,
+                            body,
+                            spans: vec![decl.span],
+                        }),
+                    }
+                }
+                TsModuleName::Str(module_specifier) => {
+                    let exports = self.namespace_exports(&body, true);
+                    let reference = Rc::new(ImportReference::Namespace {
+                        file_name: self.current_file.clone(),
+                        exports: Rc::new(exports),
+                        span: decl.span,
+                    });
+                    self.namespace_scopes.push((decl.span, reference.clone()));
+                    self.ambient_modules
+                        .insert(module_specifier.value.clone(), reference);
+                }
+            }
+        }
+        for NamespaceGroup {
+            id,
+            exported,
+            ambient,
+            body,
+            spans,
+        } in groups
+        {
+            let exports = self.namespace_exports(&body, ambient);
+            let reference = Rc::new(ImportReference::Namespace {
+                file_name: self.current_file.clone(),
+                exports: Rc::new(exports),
+                span: id.span,
+            });
+            // the members exported by any block of a merged namespace are in scope in all of them
+            for span in spans {
+                self.namespace_scopes.push((span, reference.clone()));
+            }
+            if exported {
+                self.symbol_exports.insert_unknown(
+                    id.sym.clone(),
+                    Rc::new(SymbolExport::StarOfOtherFile {
+                        reference: reference.clone(),
+                        span: id.span,
+                    }),
+                );
+            }
+            self.namespaces.insert((id.sym, id.span.ctxt), reference);
+        }
+    }
+
+    // declaration files export all of their top-level declarations,
+    // unless they contain an explicit `export {}`
+    fn export_declarations(&mut self, items: &[ModuleItem]) {
+        let has_empty_export = items.iter().any(|it| {
+            matches!(
+                it,
+                ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport { specifiers, src: None, .. }))
+                    if specifiers.is_empty()
+            )
+        });
+        if has_empty_export {
+            return;
+        }
+        for item in items {
+            if let ModuleItem::Stmt(Stmt::Decl(decl)) = item {
+                if !matches!(decl, Decl::TsModule(_)) {
+                    self.visit_export_decl(&ExportDecl {
+                        span: decl.span(),
+                        decl: decl.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    // every top-level declaration, as if it was exported
+    fn declared_exports(&mut self, items: &[ModuleItem]) -> SymbolsExportsModule {
+        let mut inner = ImportsVisitor::from_file(self.current_file.clone(), &mut *self.resolver);
+        for item in items {
+            match item {
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. }))
+                | ModuleItem::Stmt(Stmt::Decl(decl)) => inner.visit_export_decl(&ExportDecl {
+                    span: decl.span(),
+                    decl: decl.clone(),
                 }),
-            );
+                _ => {}
+            }
+        }
+        inner.symbol_exports
+    }
+
+    // `export = x` replaces the exports of the module with `x`, importers of a namespace see its
+    // members
+    fn assign_exports(&mut self, id: &Ident, items: &[ModuleItem]) {
+        let declared = self.declared_exports(items);
+        let mut exports = SymbolsExportsModule::new();
+        if let Some(namespace) = self.namespaces.get(&(id.sym.clone(), id.span.ctxt)) {
+            if let ImportReference::Namespace {
+                exports: members, ..
+            } = namespace.as_ref()
+            {
+                exports.merge(members);
+            }
+        }
+        exports.assign(
+            declared.named_types.get(&id.sym).cloned(),
+            declared.named_values.get(&id.sym).cloned(),
+        );
+        self.symbol_exports = exports;
+    }
+
+    fn namespace_exports(
+        &mut self,
+        items: &[ModuleItem],
+        implicit_exports: bool,
+    ) -> SymbolsExportsModule {
+        let mut inner = ImportsVisitor::from_file(self.current_file.clone(), &mut *self.resolver);
+        inner.index_namespaces(items, implicit_exports);
+        for item in items {
+            match item {
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(decl)) => {
+                    inner.visit_export_decl(decl)
+                }
+                ModuleItem::Stmt(Stmt::Decl(decl)) if implicit_exports => {
+                    inner.visit_export_decl(&ExportDecl {
+                        span: decl.span(),
+                        decl: decl.clone(),
+                    })
+                }
+                _ => item.visit_with(&mut inner),
+            }
         }
+        self.imports.extend(inner.imports);
+        self.import_types.extend(inner.import_types);
+        self.namespaces.extend(inner.namespaces);
+        self.namespace_scopes.extend(inner.namespace_scopes);
+        self.ambient_modules.extend(inner.ambient_modules);
+        self.ambient_imports.extend(inner.ambient_imports);
+        inner.symbol_exports
+    }
+
+    fn export_interface_decl(&mut self, n: &TsInterfaceDecl) {
+        let TsInterfaceDecl { id, .. } = n;
+        let decl = match self
+            .symbol_exports
+            .named_types
+            .get(&id.sym)
+            .map(|it| it.as_ref())
+        {
+            Some(SymbolExport::TsInterfaceDecl { decl, .. }) => merge_interface_decls(decl, n),
+            _ => n.clone(),
+        };
+        self.symbol_exports.insert_type(
+            id.sym.clone(),
+            Rc::new(SymbolExport::TsInterfaceDecl {
+                decl: Rc::new(decl),
+                span: n.span,
+                original_file: self.current_file.clone(),
+            }),
+        );
     }
 }
 
@@ -114,27 +367,19 @@ impl<'a, R: FsModuleResolver> Visit for ImportsVisitor<'a, R> {
 
     fn visit_export_decl(&mut self, n: &ExportDecl) {
         match &n.decl {
-            Decl::TsInterface(n) => {
-                let TsInterfaceDecl { id, .. } = &**n;
-                self.symbol_exports.insert_type(
-                    id.sym.clone(),
-                    Rc::new(SymbolExport::TsInterfaceDecl {
-                        decl: Rc::new(*n.clone()),
-                        span: n.span,
-                        original_file: self.current_file.clone(),
-                    }),
-                );
-            }
+            Decl::TsInterface(n) => self.export_interface_decl(n),
+            Decl::Class(n) => self.export_interface_decl(&class_to_interface_decl(n)),
             Decl::TsEnum(decl) => {
                 let TsEnumDecl { id, .. } = &**decl;
-                self.symbol_exports.insert_type(
-                    id.sym.clone(),
-                    Rc::new(SymbolExport::TsEnumDecl {
-                        decl: Rc::new(*decl.clone()),
-                        span: decl.span,
-                        original_file: self.current_file.clone(),
-                    }),
-                );
+                // enums are both types and values
+                let export = Rc::new(SymbolExport::TsEnumDecl {
+                    decl: Rc::new(*decl.clone()),
+                    span: decl.span,
+                    original_file: self.current_file.clone(),
+                });
+                self.symbol_exports
+                    .insert_type(id.sym.clone(), export.clone());
+                self.symbol_exports.insert_value(id.sym.clone(), export);
             }
             Decl::TsTypeAlias(a) => {
                 let TsTypeAliasDecl {
@@ -162,6 +407,7 @@ impl<'a, R: FsModuleResolver> Visit for ImportsVisitor<'a, R> {
                             let name = it.sym.clone();
                             let export = Rc::new(SymbolExport::ValueExpr {
                                 expr: Rc::new(*expr.clone()),
+                                kind: Some(var_decl.kind),
                                 name: name.clone(),
                                 span: it.span,
                                 original_file: self.current_file.clone(),
@@ -187,8 +433,23 @@ impl<'a, R: FsModuleResolver> Visit for ImportsVisitor<'a, R> {
                 }
             }
 
-            Decl::TsModule(_) | Decl::Using(_) | Decl::Class(_) | Decl::Fn(_) => {}
+            Decl::Fn(fn_decl) => {
+                let name = fn_decl.ident.sym.clone();
+                let export = Rc::new(SymbolExport::ValueExpr {
+                    expr: Rc::new(Expr::Fn(FnExpr {
+                        ident: Some(fn_decl.ident.clone()),
+                        function: fn_decl.function.clone(),
+                    })),
+                    kind: None,
+                    name: name.clone(),
+                    span: fn_decl.ident.span,
+                    original_file: self.current_file.clone(),
+                });
+                self.symbol_exports.insert_value(name, export);
+            }
+            Decl::TsModule(_) | Decl::Using(_) => {}
         }
+        n.visit_children_with(self);
     }
 
     fn visit_named_export(&mut self, n: &NamedExport) {
@@ -268,6 +529,27 @@ impl<'a, R: FsModuleResolver> Visit for ImportsVisitor<'a, R> {
             self.symbol_exports.extend(file_name);
         }
     }
+    fn visit_ts_export_assignment(&mut self, n: &TsExportAssignment) {
+        if let Expr::Ident(id) = n.expr.as_ref() {
+            self.export_assignment = Some(id.clone());
+        }
+    }
+    fn visit_ts_import_equals_decl(&mut self, n: &TsImportEqualsDecl) {
+        // `import x = require("y")`
+        if let TsModuleRef::TsExternalModuleRef(r) = &n.module_ref {
+            let module_specifier = r.expr.value.to_string();
+            self.insert_import_star(&n.id, &module_specifier);
+        }
+    }
+    fn visit_ts_module_decl(&mut self, _n: &TsModuleDecl) {
+        // namespace bodies are indexed by `index_namespaces`
+    }
+    fn visit_ts_import_type(&mut self, n: &TsImportType) {
+        if let Some(file_name) = self.resolve_import(&n.arg.value) {
+            self.import_types.insert(n.arg.value.clone(), file_name);
+        }
+        n.visit_children_with(self);
+    }
     fn visit_import_decl(&mut self, node: &ImportDecl) {
         let module_specifier = node.src.value.to_string();
 
@@ -295,6 +577,69 @@ impl<'a, R: FsModuleResolver> Visit for ImportsVisitor<'a, R> {
     }
 }
 
+fn namespace_body_items(body: &TsNamespaceBody) -> Vec<ModuleItem> {
+    match body {
+        TsNamespaceBody::TsModuleBlock(block) => block.body.clone(),
+        // `namespace A.B {}` is `namespace A { export namespace B {} }`
+        TsNamespaceBody::TsNamespaceDecl(decl) => {
+            vec![ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                span: decl.span,
+                decl: Decl::TsModule(Box::new(TsModuleDecl {
+                    span: decl.span,
+                    declare: decl.declare,
+                    global: decl.global,
+                    id: TsModuleName::Ident(decl.id.clone()),
+                    body: Some(*decl.body.clone()),
+                })),
+            }))]
+        }
+    }
+}
+
+// imports from `declare module` blocks of the same file are bound as locals
+fn bind_ambient_import(
+    import: &AmbientImport,
+    module: &Rc<ImportReference>,
+    imports: &mut HashMap<(JsWord, SyntaxContext), Rc<ImportReference>>,
+    locals: &mut ParsedModuleLocals,
+) {
+    let k = (import.local.sym.clone(), import.local.span.ctxt);
+    let (orig, exports) = match (&import.orig, module.as_ref()) {
+        (None, _) => {
+            imports.insert(k, module.clone());
+            return;
+        }
+        (Some(orig), ImportReference::Namespace { exports, .. }) => (orig, exports),
+        (Some(_), _) => return,
+    };
+    let found = exports
+        .named_types
+        .get(orig)
+        .or_else(|| exports.named_values.get(orig))
+        .or_else(|| exports.named_unknown.get(orig));
+    match found.map(|it| it.as_ref()) {
+        Some(SymbolExport::TsType { ty, params, .. }) => {
+            locals.type_aliases.insert(k, (params.clone(), ty.clone()));
+        }
+        Some(SymbolExport::TsInterfaceDecl { decl, .. }) => {
+            locals.interfaces.insert(k, decl.clone());
+        }
+        Some(SymbolExport::TsEnumDecl { decl, .. }) => {
+            locals.enums.insert(k, decl.clone());
+        }
+        Some(SymbolExport::StarOfOtherFile { reference, .. }) => {
+            imports.insert(k, reference.clone());
+        }
+        Some(SymbolExport::ValueExpr { expr, kind, .. }) => {
+            locals.exprs.insert(k, (expr.clone(), *kind));
+        }
+        Some(SymbolExport::ExprDecl { ty, .. }) => {
+            locals.exprs_decls.insert(k, ty.clone());
+        }
+        Some(SymbolExport::SomethingOfOtherFile { .. }) | None => {}
+    }
+}
+
 pub fn parse_and_bind<R: FsModuleResolver>(
     resolver: &mut R,
     file_name: &BffFileName,
@@ -310,12 +655,34 @@ pub fn parse_and_bind<R: FsModuleResolver>(
 
     let mut v = ImportsVisitor::from_file(BffFileName::new(module.fm.name.to_string()), resolver);
     v.visit_module(&module.module);
+    let is_declaration_file = file_name.is_declaration_file();
+    if is_declaration_file {
+        v.export_declarations(&module.module.body);
+    }
+    v.index_namespaces(&module.module.body, is_declaration_file);
+    if let Some(id) = v.export_assignment.clone() {
+        v.assign_exports(&id, &module.module.body);
+    }
 
     let mut locals = ParserOfModuleLocals {
         content: ParsedModuleLocals::new(),
     };
     locals.visit_module(&module.module);
     locals.visit_module_item_list(&module.module.body);
+    locals.content.namespaces = v.namespaces;
+    locals.content.namespace_scopes = v.namespace_scopes;
+    // the other ones are looked up in the project when they are used
+    let mut ambient_imports = HashMap::new();
+    for import in v.ambient_imports {
+        match v.ambient_modules.get(&import.module_specifier) {
+            Some(ambient_module) => {
+                bind_ambient_import(&import, ambient_module, &mut v.imports, &mut locals.content)
+            }
+            None => {
+                ambient_imports.insert((import.local.sym.clone(), import.local.span.ctxt), import);
+            }
+        }
+    }
 
     let mut symbol_exports = v.symbol_exports;
     for unresolved in v.unresolved_exports {
@@ -336,14 +703,13 @@ pub fn parse_and_bind<R: FsModuleResolver>(
         }
 
         if let Some(enum_) = locals.content.enums.get(&k) {
-            symbol_exports.insert_type(
-                renamed,
-                Rc::new(SymbolExport::TsEnumDecl {
-                    decl: enum_.clone(),
-                    span: enum_.span(),
-                    original_file: file_name.clone(),
-                }),
-            );
+            let export = Rc::new(SymbolExport::TsEnumDecl {
+                decl: enum_.clone(),
+                span: enum_.span(),
+                original_file: file_name.clone(),
+            });
+            symbol_exports.insert_type(renamed.clone(), export.clone());
+            symbol_exports.insert_value(renamed, export);
             continue;
         }
 
@@ -358,6 +724,18 @@ pub fn parse_and_bind<R: FsModuleResolver>(
             );
             continue;
         }
+        if let Some(namespace) = locals.content.namespaces.get(&k) {
+            if let ImportReference::Namespace { span, .. } = namespace.as_ref() {
+                symbol_exports.insert_unknown(
+                    renamed,
+                    Rc::new(SymbolExport::StarOfOtherFile {
+                        reference: namespace.clone(),
+                        span: *span,
+                    }),
+                );
+                continue;
+            }
+        }
         if let Some(import) = v.imports.get(&k) {
             match &**import {
                 ImportReference::Named { orig, span, .. } => {
@@ -369,7 +747,7 @@ pub fn parse_and_bind<R: FsModuleResolver>(
                     symbol_exports.insert_unknown(renamed, it);
                     continue;
                 }
-                ImportReference::Star { span, .. } => {
+                ImportReference::Star { span, .. } | ImportReference::Namespace { span, .. } => {
                     symbol_exports.insert_unknown(
                         renamed,
                         Rc::new(SymbolExport::StarOfOtherFile {
@@ -390,9 +768,12 @@ pub fn parse_and_bind<R: FsModuleResolver>(
         module,
         symbol_exports,
         imports: v.imports,
+        import_types: v.import_types,
         comments,
         locals: locals.content,
         export_default: v.export_default,
+        ambient_modules: v.ambient_modules,
+        ambient_imports,
     });
     Ok(f)
 }
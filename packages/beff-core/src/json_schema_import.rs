@@ -0,0 +1,615 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use serde_json::{Map, Value};
+use swc_common::DUMMY_SP;
+
+use crate::ast::json::Json;
+use crate::ast::json_schema::{
+    CodecName, JsonSchema, JsonSchemaConst, Optionality, PLAIN_TIME_PATTERN,
+};
+use crate::diag::{Diagnostic, DiagnosticInfoMessage, Location};
+use crate::mock::MockOptions;
+use crate::parser_extractor::{BuiltDecoder, BuiltParsers, ParserExtractResult};
+use crate::print::printer::{ToWritableModules, WritableModules};
+use crate::schema_changes::print_ts_types;
+use crate::{BffFileName, ExtractResult, UnknownKeys, Validator};
+
+// Keywords that only document a schema and do not change what it accepts.
+const ANNOTATIONS: [&str; 10] = [
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+];
+
+const DEFINITIONS: [&str; 2] = ["$defs", "definitions"];
+
+// The inverse of the patterns written by `JsonFlatConverter`.
+const BIGINT_PATTERN: &str = "^-?[0-9]+$";
+
+pub struct JsonSchemaImportResult {
+    pub file_name: BffFileName,
+    pub validators: Vec<Validator>,
+    // `strict` when every object of the document forbids additional properties
+    pub unknown_keys: UnknownKeys,
+    pub errors: Vec<Diagnostic>,
+}
+
+impl JsonSchemaImportResult {
+    pub fn to_ts_types(&self) -> String {
+        print_ts_types(
+            self.validators
+                .iter()
+                .map(|it| (it.name.clone(), it.schema.to_ts_type()))
+                .collect(),
+        )
+    }
+
+    // A single `buildParsers` call that exports every imported type.
+    pub fn to_extract_result(self) -> ExtractResult {
+        let built_decoders = self
+            .validators
+            .iter()
+            .map(|it| BuiltDecoder {
+                exported_name: it.name.clone(),
+                schema: JsonSchema::Ref(it.name.clone()),
+            })
+            .collect();
+        ExtractResult {
+            parser: Some(ParserExtractResult {
+                errors: self.errors,
+                entry_file_names: vec![self.file_name.clone()],
+                validators: self.validators,
+                built_parsers: vec![BuiltParsers {
+                    file_name: self.file_name,
+                    span: DUMMY_SP,
+                    index: 0,
+                    id: None,
+                    built_decoders,
+                    unknown_keys: self.unknown_keys,
+                }],
+                counter: 0,
+            }),
+            schema: None,
+            routes: None,
+        }
+    }
+}
+
+impl ToWritableModules for JsonSchemaImportResult {
+    fn to_module(self) -> Result<WritableModules> {
+        self.to_extract_result().to_module()
+    }
+
+    fn to_module_with_examples(self, examples: &MockOptions) -> Result<WritableModules> {
+        self.to_extract_result().to_module_with_examples(examples)
+    }
+}
+
+fn sanitize(name: &str) -> String {
+    let mut acc: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '$' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if acc.is_empty() || acc.starts_with(|c: char| c.is_ascii_digit()) {
+        acc.insert(0, '_');
+    }
+    acc
+}
+
+fn pointer_segment(it: &str) -> String {
+    it.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape_pointer_segment(it: &str) -> String {
+    it.replace("~1", "/").replace("~0", "~")
+}
+
+struct ImportCtx<'a> {
+    file_name: &'a BffFileName,
+    root_name: String,
+    // definition name to type name
+    defs: BTreeMap<String, String>,
+    root_is_referenced: bool,
+    // the pointers of the objects without an `additionalProperties` schema, by whether they
+    // forbid additional properties
+    closed_objects: Vec<String>,
+    open_objects: Vec<String>,
+    errors: Vec<Diagnostic>,
+}
+
+impl<'a> ImportCtx<'a> {
+    fn push_error(&mut self, message: DiagnosticInfoMessage) {
+        self.errors.push(
+            Location::unknown(self.file_name)
+                .to_info(message)
+                .to_diag(None),
+        );
+    }
+
+    fn invalid(&mut self, pointer: &str, reason: &str) -> JsonSchema {
+        self.push_error(DiagnosticInfoMessage::InvalidJsonSchema(
+            pointer.to_string(),
+            reason.to_string(),
+        ));
+        JsonSchema::Any
+    }
+
+    fn unsupported(&mut self, keyword: &str, pointer: &str) -> JsonSchema {
+        self.push_error(DiagnosticInfoMessage::UnsupportedJsonSchemaKeyword(
+            keyword.to_string(),
+            pointer.to_string(),
+        ));
+        JsonSchema::Any
+    }
+
+    fn reference(&mut self, r: &Value, pointer: &str) -> JsonSchema {
+        let Value::String(r) = r else {
+            return self.invalid(pointer, "'$ref' should be a string");
+        };
+        if r == "#" {
+            self.root_is_referenced = true;
+            return JsonSchema::Ref(self.root_name.clone());
+        }
+        let def = DEFINITIONS
+            .iter()
+            .find_map(|it| r.strip_prefix(&format!("#/{it}/")))
+            .map(unescape_pointer_segment)
+            .and_then(|it| self.defs.get(&it).cloned());
+        match def {
+            Some(name) => JsonSchema::Ref(name),
+            None => {
+                self.push_error(DiagnosticInfoMessage::CannotResolveJsonSchemaRef(
+                    r.to_string(),
+                ));
+                JsonSchema::Any
+            }
+        }
+    }
+
+    fn constant(&mut self, it: &Value, pointer: &str) -> JsonSchema {
+        match JsonSchemaConst::from_json(&Json::from_serde(it)) {
+            Ok(it) => JsonSchema::Const(it),
+            Err(_) => self.unsupported("const", pointer),
+        }
+    }
+
+    fn schema_list(
+        &mut self,
+        obj: &Map<String, Value>,
+        key: &str,
+        pointer: &str,
+    ) -> Vec<JsonSchema> {
+        match &obj[key] {
+            Value::Array(vs) => vs
+                .iter()
+                .enumerate()
+                .map(|(idx, it)| self.convert(it, &format!("{pointer}/{key}/{idx}")))
+                .collect(),
+            _ => vec![self.invalid(&format!("{pointer}/{key}"), "expected an array of schemas")],
+        }
+    }
+
+    fn string(
+        &mut self,
+        obj: &Map<String, Value>,
+        used: &mut BTreeSet<String>,
+        pointer: &str,
+    ) -> JsonSchema {
+        let mut acc = vec![];
+        if let Some(format) = obj.get("format") {
+            used.insert("format".into());
+            acc.push(match format {
+                Value::String(f) if f == "date-time" => JsonSchema::Codec(CodecName::ISO8061),
+                Value::String(f) if f == "date" => JsonSchema::Codec(CodecName::PlainDate),
+                Value::String(f) if f == "duration" => JsonSchema::Codec(CodecName::Duration),
+                Value::String(f) => JsonSchema::StringWithFormat(f.clone()),
+                _ => self.invalid(&format!("{pointer}/format"), "expected a string"),
+            });
+        }
+        if let Some(pattern) = obj.get("pattern") {
+            used.insert("pattern".into());
+            acc.push(match pattern {
+                Value::String(p) if p == BIGINT_PATTERN => JsonSchema::Codec(CodecName::BigInt),
+                Value::String(p) if p == PLAIN_TIME_PATTERN => {
+                    JsonSchema::Codec(CodecName::PlainTime)
+                }
+                _ => self.unsupported("pattern", pointer),
+            });
+        }
+        if let Some(encoding) = obj.get("contentEncoding") {
+            used.insert("contentEncoding".into());
+            acc.push(match encoding {
+                Value::String(e) if e == "base64" => JsonSchema::Codec(CodecName::Base64),
+                Value::String(e) if e == "base64url" => JsonSchema::Codec(CodecName::Base64Url),
+                _ => self.unsupported("contentEncoding", pointer),
+            });
+        }
+        match acc.len() {
+            0 => JsonSchema::String,
+            _ => JsonSchema::all_of(acc),
+        }
+    }
+
+    fn object(
+        &mut self,
+        obj: &Map<String, Value>,
+        used: &mut BTreeSet<String>,
+        pointer: &str,
+    ) -> JsonSchema {
+        let mut required = BTreeSet::new();
+        if let Some(it) = obj.get("required") {
+            used.insert("required".into());
+            match it {
+                Value::Array(vs) if vs.iter().all(|it| it.is_string()) => {
+                    required.extend(vs.iter().filter_map(|it| it.as_str()).map(String::from));
+                }
+                _ => {
+                    self.invalid(
+                        &format!("{pointer}/required"),
+                        "expected an array of strings",
+                    );
+                }
+            }
+        }
+        let mut vs = BTreeMap::new();
+        if let Some(it) = obj.get("properties") {
+            used.insert("properties".into());
+            match it {
+                Value::Object(props) => {
+                    for (k, v) in props {
+                        let child = format!("{pointer}/properties/{}", pointer_segment(k));
+                        let schema = self.convert(v, &child);
+                        let schema = match v.get("default") {
+                            _ if required.contains(k) => Optionality::Required(schema),
+                            Some(it) => Optionality::Default(schema, Json::from_serde(it)),
+                            None => Optionality::Optional(schema),
+                        };
+                        vs.insert(k.clone(), schema);
+                    }
+                }
+                _ => {
+                    self.invalid(&format!("{pointer}/properties"), "expected an object");
+                }
+            }
+        }
+        for k in required {
+            vs.entry(k)
+                .or_insert(Optionality::Required(JsonSchema::Any));
+        }
+        // `false` is the unknown keys policy of the decoders, see `import_json_schema`
+        let rest = match obj.get("additionalProperties") {
+            Some(Value::Bool(false)) => {
+                self.closed_objects.push(pointer.to_string());
+                None
+            }
+            Some(Value::Bool(true)) | None => {
+                self.open_objects.push(pointer.to_string());
+                None
+            }
+            Some(it) => Some(
+                self.convert(it, &format!("{pointer}/additionalProperties"))
+                    .into(),
+            ),
+        };
+        used.insert("additionalProperties".into());
+        JsonSchema::Object {
+            vs,
+            rest,
+            pattern_vs: BTreeMap::new(),
+        }
+    }
+
+    fn array(
+        &mut self,
+        obj: &Map<String, Value>,
+        used: &mut BTreeSet<String>,
+        pointer: &str,
+    ) -> JsonSchema {
+        // draft-07 writes tuples as an array of `items` followed by `additionalItems`
+        let (prefix_key, rest_key) = match obj.get("items") {
+            Some(Value::Array(_)) => ("items", "additionalItems"),
+            _ => ("prefixItems", "items"),
+        };
+        let rest = |ctx: &mut Self, used: &mut BTreeSet<String>| -> Option<JsonSchema> {
+            used.insert(rest_key.into());
+            match obj.get(rest_key) {
+                None | Some(Value::Bool(true)) => Some(JsonSchema::Any),
+                Some(Value::Bool(false)) => None,
+                Some(it) => Some(ctx.convert(it, &format!("{pointer}/{rest_key}"))),
+            }
+        };
+        let (schema, min_len, max_len) = if obj.contains_key(prefix_key) {
+            used.insert(prefix_key.into());
+            let mut prefix_items = self.schema_list(obj, prefix_key, pointer);
+            let items = rest(self, used);
+            let len = prefix_items.len() as u64;
+            let max_len = if items.is_some() { None } else { Some(len) };
+            // the items after `minItems` are optional
+            let required_len = obj
+                .get("minItems")
+                .and_then(Value::as_u64)
+                .map_or(len, |it| it.min(len));
+            let optional_items = prefix_items.split_off(required_len as usize);
+            let schema = JsonSchema::Tuple {
+                prefix_items,
+                optional_items,
+                items: items.map(Box::new),
+                suffix_items: vec![],
+            };
+            (schema, required_len, max_len)
+        } else {
+            match rest(self, used) {
+                Some(items) => (JsonSchema::Array(items.into()), 0, None),
+                // `items: false` only accepts the empty array
+                None => {
+                    let schema = JsonSchema::tuple(vec![], None);
+                    (schema, 0, Some(0))
+                }
+            }
+        };
+        // bounds that the tuple already implies are fine, anything else cannot be checked
+        if let Some(min) = obj.get("minItems") {
+            used.insert("minItems".into());
+            let implied = min.as_u64().is_some_and(|it| it <= min_len);
+            if !implied {
+                self.unsupported("minItems", pointer);
+            }
+        }
+        if let Some(max) = obj.get("maxItems") {
+            used.insert("maxItems".into());
+            let implied = max_len
+                .zip(max.as_u64())
+                .is_some_and(|(len, max)| max >= len);
+            if !implied {
+                self.unsupported("maxItems", pointer);
+            }
+        }
+        schema
+    }
+
+    fn typed(
+        &mut self,
+        ty: &str,
+        obj: &Map<String, Value>,
+        used: &mut BTreeSet<String>,
+        pointer: &str,
+    ) -> JsonSchema {
+        match ty {
+            "string" => self.string(obj, used, pointer),
+            "number" => JsonSchema::Number,
+            // there is no integer type, and widening it to a number would accept fractions
+            "integer" => {
+                self.push_error(DiagnosticInfoMessage::UnsupportedJsonSchemaInteger(
+                    pointer.to_string(),
+                ));
+                JsonSchema::Number
+            }
+            "boolean" => JsonSchema::Boolean,
+            "null" => JsonSchema::Null,
+            "object" => self.object(obj, used, pointer),
+            "array" => self.array(obj, used, pointer),
+            _ => self.invalid(&format!("{pointer}/type"), &format!("unknown type '{ty}'")),
+        }
+    }
+
+    fn types(&mut self, obj: &Map<String, Value>, pointer: &str) -> Vec<String> {
+        let mut types: Vec<String> = match obj.get("type") {
+            Some(Value::String(it)) => vec![it.clone()],
+            Some(Value::Array(vs)) if vs.iter().all(|it| it.is_string()) => vs
+                .iter()
+                .filter_map(|it| it.as_str())
+                .map(String::from)
+                .collect(),
+            Some(_) => {
+                self.invalid(
+                    &format!("{pointer}/type"),
+                    "expected a string or an array of strings",
+                );
+                vec![]
+            }
+            None => {
+                let infer = [
+                    (
+                        "object",
+                        ["properties", "required", "additionalProperties"].as_slice(),
+                    ),
+                    (
+                        "array",
+                        ["items", "prefixItems", "additionalItems"].as_slice(),
+                    ),
+                    (
+                        "string",
+                        ["format", "pattern", "contentEncoding"].as_slice(),
+                    ),
+                ];
+                infer
+                    .iter()
+                    .filter(|(_, keys)| keys.iter().any(|it| obj.contains_key(*it)))
+                    .map(|(ty, _)| ty.to_string())
+                    .collect()
+            }
+        };
+        // OpenAPI 3.0
+        if obj.get("nullable") == Some(&Value::Bool(true)) && !types.is_empty() {
+            types.push("null".into());
+        }
+        types
+    }
+
+    fn convert_object(&mut self, obj: &Map<String, Value>, pointer: &str) -> JsonSchema {
+        let mut used: BTreeSet<String> = ANNOTATIONS.iter().map(|it| it.to_string()).collect();
+        used.insert("type".into());
+        used.insert("nullable".into());
+        if pointer == "#" {
+            used.extend(DEFINITIONS.iter().map(|it| it.to_string()));
+        }
+        let mut acc = vec![];
+        if let Some(r) = obj.get("$ref") {
+            used.insert("$ref".into());
+            acc.push(self.reference(r, pointer));
+        }
+        if let Some(c) = obj.get("const") {
+            used.insert("const".into());
+            acc.push(self.constant(c, pointer));
+        }
+        if let Some(e) = obj.get("enum") {
+            used.insert("enum".into());
+            acc.push(match e {
+                Value::Array(vs) => {
+                    let vs = vs.iter().map(|it| self.constant(it, pointer)).collect();
+                    JsonSchema::any_of(vs)
+                }
+                _ => self.invalid(&format!("{pointer}/enum"), "expected an array"),
+            });
+        }
+        let types = self.types(obj, pointer);
+        if !types.is_empty() {
+            let vs = types
+                .iter()
+                .map(|ty| self.typed(ty, obj, &mut used, pointer))
+                .collect();
+            acc.push(JsonSchema::any_of(vs));
+        }
+        // `oneOf` is not exclusive here, its members are usually disjoint anyway
+        for key in ["anyOf", "oneOf"] {
+            if obj.contains_key(key) {
+                used.insert(key.into());
+                let vs = self.schema_list(obj, key, pointer);
+                acc.push(JsonSchema::any_of(vs));
+            }
+        }
+        if obj.contains_key("allOf") {
+            used.insert("allOf".into());
+            acc.extend(self.schema_list(obj, "allOf", pointer));
+        }
+        for k in obj.keys() {
+            if !used.contains(k) {
+                self.unsupported(k, pointer);
+            }
+        }
+        match acc.len() {
+            0 => JsonSchema::Any,
+            _ => JsonSchema::all_of(acc),
+        }
+    }
+
+    fn convert(&mut self, schema: &Value, pointer: &str) -> JsonSchema {
+        match schema {
+            Value::Bool(true) => JsonSchema::Any,
+            Value::Bool(false) => self.unsupported("false", pointer),
+            Value::Object(obj) => self.convert_object(obj, pointer),
+            _ => self.invalid(pointer, "a schema should be an object or a boolean"),
+        }
+    }
+}
+
+// Reads a JSON Schema document (draft-07 or 2020-12). The document is named `root_name`, and each
+// of its `$defs` (or `definitions`) becomes a type of its own.
+pub fn import_json_schema(
+    file_name: &BffFileName,
+    root_name: &str,
+    content: &str,
+) -> JsonSchemaImportResult {
+    let mut ctx = ImportCtx {
+        file_name,
+        root_name: sanitize(root_name),
+        defs: BTreeMap::new(),
+        root_is_referenced: false,
+        closed_objects: vec![],
+        open_objects: vec![],
+        errors: vec![],
+    };
+    let mut validators = vec![];
+    let root: Value = match serde_json::from_str(content) {
+        Ok(it) => it,
+        Err(e) => {
+            ctx.invalid("#", &e.to_string());
+            return JsonSchemaImportResult {
+                file_name: file_name.clone(),
+                validators,
+                unknown_keys: UnknownKeys::default(),
+                errors: ctx.errors,
+            };
+        }
+    };
+
+    let mut defs = vec![];
+    let mut taken = BTreeSet::from([ctx.root_name.clone()]);
+    if let Value::Object(obj) = &root {
+        for key in DEFINITIONS {
+            match obj.get(key) {
+                Some(Value::Object(vs)) => {
+                    for (k, v) in vs {
+                        let base = sanitize(k);
+                        let mut name = base.clone();
+                        let mut n = 1;
+                        while taken.contains(&name) {
+                            name = format!("{base}${n}");
+                            n += 1;
+                        }
+                        taken.insert(name.clone());
+                        ctx.defs.insert(k.clone(), name.clone());
+                        defs.push((format!("#/{key}/{}", pointer_segment(k)), name, v));
+                    }
+                }
+                Some(_) => {
+                    ctx.invalid(&format!("#/{key}"), "expected an object");
+                }
+                None => {}
+            }
+        }
+    }
+
+    let root_schema = ctx.convert(&root, "#");
+    for (pointer, name, v) in defs {
+        let schema = ctx.convert(v, &pointer);
+        validators.push(Validator { name, schema });
+    }
+    // a document that only holds definitions is not a type itself
+    let only_definitions = matches!(&root, Value::Object(obj) if obj
+        .keys()
+        .all(|k| DEFINITIONS.contains(&k.as_str()) || ANNOTATIONS.contains(&k.as_str())));
+    if !only_definitions || ctx.root_is_referenced {
+        validators.insert(
+            0,
+            Validator {
+                name: ctx.root_name.clone(),
+                schema: root_schema,
+            },
+        );
+    }
+    // The imported decoders are a single `buildParsers` call, which has one unknown keys policy:
+    // it is `strict` when every object forbids additional properties.
+    let unknown_keys = if ctx.closed_objects.is_empty() {
+        UnknownKeys::default()
+    } else if ctx.open_objects.is_empty() {
+        UnknownKeys::Strict
+    } else {
+        for pointer in std::mem::take(&mut ctx.closed_objects) {
+            ctx.push_error(DiagnosticInfoMessage::MixedJsonSchemaAdditionalProperties(
+                pointer,
+            ));
+        }
+        UnknownKeys::default()
+    };
+    JsonSchemaImportResult {
+        file_name: file_name.clone(),
+        validators,
+        unknown_keys,
+        errors: ctx.errors,
+    }
+}
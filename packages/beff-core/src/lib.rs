@@ -1,25 +1,38 @@
 pub mod ast;
 pub mod diag;
 pub mod emit;
+pub mod extractor;
 pub mod import_resolver;
+pub mod json_schema_import;
+pub mod mock;
+pub mod module_resolver;
 pub mod parse;
 pub mod parser_extractor;
 pub mod print;
+pub mod qualified_name;
+pub mod route_extractor;
 pub mod schema_changes;
 pub mod schema_extractor;
 pub mod subtyping;
 pub mod sym_reference;
 pub mod type_to_schema;
+pub mod validate;
 pub mod wasm_diag;
 
+pub use mock::mock;
+pub use validate::validate;
+
 use crate::ast::{
     json::Json,
     json_schema::{JsonFlatConverter, JsonSchema},
 };
 use core::fmt;
 use diag::Diagnostic;
+use import_resolver::AmbientImport;
 use parser_extractor::extract_parser;
 use parser_extractor::ParserExtractResult;
+use route_extractor::extract_routes;
+use route_extractor::RoutesExtractResult;
 use schema_extractor::SchemaExtractResult;
 use serde::Deserialize;
 use serde::Serialize;
@@ -31,14 +44,21 @@ use swc_atoms::JsWord;
 use swc_common::SourceFile;
 use swc_common::SourceMap;
 use swc_common::Span;
+use swc_common::Spanned;
 use swc_common::SyntaxContext;
 use swc_ecma_ast::Decl;
 use swc_ecma_ast::Expr;
+use swc_ecma_ast::FnExpr;
 use swc_ecma_ast::ModuleItem;
 use swc_ecma_ast::Pat;
 use swc_ecma_ast::Stmt;
 use swc_ecma_ast::TsEnumDecl;
 use swc_ecma_ast::TsTypeParamDecl;
+use swc_ecma_ast::VarDeclKind;
+use swc_ecma_ast::{
+    Accessibility, ClassDecl, ClassMember, Lit, ParamOrTsParamProp, PropName, TsExprWithTypeArgs,
+    TsInterfaceBody, TsParamPropParam, TsPropertySignature, TsTypeElement,
+};
 use swc_ecma_ast::{Module, TsType};
 use swc_ecma_ast::{TsInterfaceDecl, TsTypeAliasDecl};
 use swc_ecma_visit::Visit;
@@ -65,6 +85,8 @@ pub enum SymbolExport {
     },
     ValueExpr {
         expr: Rc<Expr>,
+        // `None` for function declarations
+        kind: Option<VarDeclKind>,
         name: JsWord,
         span: Span,
         original_file: BffFileName,
@@ -122,6 +144,11 @@ impl BffFileName {
     pub fn as_str(&self) -> &str {
         self.0.as_str()
     }
+    pub fn is_declaration_file(&self) -> bool {
+        [".d.ts", ".d.mts", ".d.cts"]
+            .iter()
+            .any(|ext| self.0.ends_with(ext))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -138,6 +165,11 @@ pub enum ImportReference {
     Default {
         file_name: BffFileName,
     },
+    Namespace {
+        file_name: BffFileName,
+        exports: Rc<SymbolsExportsModule>,
+        span: Span,
+    },
 }
 
 impl ImportReference {
@@ -146,6 +178,33 @@ impl ImportReference {
             ImportReference::Named { file_name, .. } => file_name,
             ImportReference::Star { file_name, .. } => file_name,
             ImportReference::Default { file_name, .. } => file_name,
+            ImportReference::Namespace { file_name, .. } => file_name,
+        }
+    }
+    pub fn get_type_export<R: FileManager>(
+        &self,
+        name: &JsWord,
+        files: &mut R,
+    ) -> Option<Rc<SymbolExport>> {
+        match self {
+            ImportReference::Namespace { exports, .. } => exports.get_type(name, files),
+            _ => {
+                let file = files.get_or_fetch_file(self.file_name())?;
+                file.symbol_exports.get_type(name, files)
+            }
+        }
+    }
+    pub fn get_value_export<R: FileManager>(
+        &self,
+        name: &JsWord,
+        files: &mut R,
+    ) -> Option<Rc<SymbolExport>> {
+        match self {
+            ImportReference::Namespace { exports, .. } => exports.get_value(name, files),
+            _ => {
+                let file = files.get_or_fetch_file(self.file_name())?;
+                file.symbol_exports.get_value(name, files)
+            }
         }
     }
 }
@@ -157,6 +216,10 @@ pub struct SymbolsExportsModule {
     named_unknown: HashMap<JsWord, Rc<SymbolExport>>,
 
     extends: Vec<BffFileName>,
+
+    // the target of `export =`, as a type and as a value
+    assigned_type: Option<Rc<SymbolExport>>,
+    assigned_value: Option<Rc<SymbolExport>>,
 }
 impl Default for SymbolsExportsModule {
     fn default() -> Self {
@@ -170,6 +233,8 @@ impl SymbolsExportsModule {
             named_values: HashMap::new(),
             named_unknown: HashMap::new(),
             extends: Vec::new(),
+            assigned_type: None,
+            assigned_value: None,
         }
     }
     pub fn insert_value(&mut self, name: JsWord, export: Rc<SymbolExport>) {
@@ -222,9 +287,39 @@ impl SymbolsExportsModule {
         known.or_else(|| self.named_unknown.get(name).cloned())
     }
 
+    pub fn assign(
+        &mut self,
+        assigned_type: Option<Rc<SymbolExport>>,
+        assigned_value: Option<Rc<SymbolExport>>,
+    ) {
+        self.assigned_type = assigned_type;
+        self.assigned_value = assigned_value;
+    }
+
+    pub fn get_assigned(&self, is_type: bool) -> Option<Rc<SymbolExport>> {
+        if is_type {
+            self.assigned_type.clone()
+        } else {
+            self.assigned_value.clone()
+        }
+    }
+
     pub fn extend(&mut self, other: BffFileName) {
         self.extends.push(other);
     }
+
+    pub fn merge(&mut self, other: &SymbolsExportsModule) {
+        for (k, v) in &other.named_types {
+            self.named_types.insert(k.clone(), v.clone());
+        }
+        for (k, v) in &other.named_values {
+            self.named_values.insert(k.clone(), v.clone());
+        }
+        for (k, v) in &other.named_unknown {
+            self.named_unknown.insert(k.clone(), v.clone());
+        }
+        self.extends.extend(other.extends.iter().cloned());
+    }
 }
 
 pub struct SymbolExportDefault {
@@ -237,9 +332,14 @@ pub struct ParsedModule {
     pub locals: ParsedModuleLocals,
     pub module: BffModuleData,
     pub imports: HashMap<(JsWord, SyntaxContext), Rc<ImportReference>>,
+    pub import_types: HashMap<JsWord, BffFileName>,
     pub comments: SwcComments,
     pub symbol_exports: SymbolsExportsModule,
     pub export_default: Option<Rc<SymbolExportDefault>>,
+    // the `declare module "x"` blocks of the file
+    pub ambient_modules: HashMap<JsWord, Rc<ImportReference>>,
+    // imports that are not files, they refer to a `declare module` block of the project
+    pub ambient_imports: HashMap<(JsWord, SyntaxContext), AmbientImport>,
 }
 
 type TypeAliasMap = HashMap<(JsWord, SyntaxContext), (Option<Rc<TsTypeParamDecl>>, Rc<TsType>)>;
@@ -250,8 +350,11 @@ pub struct ParsedModuleLocals {
     pub interfaces: HashMap<(JsWord, SyntaxContext), Rc<TsInterfaceDecl>>,
     pub enums: HashMap<(JsWord, SyntaxContext), Rc<TsEnumDecl>>,
 
-    pub exprs: HashMap<(JsWord, SyntaxContext), Rc<Expr>>,
+    pub exprs: HashMap<(JsWord, SyntaxContext), (Rc<Expr>, Option<VarDeclKind>)>,
     pub exprs_decls: HashMap<(JsWord, SyntaxContext), Rc<TsType>>,
+    pub namespaces: HashMap<(JsWord, SyntaxContext), Rc<ImportReference>>,
+    // the source range of each namespace block, with the exports of the merged namespace
+    pub namespace_scopes: Vec<(Span, Rc<ImportReference>)>,
 }
 impl ParsedModuleLocals {
     pub fn new() -> ParsedModuleLocals {
@@ -261,6 +364,8 @@ impl ParsedModuleLocals {
             enums: HashMap::new(),
             exprs: HashMap::new(),
             exprs_decls: HashMap::new(),
+            namespaces: HashMap::new(),
+            namespace_scopes: Vec::new(),
         }
     }
 }
@@ -289,6 +394,19 @@ impl ParserOfModuleLocals {
         for it in it {
             match it {
                 ModuleItem::Stmt(Stmt::Decl(decl)) => {
+                    // functions are stored as expressions, so that call results can be typed
+                    if let Decl::Fn(fn_decl) = decl {
+                        self.content.exprs.insert(
+                            (fn_decl.ident.sym.clone(), fn_decl.ident.span.ctxt),
+                            (
+                                Rc::new(Expr::Fn(FnExpr {
+                                    ident: Some(fn_decl.ident.clone()),
+                                    function: fn_decl.function.clone(),
+                                })),
+                                None,
+                            ),
+                        );
+                    }
                     // add expr to self.content
                     if let Decl::Var(var_decl) = decl {
                         for it in &var_decl.decls {
@@ -296,7 +414,7 @@ impl ParserOfModuleLocals {
                                 if let Pat::Ident(id) = &it.name {
                                     self.content.exprs.insert(
                                         (id.sym.clone(), id.span.ctxt),
-                                        Rc::new(*expr.clone()),
+                                        (Rc::new(*expr.clone()), Some(var_decl.kind)),
                                     );
                                 }
                             }
@@ -339,9 +457,16 @@ impl Visit for ParserOfModuleLocals {
     }
     fn visit_ts_interface_decl(&mut self, n: &TsInterfaceDecl) {
         let TsInterfaceDecl { id, .. } = n;
-        self.content
-            .interfaces
-            .insert((id.sym.clone(), id.span.ctxt), Rc::new(n.clone()));
+        let k = (id.sym.clone(), id.span.ctxt);
+        let decl = match self.content.interfaces.get(&k) {
+            Some(existing) => merge_interface_decls(existing, n),
+            None => n.clone(),
+        };
+        self.content.interfaces.insert(k, Rc::new(decl));
+    }
+    fn visit_class_decl(&mut self, n: &ClassDecl) {
+        let intf = class_to_interface_decl(n);
+        self.visit_ts_interface_decl(&intf);
     }
 
     fn visit_ts_enum_decl(&mut self, n: &swc_ecma_ast::TsEnumDecl) {
@@ -352,54 +477,222 @@ impl Visit for ParserOfModuleLocals {
     }
 }
 
+// interfaces with the same name are merged, as in TypeScript declaration merging
+pub fn merge_interface_decls(a: &TsInterfaceDecl, b: &TsInterfaceDecl) -> TsInterfaceDecl {
+    let mut merged = a.clone();
+    merged.extends.extend(b.extends.iter().cloned());
+    merged.body.body.extend(b.body.body.iter().cloned());
+    if merged.type_params.is_none() {
+        merged.type_params = b.type_params.clone();
+    }
+    merged
+}
+
+// classes are used as types through their public instance fields
+pub fn class_to_interface_decl(n: &ClassDecl) -> TsInterfaceDecl {
+    let is_public = |it: &Option<Accessibility>| {
+        !matches!(
+            it,
+            Some(Accessibility::Private) | Some(Accessibility::Protected)
+        )
+    };
+    // fields without an annotation keep their initializer, their type is inferred from it
+    let property = |span, key: Expr, computed, optional, readonly, type_ann, init| {
+        TsTypeElement::TsPropertySignature(TsPropertySignature {
+            span,
+            readonly,
+            key: Box::new(key),
+            computed,
+            optional,
+            init,
+            params: vec![],
+            type_ann,
+            type_params: None,
+        })
+    };
+    let mut body = vec![];
+    for member in &n.class.body {
+        match member {
+            ClassMember::ClassProp(prop) if !prop.is_static && is_public(&prop.accessibility) => {
+                let (key, computed) = match &prop.key {
+                    PropName::Ident(id) => (Expr::Ident(id.clone()), false),
+                    PropName::Str(st) => (Expr::Lit(Lit::Str(st.clone())), false),
+                    PropName::Num(num) => (Expr::Lit(Lit::Num(num.clone())), false),
+                    PropName::BigInt(num) => (Expr::Lit(Lit::BigInt(num.clone())), false),
+                    PropName::Computed(c) => (*c.expr.clone(), true),
+                };
+                body.push(property(
+                    prop.span,
+                    key,
+                    computed,
+                    prop.is_optional,
+                    prop.readonly,
+                    prop.type_ann.clone(),
+                    prop.value.clone(),
+                ));
+            }
+            // `constructor(public id: string)` declares an instance field
+            ClassMember::Constructor(ctor) => {
+                for param in &ctor.params {
+                    if let ParamOrTsParamProp::TsParamProp(param) = param {
+                        if !is_public(&param.accessibility) {
+                            continue;
+                        }
+                        let (id, init) = match &param.param {
+                            TsParamPropParam::Ident(id) => (id, None),
+                            TsParamPropParam::Assign(assign) => match assign.left.as_ref() {
+                                Pat::Ident(id) => (id, Some(assign.right.clone())),
+                                _ => continue,
+                            },
+                        };
+                        body.push(property(
+                            param.span,
+                            Expr::Ident(id.id.clone()),
+                            false,
+                            id.id.optional,
+                            param.readonly,
+                            id.type_ann.clone(),
+                            init,
+                        ));
+                    }
+                }
+            }
+            ClassMember::TsIndexSignature(sig) if !sig.is_static => {
+                body.push(TsTypeElement::TsIndexSignature(sig.clone()));
+            }
+            _ => {}
+        }
+    }
+    TsInterfaceDecl {
+        span: n.class.span,
+        id: n.ident.clone(),
+        declare: n.declare,
+        type_params: n.class.type_params.clone(),
+        extends: n
+            .class
+            .super_class
+            .iter()
+            .map(|it| TsExprWithTypeArgs {
+                span: it.span(),
+                expr: it.clone(),
+                type_args: n.class.super_type_params.clone(),
+            })
+            .collect(),
+        body: TsInterfaceBody {
+            span: n.class.span,
+            body,
+        },
+    }
+}
+
 pub struct UnresolvedExport {
     pub name: JsWord,
     pub span: SyntaxContext,
     pub renamed: JsWord,
 }
 
+// What decoders do with the keys of an object that its type does not declare
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UnknownKeys {
+    // report them as errors
+    Strict,
+    // leave them out of the decoded value
+    #[default]
+    Strip,
+    // copy them to the decoded value
+    Passthrough,
+}
+
+impl UnknownKeys {
+    pub fn parse(it: &str) -> Option<UnknownKeys> {
+        match it {
+            "strict" => Some(UnknownKeys::Strict),
+            "strip" => Some(UnknownKeys::Strip),
+            "passthrough" => Some(UnknownKeys::Passthrough),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for UnknownKeys {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let it = match self {
+            UnknownKeys::Strict => "strict",
+            UnknownKeys::Strip => "strip",
+            UnknownKeys::Passthrough => "passthrough",
+        };
+        write!(f, "{}", it)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BeffUserSettings {
     pub custom_formats: BTreeSet<String>,
+    #[serde(default)]
+    pub custom_number_formats: BTreeSet<String>,
+    #[serde(default)]
+    pub unknown_keys: UnknownKeys,
 }
 
 pub struct EntryPoints {
-    pub parser_entry_point: Option<BffFileName>,
+    pub parser_entry_points: Vec<BffFileName>,
     pub schema_entry_point: Option<BffFileName>,
+    pub routes_entry_point: Option<BffFileName>,
     pub settings: BeffUserSettings,
 }
 pub trait FileManager {
     fn get_or_fetch_file(&mut self, name: &BffFileName) -> Option<Rc<ParsedModule>>;
     fn get_existing_file(&self, name: &BffFileName) -> Option<Rc<ParsedModule>>;
+    // the `declare module` block of a module specifier, in any file of the project
+    fn get_ambient_module(&self, module_specifier: &JsWord) -> Option<Rc<ImportReference>>;
+}
+
+// the project-level table of `declare module` blocks, the first file by name wins
+pub fn find_ambient_module<'a>(
+    files: impl Iterator<Item = (&'a BffFileName, &'a Rc<ParsedModule>)>,
+    module_specifier: &JsWord,
+) -> Option<Rc<ImportReference>> {
+    files
+        .filter_map(|(name, file)| {
+            file.ambient_modules
+                .get(module_specifier)
+                .map(|it| (name, it.clone()))
+        })
+        .min_by(|(a, _), (b, _)| a.to_string().cmp(&b.to_string()))
+        .map(|(_, it)| it)
 }
 
 pub struct ExtractResult {
     pub parser: Option<ParserExtractResult>,
     pub schema: Option<SchemaExtractResult>,
+    pub routes: Option<RoutesExtractResult>,
 }
 
 impl ExtractResult {
     pub fn is_empty(&self) -> bool {
-        self.parser.is_none()
+        self.parser.is_none() && self.routes.is_none()
     }
     pub fn errors(&self) -> Vec<&Diagnostic> {
-        self.parser
-            .as_ref()
-            .map(|it| it.errors.iter().collect::<Vec<_>>())
-            .unwrap_or_default()
+        let parser = self.parser.iter().flat_map(|it| it.errors.iter());
+        let routes = self.routes.iter().flat_map(|it| it.errors.iter());
+        parser.chain(routes).collect()
     }
     pub fn validators(&self) -> Vec<&Validator> {
-        self.parser
-            .as_ref()
-            .map(|it| it.validators.iter().collect::<Vec<_>>())
-            .unwrap_or_default()
+        let parser = self.parser.iter().flat_map(|it| it.validators.iter());
+        let routes = self.routes.iter().flat_map(|it| it.validators.iter());
+        parser.chain(routes).collect()
     }
 }
 pub fn extract<R: FileManager>(files: &mut R, entry_points: EntryPoints) -> ExtractResult {
     let mut parser = None;
 
-    if let Some(entry) = entry_points.parser_entry_point {
-        parser = Some(extract_parser(files, entry, &entry_points.settings));
+    if !entry_points.parser_entry_points.is_empty() {
+        parser = Some(extract_parser(
+            files,
+            entry_points.parser_entry_points,
+            &entry_points.settings,
+        ));
     }
 
     let mut schema = None;
@@ -412,7 +705,17 @@ pub fn extract<R: FileManager>(files: &mut R, entry_points: EntryPoints) -> Extr
         ));
     }
 
-    ExtractResult { parser, schema }
+    let mut routes = None;
+
+    if let Some(entry) = entry_points.routes_entry_point {
+        routes = Some(extract_routes(files, entry, &entry_points.settings));
+    }
+
+    qualified_name::assign_display_names(ExtractResult {
+        parser,
+        schema,
+        routes,
+    })
 }
 
 #[derive(Debug, Clone)]
@@ -424,7 +727,8 @@ impl Validator {
     pub fn to_json_kv(&self, validators: &[Validator]) -> Vec<(String, Json)> {
         vec![(
             self.name.clone(),
-            JsonFlatConverter::new(validators).to_json_flat(self.schema.clone()),
+            JsonFlatConverter::new(validators, UnknownKeys::default())
+                .to_json_flat(self.schema.clone()),
         )]
     }
 }
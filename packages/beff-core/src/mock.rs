@@ -0,0 +1,483 @@
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use anyhow::{anyhow, Result};
+
+use crate::ast::json::Json;
+use crate::ast::json_schema::{
+    built_in_string_format, CodecName, JsonSchema, Optionality, TplLitTypeItem,
+};
+use crate::parser_extractor::BuiltDecoder;
+use crate::{validate, ExtractResult, Validator};
+
+pub struct MockOptions {
+    pub seed: u64,
+    // How many references can be followed before the generator only takes the shortest way out:
+    // no optional properties, empty arrays and the least recursive member of every union.
+    pub max_depth: usize,
+    // Sample values for custom string formats. Built-in formats have their own, for the others the
+    // format name is used.
+    pub custom_formats: BTreeMap<String, String>,
+    // Sample values for custom number formats, a random number is used when there is none.
+    pub custom_number_formats: BTreeMap<String, Json>,
+}
+
+impl Default for MockOptions {
+    fn default() -> Self {
+        MockOptions {
+            seed: 0,
+            max_depth: 3,
+            custom_formats: BTreeMap::new(),
+            custom_number_formats: BTreeMap::new(),
+        }
+    }
+}
+
+const WORDS: [&str; 8] = [
+    "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel",
+];
+
+// How many tries an intersection gets before giving up.
+const ALL_OF_ATTEMPTS: usize = 8;
+
+// splitmix64, small and stable across platforms so that a seed always produces the same values
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+    fn flip(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+    fn pick<'b, T>(&mut self, vs: &'b [T]) -> &'b T {
+        &vs[self.below(vs.len())]
+    }
+}
+
+// days since 1970-01-01 to (year, month, day)
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+struct MockCtx<'a> {
+    validators: &'a [Validator],
+    options: &'a MockOptions,
+    rng: Rng,
+    depth: usize,
+    min_refs_cache: HashMap<String, usize>,
+}
+
+impl<'a> MockCtx<'a> {
+    fn new(validators: &'a [Validator], options: &'a MockOptions) -> Self {
+        MockCtx {
+            validators,
+            options,
+            rng: Rng(options.seed),
+            depth: 0,
+            min_refs_cache: HashMap::new(),
+        }
+    }
+
+    fn get_validator(&self, name: &str) -> &'a Validator {
+        self.validators
+            .iter()
+            .find(|it| it.name == name)
+            .expect("everything should be resolved by now")
+    }
+
+    fn exhausted(&self) -> bool {
+        self.depth >= self.options.max_depth
+    }
+
+    // The fewest references that must be followed to build a value, `None` if every value is
+    // infinite, ie: `type A = { a: A }`.
+    fn min_refs(&mut self, schema: &JsonSchema, visiting: &mut BTreeSet<String>) -> Option<usize> {
+        match schema {
+            JsonSchema::Ref(name) => {
+                if let Some(it) = self.min_refs_cache.get(name) {
+                    return Some(*it);
+                }
+                if !visiting.insert(name.clone()) {
+                    return None;
+                }
+                let res = self
+                    .min_refs(&self.get_validator(name).schema, visiting)
+                    .map(|it| it + 1);
+                visiting.remove(name);
+                if let Some(it) = res {
+                    self.min_refs_cache.insert(name.clone(), it);
+                }
+                res
+            }
+            JsonSchema::Object { vs, .. } => vs
+                .values()
+                .filter_map(|it| match it {
+                    Optionality::Required(it) => Some(it),
+                    Optionality::Optional(_) | Optionality::Default(_, _) => None,
+                })
+                .try_fold(0, |acc, it| Some(acc + self.min_refs(it, visiting)?)),
+            JsonSchema::Tuple {
+                prefix_items,
+                suffix_items,
+                ..
+            } => prefix_items
+                .iter()
+                .chain(suffix_items)
+                .try_fold(0, |acc, it| Some(acc + self.min_refs(it, visiting)?)),
+            JsonSchema::AllOf(vs) => vs
+                .iter()
+                .try_fold(0, |acc, it| Some(acc + self.min_refs(it, visiting)?)),
+            JsonSchema::AnyOf(vs) => vs.iter().filter_map(|it| self.min_refs(it, visiting)).min(),
+            JsonSchema::Brand(ty, _) => self.min_refs(ty, visiting),
+            JsonSchema::StNever | JsonSchema::StNot(_) => None,
+            JsonSchema::Null
+            | JsonSchema::Boolean
+            | JsonSchema::String
+            | JsonSchema::Number
+            | JsonSchema::Any
+            | JsonSchema::AnyArrayLike
+            | JsonSchema::StringWithFormat(_)
+            | JsonSchema::NumberWithFormat(_)
+            | JsonSchema::TplLitType(_)
+            | JsonSchema::Array(_)
+            | JsonSchema::Const(_)
+            | JsonSchema::Codec(_) => Some(0),
+        }
+    }
+
+    fn word(&mut self) -> String {
+        self.rng.pick(&WORDS).to_string()
+    }
+
+    fn tpl_lit(&mut self, items: &[TplLitTypeItem]) -> String {
+        let mut acc = String::new();
+        for item in items {
+            match item {
+                TplLitTypeItem::String => acc.push_str(&self.word()),
+                TplLitTypeItem::Number => acc.push_str(&self.rng.below(100).to_string()),
+                TplLitTypeItem::Boolean => acc.push_str(&self.rng.flip().to_string()),
+                TplLitTypeItem::StringConst(it) | TplLitTypeItem::Quasis(it) => acc.push_str(it),
+                TplLitTypeItem::OneOf(vs) => {
+                    let vs: Vec<_> = vs.iter().cloned().collect();
+                    let it = self.rng.pick(&vs).clone();
+                    acc.push_str(&self.tpl_lit(&[it]));
+                }
+            }
+        }
+        acc
+    }
+
+    fn codec(&mut self, codec: &CodecName) -> Json {
+        match codec {
+            CodecName::ISO8061 => {
+                // somewhere in 2020-2029
+                let days = 18_262 + self.rng.below(3_653) as i64;
+                let (year, month, day) = civil_from_days(days);
+                let secs = self.rng.below(86_400);
+                Json::String(format!(
+                    "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.000Z",
+                    secs / 3600,
+                    secs / 60 % 60,
+                    secs % 60
+                ))
+            }
+            CodecName::BigInt => Json::String(self.rng.next_u64().to_string()),
+            CodecName::PlainDate => {
+                let days = 18_262 + self.rng.below(3_653) as i64;
+                let (year, month, day) = civil_from_days(days);
+                Json::String(format!("{year:04}-{month:02}-{day:02}"))
+            }
+            CodecName::PlainTime => {
+                let secs = self.rng.below(86_400);
+                Json::String(format!(
+                    "{:02}:{:02}:{:02}",
+                    secs / 3600,
+                    secs / 60 % 60,
+                    secs % 60
+                ))
+            }
+            CodecName::Duration => Json::String(format!(
+                "P{}DT{}H{}M",
+                self.rng.below(30),
+                self.rng.below(24),
+                self.rng.below(60)
+            )),
+            CodecName::Base64 | CodecName::Base64Url => {
+                let alphabet: &[u8] = match codec {
+                    CodecName::Base64Url => {
+                        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_"
+                    }
+                    _ => b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
+                };
+                // every group of 4 characters holds 3 bytes, no padding needed
+                let len = 4 * (1 + self.rng.below(4));
+                let s = (0..len)
+                    .map(|_| alphabet[self.rng.below(64)] as char)
+                    .collect();
+                Json::String(s)
+            }
+        }
+    }
+
+    fn object(
+        &mut self,
+        vs: &BTreeMap<String, Optionality<JsonSchema>>,
+        rest: &Option<Box<JsonSchema>>,
+        pattern_vs: &BTreeMap<Vec<TplLitTypeItem>, JsonSchema>,
+    ) -> Result<Json> {
+        let mut acc = BTreeMap::new();
+        for (k, v) in vs {
+            match v {
+                Optionality::Required(v) => {
+                    acc.insert(k.clone(), self.schema(v)?);
+                }
+                Optionality::Optional(v) | Optionality::Default(v, _) => {
+                    if !self.exhausted() && self.rng.flip() {
+                        acc.insert(k.clone(), self.schema(v)?);
+                    }
+                }
+            }
+        }
+        if !self.exhausted() {
+            for (pattern, v) in pattern_vs {
+                let k = self.tpl_lit(pattern);
+                if let Entry::Vacant(e) = acc.entry(k) {
+                    e.insert(self.schema(v)?);
+                }
+            }
+            if let Some(rest) = rest {
+                for _ in 0..self.rng.below(3) {
+                    let k = self.word();
+                    if let Entry::Vacant(e) = acc.entry(k) {
+                        e.insert(self.schema(rest)?);
+                    }
+                }
+            }
+        }
+        Ok(Json::Object(acc))
+    }
+
+    fn items(&mut self, schema: &JsonSchema, max: usize) -> Result<Vec<Json>> {
+        let len = if self.exhausted() {
+            0
+        } else {
+            self.rng.below(max + 1)
+        };
+        (0..len).map(|_| self.schema(schema)).collect()
+    }
+
+    fn any_of(&mut self, vs: &BTreeSet<JsonSchema>) -> Result<Json> {
+        let vs: Vec<&JsonSchema> = if self.exhausted() {
+            let costs: Vec<_> = vs
+                .iter()
+                .map(|it| (it, self.min_refs(it, &mut BTreeSet::new())))
+                .collect();
+            let min = costs.iter().filter_map(|(_, cost)| *cost).min();
+            costs
+                .into_iter()
+                .filter(|(_, cost)| cost.is_some() && *cost == min)
+                .map(|(it, _)| it)
+                .collect()
+        } else {
+            vs.iter().collect()
+        };
+        if vs.is_empty() {
+            return Err(anyhow!("no member of the union has a finite value"));
+        }
+        let it = *self.rng.pick(&vs);
+        self.schema(it)
+    }
+
+    fn resolve(&self, schema: &JsonSchema) -> JsonSchema {
+        match schema {
+            JsonSchema::Ref(name) => self.resolve(&self.get_validator(name).schema),
+            _ => schema.clone(),
+        }
+    }
+
+    // Objects are intersected by merging their properties, anything else is generated from one of
+    // the members and kept if it is valid for all of them.
+    fn all_of(&mut self, vs: &BTreeSet<JsonSchema>) -> Result<Json> {
+        let resolved: Vec<JsonSchema> = vs.iter().map(|it| self.resolve(it)).collect();
+        let all_objects = resolved
+            .iter()
+            .all(|it| matches!(it, JsonSchema::Object { .. }));
+        if all_objects {
+            let mut merged: BTreeMap<String, (bool, BTreeSet<JsonSchema>)> = BTreeMap::new();
+            for it in &resolved {
+                if let JsonSchema::Object { vs, .. } = it {
+                    for (k, v) in vs {
+                        let entry = merged.entry(k.clone()).or_default();
+                        entry.0 |= v.is_required();
+                        entry.1.insert(v.inner().clone());
+                    }
+                }
+            }
+            let vs = merged
+                .into_iter()
+                .map(|(k, (required, vs))| {
+                    let schema = if vs.len() == 1 {
+                        vs.into_iter().next().expect("checked above")
+                    } else {
+                        JsonSchema::AllOf(vs)
+                    };
+                    let v = if required {
+                        Optionality::Required(schema)
+                    } else {
+                        Optionality::Optional(schema)
+                    };
+                    (k, v)
+                })
+                .collect();
+            return self.object(&vs, &None, &BTreeMap::new());
+        }
+        let whole = JsonSchema::AllOf(vs.clone());
+        for _ in 0..ALL_OF_ATTEMPTS {
+            for it in vs {
+                let candidate = self.schema(it)?;
+                if validate(&whole, self.validators, &candidate.to_serde()).is_ok() {
+                    return Ok(candidate);
+                }
+            }
+        }
+        Err(anyhow!("could not find a value for an intersection"))
+    }
+
+    fn schema(&mut self, schema: &JsonSchema) -> Result<Json> {
+        match schema {
+            JsonSchema::StNever | JsonSchema::StNot(_) => {
+                unreachable!("should not generate semantic types")
+            }
+            JsonSchema::Null => Ok(Json::Null),
+            JsonSchema::Boolean => Ok(Json::Bool(self.rng.flip())),
+            JsonSchema::String => Ok(Json::String(self.word())),
+            JsonSchema::Number => Ok(Json::parse_int(self.rng.below(100) as i64)),
+            JsonSchema::Any => Ok(match self.rng.below(3) {
+                0 => Json::String(self.word()),
+                1 => Json::parse_int(self.rng.below(100) as i64),
+                _ => Json::Bool(self.rng.flip()),
+            }),
+            JsonSchema::AnyArrayLike => Ok(Json::Array(vec![])),
+            JsonSchema::StringWithFormat(format) => Ok(Json::String(
+                match (
+                    self.options.custom_formats.get(format),
+                    built_in_string_format(format),
+                ) {
+                    (Some(it), _) => it.clone(),
+                    (None, Some(built_in)) => built_in.example.to_string(),
+                    (None, None) => format.clone(),
+                },
+            )),
+            JsonSchema::NumberWithFormat(format) => {
+                Ok(match self.options.custom_number_formats.get(format) {
+                    Some(it) => it.clone(),
+                    None => Json::parse_int(self.rng.below(100) as i64),
+                })
+            }
+            JsonSchema::TplLitType(items) => Ok(Json::String(self.tpl_lit(items))),
+            JsonSchema::Brand(ty, _) => self.schema(ty),
+            JsonSchema::Object {
+                vs,
+                rest,
+                pattern_vs,
+            } => self.object(vs, rest, pattern_vs),
+            JsonSchema::Array(items) => Ok(Json::Array(self.items(items, 2)?)),
+            JsonSchema::Tuple {
+                prefix_items,
+                optional_items,
+                items,
+                suffix_items,
+            } => {
+                let mut acc = prefix_items
+                    .iter()
+                    .map(|it| self.schema(it))
+                    .collect::<Result<Vec<_>>>()?;
+                // the optional items are filled in order, the rest only follows all of them
+                let mut filled = 0;
+                while filled < optional_items.len() && !self.exhausted() && self.rng.flip() {
+                    acc.push(self.schema(&optional_items[filled])?);
+                    filled += 1;
+                }
+                if let Some(items) = items.as_ref().filter(|_| filled == optional_items.len()) {
+                    acc.extend(self.items(items, 1)?);
+                }
+                for it in suffix_items {
+                    acc.push(self.schema(it)?);
+                }
+                Ok(Json::Array(acc))
+            }
+            JsonSchema::Ref(name) => {
+                // the unions already took the shortest way out, this only fails on infinite types
+                if self.depth > self.options.max_depth + self.validators.len() {
+                    return Err(anyhow!("type '{name}' has no finite value"));
+                }
+                let schema = &self.get_validator(name).schema;
+                self.depth += 1;
+                let res = self.schema(schema);
+                self.depth -= 1;
+                res
+            }
+            JsonSchema::AnyOf(vs) => self.any_of(vs),
+            JsonSchema::AllOf(vs) => self.all_of(vs),
+            JsonSchema::Const(it) => Ok(it.clone().to_json()),
+            JsonSchema::Codec(codec) => Ok(self.codec(codec)),
+        }
+    }
+}
+
+// Builds a value the schema accepts. The same seed always builds the same value.
+pub fn mock(schema: &JsonSchema, validators: &[Validator], options: &MockOptions) -> Result<Json> {
+    MockCtx::new(validators, options).schema(schema)
+}
+
+fn mock_decoders<'b>(
+    decoders: impl Iterator<Item = (String, &'b BuiltDecoder)>,
+    validators: &[Validator],
+    options: &MockOptions,
+) -> Result<Json> {
+    let vs = decoders
+        .map(|(name, it)| {
+            let v = mock(&it.schema, validators, options)
+                .map_err(|e| anyhow!("failed to build an example of '{name}': {e}"))?;
+            Ok((name, v))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Json::object(vs))
+}
+
+// An example of every type exported by the parser and schema entry points.
+pub fn examples(result: &ExtractResult, options: &MockOptions) -> Result<Json> {
+    let mut acc = vec![];
+    if let Some(parser) = &result.parser {
+        let decoders = parser.exported_decoders().into_iter();
+        let vs = mock_decoders(decoders, &parser.validators, options)?;
+        acc.push(("parser".to_string(), vs));
+    }
+    if let Some(schema) = &result.schema {
+        let decoders = schema
+            .built_decoders
+            .iter()
+            .flatten()
+            .map(|it| (it.exported_name.clone(), it));
+        let vs = mock_decoders(decoders, &schema.validators, options)?;
+        acc.push(("schema".to_string(), vs));
+    }
+    Ok(Json::object(acc))
+}
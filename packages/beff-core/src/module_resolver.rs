@@ -0,0 +1,724 @@
+use crate::{import_resolver::FsModuleResolver, BffFileName};
+use anyhow::{anyhow, Result};
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+pub trait ResolverHost {
+    fn read_file(&self, path: &str) -> Option<String>;
+    fn file_exists(&self, path: &str) -> bool {
+        self.read_file(path).is_some()
+    }
+}
+
+#[derive(Default)]
+pub struct InMemoryHost {
+    pub files: HashMap<String, String>,
+}
+
+impl InMemoryHost {
+    pub fn new(files: Vec<(&str, &str)>) -> InMemoryHost {
+        InMemoryHost {
+            files: files
+                .into_iter()
+                .map(|(k, v)| (normalize_path(k), v.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl ResolverHost for InMemoryHost {
+    fn read_file(&self, path: &str) -> Option<String> {
+        self.files.get(path).cloned()
+    }
+    fn file_exists(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModuleResolutionKind {
+    #[default]
+    Bundler,
+    Node16,
+}
+
+impl ModuleResolutionKind {
+    fn conditions(&self, format: ModuleFormat) -> &'static [&'static str] {
+        match (self, format) {
+            (ModuleResolutionKind::Bundler, _) => &["types", "import", "default"],
+            (ModuleResolutionKind::Node16, ModuleFormat::Esm) => {
+                &["types", "node", "import", "default"]
+            }
+            (ModuleResolutionKind::Node16, ModuleFormat::CommonJs) => {
+                &["types", "node", "require", "default"]
+            }
+        }
+    }
+}
+
+// the module format of the importing file, it picks between `import` and `require` conditions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModuleFormat {
+    Esm,
+    CommonJs,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompilerOptions {
+    pub module_resolution: ModuleResolutionKind,
+    // absolute directory
+    pub base_url: Option<String>,
+    pub paths: Vec<(String, Vec<String>)>,
+    // directory `paths` are relative to, the baseUrl or the tsconfig defining them
+    pub paths_base: Option<String>,
+}
+
+pub struct TsModuleResolver<H: ResolverHost> {
+    pub host: H,
+    pub options: CompilerOptions,
+}
+
+impl<H: ResolverHost> TsModuleResolver<H> {
+    pub fn new(host: H, options: CompilerOptions) -> TsModuleResolver<H> {
+        TsModuleResolver { host, options }
+    }
+
+    pub fn from_tsconfig(host: H, tsconfig_path: &str) -> Result<TsModuleResolver<H>> {
+        let mut options = CompilerOptions::default();
+        load_tsconfig(&host, &normalize_path(tsconfig_path), &mut options, 0)?;
+        Ok(TsModuleResolver { host, options })
+    }
+
+    pub fn resolve(&self, containing_file: &str, specifier: &str) -> Option<String> {
+        if is_relative(specifier) {
+            let path = join_path(&dirname(containing_file), specifier);
+            return self.resolve_file_or_directory(&path);
+        }
+        if specifier.starts_with('/') {
+            return self.resolve_file_or_directory(&normalize_path(specifier));
+        }
+        self.resolve_with_paths(specifier)
+            .or_else(|| {
+                let base_url = self.options.base_url.as_ref()?;
+                self.resolve_file_or_directory(&join_path(base_url, specifier))
+            })
+            .or_else(|| {
+                let conditions = self
+                    .options
+                    .module_resolution
+                    .conditions(self.module_format(containing_file));
+                self.resolve_node_modules(&dirname(containing_file), specifier, conditions)
+            })
+    }
+
+    fn module_format(&self, containing_file: &str) -> ModuleFormat {
+        if self.options.module_resolution == ModuleResolutionKind::Bundler {
+            return ModuleFormat::Esm;
+        }
+        if [".mts", ".mjs"]
+            .iter()
+            .any(|ext| containing_file.ends_with(ext))
+        {
+            return ModuleFormat::Esm;
+        }
+        if [".cts", ".cjs"]
+            .iter()
+            .any(|ext| containing_file.ends_with(ext))
+        {
+            return ModuleFormat::CommonJs;
+        }
+        // otherwise the `type` field of the nearest package.json decides
+        let mut dir = dirname(containing_file);
+        loop {
+            if let Some(package_json) = self.read_package_json(&dir) {
+                return match package_json.get("type") {
+                    Some(Value::String(it)) if it == "module" => ModuleFormat::Esm,
+                    _ => ModuleFormat::CommonJs,
+                };
+            }
+            if dir.is_empty() || dir == "/" {
+                return ModuleFormat::CommonJs;
+            }
+            dir = dirname(&dir);
+        }
+    }
+
+    fn resolve_with_paths(&self, specifier: &str) -> Option<String> {
+        let paths_base = self.options.paths_base.as_ref()?;
+        // the pattern with the longest prefix wins
+        let mut best: Option<(usize, &Vec<String>, String)> = None;
+        for (pattern, substitutions) in &self.options.paths {
+            if let Some(matched) = match_pattern(pattern, specifier) {
+                let prefix_len = pattern.find('*').unwrap_or(pattern.len());
+                if best.as_ref().is_none_or(|(len, ..)| prefix_len > *len) {
+                    best = Some((prefix_len, substitutions, matched));
+                }
+            }
+        }
+        let (_, substitutions, matched) = best?;
+        substitutions.iter().find_map(|it| {
+            let path = join_path(paths_base, &it.replace('*', &matched));
+            self.resolve_file_or_directory(&path)
+        })
+    }
+
+    fn resolve_node_modules(
+        &self,
+        start_dir: &str,
+        specifier: &str,
+        conditions: &[&str],
+    ) -> Option<String> {
+        let (package_name, subpath) = split_package_specifier(specifier);
+        let types_package = format!("@types/{}", mangle_types_package_name(&package_name));
+        let mut dir = start_dir.to_string();
+        loop {
+            if !dir.ends_with("/node_modules") {
+                let node_modules = join_path(&dir, "node_modules");
+                for name in [&package_name, &types_package] {
+                    let package_dir = join_path(&node_modules, name);
+                    if let Some(found) = self.resolve_package(&package_dir, &subpath, conditions) {
+                        return Some(found);
+                    }
+                }
+            }
+            if dir.is_empty() || dir == "/" {
+                return None;
+            }
+            dir = dirname(&dir);
+        }
+    }
+
+    fn read_package_json(&self, package_dir: &str) -> Option<Value> {
+        let content = self
+            .host
+            .read_file(&join_path(package_dir, "package.json"))?;
+        serde_json::from_str(&content).ok()
+    }
+
+    // a field of package.json whose key order matters, like `exports` or `typesVersions`
+    fn read_package_json_field(&self, package_dir: &str, field: &str) -> Option<OrderedJson> {
+        let content = self
+            .host
+            .read_file(&join_path(package_dir, "package.json"))?;
+        match serde_json::from_str(&content).ok()? {
+            OrderedJson::Object(fields) => fields
+                .into_iter()
+                .find_map(|(k, v)| (k == field).then_some(v)),
+            _ => None,
+        }
+    }
+
+    fn resolve_package(
+        &self,
+        package_dir: &str,
+        subpath: &str,
+        conditions: &[&str],
+    ) -> Option<String> {
+        if let Some(exports) = self.read_package_json_field(package_dir, "exports") {
+            // when `exports` is present, it is the only way into the package
+            let target = self.resolve_exports(&exports, subpath, conditions)?;
+            return self.resolve_file(&join_path(package_dir, &target));
+        }
+        let subpath = match self.read_package_json_field(package_dir, "typesVersions") {
+            Some(types_versions) => {
+                map_types_versions(&types_versions, subpath).unwrap_or(subpath.to_string())
+            }
+            None => subpath.to_string(),
+        };
+        if subpath != "." {
+            return self.resolve_file_or_directory(&join_path(package_dir, &subpath));
+        }
+        self.resolve_directory(package_dir)
+    }
+
+    fn resolve_exports(
+        &self,
+        exports: &OrderedJson,
+        subpath: &str,
+        conditions: &[&str],
+    ) -> Option<String> {
+        let map = match exports {
+            OrderedJson::Object(map) if map.iter().all(|(k, _)| k.starts_with('.')) => map,
+            _ if subpath == "." => return self.resolve_export_target(exports, "", conditions),
+            _ => return None,
+        };
+        if let Some((_, target)) = map.iter().find(|(k, _)| k == subpath) {
+            return self.resolve_export_target(target, "", conditions);
+        }
+        // the pattern with the longest prefix wins
+        let mut best: Option<(usize, &OrderedJson, String)> = None;
+        for (pattern, target) in map {
+            if let Some(matched) = match_pattern(pattern, subpath) {
+                let prefix_len = pattern.find('*').unwrap_or(pattern.len());
+                if best.as_ref().is_none_or(|(len, ..)| prefix_len > *len) {
+                    best = Some((prefix_len, target, matched));
+                }
+            }
+        }
+        let (_, target, matched) = best?;
+        self.resolve_export_target(target, &matched, conditions)
+    }
+
+    fn resolve_export_target(
+        &self,
+        target: &OrderedJson,
+        matched: &str,
+        conditions: &[&str],
+    ) -> Option<String> {
+        match target {
+            OrderedJson::String(s) => Some(s.replace('*', matched)),
+            OrderedJson::Array(vs) => vs
+                .iter()
+                .find_map(|it| self.resolve_export_target(it, matched, conditions)),
+            // the first matching condition in package.json key order wins
+            OrderedJson::Object(map) => map
+                .iter()
+                .filter(|(k, _)| conditions.contains(&k.as_str()))
+                .find_map(|(_, v)| self.resolve_export_target(v, matched, conditions)),
+            OrderedJson::Other => None,
+        }
+    }
+
+    fn resolve_file_or_directory(&self, path: &str) -> Option<String> {
+        self.resolve_file(path)
+            .or_else(|| self.resolve_directory(path))
+    }
+
+    fn resolve_file(&self, path: &str) -> Option<String> {
+        let candidates: Vec<String> = if is_ts_file(path) {
+            vec![path.to_string()]
+        } else if let Some((stem, ext)) = split_js_extension(path) {
+            // `.js` imports are resolved to the TypeScript sources
+            let ts_exts: &[&str] = match ext {
+                ".js" => &[".ts", ".tsx", ".d.ts"],
+                ".jsx" => &[".tsx", ".d.ts"],
+                ".mjs" => &[".mts", ".d.mts"],
+                ".cjs" => &[".cts", ".d.cts"],
+                _ => &[],
+            };
+            ts_exts.iter().map(|it| format!("{stem}{it}")).collect()
+        } else {
+            [".ts", ".tsx", ".d.ts"]
+                .iter()
+                .map(|it| format!("{path}{it}"))
+                .collect()
+        };
+        candidates.into_iter().find(|it| self.host.file_exists(it))
+    }
+
+    fn resolve_directory(&self, dir: &str) -> Option<String> {
+        if let Some(package_json) = self.read_package_json(dir) {
+            for field in ["types", "typings", "main"] {
+                if let Some(Value::String(entry)) = package_json.get(field) {
+                    let path = join_path(dir, entry);
+                    if let Some(found) = self.resolve_file(&path) {
+                        return Some(found);
+                    }
+                }
+            }
+        }
+        self.resolve_file(&join_path(dir, "index"))
+    }
+}
+
+// implements the import resolution for a single file
+pub struct TsFileModuleResolver<H: ResolverHost> {
+    pub resolver: Rc<TsModuleResolver<H>>,
+    pub current_file: BffFileName,
+}
+
+impl<H: ResolverHost> FsModuleResolver for TsFileModuleResolver<H> {
+    fn resolve_import(&mut self, module_specifier: &str) -> Option<BffFileName> {
+        self.resolver
+            .resolve(self.current_file.as_str(), module_specifier)
+            .map(BffFileName::new)
+    }
+}
+
+fn load_tsconfig<H: ResolverHost>(
+    host: &H,
+    path: &str,
+    options: &mut CompilerOptions,
+    depth: usize,
+) -> Result<()> {
+    if depth > 32 {
+        return Err(anyhow!("tsconfig extends chain is too deep: {path}"));
+    }
+    let content = host
+        .read_file(path)
+        .ok_or_else(|| anyhow!("cannot read tsconfig: {path}"))?;
+    let config: Value = serde_json::from_str(&strip_jsonc(&content))
+        .map_err(|e| anyhow!("cannot parse tsconfig {path}: {e}"))?;
+    let config_dir = dirname(path);
+
+    let extends = match config.get("extends") {
+        Some(Value::String(it)) => vec![it.clone()],
+        Some(Value::Array(vs)) => vs
+            .iter()
+            .filter_map(|it| it.as_str().map(|it| it.to_string()))
+            .collect(),
+        _ => vec![],
+    };
+    for base in extends {
+        let base_path = resolve_tsconfig_extends(host, &config_dir, &base)
+            .ok_or_else(|| anyhow!("cannot resolve tsconfig extends '{base}' of {path}"))?;
+        load_tsconfig(host, &base_path, options, depth + 1)?;
+    }
+
+    let compiler_options = match config.get("compilerOptions") {
+        Some(it) => it,
+        None => return Ok(()),
+    };
+    if let Some(Value::String(kind)) = compiler_options.get("moduleResolution") {
+        options.module_resolution = match kind.to_lowercase().as_str() {
+            "node16" | "nodenext" => ModuleResolutionKind::Node16,
+            _ => ModuleResolutionKind::Bundler,
+        };
+    }
+    if let Some(Value::String(base_url)) = compiler_options.get("baseUrl") {
+        let base_url = join_path(&config_dir, base_url);
+        options.paths_base = Some(base_url.clone());
+        options.base_url = Some(base_url);
+    }
+    if let Some(Value::Object(paths)) = compiler_options.get("paths") {
+        options.paths = paths
+            .iter()
+            .map(|(k, v)| {
+                let substitutions = v
+                    .as_array()
+                    .map(|vs| {
+                        vs.iter()
+                            .filter_map(|it| it.as_str().map(|it| it.to_string()))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (k.clone(), substitutions)
+            })
+            .collect();
+        // with a baseUrl, possibly inherited, paths are relative to it
+        if options.base_url.is_none() {
+            options.paths_base = Some(config_dir.clone());
+        }
+    }
+    Ok(())
+}
+
+fn resolve_tsconfig_extends<H: ResolverHost>(
+    host: &H,
+    config_dir: &str,
+    base: &str,
+) -> Option<String> {
+    let with_json = |it: String| {
+        if it.ends_with(".json") {
+            it
+        } else {
+            format!("{it}.json")
+        }
+    };
+    if is_relative(base) || base.starts_with('/') {
+        let path = with_json(join_path(config_dir, base));
+        return host.file_exists(&path).then_some(path);
+    }
+    // a package, or a file inside a package
+    let mut dir = config_dir.to_string();
+    loop {
+        let package_path = join_path(&join_path(&dir, "node_modules"), base);
+        let candidates = [
+            with_json(package_path.clone()),
+            join_path(&package_path, "tsconfig.json"),
+        ];
+        if let Some(found) = candidates.into_iter().find(|it| host.file_exists(it)) {
+            return Some(found);
+        }
+        if dir.is_empty() || dir == "/" {
+            return None;
+        }
+        dir = dirname(&dir);
+    }
+}
+
+// A JSON value whose objects keep the order of their keys, `serde_json::Value` sorts them.
+#[derive(Debug)]
+enum OrderedJson {
+    String(String),
+    Array(Vec<OrderedJson>),
+    Object(Vec<(String, OrderedJson)>),
+    Other,
+}
+
+impl<'de> Deserialize<'de> for OrderedJson {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OrderedJsonVisitor;
+        impl<'de> Visitor<'de> for OrderedJsonVisitor {
+            type Value = OrderedJson;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a JSON value")
+            }
+            fn visit_str<E>(self, v: &str) -> Result<OrderedJson, E> {
+                Ok(OrderedJson::String(v.to_string()))
+            }
+            fn visit_bool<E>(self, _: bool) -> Result<OrderedJson, E> {
+                Ok(OrderedJson::Other)
+            }
+            fn visit_i64<E>(self, _: i64) -> Result<OrderedJson, E> {
+                Ok(OrderedJson::Other)
+            }
+            fn visit_u64<E>(self, _: u64) -> Result<OrderedJson, E> {
+                Ok(OrderedJson::Other)
+            }
+            fn visit_f64<E>(self, _: f64) -> Result<OrderedJson, E> {
+                Ok(OrderedJson::Other)
+            }
+            fn visit_unit<E>(self) -> Result<OrderedJson, E> {
+                Ok(OrderedJson::Other)
+            }
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<OrderedJson, A::Error> {
+                let mut vs = vec![];
+                while let Some(v) = seq.next_element()? {
+                    vs.push(v);
+                }
+                Ok(OrderedJson::Array(vs))
+            }
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<OrderedJson, A::Error> {
+                let mut vs = vec![];
+                while let Some(kv) = map.next_entry()? {
+                    vs.push(kv);
+                }
+                Ok(OrderedJson::Object(vs))
+            }
+        }
+        deserializer.deserialize_any(OrderedJsonVisitor)
+    }
+}
+
+// The TypeScript version we resolve as, it picks the `typesVersions` range.
+const TS_VERSION: [u64; 3] = [5, 4, 0];
+
+fn map_types_versions(types_versions: &OrderedJson, subpath: &str) -> Option<String> {
+    // the first range that matches applies, like TypeScript does
+    let OrderedJson::Object(ranges) = types_versions else {
+        return None;
+    };
+    let (_, mappings) = ranges
+        .iter()
+        .find(|(range, _)| version_range_matches(range, TS_VERSION))?;
+    let OrderedJson::Object(mappings) = mappings else {
+        return None;
+    };
+    let subpath = subpath.trim_start_matches("./");
+    let subpath = if subpath == "." { "index" } else { subpath };
+    for (pattern, targets) in mappings {
+        if let Some(matched) = match_pattern(pattern, subpath) {
+            let OrderedJson::Array(targets) = targets else {
+                return None;
+            };
+            let OrderedJson::String(target) = targets.first()? else {
+                return None;
+            };
+            return Some(format!("./{}", target.replace('*', &matched)));
+        }
+    }
+    None
+}
+
+// A semver range, ie: `>=4.2`, `>=3.1 <4`, `~4.8` or `<4 || >=5`. Unknown syntax never matches.
+fn version_range_matches(range: &str, version: [u64; 3]) -> bool {
+    range.split("||").any(|alternative| {
+        alternative
+            .split_whitespace()
+            .all(|comparator| comparator_matches(comparator, version))
+    })
+}
+
+fn comparator_matches(comparator: &str, version: [u64; 3]) -> bool {
+    if comparator == "*" {
+        return true;
+    }
+    let (op, rest) = [">=", "<=", ">", "<", "=", "~", "^"]
+        .iter()
+        .find_map(|op| comparator.strip_prefix(op).map(|rest| (*op, rest)))
+        .unwrap_or(("=", comparator));
+    let parts: Vec<&str> = rest.split('.').collect();
+    if parts.is_empty() || parts.len() > 3 {
+        return false;
+    }
+    let mut bound = [0; 3];
+    for (idx, part) in parts.iter().enumerate() {
+        match part.parse() {
+            Ok(n) => bound[idx] = n,
+            Err(_) => return false,
+        }
+    }
+    // the first version after the written parts, ie: `4.8` covers up to `4.9.0`
+    let next = |len: usize| {
+        let mut next = [0; 3];
+        next[..len].copy_from_slice(&bound[..len]);
+        next[len - 1] += 1;
+        next
+    };
+    match op {
+        ">=" => version >= bound,
+        ">" => version >= next(parts.len()),
+        "<=" => version < next(parts.len()),
+        "<" => version < bound,
+        "~" => version >= bound && version < next(parts.len().min(2)),
+        "^" => version >= bound && version < next(1),
+        _ => version >= bound && version < next(parts.len()),
+    }
+}
+
+fn match_pattern(pattern: &str, value: &str) -> Option<String> {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            if value.len() >= prefix.len() + suffix.len()
+                && value.starts_with(prefix)
+                && value.ends_with(suffix)
+            {
+                Some(value[prefix.len()..value.len() - suffix.len()].to_string())
+            } else {
+                None
+            }
+        }
+        None => (pattern == value).then(String::new),
+    }
+}
+
+fn split_package_specifier(specifier: &str) -> (String, String) {
+    let mut parts = specifier.splitn(if specifier.starts_with('@') { 3 } else { 2 }, '/');
+    let package_name = if specifier.starts_with('@') {
+        let scope = parts.next().unwrap_or_default();
+        let name = parts.next().unwrap_or_default();
+        format!("{scope}/{name}")
+    } else {
+        parts.next().unwrap_or_default().to_string()
+    };
+    let subpath = match parts.next() {
+        Some(rest) => format!("./{rest}"),
+        None => ".".to_string(),
+    };
+    (package_name, subpath)
+}
+
+// `@scope/name` types live in `@types/scope__name`
+fn mangle_types_package_name(package_name: &str) -> String {
+    match package_name.strip_prefix('@') {
+        Some(scoped) => scoped.replacen('/', "__", 1),
+        None => package_name.to_string(),
+    }
+}
+
+fn is_relative(specifier: &str) -> bool {
+    specifier == "."
+        || specifier == ".."
+        || specifier.starts_with("./")
+        || specifier.starts_with("../")
+}
+
+fn is_ts_file(path: &str) -> bool {
+    [".ts", ".tsx", ".mts", ".cts"]
+        .iter()
+        .any(|ext| path.ends_with(ext))
+}
+
+fn split_js_extension(path: &str) -> Option<(&str, &str)> {
+    [".js", ".jsx", ".mjs", ".cjs"]
+        .iter()
+        .find_map(|ext| path.strip_suffix(ext).map(|stem| (stem, *ext)))
+}
+
+fn dirname(path: &str) -> String {
+    match path.rfind('/') {
+        Some(0) => "/".to_string(),
+        Some(idx) => path[..idx].to_string(),
+        None => String::new(),
+    }
+}
+
+fn join_path(dir: &str, path: &str) -> String {
+    if path.starts_with('/') || dir.is_empty() {
+        normalize_path(path)
+    } else {
+        normalize_path(&format!("{dir}/{path}"))
+    }
+}
+
+pub fn normalize_path(path: &str) -> String {
+    let is_absolute = path.starts_with('/');
+    let mut parts: Vec<&str> = vec![];
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                if parts.last().is_some_and(|it| *it != "..") {
+                    parts.pop();
+                } else if !is_absolute {
+                    parts.push("..");
+                }
+            }
+            _ => parts.push(part),
+        }
+    }
+    let joined = parts.join("/");
+    if is_absolute {
+        format!("/{joined}")
+    } else {
+        joined
+    }
+}
+
+// tsconfig files allow comments and trailing commas
+fn strip_jsonc(content: &str) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let mut out = String::with_capacity(content.len());
+    let mut i = 0;
+    let mut in_string = false;
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            out.push(c);
+            if c == '\\' && i + 1 < chars.len() {
+                out.push(chars[i + 1]);
+                i += 1;
+            } else if c == '"' {
+                in_string = false;
+            }
+            i += 1;
+            continue;
+        }
+        match (c, chars.get(i + 1)) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+                i += 1;
+            }
+            ('/', Some('/')) => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            ('/', Some('*')) => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            (',', _) => {
+                let next = chars[i + 1..].iter().find(|it| !it.is_whitespace());
+                if !matches!(next, Some('}') | Some(']')) {
+                    out.push(c);
+                }
+                i += 1;
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
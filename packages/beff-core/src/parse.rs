@@ -27,7 +27,10 @@ pub fn parse_with_swc(
     let comments: SwcComments = SwcComments::default();
     let module = parse_file_as_module(
         fm,
-        Syntax::Typescript(TsConfig::default()),
+        Syntax::Typescript(TsConfig {
+            dts: bff_fname.is_declaration_file(),
+            ..TsConfig::default()
+        }),
         EsVersion::latest(),
         Some(&comments),
         &mut vec![],
@@ -1,18 +1,15 @@
-use std::rc::Rc;
-
 use crate::ast::json::Json;
 use crate::ast::json_schema::{JsonFlatConverter, JsonSchema};
-use crate::diag::{Diagnostic, DiagnosticInfoMessage, DiagnosticInformation, Location};
-use crate::type_to_schema::TypeToSchema;
-use crate::{BeffUserSettings, ParsedModule};
+use crate::diag::{Diagnostic, DiagnosticInfoMessage};
+use crate::extractor::{ExtractorState, SpecialCallVisitor};
+use crate::{BeffUserSettings, UnknownKeys};
 use crate::{BffFileName, FileManager, Validator};
-use anyhow::anyhow;
 use anyhow::Result;
-use swc_common::{Span, DUMMY_SP};
+use swc_common::Span;
 use swc_ecma_ast::{
-    CallExpr, Callee, Expr, Ident, MemberExpr, MemberProp, TsCallSignatureDecl,
-    TsConstructSignatureDecl, TsGetterSignature, TsIndexSignature, TsMethodSignature,
-    TsPropertySignature, TsSetterSignature, TsType, TsTypeElement, TsTypeLit,
+    CallExpr, Expr, Ident, KeyValueProp, Lit, ObjectLit, Prop, PropName, PropOrSpread,
+    TsCallSignatureDecl, TsConstructSignatureDecl, TsGetterSignature, TsIndexSignature,
+    TsMethodSignature, TsPropertySignature, TsSetterSignature, TsType, TsTypeElement, TsTypeLit,
     TsTypeParamInstantiation,
 };
 use swc_ecma_visit::Visit;
@@ -23,31 +20,75 @@ pub struct BuiltDecoder {
     pub schema: JsonSchema,
 }
 impl BuiltDecoder {
-    pub fn to_json_kv(&self, validators: &[Validator]) -> Vec<(String, Json)> {
+    pub fn to_json_kv(
+        &self,
+        validators: &[Validator],
+        unknown_keys: UnknownKeys,
+    ) -> Vec<(String, Json)> {
         vec![(
             self.exported_name.clone(),
-            JsonFlatConverter::new(validators).to_json_flat(self.schema.clone()),
+            JsonFlatConverter::new(validators, unknown_keys).to_json_flat(self.schema.clone()),
         )]
     }
 }
 
+// the decoders of one `buildParsers` call
+#[derive(Debug)]
+pub struct BuiltParsers {
+    pub file_name: BffFileName,
+    pub span: Span,
+    // the position of the call among the calls of its file
+    pub index: usize,
+    // the `id` option, the runtime finds the decoders of a call by it
+    pub id: Option<String>,
+    pub built_decoders: Vec<BuiltDecoder>,
+    pub unknown_keys: UnknownKeys,
+}
+impl BuiltParsers {
+    pub fn call_site_id(&self) -> String {
+        match &self.id {
+            Some(id) => id.clone(),
+            None => format!("{}:{}", self.file_name.as_str(), self.index),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ParserExtractResult {
     pub errors: Vec<Diagnostic>,
-    pub entry_file_name: BffFileName,
+    pub entry_file_names: Vec<BffFileName>,
     pub validators: Vec<Validator>,
-    pub built_decoders: Option<Vec<BuiltDecoder>>,
+    pub built_parsers: Vec<BuiltParsers>,
     pub counter: usize,
 }
+impl ParserExtractResult {
+    // the decoders of all calls, a different type under a name another call exports gets a `$n` suffix
+    pub fn exported_decoders(&self) -> Vec<(String, &BuiltDecoder)> {
+        let mut acc: Vec<(String, &BuiltDecoder)> = vec![];
+        for it in self.built_parsers.iter().flat_map(|it| &it.built_decoders) {
+            let mut name = it.exported_name.clone();
+            let mut n = 0;
+            loop {
+                match acc.iter().find(|(other, _)| *other == name) {
+                    Some((_, other)) if other.schema == it.schema => break,
+                    Some(_) => {
+                        n += 1;
+                        name = format!("{}${n}", it.exported_name);
+                    }
+                    None => {
+                        acc.push((name, it));
+                        break;
+                    }
+                }
+            }
+        }
+        acc
+    }
+}
 
 struct ExtractParserVisitor<'a, R: FileManager> {
-    files: &'a mut R,
-    current_file: BffFileName,
-    validators: Vec<Validator>,
-    errors: Vec<Diagnostic>,
-    built_decoders: Option<Vec<BuiltDecoder>>,
-    settings: &'a BeffUserSettings,
-    counter: usize,
+    state: ExtractorState<'a, R>,
+    built_parsers: Vec<BuiltParsers>,
 }
 impl<'a, R: FileManager> ExtractParserVisitor<'a, R> {
     fn new(
@@ -56,118 +97,11 @@ impl<'a, R: FileManager> ExtractParserVisitor<'a, R> {
         settings: &'a BeffUserSettings,
     ) -> ExtractParserVisitor<'a, R> {
         ExtractParserVisitor {
-            files,
-            current_file,
-            validators: vec![],
-            errors: vec![],
-            built_decoders: None,
-            settings,
-            counter: 0,
-        }
-    }
-}
-
-impl<'a, R: FileManager> ExtractParserVisitor<'a, R> {
-    fn build_error(&self, span: &Span, msg: DiagnosticInfoMessage) -> DiagnosticInformation {
-        let file = self.files.get_existing_file(&self.current_file);
-        Location::build(file, span, &self.current_file).to_info(msg)
-    }
-    fn push_error(&mut self, span: &Span, msg: DiagnosticInfoMessage) {
-        self.errors.push(self.build_error(span, msg).to_diag(None));
-    }
-
-    fn error<T>(&mut self, span: &Span, msg: DiagnosticInfoMessage) -> Result<T> {
-        let e = anyhow!("{:?}", &msg);
-        self.errors.push(self.build_error(span, msg).to_diag(None));
-        Err(e)
-    }
-
-    fn get_current_file(&mut self) -> Result<Rc<ParsedModule>> {
-        let res = self.files.get_or_fetch_file(&self.current_file);
-
-        match res {
-            Some(it) => Ok(it),
-            None => {
-                self.errors.push(
-                    self.build_error(
-                        &DUMMY_SP,
-                        DiagnosticInfoMessage::CannotFindFileWhenConvertingToSchema(
-                            self.current_file.clone(),
-                        ),
-                    )
-                    .to_diag(None),
-                );
-                Err(anyhow!("cannot find file: {}", self.current_file.0))
-            }
+            state: ExtractorState::new(files, current_file, settings),
+            built_parsers: vec![],
         }
     }
-    fn visit_current_file(&mut self) -> Result<()> {
-        let file = self.get_current_file()?;
-        let module = file.module.module.clone();
-        self.visit_module(&module);
-        Ok(())
-    }
-
-    fn extend_components(&mut self, defs: Vec<Validator>, span: &Span) {
-        for d in defs {
-            let found = self.validators.iter_mut().find(|x| x.name == d.name);
-            if let Some(found) = found {
-                if found.schema != d.schema {
-                    // let found_schema = format!("{:?}", found.schema);
-                    // let d_schema = format!("{:?}", d.schema);
-
-                    // let msg = format!(
-                    //     "Found schema: {},\n\n\n new schema: {}",
-                    //     found_schema, d_schema
-                    // );
-                    // web_sys::console::log_1(&msg.into());
-                    // todo!("{:?} Two different types with the same name.", d.name);
-                    self.push_error(
-                        span,
-                        DiagnosticInfoMessage::TwoDifferentTypesWithTheSameName(d.name.clone()),
-                    );
-                }
-            } else {
-                self.validators.push(d);
-            }
-        }
-    }
-    fn convert_to_json_schema(&mut self, ty: &TsType, span: &Span) -> JsonSchema {
-        let mut to_schema = TypeToSchema::new(
-            self.files,
-            self.current_file.clone(),
-            self.settings,
-            &mut self.counter,
-        );
-        let res = to_schema.convert_ts_type(ty);
-        match res {
-            Ok(res) => {
-                let mut kvs = vec![];
-                for (k, v) in to_schema.components {
-                    // We store type in an Option to support self-recursion.
-                    // When we encounter the type while transforming it we return string with the type name.
-                    // And we need the option to allow a type to refer to itself before it has been resolved.
-                    match v {
-                        Some(s) => kvs.push((k, s)),
-                        None => self.push_error(
-                            span,
-                            DiagnosticInfoMessage::CannotResolveTypeReferenceOnExtracting(k),
-                        ),
-                    }
-                }
-
-                kvs.sort_by(|(ka, _), (kb, _)| ka.cmp(kb));
-                let ext: Vec<Validator> = kvs.into_iter().map(|(_k, v)| v).collect();
-                self.extend_components(ext, span);
 
-                res
-            }
-            Err(diag) => {
-                self.errors.push(*diag);
-                JsonSchema::Any
-            }
-        }
-    }
     fn extract_one_built_decoder(&mut self, prop: &TsTypeElement) -> Result<BuiltDecoder> {
         match prop {
             TsTypeElement::TsPropertySignature(TsPropertySignature {
@@ -178,21 +112,27 @@ impl<'a, R: FileManager> ExtractParserVisitor<'a, R> {
                 ..
             }) => {
                 if type_params.is_some() {
-                    return self.error(span, DiagnosticInfoMessage::GenericDecoderIsNotSupported);
+                    return self
+                        .state
+                        .error(span, DiagnosticInfoMessage::GenericDecoderIsNotSupported);
                 }
 
                 let key = match &**key {
                     Expr::Ident(ident) => ident.sym.to_string(),
                     _ => {
-                        return self.error(span, DiagnosticInfoMessage::InvalidDecoderKey);
+                        return self
+                            .state
+                            .error(span, DiagnosticInfoMessage::InvalidDecoderKey);
                     }
                 };
                 match type_ann.as_ref().map(|it| &it.type_ann) {
                     Some(ann) => Ok(BuiltDecoder {
                         exported_name: key,
-                        schema: self.convert_to_json_schema(ann, span),
+                        schema: self.state.convert_to_json_schema(ann, span),
                     }),
-                    None => self.error(span, DiagnosticInfoMessage::DecoderMustHaveTypeAnnotation),
+                    None => self
+                        .state
+                        .error(span, DiagnosticInfoMessage::DecoderMustHaveTypeAnnotation),
                 }
             }
             TsTypeElement::TsGetterSignature(TsGetterSignature { span, .. })
@@ -201,7 +141,8 @@ impl<'a, R: FileManager> ExtractParserVisitor<'a, R> {
             | TsTypeElement::TsIndexSignature(TsIndexSignature { span, .. })
             | TsTypeElement::TsCallSignatureDecl(TsCallSignatureDecl { span, .. })
             | TsTypeElement::TsConstructSignatureDecl(TsConstructSignatureDecl { span, .. }) => {
-                self.error(span, DiagnosticInfoMessage::InvalidDecoderProperty)
+                self.state
+                    .error(span, DiagnosticInfoMessage::InvalidDecoderProperty)
             }
         }
     }
@@ -212,7 +153,7 @@ impl<'a, R: FileManager> ExtractParserVisitor<'a, R> {
         match params.params.split_first() {
             Some((head, tail)) => {
                 if !tail.is_empty() {
-                    return self.error(
+                    return self.state.error(
                         &params.span,
                         DiagnosticInfoMessage::TooManyTypeParamsOnDecoder,
                     );
@@ -222,79 +163,163 @@ impl<'a, R: FileManager> ExtractParserVisitor<'a, R> {
                         .iter()
                         .map(|prop| self.extract_one_built_decoder(prop))
                         .collect(),
-                    _ => self.error(
+                    _ => self.state.error(
                         &params.span,
                         DiagnosticInfoMessage::DecoderShouldBeObjectWithTypesAndNames,
                     ),
                 }
             }
-            None => self.error(
+            None => self.state.error(
                 &params.span,
                 DiagnosticInfoMessage::TooFewTypeParamsOnDecoder,
             ),
         }
     }
 
-    pub fn extract_special_calls(&mut self, id: &Ident, n: &CallExpr) {
-        let Ident { sym, span, .. } = id;
-        if sym == "buildParsers" {
-            match self.built_decoders {
-                Some(_) => self.push_error(span, DiagnosticInfoMessage::TwoCallsToBuildParsers),
-                None => {
-                    if let Some(ref params) = n.type_args {
-                        if let Ok(x) = self.extract_built_decoders_from_call(params.as_ref()) {
-                            self.built_decoders = Some(x)
-                        }
-                    }
+    // the value of an option in the object literal passed to the call
+    fn find_option<'b>(n: &'b CallExpr, name: &str) -> Option<&'b Expr> {
+        let props = match n.args.first().map(|it| &*it.expr) {
+            Some(Expr::Object(ObjectLit { props, .. })) => props,
+            _ => return None,
+        };
+        props.iter().find_map(|prop| match prop {
+            PropOrSpread::Prop(prop) => match &**prop {
+                Prop::KeyValue(KeyValueProp { key, value }) => {
+                    let matches = match key {
+                        PropName::Ident(it) => it.sym == *name,
+                        PropName::Str(it) => it.value == *name,
+                        _ => false,
+                    };
+                    matches.then_some(&**value)
                 }
+                _ => None,
+            },
+            PropOrSpread::Spread(_) => None,
+        })
+    }
+
+    // the `unknownKeys` option of the call, or the one of the settings
+    fn extract_unknown_keys(&mut self, n: &CallExpr) -> UnknownKeys {
+        let value = match Self::find_option(n, "unknownKeys") {
+            Some(value) => value,
+            None => return self.state.settings.unknown_keys,
+        };
+        let parsed = match value {
+            Expr::Lit(Lit::Str(it)) => UnknownKeys::parse(&it.value),
+            _ => None,
+        };
+        match parsed {
+            Some(it) => it,
+            None => {
+                self.state
+                    .push_error(&n.span, DiagnosticInfoMessage::InvalidUnknownKeys);
+                self.state.settings.unknown_keys
             }
         }
     }
-}
 
-impl<'a, R: FileManager> Visit for ExtractParserVisitor<'a, R> {
-    fn visit_call_expr(&mut self, n: &CallExpr) {
-        match n.callee {
-            Callee::Super(_) => {}
-            Callee::Import(_) => {}
-            Callee::Expr(ref expr) => {
-                if let Expr::Ident(id) = &**expr {
-                    self.extract_special_calls(id, n)
+    fn extract_id(&mut self, n: &CallExpr) -> Option<String> {
+        match Self::find_option(n, "id")? {
+            Expr::Lit(Lit::Str(it)) => Some(it.value.to_string()),
+            _ => {
+                self.state
+                    .push_error(&n.span, DiagnosticInfoMessage::InvalidBuildParsersId);
+                None
+            }
+        }
+    }
+
+    // with more than one call the runtime tells them apart by their `id`
+    fn validate_call_site_ids(&mut self) {
+        if self.built_parsers.len() < 2 {
+            return;
+        }
+        let mut seen = vec![];
+        let calls: Vec<_> = self
+            .built_parsers
+            .iter()
+            .map(|it| (it.file_name.clone(), it.span, it.id.clone()))
+            .collect();
+        for (file_name, span, id) in calls {
+            self.state.current_file = file_name;
+            let msg = match id {
+                None => DiagnosticInfoMessage::BuildParsersShouldHaveId,
+                Some(id) if seen.contains(&id) => {
+                    DiagnosticInfoMessage::DuplicatedBuildParsersId(id)
+                }
+                Some(id) => {
+                    seen.push(id);
+                    continue;
                 }
+            };
+            self.state.push_error(&span, msg);
+        }
+    }
+}
 
-                if let Expr::Member(MemberExpr { prop, .. }) = &**expr {
-                    match prop {
-                        MemberProp::Ident(id) => self.extract_special_calls(id, n),
-                        MemberProp::PrivateName(_) => {}
-                        MemberProp::Computed(_) => {}
-                    }
+impl<'a, R: FileManager> SpecialCallVisitor<'a, R> for ExtractParserVisitor<'a, R> {
+    fn state(&mut self) -> &mut ExtractorState<'a, R> {
+        &mut self.state
+    }
+
+    fn extract_special_calls(&mut self, id: &Ident, n: &CallExpr) {
+        let Ident { sym, span, .. } = id;
+        if sym == "buildParsers" {
+            if let Some(ref params) = n.type_args {
+                if let Ok(built_decoders) = self.extract_built_decoders_from_call(params.as_ref()) {
+                    let unknown_keys = self.extract_unknown_keys(n);
+                    let id = self.extract_id(n);
+                    let index = self
+                        .built_parsers
+                        .iter()
+                        .filter(|it| it.file_name == self.state.current_file)
+                        .count();
+                    self.built_parsers.push(BuiltParsers {
+                        file_name: self.state.current_file.clone(),
+                        span: *span,
+                        index,
+                        id,
+                        built_decoders,
+                        unknown_keys,
+                    })
                 }
             }
         }
     }
 }
 
+impl<'a, R: FileManager> Visit for ExtractParserVisitor<'a, R> {
+    fn visit_call_expr(&mut self, n: &CallExpr) {
+        self.visit_special_call(n)
+    }
+}
+
 pub fn extract_parser<R: FileManager>(
     files: &mut R,
-    entry_file_name: BffFileName,
+    entry_file_names: Vec<BffFileName>,
     settings: &BeffUserSettings,
 ) -> ParserExtractResult {
-    let (errors, validators, built_decoders, counter) = {
-        let mut visitor = ExtractParserVisitor::new(files, entry_file_name.clone(), settings);
-        let _ = visitor.visit_current_file();
+    let (errors, validators, built_parsers, counter) = {
+        let mut visitor = ExtractParserVisitor::new(files, entry_file_names[0].clone(), settings);
+        // validators are shared by all entry files
+        for entry_file_name in &entry_file_names {
+            visitor.state.current_file = entry_file_name.clone();
+            let _ = visitor.visit_current_file();
+        }
+        visitor.validate_call_site_ids();
         (
-            visitor.errors,
-            visitor.validators,
-            visitor.built_decoders,
-            visitor.counter,
+            visitor.state.errors,
+            visitor.state.validators,
+            visitor.built_parsers,
+            visitor.state.counter,
         )
     };
 
     ParserExtractResult {
         errors,
-        entry_file_name,
+        entry_file_names,
         validators,
-        built_decoders,
+        built_parsers,
         counter,
     }
 }
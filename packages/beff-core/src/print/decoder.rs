@@ -2,14 +2,17 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use super::expr::ToExpr;
 use crate::{
-    ast::json_schema::{JsonSchema, JsonSchemaConst, Optionality, TplLitTypeItem},
-    Validator,
+    ast::json_schema::{
+        built_in_string_format, JsonSchema, JsonSchemaConst, Optionality, TplLitTypeItem,
+    },
+    UnknownKeys, Validator,
 };
 use swc_common::DUMMY_SP;
 use swc_ecma_ast::{
-    ArrayLit, ArrowExpr, AssignPat, BindingIdent, BlockStmt, BlockStmtOrExpr, Bool, CallExpr,
-    Callee, Expr, ExprOrSpread, Function, Ident, KeyValueProp, Lit, MemberExpr, MemberProp, Null,
-    ObjectLit, Param, ParenExpr, Pat, Prop, PropName, PropOrSpread, Regex, ReturnStmt, Stmt, Str,
+    ArrayLit, ArrowExpr, AssignExpr, AssignOp, AssignPat, BindingIdent, BlockStmt, BlockStmtOrExpr,
+    Bool, CallExpr, Callee, Expr, ExprOrSpread, ExprStmt, Function, Ident, KeyValueProp, Lit,
+    MemberExpr, MemberProp, Null, ObjectLit, Param, ParenExpr, Pat, PatOrExpr, Prop, PropName,
+    PropOrSpread, Regex, ReturnStmt, Stmt, Str,
 };
 struct SwcBuilder;
 
@@ -267,14 +270,14 @@ impl<'a> DecoderFnGenerator<'a> {
         flat_values: &BTreeSet<JsonSchema>,
         required: Required,
     ) -> Option<Expr> {
-        let all_objects_without_rest = flat_values
-            .iter()
-            .all(|it| matches!(it, JsonSchema::Object { rest: None, .. }));
+        let all_objects_without_rest = flat_values.iter().all(|it| {
+            matches!(it, JsonSchema::Object { rest: None, pattern_vs, .. } if pattern_vs.is_empty())
+        });
 
         let object_vs = flat_values
             .iter()
             .filter_map(|it| match it {
-                JsonSchema::Object { vs, rest: _ } => Some(vs),
+                JsonSchema::Object { vs, .. } => Some(vs),
                 _ => None,
             })
             .collect::<Vec<_>>();
@@ -379,6 +382,66 @@ impl<'a> DecoderFnGenerator<'a> {
         }
         None
     }
+    fn decode_tuple(
+        &self,
+        prefix_items: &[JsonSchema],
+        optional_items: &[JsonSchema],
+        items: &Option<Box<JsonSchema>>,
+        suffix_items: &[JsonSchema],
+        required: Required,
+    ) -> Expr {
+        let prop = |key: &str, value: Expr| {
+            PropOrSpread::Prop(
+                Prop::KeyValue(KeyValueProp {
+                    key: PropName::Ident(Ident {
+                        span: DUMMY_SP,
+                        sym: key.into(),
+                        optional: false,
+                    }),
+                    value: Box::new(value),
+                })
+                .into(),
+            )
+        };
+        let decoders = |vs: &[JsonSchema], required: bool| {
+            Expr::Array(ArrayLit {
+                span: DUMMY_SP,
+                elems: vs
+                    .iter()
+                    .map(|it| {
+                        Some(ExprOrSpread {
+                            spread: None,
+                            expr: Self::make_cb(self.decode_expr(it, Required::Known(required)))
+                                .into(),
+                        })
+                    })
+                    .collect(),
+            })
+        };
+        let mut props = vec![prop("prefix", decoders(prefix_items, true))];
+        if !optional_items.is_empty() {
+            props.push(prop("optional", decoders(optional_items, false)));
+        }
+        props.push(prop(
+            "items",
+            match items {
+                Some(v) => Self::make_cb(self.decode_expr(v, Required::Known(true))),
+                None => Expr::Lit(Lit::Null(Null { span: DUMMY_SP })),
+            },
+        ));
+        if !suffix_items.is_empty() {
+            props.push(prop("suffix", decoders(suffix_items, true)));
+        }
+        Self::decode_call_extra(
+            "decodeTuple",
+            required,
+            vec![Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props,
+            })],
+        )
+    }
+
     fn decode_any_of(&self, vs: &BTreeSet<JsonSchema>, required: Required) -> Expr {
         if vs.is_empty() {
             panic!("empty anyOf is not allowed")
@@ -414,8 +477,24 @@ impl<'a> DecoderFnGenerator<'a> {
             JsonSchema::String => Self::decode_call("decodeString", required),
             JsonSchema::Number => Self::decode_call("decodeNumber", required),
             JsonSchema::Any => Self::decode_call("decodeAny", required),
-            JsonSchema::StringWithFormat(format) => Self::decode_call_extra(
-                "decodeStringWithFormat",
+            JsonSchema::StringWithFormat(format) => {
+                let mut extra = vec![Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: format.to_string().into(),
+                    raw: None,
+                }))];
+                // used when no custom format of the same name is registered
+                if let Some(built_in) = built_in_string_format(format) {
+                    extra.push(Expr::Lit(Lit::Regex(Regex {
+                        span: DUMMY_SP,
+                        exp: built_in.regex.into(),
+                        flags: "".into(),
+                    })));
+                }
+                Self::decode_call_extra("decodeStringWithFormat", required, extra)
+            }
+            JsonSchema::NumberWithFormat(format) => Self::decode_call_extra(
+                "decodeNumberWithFormat",
                 required,
                 vec![Expr::Lit(Lit::Str(Str {
                     span: DUMMY_SP,
@@ -424,7 +503,23 @@ impl<'a> DecoderFnGenerator<'a> {
                 }))],
             ),
             JsonSchema::Ref(r_name) => Self::decode_ref(r_name, required),
-            JsonSchema::Object { vs, rest } => {
+            JsonSchema::Brand(ty, brand) => Self::decode_call_extra(
+                "decodeBrand",
+                required,
+                vec![
+                    Expr::Lit(Lit::Str(Str {
+                        span: DUMMY_SP,
+                        value: brand.clone().into(),
+                        raw: None,
+                    })),
+                    Self::make_cb(self.decode_expr(ty, Required::Known(true))),
+                ],
+            ),
+            JsonSchema::Object {
+                vs,
+                rest,
+                pattern_vs,
+            } => {
                 let mut extra = vec![Expr::Object(ObjectLit {
                     span: DUMMY_SP,
                     props: vs
@@ -443,14 +538,65 @@ impl<'a> DecoderFnGenerator<'a> {
                                     Optionality::Required(schema) => {
                                         self.decode_expr(schema, Required::Known(true))
                                     }
+                                    Optionality::Default(schema, value) => Self::decode_call_extra(
+                                        "decodeDefault",
+                                        Required::Known(false),
+                                        vec![
+                                            value.clone().to_expr(),
+                                            Self::make_cb(
+                                                self.decode_expr(schema, Required::Known(false)),
+                                            ),
+                                        ],
+                                    ),
                                 })),
                             })))
                         })
                         .collect(),
                 })];
-                if let Some(rest) = rest {
-                    let rest = self.decode_expr(rest, Required::Known(false));
-                    extra.push(Self::make_cb(rest));
+                match rest {
+                    Some(rest) => {
+                        let rest = self.decode_expr(rest, Required::Known(false));
+                        extra.push(Self::make_cb(rest));
+                    }
+                    None if !pattern_vs.is_empty() => {
+                        extra.push(Expr::Lit(Lit::Null(Null { span: DUMMY_SP })));
+                    }
+                    None => {}
+                }
+                if !pattern_vs.is_empty() {
+                    extra.push(Expr::Array(ArrayLit {
+                        span: DUMMY_SP,
+                        elems: pattern_vs
+                            .iter()
+                            .map(|(key, value)| {
+                                let pair = Expr::Array(ArrayLit {
+                                    span: DUMMY_SP,
+                                    elems: vec![
+                                        Some(ExprOrSpread {
+                                            spread: None,
+                                            expr: Expr::Lit(Lit::Regex(Regex {
+                                                span: DUMMY_SP,
+                                                exp: TplLitTypeItem::pattern_vec(key).into(),
+                                                flags: "".into(),
+                                            }))
+                                            .into(),
+                                        }),
+                                        Some(ExprOrSpread {
+                                            spread: None,
+                                            expr: Self::make_cb(
+                                                self.decode_expr(value, Required::Known(false)),
+                                            )
+                                            .into(),
+                                        }),
+                                    ],
+                                });
+                                Some(ExprOrSpread {
+                                    spread: None,
+                                    expr: pair.into(),
+                                })
+                            })
+                            .collect(),
+                    }));
                 }
                 Self::decode_call_extra("decodeObject", required, extra)
             }
@@ -461,57 +607,10 @@ impl<'a> DecoderFnGenerator<'a> {
             ),
             JsonSchema::Tuple {
                 prefix_items,
+                optional_items,
                 items,
-            } => Self::decode_call_extra(
-                "decodeTuple",
-                required,
-                vec![Expr::Object(ObjectLit {
-                    span: DUMMY_SP,
-                    props: vec![
-                        PropOrSpread::Prop(
-                            Prop::KeyValue(KeyValueProp {
-                                key: PropName::Ident(Ident {
-                                    span: DUMMY_SP,
-                                    sym: "prefix".into(),
-                                    optional: false,
-                                }),
-                                value: Box::new(Expr::Array(ArrayLit {
-                                    span: DUMMY_SP,
-                                    elems: prefix_items
-                                        .iter()
-                                        .map(|it| {
-                                            Some(ExprOrSpread {
-                                                spread: None,
-                                                expr: Self::make_cb(
-                                                    self.decode_expr(it, Required::Known(true)),
-                                                )
-                                                .into(),
-                                            })
-                                        })
-                                        .collect(),
-                                })),
-                            })
-                            .into(),
-                        ),
-                        PropOrSpread::Prop(
-                            Prop::KeyValue(KeyValueProp {
-                                key: PropName::Ident(Ident {
-                                    span: DUMMY_SP,
-                                    sym: "items".into(),
-                                    optional: false,
-                                }),
-                                value: Box::new(match items {
-                                    Some(v) => {
-                                        Self::make_cb(self.decode_expr(v, Required::Known(true)))
-                                    }
-                                    None => Expr::Lit(Lit::Null(Null { span: DUMMY_SP })),
-                                }),
-                            })
-                            .into(),
-                        ),
-                    ],
-                })],
-            ),
+                suffix_items,
+            } => self.decode_tuple(prefix_items, optional_items, items, suffix_items, required),
             JsonSchema::AnyOf(vs) => self.decode_any_of(vs, required),
             JsonSchema::AllOf(vs) => self.decode_union_or_intersection("decodeAllOf", required, vs),
             JsonSchema::Const(json) => Self::decode_call_extra(
@@ -555,7 +654,83 @@ impl<'a> DecoderFnGenerator<'a> {
         }
     }
 
-    fn fn_decoder_from_schema(&mut self, schema: &JsonSchema) -> Function {
+    // `ctx.unknownKeys ??= "strict";`, the options of `parse` take precedence
+    fn unknown_keys_stmt(unknown_keys: UnknownKeys) -> Stmt {
+        Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Expr::Assign(AssignExpr {
+                span: DUMMY_SP,
+                op: AssignOp::NullishAssign,
+                left: PatOrExpr::Expr(
+                    Expr::Member(MemberExpr {
+                        span: DUMMY_SP,
+                        obj: SwcBuilder::ident_expr("ctx").into(),
+                        prop: MemberProp::Ident(Ident {
+                            span: DUMMY_SP,
+                            sym: "unknownKeys".into(),
+                            optional: false,
+                        }),
+                    })
+                    .into(),
+                ),
+                right: Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: unknown_keys.to_string().into(),
+                    raw: None,
+                }))
+                .into(),
+            })
+            .into(),
+        })
+    }
+
+    // `ctx.coerce = true;`, path and query parameters are strings in the URL
+    fn coerce_stmt() -> Stmt {
+        Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Expr::Assign(AssignExpr {
+                span: DUMMY_SP,
+                op: AssignOp::Assign,
+                left: PatOrExpr::Expr(
+                    Expr::Member(MemberExpr {
+                        span: DUMMY_SP,
+                        obj: SwcBuilder::ident_expr("ctx").into(),
+                        prop: MemberProp::Ident(Ident {
+                            span: DUMMY_SP,
+                            sym: "coerce".into(),
+                            optional: false,
+                        }),
+                    })
+                    .into(),
+                ),
+                right: Expr::Lit(Lit::Bool(Bool {
+                    span: DUMMY_SP,
+                    value: true,
+                }))
+                .into(),
+            })
+            .into(),
+        })
+    }
+
+    fn fn_decoder_from_schema(
+        &mut self,
+        schema: &JsonSchema,
+        unknown_keys: UnknownKeys,
+        coerce: bool,
+    ) -> Function {
+        let mut stmts = vec![];
+        // stripping is what objects do when the policy is not set
+        if unknown_keys != UnknownKeys::Strip {
+            stmts.push(Self::unknown_keys_stmt(unknown_keys));
+        }
+        if coerce {
+            stmts.push(Self::coerce_stmt());
+        }
+        stmts.push(Stmt::Return(ReturnStmt {
+            span: DUMMY_SP,
+            arg: Some(Box::new(self.decode_expr(schema, Required::FromArgs))),
+        }));
         Function {
             params: vec![
                 Param {
@@ -604,10 +779,7 @@ impl<'a> DecoderFnGenerator<'a> {
             span: DUMMY_SP,
             body: BlockStmt {
                 span: DUMMY_SP,
-                stmts: vec![Stmt::Return(ReturnStmt {
-                    span: DUMMY_SP,
-                    arg: Some(Box::new(self.decode_expr(schema, Required::FromArgs))),
-                })],
+                stmts,
             }
             .into(),
             is_async: false,
@@ -619,5 +791,17 @@ impl<'a> DecoderFnGenerator<'a> {
 }
 #[must_use]
 pub fn from_schema(schema: &JsonSchema, validators: &Vec<Validator>) -> Function {
-    DecoderFnGenerator { validators }.fn_decoder_from_schema(schema)
+    DecoderFnGenerator { validators }.fn_decoder_from_schema(schema, UnknownKeys::default(), false)
+}
+
+// The decoder of a parser or route, it sets the unknown keys policy of the whole decoding.
+// Coercing decoders read numbers, booleans and arrays from their string form.
+#[must_use]
+pub fn entry_from_schema(
+    schema: &JsonSchema,
+    validators: &Vec<Validator>,
+    unknown_keys: UnknownKeys,
+    coerce: bool,
+) -> Function {
+    DecoderFnGenerator { validators }.fn_decoder_from_schema(schema, unknown_keys, coerce)
 }
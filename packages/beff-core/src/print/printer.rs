@@ -1,11 +1,18 @@
 use crate::ast::json::Json;
+use crate::ast::json_schema::JsonSchema;
 use crate::emit::emit_module;
-use crate::parser_extractor::BuiltDecoder;
+use crate::mock::{mock, MockOptions};
+use crate::parser_extractor::{BuiltDecoder, ParserExtractResult};
 use crate::print::decoder;
+use crate::qualified_name::rename_schema;
+use crate::route_extractor::{routes_to_openapi_paths, BuiltRoute};
+use crate::schema_changes::print_type_aliases;
 use crate::ExtractResult;
+use crate::UnknownKeys;
 use crate::Validator;
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use swc_common::DUMMY_SP;
 use swc_ecma_ast::{
     BindingIdent, Decl, Expr, FnDecl, FnExpr, Ident, KeyValueProp, ModuleItem, ObjectLit, Pat,
@@ -40,13 +47,22 @@ fn const_decl(name: &str, init: Expr) -> ModuleItem {
 pub struct WritableModules {
     pub js_validators: String,
     pub js_built_parsers: Option<String>,
+    pub ts_declarations: Option<String>,
     pub json_schema: Option<String>,
+    pub js_built_routes: Option<String>,
+    pub openapi_paths: Option<String>,
 }
 
 pub trait ToWritableModules {
     fn to_module(self) -> Result<WritableModules>;
+    // Like `to_module`, and every entry of the JSON Schema gets an example value.
+    fn to_module_with_examples(self, examples: &MockOptions) -> Result<WritableModules>;
 }
-fn build_decoders_expr(decs: &[BuiltDecoder], validators: &Vec<Validator>) -> Expr {
+fn build_decoders_expr(
+    decs: &[BuiltDecoder],
+    validators: &Vec<Validator>,
+    unknown_keys: UnknownKeys,
+) -> Expr {
     let mut exprs: Vec<_> = decs
         .iter()
         .map(|decoder| {
@@ -54,7 +70,13 @@ fn build_decoders_expr(decs: &[BuiltDecoder], validators: &Vec<Validator>) -> Ex
                 decoder.exported_name.clone(),
                 Expr::Fn(FnExpr {
                     ident: None,
-                    function: decoder::from_schema(&decoder.schema, validators).into(),
+                    function: decoder::entry_from_schema(
+                        &decoder.schema,
+                        validators,
+                        unknown_keys,
+                        false,
+                    )
+                    .into(),
                 }),
             )
         })
@@ -66,27 +88,193 @@ fn build_decoders_expr(decs: &[BuiltDecoder], validators: &Vec<Validator>) -> Ex
         span: DUMMY_SP,
         props: exprs
             .into_iter()
-            .map(|(key, value)| {
-                PropOrSpread::Prop(
-                    Prop::KeyValue(KeyValueProp {
-                        key: PropName::Str(Str {
-                            span: DUMMY_SP,
-                            value: key.into(),
-                            raw: None,
-                        }),
-                        value: value.into(),
-                    })
-                    .into(),
-                )
-            })
+            .map(|(key, value)| string_key_prop(&key, value))
+            .collect(),
+    })
+}
+
+fn string_key_prop(key: &str, value: Expr) -> PropOrSpread {
+    PropOrSpread::Prop(
+        Prop::KeyValue(KeyValueProp {
+            key: PropName::Str(Str {
+                span: DUMMY_SP,
+                value: key.into(),
+                raw: None,
+            }),
+            value: value.into(),
+        })
+        .into(),
+    )
+}
+
+fn build_routes_expr(
+    routes: &[BuiltRoute],
+    validators: &Vec<Validator>,
+    unknown_keys: UnknownKeys,
+) -> Expr {
+    let decoder_expr = |schema: &JsonSchema, coerce: bool| {
+        Expr::Fn(FnExpr {
+            ident: None,
+            function: decoder::entry_from_schema(schema, validators, unknown_keys, coerce).into(),
+        })
+    };
+    let mut exprs: Vec<_> = routes
+        .iter()
+        .map(|route| {
+            let parts = [
+                ("params", route.params.as_ref()),
+                ("query", route.query.as_ref()),
+                ("body", route.body.as_ref()),
+                ("response", Some(&route.response)),
+            ];
+            let props = parts
+                .into_iter()
+                .filter_map(|(name, schema)| {
+                    // path and query parameters are read from the URL
+                    let coerce = matches!(name, "params" | "query");
+                    schema.map(|it| string_key_prop(name, decoder_expr(it, coerce)))
+                })
+                .collect();
+            (
+                route.key.clone(),
+                Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props,
+                }),
+            )
+        })
+        .collect();
+
+    exprs.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Expr::Object(ObjectLit {
+        span: DUMMY_SP,
+        props: exprs
+            .into_iter()
+            .map(|(key, value)| string_key_prop(&key, value))
             .collect(),
     })
 }
 
-fn merge_validator(parser: Option<&Vec<Validator>>) -> Result<Vec<Validator>> {
+pub(crate) fn collect_refs(schema: &JsonSchema, acc: &mut Vec<String>) {
+    match schema {
+        JsonSchema::Ref(name) => acc.push(name.clone()),
+        JsonSchema::Object {
+            vs,
+            rest,
+            pattern_vs,
+        } => {
+            for v in vs.values() {
+                collect_refs(v.inner(), acc);
+            }
+            for v in rest.iter().map(|it| it.as_ref()).chain(pattern_vs.values()) {
+                collect_refs(v, acc);
+            }
+        }
+        JsonSchema::Array(it) | JsonSchema::StNot(it) | JsonSchema::Brand(it, _) => {
+            collect_refs(it, acc)
+        }
+        JsonSchema::Tuple {
+            prefix_items,
+            optional_items,
+            items,
+            suffix_items,
+        } => {
+            for v in prefix_items
+                .iter()
+                .chain(optional_items)
+                .chain(items.iter().map(|it| it.as_ref()))
+                .chain(suffix_items)
+            {
+                collect_refs(v, acc);
+            }
+        }
+        JsonSchema::AnyOf(vs) | JsonSchema::AllOf(vs) => {
+            for v in vs {
+                collect_refs(v, acc);
+            }
+        }
+        JsonSchema::Null
+        | JsonSchema::Boolean
+        | JsonSchema::String
+        | JsonSchema::Number
+        | JsonSchema::Any
+        | JsonSchema::AnyArrayLike
+        | JsonSchema::StringWithFormat(_)
+        | JsonSchema::NumberWithFormat(_)
+        | JsonSchema::TplLitType(_)
+        | JsonSchema::Const(_)
+        | JsonSchema::Codec(_)
+        | JsonSchema::StNever => {}
+    }
+}
+
+// A declaration file that only depends on itself: every parser type is exported, and the types
+// they reference are declared next to them without being exported.
+fn ts_declarations(parser: &ParserExtractResult) -> String {
+    let exports: BTreeMap<String, &JsonSchema> = parser
+        .exported_decoders()
+        .into_iter()
+        .map(|(name, it)| (name, &it.schema))
+        .collect();
+
+    // a type that has the name of a different exported parser is renamed
+    let mut taken: BTreeSet<String> = exports.keys().map(|it| it.to_string()).collect();
+    taken.extend(parser.validators.iter().map(|it| it.name.clone()));
+    let mut renames = BTreeMap::new();
+    for v in &parser.validators {
+        let clashes = exports
+            .get(v.name.as_str())
+            .is_some_and(|it| **it != JsonSchema::Ref(v.name.clone()));
+        if clashes {
+            let mut n = 1;
+            while taken.contains(&format!("{}${n}", v.name)) {
+                n += 1;
+            }
+            let name = format!("{}${n}", v.name);
+            taken.insert(name.clone());
+            renames.insert(v.name.clone(), name);
+        }
+    }
+    let validators: BTreeMap<String, JsonSchema> = parser
+        .validators
+        .iter()
+        .map(|it| {
+            let name = renames.get(&it.name).unwrap_or(&it.name).clone();
+            (name, rename_schema(it.schema.clone(), &renames))
+        })
+        .collect();
+
+    let mut acc = vec![];
+    let mut declared = BTreeSet::new();
+    let mut pending = vec![];
+    for (name, schema) in exports {
+        let schema = rename_schema(schema.clone(), &renames);
+        // `{ User: User }` exports the declaration of `User` itself
+        let schema = match &schema {
+            JsonSchema::Ref(r) if *r == name => validators[r].clone(),
+            _ => schema,
+        };
+        collect_refs(&schema, &mut pending);
+        declared.insert(name.clone());
+        acc.push((name, schema.to_ts_type(), true));
+    }
+    let mut helpers = BTreeMap::new();
+    while let Some(name) = pending.pop() {
+        if declared.insert(name.clone()) {
+            let schema = &validators[&name];
+            collect_refs(schema, &mut pending);
+            helpers.insert(name, schema.to_ts_type());
+        }
+    }
+    acc.extend(helpers.into_iter().map(|(name, ty)| (name, ty, false)));
+    print_type_aliases(acc)
+}
+
+fn merge_validator<'a>(all: impl Iterator<Item = &'a Vec<Validator>>) -> Result<Vec<Validator>> {
     let mut acc: Vec<Validator> = vec![];
 
-    if let Some(parser) = parser {
+    for parser in all {
         for d in parser {
             let found = acc.iter_mut().find(|x| x.name == d.name);
             if let Some(found) = found {
@@ -104,80 +292,135 @@ fn merge_validator(parser: Option<&Vec<Validator>>) -> Result<Vec<Validator>> {
 }
 impl ToWritableModules for ExtractResult {
     fn to_module(self) -> Result<WritableModules> {
-        let mut stmt_validators = vec![];
+        extract_result_to_module(self, None)
+    }
+
+    fn to_module_with_examples(self, examples: &MockOptions) -> Result<WritableModules> {
+        extract_result_to_module(self, Some(examples))
+    }
+}
 
-        let mut validator_names = vec![];
+fn extract_result_to_module(
+    res: ExtractResult,
+    examples: Option<&MockOptions>,
+) -> Result<WritableModules> {
+    let mut stmt_validators = vec![];
 
-        let validators = merge_validator(self.parser.as_ref().map(|it| &it.validators))?;
+    let mut validator_names = vec![];
 
-        for comp in &validators {
-            validator_names.push(comp.name.clone());
-            let decoder_fn = decoder::from_schema(&comp.schema, &validators);
-            let decoder_fn_decl = ModuleItem::Stmt(Stmt::Decl(Decl::Fn(FnDecl {
-                ident: Ident {
-                    span: DUMMY_SP,
-                    sym: ("Decode".to_string() + comp.name.as_str()).into(),
-                    optional: false,
-                },
-                declare: false,
-                function: decoder_fn.into(),
-            })));
-            stmt_validators.push(decoder_fn_decl);
-        }
+    let validators = merge_validator(
+        res.parser
+            .iter()
+            .map(|it| &it.validators)
+            .chain(res.routes.iter().map(|it| &it.validators)),
+    )?;
 
-        stmt_validators.push(const_decl(
-            "validators",
-            Expr::Object(ObjectLit {
+    for comp in &validators {
+        validator_names.push(comp.name.clone());
+        let decoder_fn = decoder::from_schema(&comp.schema, &validators);
+        let decoder_fn_decl = ModuleItem::Stmt(Stmt::Decl(Decl::Fn(FnDecl {
+            ident: Ident {
                 span: DUMMY_SP,
-                props: validator_names
-                    .clone()
-                    .into_iter()
-                    .map(|it| {
-                        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
-                            key: PropName::Ident(Ident {
-                                span: DUMMY_SP,
-                                sym: it.clone().into(),
-                                optional: false,
-                            }),
-                            value: Expr::Ident(Ident {
-                                span: DUMMY_SP,
-                                sym: ("Decode".to_string() + it.as_str()).into(),
-                                optional: false,
-                            })
-                            .into(),
-                        })))
-                    })
-                    .collect(),
-            }),
-        ));
+                sym: ("Decode".to_string() + comp.name.as_str()).into(),
+                optional: false,
+            },
+            declare: false,
+            function: decoder_fn.into(),
+        })));
+        stmt_validators.push(decoder_fn_decl);
+    }
 
-        let js_validators = emit_module(stmt_validators, "\n")?;
+    stmt_validators.push(const_decl(
+        "validators",
+        Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: validator_names
+                .clone()
+                .into_iter()
+                .map(|it| {
+                    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                        key: PropName::Ident(Ident {
+                            span: DUMMY_SP,
+                            sym: it.clone().into(),
+                            optional: false,
+                        }),
+                        value: Expr::Ident(Ident {
+                            span: DUMMY_SP,
+                            sym: ("Decode".to_string() + it.as_str()).into(),
+                            optional: false,
+                        })
+                        .into(),
+                    })))
+                })
+                .collect(),
+        }),
+    ));
 
-        let mut js_built_parsers = None;
+    let js_validators = emit_module(stmt_validators, "\n")?;
 
-        if let Some(parser) = self.parser {
-            let decoders = parser.built_decoders.unwrap_or_default();
-            let decoders_expr = build_decoders_expr(&decoders, &validators);
-            let built_st = const_decl("buildParsersInput", decoders_expr);
-            js_built_parsers = Some(emit_module(vec![built_st], "\n")?);
-        }
+    let mut js_built_parsers = None;
+    let mut ts_declarations = None;
 
-        let mut json_schema = None;
-        if let Some(schema) = self.schema {
-            let decoders = schema.built_decoders.unwrap_or_default();
-            let json_schema_obj = Json::object(
-                decoders
-                    .iter()
-                    .flat_map(|it| BuiltDecoder::to_json_kv(it, &validators))
-                    .collect(),
-            );
-            // schema
-            json_schema = Some(json_schema_obj.to_string());
+    if let Some(parser) = res.parser {
+        ts_declarations = Some(self::ts_declarations(&parser));
+        // each `buildParsers` call gets its own decoders, keyed by call site
+        let calls_expr = Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: parser
+                .built_parsers
+                .iter()
+                .map(|it| {
+                    string_key_prop(
+                        &it.call_site_id(),
+                        build_decoders_expr(&it.built_decoders, &validators, it.unknown_keys),
+                    )
+                })
+                .collect(),
+        });
+        let built_st = const_decl("buildParsersInput", calls_expr);
+        js_built_parsers = Some(emit_module(vec![built_st], "\n")?);
+    }
+
+    let mut json_schema = None;
+    if let Some(schema) = res.schema {
+        let decoders = schema.built_decoders.unwrap_or_default();
+        let mut kvs = vec![];
+        for it in &decoders {
+            for (name, json) in it.to_json_kv(&schema.validators, schema.unknown_keys) {
+                let json = match (json, examples) {
+                    (Json::Object(mut vs), Some(options)) => {
+                        let example = mock(&it.schema, &schema.validators, options)
+                            .map_err(|e| anyhow!("failed to build an example of '{name}': {e}"))?;
+                        vs.insert("examples".into(), Json::Array(vec![example]));
+                        Json::Object(vs)
+                    }
+                    (json, _) => json,
+                };
+                kvs.push((name, json));
+            }
         }
-        Ok(WritableModules {
-            js_validators,
-            js_built_parsers,
-            json_schema,
-        })
+        let json_schema_obj = Json::object(kvs);
+        // schema
+        json_schema = Some(json_schema_obj.to_string());
+    }
+
+    let mut js_built_routes = None;
+    let mut openapi_paths = None;
+    if let Some(routes) = res.routes {
+        let unknown_keys = routes.unknown_keys;
+        let routes = routes.built_routes.unwrap_or_default();
+        let routes_expr = build_routes_expr(&routes, &validators, unknown_keys);
+        let built_st = const_decl("buildRoutesInput", routes_expr);
+        js_built_routes = Some(emit_module(vec![built_st], "\n")?);
+        openapi_paths =
+            Some(routes_to_openapi_paths(&routes, &validators, unknown_keys).to_string());
     }
+    Ok(WritableModules {
+        js_validators,
+        js_built_parsers,
+        ts_declarations,
+        json_schema,
+        js_built_routes,
+        openapi_paths,
+    })
 }
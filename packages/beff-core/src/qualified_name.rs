@@ -0,0 +1,306 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use swc_common::Span;
+
+use crate::ast::json_schema::{JsonSchema, Optionality};
+use crate::parser_extractor::ParserExtractResult;
+use crate::route_extractor::RoutesExtractResult;
+use crate::schema_extractor::SchemaExtractResult;
+use crate::{BffFileName, ExtractResult, Validator};
+
+const SEPARATOR: &str = "::";
+
+// Components are keyed by the declaration they come from, so that two types with the same name
+// declared in different places do not clash. The keys are replaced by display names once every
+// entry point has been extracted.
+pub fn qualified_type_name(name: &str, file: &BffFileName, span: Span) -> String {
+    qualified_type_name_from(name, file.as_str(), span.lo.0)
+}
+
+fn qualified_type_name_from(name: &str, file: &str, pos: u32) -> String {
+    format!("{name}{SEPARATOR}{file}{SEPARATOR}{pos}")
+}
+
+struct QualifiedName<'a> {
+    name: &'a str,
+    file: &'a str,
+    pos: u32,
+}
+
+fn parse_qualified_name(it: &str) -> Option<QualifiedName<'_>> {
+    let (name, rest) = it.split_once(SEPARATOR)?;
+    let (file, pos) = rest.rsplit_once(SEPARATOR)?;
+    Some(QualifiedName {
+        name,
+        file,
+        pos: pos.parse().ok()?,
+    })
+}
+
+// The name the user wrote, for diagnostics.
+pub fn type_name(it: &str) -> &str {
+    parse_qualified_name(it).map(|it| it.name).unwrap_or(it)
+}
+
+fn sanitize(segment: &str) -> String {
+    segment
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '$' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn file_segments(file: &str) -> Vec<String> {
+    let stem = file
+        .rsplit_once('/')
+        .map(|(_, it)| it)
+        .unwrap_or(file)
+        .split('.')
+        .next()
+        .unwrap_or_default();
+    let mut segments: Vec<String> = file
+        .split('/')
+        .filter(|it| !it.is_empty())
+        .map(sanitize)
+        .collect();
+    segments.pop();
+    segments.push(sanitize(stem));
+    segments
+}
+
+// Types with the same name are prefixed by the shortest trailing part of their file path that
+// tells them apart, ie: `orders_Status` and `users_Status`. A type declared in an entry file keeps
+// its name, so that adding a type elsewhere does not rename it. Types with the same name in the
+// same file (in different namespaces) are numbered in declaration order, ie: `Status` and `Status$1`.
+fn display_names(
+    names: &BTreeSet<String>,
+    entry_files: &BTreeSet<&str>,
+) -> BTreeMap<String, String> {
+    let mut taken: BTreeSet<String> = names
+        .iter()
+        .filter(|it| parse_qualified_name(it).is_none())
+        .cloned()
+        .collect();
+
+    let mut by_name: BTreeMap<&str, Vec<QualifiedName>> = BTreeMap::new();
+    for it in names {
+        if let Some(q) = parse_qualified_name(it) {
+            by_name.entry(q.name).or_default().push(q);
+        }
+    }
+
+    let mut acc = BTreeMap::new();
+    for (name, mut decls) in by_name {
+        decls.sort_by(|a, b| (a.file, a.pos).cmp(&(b.file, b.pos)));
+        let plain_file = decls
+            .iter()
+            .map(|it| it.file)
+            .find(|it| entry_files.contains(it));
+        let files: BTreeSet<&str> = decls
+            .iter()
+            .map(|it| it.file)
+            .filter(|it| Some(*it) != plain_file)
+            .collect();
+        let segments: BTreeMap<&str, Vec<String>> =
+            files.iter().map(|it| (*it, file_segments(it))).collect();
+
+        let mut depth = 0;
+        let prefix = |file: &str, depth: usize| -> Vec<String> {
+            match segments.get(file) {
+                Some(segs) => segs[segs.len().saturating_sub(depth)..].to_vec(),
+                None => vec![],
+            }
+        };
+        if files.len() > 1 || (plain_file.is_some() && !files.is_empty()) {
+            let max_depth = segments.values().map(|it| it.len()).max().unwrap_or(0);
+            depth = 1;
+            while depth < max_depth {
+                let prefixes: BTreeSet<Vec<String>> =
+                    files.iter().map(|it| prefix(it, depth)).collect();
+                if prefixes.len() == files.len() {
+                    break;
+                }
+                depth += 1;
+            }
+        }
+
+        let mut file_counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for decl in &decls {
+            let count = file_counts.entry(decl.file).or_default();
+            let mut display = prefix(decl.file, depth);
+            display.push(name.to_string());
+            let mut display = display.join("_");
+            if *count > 0 {
+                display = format!("{display}${count}");
+            }
+            *count += 1;
+            let base = display.clone();
+            let mut n = 1;
+            while taken.contains(&display) {
+                display = format!("{base}${n}");
+                n += 1;
+            }
+            taken.insert(display.clone());
+            acc.insert(qualified_type_name_from(name, decl.file, decl.pos), display);
+        }
+    }
+    acc
+}
+
+fn rename(it: &str, names: &BTreeMap<String, String>) -> String {
+    names.get(it).cloned().unwrap_or_else(|| it.to_string())
+}
+
+fn rename_optionality(
+    it: Optionality<JsonSchema>,
+    names: &BTreeMap<String, String>,
+) -> Optionality<JsonSchema> {
+    it.map(|it| rename_schema(it, names))
+}
+
+pub fn rename_schema(schema: JsonSchema, names: &BTreeMap<String, String>) -> JsonSchema {
+    match schema {
+        JsonSchema::Ref(r) => JsonSchema::Ref(rename(&r, names)),
+        JsonSchema::Object {
+            vs,
+            rest,
+            pattern_vs,
+        } => JsonSchema::Object {
+            vs: vs
+                .into_iter()
+                .map(|(k, v)| (k, rename_optionality(v, names)))
+                .collect(),
+            rest: rest.map(|it| rename_schema(*it, names).into()),
+            pattern_vs: pattern_vs
+                .into_iter()
+                .map(|(k, v)| (k, rename_schema(v, names)))
+                .collect(),
+        },
+        JsonSchema::Array(it) => JsonSchema::Array(rename_schema(*it, names).into()),
+        JsonSchema::Tuple {
+            prefix_items,
+            optional_items,
+            items,
+            suffix_items,
+        } => JsonSchema::Tuple {
+            prefix_items: prefix_items
+                .into_iter()
+                .map(|it| rename_schema(it, names))
+                .collect(),
+            optional_items: optional_items
+                .into_iter()
+                .map(|it| rename_schema(it, names))
+                .collect(),
+            items: items.map(|it| rename_schema(*it, names).into()),
+            suffix_items: suffix_items
+                .into_iter()
+                .map(|it| rename_schema(it, names))
+                .collect(),
+        },
+        JsonSchema::AnyOf(vs) => {
+            JsonSchema::AnyOf(vs.into_iter().map(|it| rename_schema(it, names)).collect())
+        }
+        JsonSchema::AllOf(vs) => {
+            JsonSchema::AllOf(vs.into_iter().map(|it| rename_schema(it, names)).collect())
+        }
+        JsonSchema::StNot(it) => JsonSchema::StNot(rename_schema(*it, names).into()),
+        JsonSchema::Brand(it, brand) => JsonSchema::Brand(rename_schema(*it, names).into(), brand),
+        JsonSchema::Null
+        | JsonSchema::Boolean
+        | JsonSchema::String
+        | JsonSchema::Number
+        | JsonSchema::Any
+        | JsonSchema::AnyArrayLike
+        | JsonSchema::StringWithFormat(_)
+        | JsonSchema::NumberWithFormat(_)
+        | JsonSchema::TplLitType(_)
+        | JsonSchema::Const(_)
+        | JsonSchema::Codec(_)
+        | JsonSchema::StNever => schema,
+    }
+}
+
+fn rename_validators(
+    validators: Vec<Validator>,
+    names: &BTreeMap<String, String>,
+) -> Vec<Validator> {
+    validators
+        .into_iter()
+        .map(|it| Validator {
+            name: rename(&it.name, names),
+            schema: rename_schema(it.schema, names),
+        })
+        .collect()
+}
+
+fn rename_parser(
+    mut it: ParserExtractResult,
+    names: &BTreeMap<String, String>,
+) -> ParserExtractResult {
+    it.validators = rename_validators(it.validators, names);
+    for call in it.built_parsers.iter_mut() {
+        for decoder in call.built_decoders.iter_mut() {
+            decoder.schema = rename_schema(decoder.schema.clone(), names);
+        }
+    }
+    it
+}
+
+fn rename_schema_result(
+    mut it: SchemaExtractResult,
+    names: &BTreeMap<String, String>,
+) -> SchemaExtractResult {
+    it.validators = rename_validators(it.validators, names);
+    for decoder in it.built_decoders.iter_mut().flatten() {
+        decoder.schema = rename_schema(decoder.schema.clone(), names);
+    }
+    it
+}
+
+fn rename_routes(
+    mut it: RoutesExtractResult,
+    names: &BTreeMap<String, String>,
+) -> RoutesExtractResult {
+    it.validators = rename_validators(it.validators, names);
+    for route in it.built_routes.iter_mut().flatten() {
+        for part in [&mut route.params, &mut route.query, &mut route.body] {
+            *part = part.take().map(|it| rename_schema(it, names));
+        }
+        route.response = rename_schema(route.response.clone(), names);
+    }
+    it
+}
+
+// Replaces the qualified names of all entry points by display names, so that the parser, schema
+// and routes modules agree on the name of every type.
+pub fn assign_display_names(result: ExtractResult) -> ExtractResult {
+    let mut all = BTreeSet::new();
+    let validators = result
+        .parser
+        .iter()
+        .flat_map(|it| it.validators.iter())
+        .chain(result.schema.iter().flat_map(|it| it.validators.iter()))
+        .chain(result.routes.iter().flat_map(|it| it.validators.iter()));
+    for v in validators {
+        all.insert(v.name.clone());
+    }
+    let entry_files: BTreeSet<&str> = result
+        .parser
+        .iter()
+        .flat_map(|it| it.entry_file_names.iter())
+        .chain(result.schema.iter().map(|it| &it.entry_file_name))
+        .chain(result.routes.iter().map(|it| &it.entry_file_name))
+        .map(|it| it.as_str())
+        .collect();
+    let names = display_names(&all, &entry_files);
+    ExtractResult {
+        parser: result.parser.map(|it| rename_parser(it, &names)),
+        schema: result.schema.map(|it| rename_schema_result(it, &names)),
+        routes: result.routes.map(|it| rename_routes(it, &names)),
+    }
+}
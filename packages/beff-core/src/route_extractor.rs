@@ -0,0 +1,525 @@
+use crate::ast::json::Json;
+use crate::ast::json_schema::{JsonFlatConverter, JsonSchema, Optionality};
+use crate::diag::{Diagnostic, DiagnosticInfoMessage, DiagnosticParentMessage};
+use crate::extractor::{ExtractorState, SpecialCallVisitor};
+use crate::{BeffUserSettings, UnknownKeys};
+use crate::{BffFileName, FileManager, Validator};
+use anyhow::Result;
+use swc_common::Span;
+use swc_ecma_ast::{
+    CallExpr, Expr, Ident, Lit, TsCallSignatureDecl, TsConstructSignatureDecl, TsGetterSignature,
+    TsIndexSignature, TsLit, TsLitType, TsMethodSignature, TsPropertySignature, TsSetterSignature,
+    TsType, TsTypeElement, TsTypeLit, TsTypeParamInstantiation,
+};
+use swc_ecma_visit::Visit;
+
+const HTTP_METHODS: [&str; 7] = ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+
+#[derive(Debug)]
+pub struct BuiltRoute {
+    // the key used in `buildRoutes`, ie: "GET /users/{id}"
+    pub key: String,
+    pub method: String,
+    pub path: String,
+    pub path_params: Vec<String>,
+    pub params: Option<JsonSchema>,
+    pub query: Option<JsonSchema>,
+    pub body: Option<JsonSchema>,
+    pub response: JsonSchema,
+    // the declared `status`, or 204 for a `null` response and 200 otherwise
+    pub status: u16,
+}
+
+impl BuiltRoute {
+    fn object_parameters(
+        &self,
+        schema: &JsonSchema,
+        location: &str,
+        validators: &[Validator],
+        unknown_keys: UnknownKeys,
+    ) -> Vec<Json> {
+        let vs = match resolve_object(schema, validators) {
+            Some(vs) => vs,
+            None => return vec![],
+        };
+        vs.into_iter()
+            .map(|(name, v)| {
+                Json::object(vec![
+                    ("name".into(), Json::String(name)),
+                    ("in".into(), Json::String(location.into())),
+                    ("required".into(), Json::Bool(v.is_required())),
+                    (
+                        "schema".into(),
+                        JsonFlatConverter::new(validators, unknown_keys).property(v),
+                    ),
+                ])
+            })
+            .collect()
+    }
+
+    fn json_content(
+        schema: &JsonSchema,
+        validators: &[Validator],
+        unknown_keys: UnknownKeys,
+    ) -> Json {
+        Json::object(vec![(
+            "application/json".into(),
+            Json::object(vec![(
+                "schema".into(),
+                JsonFlatConverter::new(validators, unknown_keys).to_json_flat(schema.clone()),
+            )]),
+        )])
+    }
+
+    pub fn to_openapi_operation(
+        &self,
+        validators: &[Validator],
+        unknown_keys: UnknownKeys,
+    ) -> Json {
+        let mut parameters = vec![];
+        if let Some(params) = &self.params {
+            parameters.extend(self.object_parameters(params, "path", validators, unknown_keys));
+        }
+        if let Some(query) = &self.query {
+            parameters.extend(self.object_parameters(query, "query", validators, unknown_keys));
+        }
+        let mut vs = vec![];
+        if !parameters.is_empty() {
+            vs.push(("parameters".into(), Json::Array(parameters)));
+        }
+        if let Some(body) = &self.body {
+            vs.push((
+                "requestBody".into(),
+                Json::object(vec![
+                    ("required".into(), Json::Bool(true)),
+                    (
+                        "content".into(),
+                        Self::json_content(body, validators, unknown_keys),
+                    ),
+                ]),
+            ));
+        }
+        let mut response = vec![(
+            "description".into(),
+            Json::String("successful operation".into()),
+        )];
+        // a 204 response has no body
+        if self.status != 204 {
+            response.push((
+                "content".into(),
+                Self::json_content(&self.response, validators, unknown_keys),
+            ));
+        }
+        vs.push((
+            "responses".into(),
+            Json::object(vec![(self.status.to_string(), Json::object(response))]),
+        ));
+        Json::object(vs)
+    }
+}
+
+// builds the OpenAPI `paths` object, routes sharing a path are grouped by method
+pub fn routes_to_openapi_paths(
+    routes: &[BuiltRoute],
+    validators: &[Validator],
+    unknown_keys: UnknownKeys,
+) -> Json {
+    let mut paths: Vec<(String, Vec<(String, Json)>)> = vec![];
+    for route in routes {
+        let operation = (
+            route.method.to_lowercase(),
+            route.to_openapi_operation(validators, unknown_keys),
+        );
+        match paths.iter_mut().find(|(path, _)| path == &route.path) {
+            Some((_, operations)) => operations.push(operation),
+            None => paths.push((route.path.clone(), vec![operation])),
+        }
+    }
+    Json::object(
+        paths
+            .into_iter()
+            .map(|(path, operations)| (path, Json::object(operations)))
+            .collect(),
+    )
+}
+
+fn resolve_object(
+    schema: &JsonSchema,
+    validators: &[Validator],
+) -> Option<Vec<(String, Optionality<JsonSchema>)>> {
+    match schema {
+        JsonSchema::Object { vs, .. } => Some(vs.clone().into_iter().collect()),
+        JsonSchema::Ref(r) => {
+            let validator = validators.iter().find(|it| &it.name == r)?;
+            resolve_object(&validator.schema, validators)
+        }
+        _ => None,
+    }
+}
+
+// path and query parameters are strings in the URL, they can only hold primitive values
+fn is_simple_param(schema: &JsonSchema, validators: &[Validator]) -> bool {
+    match schema {
+        JsonSchema::String
+        | JsonSchema::StringWithFormat(_)
+        | JsonSchema::NumberWithFormat(_)
+        | JsonSchema::TplLitType(_)
+        | JsonSchema::Number
+        | JsonSchema::Boolean
+        | JsonSchema::Const(_)
+        | JsonSchema::Codec(_) => true,
+        JsonSchema::AnyOf(vs) => vs.iter().all(|it| is_simple_param(it, validators)),
+        JsonSchema::Brand(it, _) => is_simple_param(it, validators),
+        JsonSchema::Ref(r) => validators
+            .iter()
+            .find(|it| &it.name == r)
+            .is_some_and(|it| is_simple_param(&it.schema, validators)),
+        _ => false,
+    }
+}
+
+fn is_simple_query_param(schema: &JsonSchema, validators: &[Validator]) -> bool {
+    match schema {
+        JsonSchema::Array(items) => is_simple_param(items, validators),
+        _ => is_simple_param(schema, validators),
+    }
+}
+
+fn parse_path_params(path: &str) -> Vec<String> {
+    path.split('/')
+        .filter_map(|segment| {
+            segment
+                .strip_prefix('{')
+                .and_then(|it| it.strip_suffix('}'))
+                .map(|it| it.to_string())
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+pub struct RoutesExtractResult {
+    pub errors: Vec<Diagnostic>,
+    pub entry_file_name: BffFileName,
+    pub validators: Vec<Validator>,
+    pub unknown_keys: UnknownKeys,
+    pub built_routes: Option<Vec<BuiltRoute>>,
+    pub counter: usize,
+}
+
+struct ExtractRoutesVisitor<'a, R: FileManager> {
+    state: ExtractorState<'a, R>,
+    built_routes: Option<Vec<BuiltRoute>>,
+}
+impl<'a, R: FileManager> ExtractRoutesVisitor<'a, R> {
+    fn new(
+        files: &'a mut R,
+        current_file: BffFileName,
+        settings: &'a BeffUserSettings,
+    ) -> ExtractRoutesVisitor<'a, R> {
+        ExtractRoutesVisitor {
+            state: ExtractorState::new(files, current_file, settings),
+            built_routes: None,
+        }
+    }
+
+    fn validate_path_params(&mut self, params: &JsonSchema, path_params: &[String], span: &Span) {
+        let vs = match resolve_object(params, &self.state.validators) {
+            Some(vs) => vs,
+            None => {
+                return self
+                    .state
+                    .push_error(span, DiagnosticInfoMessage::RouteParamsShouldBeObject);
+            }
+        };
+        for name in path_params {
+            if !vs.iter().any(|(k, _)| k == name) {
+                self.state.push_error(
+                    span,
+                    DiagnosticInfoMessage::RouteParamNotDeclared(name.clone()),
+                );
+            }
+        }
+        for (name, v) in vs {
+            if !path_params.contains(&name) {
+                self.state
+                    .push_error(span, DiagnosticInfoMessage::RouteParamNotInPath(name));
+                continue;
+            }
+            let msg = match v {
+                Optionality::Optional(_) | Optionality::Default(_, _) => {
+                    DiagnosticInfoMessage::OptionalRouteParam(name)
+                }
+                Optionality::Required(it) if !is_simple_param(&it, &self.state.validators) => {
+                    DiagnosticInfoMessage::ComplexRouteParam(name)
+                }
+                Optionality::Required(_) => continue,
+            };
+            self.state.errors.push(
+                self.state
+                    .build_error(span, msg)
+                    .to_diag(Some(DiagnosticParentMessage::ComplexPathParam)),
+            );
+        }
+    }
+
+    fn validate_query(&mut self, query: &JsonSchema, span: &Span) {
+        let vs = match resolve_object(query, &self.state.validators) {
+            Some(vs) => vs,
+            None => {
+                return self
+                    .state
+                    .push_error(span, DiagnosticInfoMessage::RouteQueryShouldBeObject);
+            }
+        };
+        for (name, v) in vs {
+            if !is_simple_query_param(&v.inner_move(), &self.state.validators) {
+                self.state
+                    .push_error(span, DiagnosticInfoMessage::ComplexRouteParam(name));
+            }
+        }
+    }
+
+    fn extract_status(&mut self, ann: &TsType, span: &Span) -> Option<u16> {
+        if let TsType::TsLitType(TsLitType {
+            lit: TsLit::Number(n),
+            ..
+        }) = ann
+        {
+            if n.value.fract() == 0.0 && (100.0..600.0).contains(&n.value) {
+                return Some(n.value as u16);
+            }
+        }
+        self.state
+            .push_error(span, DiagnosticInfoMessage::InvalidRouteStatus);
+        None
+    }
+
+    fn extract_route_key(&mut self, key: &Expr, span: &Span) -> Result<(String, String, String)> {
+        let key = match key {
+            Expr::Lit(Lit::Str(st)) => st.value.to_string(),
+            _ => {
+                return self
+                    .state
+                    .error(span, DiagnosticInfoMessage::RouteKeyShouldBeMethodAndPath)
+            }
+        };
+        match key.split_once(' ') {
+            Some((method, path)) if path.starts_with('/') => {
+                if !HTTP_METHODS.contains(&method) {
+                    return self.state.error(
+                        span,
+                        DiagnosticInfoMessage::InvalidRouteMethod(method.to_string()),
+                    );
+                }
+                Ok((key.clone(), method.to_string(), path.to_string()))
+            }
+            _ => self
+                .state
+                .error(span, DiagnosticInfoMessage::RouteKeyShouldBeMethodAndPath),
+        }
+    }
+
+    fn extract_one_built_route(&mut self, prop: &TsTypeElement) -> Result<BuiltRoute> {
+        match prop {
+            TsTypeElement::TsPropertySignature(TsPropertySignature {
+                key,
+                type_ann,
+                type_params,
+                span,
+                ..
+            }) => {
+                if type_params.is_some() {
+                    return self
+                        .state
+                        .error(span, DiagnosticInfoMessage::GenericDecoderIsNotSupported);
+                }
+                let (key, method, path) = self.extract_route_key(key, span)?;
+                let members = match type_ann.as_ref().map(|it| it.type_ann.as_ref()) {
+                    Some(TsType::TsTypeLit(TsTypeLit { members, .. })) => members,
+                    _ => {
+                        return self
+                            .state
+                            .error(span, DiagnosticInfoMessage::RouteShouldBeObjectLiteral)
+                    }
+                };
+                let mut params = None;
+                let mut query = None;
+                let mut body = None;
+                let mut response = None;
+                let mut status = None;
+                for member in members {
+                    let (name, ann, member_span) = match member {
+                        TsTypeElement::TsPropertySignature(TsPropertySignature {
+                            key,
+                            type_ann: Some(ann),
+                            span,
+                            ..
+                        }) => match key.as_ref() {
+                            Expr::Ident(id) => (id.sym.to_string(), &ann.type_ann, span),
+                            _ => {
+                                self.state
+                                    .push_error(span, DiagnosticInfoMessage::InvalidRouteProperty);
+                                continue;
+                            }
+                        },
+                        _ => {
+                            self.state
+                                .push_error(span, DiagnosticInfoMessage::InvalidRouteProperty);
+                            continue;
+                        }
+                    };
+                    if name == "status" {
+                        status = self.extract_status(ann, member_span);
+                        continue;
+                    }
+                    let schema = self.state.convert_to_json_schema(ann, member_span);
+                    match name.as_str() {
+                        "params" => params = Some(schema),
+                        "query" => query = Some(schema),
+                        "body" => body = Some(schema),
+                        "response" => response = Some(schema),
+                        _ => self
+                            .state
+                            .push_error(member_span, DiagnosticInfoMessage::InvalidRouteProperty),
+                    }
+                }
+                let path_params = parse_path_params(&path);
+                match &params {
+                    Some(params) => self.validate_path_params(params, &path_params, span),
+                    None => {
+                        for name in &path_params {
+                            self.state.push_error(
+                                span,
+                                DiagnosticInfoMessage::RouteParamNotDeclared(name.clone()),
+                            );
+                        }
+                    }
+                }
+                if let Some(query) = &query {
+                    self.validate_query(query, span);
+                }
+                let response = match response {
+                    Some(response) => response,
+                    None => {
+                        return self
+                            .state
+                            .error(span, DiagnosticInfoMessage::RouteMustHaveResponse)
+                    }
+                };
+                let status = status.unwrap_or(match response {
+                    JsonSchema::Null => 204,
+                    _ => 200,
+                });
+                Ok(BuiltRoute {
+                    key,
+                    method,
+                    path,
+                    path_params,
+                    params,
+                    query,
+                    body,
+                    response,
+                    status,
+                })
+            }
+            TsTypeElement::TsGetterSignature(TsGetterSignature { span, .. })
+            | TsTypeElement::TsSetterSignature(TsSetterSignature { span, .. })
+            | TsTypeElement::TsMethodSignature(TsMethodSignature { span, .. })
+            | TsTypeElement::TsIndexSignature(TsIndexSignature { span, .. })
+            | TsTypeElement::TsCallSignatureDecl(TsCallSignatureDecl { span, .. })
+            | TsTypeElement::TsConstructSignatureDecl(TsConstructSignatureDecl { span, .. }) => {
+                self.state
+                    .error(span, DiagnosticInfoMessage::InvalidDecoderProperty)
+            }
+        }
+    }
+    fn extract_built_routes_from_call(
+        &mut self,
+        params: &TsTypeParamInstantiation,
+    ) -> Result<Vec<BuiltRoute>> {
+        match params.params.split_first() {
+            Some((head, tail)) => {
+                if !tail.is_empty() {
+                    return self.state.error(
+                        &params.span,
+                        DiagnosticInfoMessage::TooManyTypeParamsOnDecoder,
+                    );
+                }
+                match &**head {
+                    TsType::TsTypeLit(TsTypeLit { members, .. }) => {
+                        // every route is extracted, so that all errors are reported
+                        let routes: Vec<Result<BuiltRoute>> = members
+                            .iter()
+                            .map(|prop| self.extract_one_built_route(prop))
+                            .collect();
+                        routes.into_iter().collect()
+                    }
+                    _ => self.state.error(
+                        &params.span,
+                        DiagnosticInfoMessage::RoutesShouldBeObjectWithRouteKeys,
+                    ),
+                }
+            }
+            None => self.state.error(
+                &params.span,
+                DiagnosticInfoMessage::TooFewTypeParamsOnDecoder,
+            ),
+        }
+    }
+}
+
+impl<'a, R: FileManager> SpecialCallVisitor<'a, R> for ExtractRoutesVisitor<'a, R> {
+    fn state(&mut self) -> &mut ExtractorState<'a, R> {
+        &mut self.state
+    }
+
+    fn extract_special_calls(&mut self, id: &Ident, n: &CallExpr) {
+        let Ident { sym, span, .. } = id;
+        if sym == "buildRoutes" {
+            match self.built_routes {
+                Some(_) => self
+                    .state
+                    .push_error(span, DiagnosticInfoMessage::TwoCallsToBuildRoutes),
+                None => {
+                    if let Some(ref params) = n.type_args {
+                        if let Ok(x) = self.extract_built_routes_from_call(params.as_ref()) {
+                            self.built_routes = Some(x)
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a, R: FileManager> Visit for ExtractRoutesVisitor<'a, R> {
+    fn visit_call_expr(&mut self, n: &CallExpr) {
+        self.visit_special_call(n)
+    }
+}
+
+pub fn extract_routes<R: FileManager>(
+    files: &mut R,
+    entry_file_name: BffFileName,
+    settings: &BeffUserSettings,
+) -> RoutesExtractResult {
+    let (errors, validators, built_routes, counter) = {
+        let mut visitor = ExtractRoutesVisitor::new(files, entry_file_name.clone(), settings);
+        let _ = visitor.visit_current_file();
+        (
+            visitor.state.errors,
+            visitor.state.validators,
+            visitor.built_routes,
+            visitor.state.counter,
+        )
+    };
+
+    RoutesExtractResult {
+        errors,
+        entry_file_name,
+        validators,
+        unknown_keys: settings.unknown_keys,
+        built_routes,
+        counter,
+    }
+}
@@ -6,29 +6,42 @@ use dprint_plugin_typescript::{
     *,
 };
 use swc_common::DUMMY_SP;
-use swc_ecma_ast::{Decl, Ident, ModuleItem, Stmt, TsType, TsTypeAliasDecl};
+use swc_ecma_ast::{
+    Decl, ExportDecl, Ident, ModuleDecl, ModuleItem, Stmt, TsType, TsTypeAliasDecl,
+};
+
+fn type_alias(name: &str, ty: &TsType, export: bool) -> ModuleItem {
+    let decl = Decl::TsTypeAlias(Box::new(TsTypeAliasDecl {
+        span: DUMMY_SP,
+        declare: false,
+        id: Ident {
+            span: DUMMY_SP,
+            sym: name.into(),
+            optional: false,
+        },
+        type_params: None,
+        type_ann: Box::new(ty.clone()),
+    }));
+    if export {
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+            span: DUMMY_SP,
+            decl,
+        }))
+    } else {
+        ModuleItem::Stmt(Stmt::Decl(decl))
+    }
+}
 
 pub fn print_ts_types(vs: Vec<(String, TsType)>) -> String {
+    print_type_aliases(vs.into_iter().map(|(name, ty)| (name, ty, false)).collect())
+}
+
+pub fn print_type_aliases(vs: Vec<(String, TsType, bool)>) -> String {
     let codes = vs
         .iter()
-        .map(|(name, ty)| {
-            emit_module(
-                vec![ModuleItem::Stmt(Stmt::Decl(Decl::TsTypeAlias(Box::new(
-                    TsTypeAliasDecl {
-                        span: DUMMY_SP,
-                        declare: false,
-                        id: Ident {
-                            span: DUMMY_SP,
-                            sym: name.clone().into(),
-                            optional: false,
-                        },
-                        type_params: None,
-                        type_ann: Box::new(ty.clone()),
-                    },
-                ))))],
-                "",
-            )
-            .expect("emitting module should work")
+        .map(|(name, ty, export)| {
+            emit_module(vec![type_alias(name, ty, *export)], "")
+                .expect("emitting module should work")
         })
         .collect::<Vec<String>>()
         .join("\n");
@@ -3,8 +3,9 @@ use std::rc::Rc;
 use crate::ast::json_schema::JsonSchema;
 use crate::diag::{Diagnostic, DiagnosticInfoMessage, DiagnosticInformation, Location};
 use crate::parser_extractor::BuiltDecoder;
+use crate::qualified_name::type_name;
 use crate::type_to_schema::TypeToSchema;
-use crate::{BeffUserSettings, ParsedModule};
+use crate::{BeffUserSettings, ParsedModule, UnknownKeys};
 use crate::{BffFileName, FileManager, Validator};
 use anyhow::anyhow;
 use anyhow::Result;
@@ -22,6 +23,7 @@ pub struct SchemaExtractResult {
     pub errors: Vec<Diagnostic>,
     pub entry_file_name: BffFileName,
     pub validators: Vec<Validator>,
+    pub unknown_keys: UnknownKeys,
     pub built_decoders: Option<Vec<BuiltDecoder>>,
     pub counter: usize,
 }
@@ -110,7 +112,9 @@ impl<'a, R: FileManager> ExtractSchemaVisitor<'a, R> {
                     // todo!("{:?} Two different types with the same name.", d.name);
                     self.push_error(
                         span,
-                        DiagnosticInfoMessage::TwoDifferentTypesWithTheSameName(d.name.clone()),
+                        DiagnosticInfoMessage::TwoDifferentTypesWithTheSameName(
+                            type_name(&d.name).to_string(),
+                        ),
                     );
                 }
             } else {
@@ -137,7 +141,9 @@ impl<'a, R: FileManager> ExtractSchemaVisitor<'a, R> {
                         Some(s) => kvs.push((k, s)),
                         None => self.push_error(
                             span,
-                            DiagnosticInfoMessage::CannotResolveTypeReferenceOnExtracting(k),
+                            DiagnosticInfoMessage::CannotResolveTypeReferenceOnExtracting(
+                                type_name(&k).to_string(),
+                            ),
                         ),
                     }
                 }
@@ -280,6 +286,7 @@ pub fn extract_schema<R: FileManager>(
         errors,
         entry_file_name,
         validators,
+        unknown_keys: settings.unknown_keys,
         built_decoders,
         counter,
     }
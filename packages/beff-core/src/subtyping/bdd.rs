@@ -13,7 +13,7 @@ use super::{
         Evidence, EvidenceResult, ListEvidence, ProperSubtypeEvidence, ProperSubtypeEvidenceResult,
     },
     semtype::{BddMemoEmptyRef, MappingAtomicType, MemoEmpty, SemType, SemTypeOps},
-    subtype::{ProperSubtype, StringLitOrFormat, SubType, SubTypeTag},
+    subtype::{NumberLitOrFormat, ProperSubtype, StringLitOrFormat, SubType, SubTypeTag},
 };
 
 pub type MappingAtomic = BTreeMap<String, Rc<SemType>>;
@@ -21,7 +21,39 @@ pub type MappingAtomic = BTreeMap<String, Rc<SemType>>;
 #[derive(PartialEq, Eq, Hash, Debug, Ord, PartialOrd, Clone)]
 pub struct ListAtomic {
     pub prefix_items: Vec<Rc<SemType>>,
+    pub optional_items: Vec<Rc<SemType>>,
     pub items: Rc<SemType>,
+    pub suffix_items: Vec<Rc<SemType>>,
+}
+
+impl ListAtomic {
+    // plain atoms are handled by the prefix/items algorithm
+    fn is_plain(&self) -> bool {
+        self.optional_items.is_empty() && self.suffix_items.is_empty()
+    }
+
+    fn max_fixed_len(&self) -> usize {
+        self.prefix_items.len() + self.optional_items.len() + self.suffix_items.len()
+    }
+
+    fn accepts_len(&self, len: usize) -> bool {
+        len >= self.prefix_items.len() + self.suffix_items.len()
+            && (!self.items.is_never() || len <= self.max_fixed_len())
+    }
+
+    // type at position `i` of a list of length `len`, requires `accepts_len(len)`
+    fn member_at(&self, i: usize, len: usize) -> Rc<SemType> {
+        if i < self.prefix_items.len() {
+            return self.prefix_items[i].clone();
+        }
+        if i >= len - self.suffix_items.len() {
+            return self.suffix_items[i - (len - self.suffix_items.len())].clone();
+        }
+        match self.optional_items.get(i - self.prefix_items.len()) {
+            Some(it) => it.clone(),
+            None => self.items.clone(),
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Hash, Debug, Ord, PartialOrd)]
@@ -522,28 +554,24 @@ enum ListInhabited {
 fn list_inhabited(
     prefix_items: &mut Vec<Rc<SemType>>,
     items: &Rc<SemType>,
-    neg: &Option<Rc<Conjunction>>,
+    neg: &[Rc<ListAtomic>],
     builder: &mut SemTypeContext,
 ) -> ListInhabited {
-    match neg {
+    match neg.split_first() {
         None => ListInhabited::Yes(None, prefix_items.clone()),
-        Some(neg) => {
+        Some((nt, next)) => {
             let mut len = prefix_items.len();
-            let nt = match &*neg.atom {
-                Atom::List(a) => builder.get_list_atomic(*a),
-                _ => unreachable!(),
-            };
             let neg_len = nt.prefix_items.len();
             if len < neg_len {
                 if items.is_never() {
-                    return list_inhabited(prefix_items, items, &neg.next, builder);
+                    return list_inhabited(prefix_items, items, next, builder);
                 }
                 for _i in len..neg_len {
                     prefix_items.push(items.clone());
                 }
                 len = neg_len;
             } else if neg_len < len && nt.items.is_never() {
-                return list_inhabited(prefix_items, items, &neg.next, builder);
+                return list_inhabited(prefix_items, items, next, builder);
             }
 
             // now we have nt.members.length() <= len
@@ -578,8 +606,7 @@ fn list_inhabited(
                 if !d.is_empty(builder) {
                     let mut s = prefix_items.clone();
                     s[i] = d;
-                    if let ListInhabited::Yes(a, _b) =
-                        list_inhabited(&mut s, items, &neg.next, builder)
+                    if let ListInhabited::Yes(a, _b) = list_inhabited(&mut s, items, next, builder)
                     {
                         return ListInhabited::Yes(a, s.clone());
                     }
@@ -598,11 +625,122 @@ fn list_inhabited(
     }
 }
 
+fn conjunction_list_atomics(
+    mut c: &Option<Rc<Conjunction>>,
+    builder: &SemTypeContext,
+) -> Vec<Rc<ListAtomic>> {
+    let mut acc = vec![];
+    while let Some(some_c) = c {
+        match some_c.atom.as_ref() {
+            Atom::List(a) => acc.push(builder.get_list_atomic(*a)),
+            _ => unreachable!(),
+        }
+        c = &some_c.next;
+    }
+    acc
+}
+
+fn list_evidence_result(
+    prefix: Vec<Rc<SemType>>,
+    items: Option<Rc<Evidence>>,
+    builder: &mut SemTypeContext,
+) -> ProperSubtypeEvidenceResult {
+    let prefix_items = prefix
+        .into_iter()
+        .map(|it| match it.is_empty_evidence(builder) {
+            EvidenceResult::Evidence(e) => Rc::new(e),
+            EvidenceResult::IsEmpty => {
+                unreachable!("list_inhabited should have returned false")
+            }
+        })
+        .collect::<Vec<_>>();
+    let list_evidence = ListEvidence {
+        prefix_items,
+        items,
+    };
+    ProperSubtypeEvidence::List(Rc::new(list_evidence)).to_result()
+}
+
+// Optional items and items after the rest make the position of an item depend on the
+// length of the list, so each length is checked as a fixed tuple.
+// Past the longest fixed part every length only adds another column typed by `items`,
+// each negated atom needs at most one such column to be escaped,
+// so checking one extra length per negated atom is enough.
+fn list_formula_is_empty_by_len(
+    pos: &[Rc<ListAtomic>],
+    neg: &[Rc<ListAtomic>],
+    builder: &mut SemTypeContext,
+) -> ProperSubtypeEvidenceResult {
+    let never: Rc<SemType> = Rc::new(SemTypeContext::never());
+    let max_prefix = pos
+        .iter()
+        .chain(neg)
+        .map(|it| it.prefix_items.len() + it.optional_items.len())
+        .max()
+        .unwrap_or(0);
+    let max_suffix = pos
+        .iter()
+        .chain(neg)
+        .map(|it| it.suffix_items.len())
+        .max()
+        .unwrap_or(0);
+    let max_len = max_prefix + max_suffix + neg.len() + 1;
+
+    'lengths: for len in 0..=max_len {
+        let mut prefix_items: Vec<Rc<SemType>> = (0..len)
+            .map(|_| Rc::new(SemTypeContext::unknown()))
+            .collect();
+        for atom in pos {
+            if !atom.accepts_len(len) {
+                continue 'lengths;
+            }
+            for (i, it) in prefix_items.iter_mut().enumerate() {
+                *it = it.intersect(&atom.member_at(i, len));
+            }
+        }
+        for it in prefix_items.iter() {
+            if it.is_empty(builder) {
+                continue 'lengths;
+            }
+        }
+
+        let fixed_neg = neg
+            .iter()
+            .filter(|it| it.accepts_len(len))
+            .map(|atom| {
+                Rc::new(ListAtomic {
+                    prefix_items: (0..len).map(|i| atom.member_at(i, len)).collect(),
+                    optional_items: vec![],
+                    items: never.clone(),
+                    suffix_items: vec![],
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if let ListInhabited::Yes(e, prefix) =
+            list_inhabited(&mut prefix_items, &never, &fixed_neg, builder)
+        {
+            return list_evidence_result(prefix, e, builder);
+        }
+    }
+    ProperSubtypeEvidenceResult::IsEmpty
+}
+
 fn list_formula_is_empty(
     pos: &Option<Rc<Conjunction>>,
     neg: &Option<Rc<Conjunction>>,
     builder: &mut SemTypeContext,
 ) -> ProperSubtypeEvidenceResult {
+    let pos_atoms = conjunction_list_atomics(pos, builder);
+    let neg_atoms = conjunction_list_atomics(neg, builder);
+    if !pos_atoms
+        .iter()
+        .chain(neg_atoms.iter())
+        .all(|it| it.is_plain())
+    {
+        return list_formula_is_empty_by_len(&pos_atoms, &neg_atoms, builder);
+    }
+
     let mut prefix_items = vec![];
     let mut items = Rc::new(SemTypeContext::unknown());
 
@@ -656,24 +794,8 @@ fn list_formula_is_empty(
             }
         }
     }
-    match list_inhabited(&mut prefix_items, &items, neg, builder) {
-        ListInhabited::Yes(e, prefix) => {
-            let prefix2 = prefix
-                .into_iter()
-                .map(|it| match it.is_empty_evidence(builder) {
-                    EvidenceResult::Evidence(e) => Rc::new(e),
-                    EvidenceResult::IsEmpty => {
-                        unreachable!("list_inhabited should have returned false")
-                    }
-                })
-                .collect::<Vec<_>>();
-            let list_evidence = ListEvidence {
-                prefix_items: prefix2,
-                items: e,
-            };
-            ProperSubtypeEvidence::List(Rc::new(list_evidence)).to_result()
-        }
-
+    match list_inhabited(&mut prefix_items, &items, &neg_atoms, builder) {
+        ListInhabited::Yes(e, prefix) => list_evidence_result(prefix, e, builder),
         ListInhabited::No => ProperSubtypeEvidenceResult::IsEmpty,
     }
 }
@@ -753,31 +875,25 @@ fn mapping_atomic_applicable_member_types_inner(
                 return Ok(vec![]);
             }
             let mut member_types = vec![];
-            for (k, ty) in atomic.vs.iter() {
-                let mut found = false;
-
-                for it in &values {
-                    match it {
-                        StringLitOrFormat::Lit(l) => {
-                            if l == k {
-                                found = true;
-                                break;
-                            }
-                        }
-                        StringLitOrFormat::Tpl(_)
-                        | StringLitOrFormat::Format(_)
-                        | StringLitOrFormat::Codec(_) => {
-                            bail!("format or codec cannot be used as mapping key")
+            let mut found_count = 0;
+            for it in &values {
+                match it {
+                    StringLitOrFormat::Lit(l) => {
+                        if let Some(ty) = atomic.vs.get(l) {
+                            found_count += 1;
+                            member_types.push(ty.clone());
                         }
                     }
-                }
-
-                if found {
-                    member_types.push(ty.clone());
+                    StringLitOrFormat::Tpl(_)
+                    | StringLitOrFormat::Format(_)
+                    | StringLitOrFormat::Codec(_) => {
+                        bail!("format or codec cannot be used as mapping key")
+                    }
                 }
             }
 
-            let is_subtype = member_types.len() == atomic.vs.len();
+            // keys that are not declared are looked up in the rest
+            let is_subtype = found_count == values.len();
             if !is_subtype {
                 member_types.push(atomic.rest.clone());
             }
@@ -896,31 +1012,42 @@ fn int_subtype_max(allowed: bool, values: &Vec<N>) -> i64 {
     max
 }
 
-fn list_atomic_member_type_at_inner(
-    prefix_items: &[Rc<SemType>],
-    items: &Rc<SemType>,
-    key: ListNumberKey,
-) -> Rc<SemType> {
+fn list_atomic_member_type_inner(atomic: Rc<ListAtomic>, key: ListNumberKey) -> Rc<SemType> {
     match key {
         ListNumberKey::N { allowed, values } => {
             let mut m = Rc::new(SemTypeContext::never());
-            let init_len = prefix_items.len();
+            let init_len = atomic.prefix_items.len() + atomic.optional_items.len();
 
-            if init_len > 0 {
-                for (i, v) in prefix_items.iter().enumerate() {
-                    if int_subtype_contains(allowed, &values, i) {
-                        m = m.union(v);
-                    }
+            for (i, v) in atomic
+                .prefix_items
+                .iter()
+                .chain(atomic.optional_items.iter())
+                .enumerate()
+            {
+                if int_subtype_contains(allowed, &values, i) {
+                    m = m.union(v);
                 }
             }
-            if init_len == 0 || int_subtype_max(allowed, &values) > (init_len as i64) - 1 {
-                m = m.union(items);
+            let max = int_subtype_max(allowed, &values);
+            if init_len == 0 || max > (init_len as i64) - 1 {
+                m = m.union(&atomic.items);
+            }
+            // the j-th suffix item is at index `prefix + j` in the shortest list
+            for (j, v) in atomic.suffix_items.iter().enumerate() {
+                if !allowed || max >= (atomic.prefix_items.len() + j) as i64 {
+                    m = m.union(v);
+                }
             }
             m
         }
         ListNumberKey::True => {
-            let mut m = items.clone();
-            for it in prefix_items.iter() {
+            let mut m = atomic.items.clone();
+            for it in atomic
+                .prefix_items
+                .iter()
+                .chain(atomic.optional_items.iter())
+                .chain(atomic.suffix_items.iter())
+            {
                 m = m.union(it);
             }
             m
@@ -928,10 +1055,6 @@ fn list_atomic_member_type_at_inner(
     }
 }
 
-fn list_atomic_member_type_inner(atomic: Rc<ListAtomic>, key: ListNumberKey) -> Rc<SemType> {
-    list_atomic_member_type_at_inner(&atomic.prefix_items, &atomic.items, key)
-}
-
 fn list_member_type_inner_val(atomic: Rc<ListAtomic>, key: ListNumberKey) -> Rc<SemType> {
     let a = list_atomic_member_type_inner(atomic, key);
     a.diff(&Rc::new(SemTypeContext::void()))
@@ -986,10 +1109,24 @@ pub fn list_indexed_access(
         SubType::False(_) => return Ok(SemTypeContext::never().into()),
         SubType::True(_) => ListNumberKey::True,
         SubType::Proper(proper) => match proper.as_ref() {
-            ProperSubtype::Number { allowed, values } => ListNumberKey::N {
-                allowed: *allowed,
-                values: values.clone(),
-            },
+            ProperSubtype::Number { allowed, values } => {
+                let lits: Vec<N> = values
+                    .iter()
+                    .filter_map(|it| match it {
+                        NumberLitOrFormat::Lit(n) => Some(n.clone()),
+                        NumberLitOrFormat::Format(_) => None,
+                    })
+                    .collect();
+                // a format can be any index
+                if *allowed && lits.len() < values.len() {
+                    ListNumberKey::True
+                } else {
+                    ListNumberKey::N {
+                        allowed: *allowed,
+                        values: lits,
+                    }
+                }
+            }
             _ => unreachable!("should be string"),
         },
     };
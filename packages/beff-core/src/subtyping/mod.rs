@@ -1,12 +1,12 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::rc::Rc;
 
-use crate::ast::json_schema::{JsonSchemaConst, Optionality};
+use crate::ast::json_schema::{JsonSchemaConst, Optionality, TplLitTypeItem};
 use crate::{ast::json_schema::JsonSchema, Validator};
 
 use self::bdd::{ListAtomic, MappingAtomic};
 use self::semtype::{ComplexSemType, MappingAtomicType, SemType, SemTypeContext, SemTypeOps};
-use self::subtype::StringLitOrFormat;
+use self::subtype::{NumberLitOrFormat, StringLitOrFormat};
 pub mod bdd;
 pub mod evidence;
 pub mod semtype;
@@ -37,6 +37,35 @@ impl<'a> ToSemTypeConverter<'a> {
         Err(anyhow!("reference not found: {}", name))
     }
 
+    fn convert_rest_to_sem_type(
+        &mut self,
+        rest: &Option<Box<JsonSchema>>,
+        pattern_vs: &BTreeMap<Vec<TplLitTypeItem>, JsonSchema>,
+        builder: &mut SemTypeContext,
+    ) -> Result<Rc<SemType>> {
+        // mapping atoms have no key patterns, values of pattern keys are folded into the rest
+        match rest {
+            Some(r) => {
+                let mut acc = self.convert_to_sem_type(r, builder)?;
+                for v in pattern_vs.values() {
+                    acc = acc.union(&self.convert_to_sem_type(v, builder)?);
+                }
+                Ok(acc)
+            }
+            None => Ok(SemTypeContext::unknown().into()),
+        }
+    }
+
+    fn convert_all_to_sem_type(
+        &mut self,
+        vs: &[JsonSchema],
+        builder: &mut SemTypeContext,
+    ) -> Result<Vec<Rc<ComplexSemType>>> {
+        vs.iter()
+            .map(|v| self.convert_to_sem_type(v, builder))
+            .collect()
+    }
+
     fn convert_to_sem_type(
         &mut self,
         schema: &JsonSchema,
@@ -49,12 +78,14 @@ impl<'a> ToSemTypeConverter<'a> {
                 // handle recursive types
                 if let JsonSchema::Tuple {
                     prefix_items,
+                    optional_items,
                     items,
+                    suffix_items,
                 } = schema
                 {
                     match builder.list_json_schema_ref_memo.get(name) {
                         Some(idx) => {
-                            let ty = Rc::new(SemTypeContext::mapping_definition_from_idx(*idx));
+                            let ty = Rc::new(SemTypeContext::list_definition_from_idx(*idx));
                             return Ok(ty);
                         }
                         None => {
@@ -68,15 +99,19 @@ impl<'a> ToSemTypeConverter<'a> {
                                 Some(items) => Some(self.convert_to_sem_type(&items, builder)?),
                                 None => None,
                             };
-                            let prefix_items: Vec<Rc<ComplexSemType>> = prefix_items
-                                .iter()
-                                .map(|v| self.convert_to_sem_type(v, builder))
-                                .collect::<Result<_>>()?;
+                            let prefix_items =
+                                self.convert_all_to_sem_type(&prefix_items, builder)?;
+                            let optional_items =
+                                self.convert_all_to_sem_type(&optional_items, builder)?;
+                            let suffix_items =
+                                self.convert_all_to_sem_type(&suffix_items, builder)?;
 
                             builder.list_definitions[idx] = Some(Rc::new(ListAtomic {
                                 prefix_items,
+                                optional_items,
                                 // todo: should be unknown?
                                 items: items.unwrap_or(SemTypeContext::never().into()),
+                                suffix_items,
                             }));
 
                             let ty = Rc::new(SemTypeContext::list_definition_from_idx(idx));
@@ -86,7 +121,12 @@ impl<'a> ToSemTypeConverter<'a> {
                     }
                 }
                 // handle recursive types
-                if let JsonSchema::Object { vs, rest } = schema {
+                if let JsonSchema::Object {
+                    vs,
+                    rest,
+                    pattern_vs,
+                } = schema
+                {
                     match builder.mapping_json_schema_ref_memo.get(name) {
                         Some(idx) => {
                             let ty = Rc::new(SemTypeContext::mapping_definition_from_idx(*idx));
@@ -101,7 +141,7 @@ impl<'a> ToSemTypeConverter<'a> {
                             let vs: MappingAtomic = vs
                                 .iter()
                                 .map(|(k, v)| match v {
-                                    Optionality::Optional(v) => self
+                                    Optionality::Optional(v) | Optionality::Default(v, _) => self
                                         .convert_to_sem_type(v, builder)
                                         .map(|v| (k.clone(), SemTypeContext::optional(v))),
                                     Optionality::Required(v) => {
@@ -109,10 +149,8 @@ impl<'a> ToSemTypeConverter<'a> {
                                     }
                                 })
                                 .collect::<Result<_>>()?;
-                            let rest = match rest {
-                                Some(r) => self.convert_to_sem_type(&r, builder)?,
-                                None => SemTypeContext::unknown().into(),
-                            };
+                            let rest =
+                                self.convert_rest_to_sem_type(&rest, &pattern_vs, builder)?;
 
                             builder.mapping_definitions[idx] = Some(Rc::new(MappingAtomicType {
                                 vs: vs.into(),
@@ -156,14 +194,23 @@ impl<'a> ToSemTypeConverter<'a> {
             JsonSchema::StringWithFormat(s) => {
                 Ok(SemTypeContext::string_const(StringLitOrFormat::Format(s.clone())).into())
             }
+            JsonSchema::NumberWithFormat(s) => {
+                Ok(SemTypeContext::number_const(NumberLitOrFormat::Format(s.clone())).into())
+            }
+            // brands are not checked, a branded type is its base type
+            JsonSchema::Brand(ty, _) => self.convert_to_sem_type(ty, builder),
             JsonSchema::TplLitType(tpl) => {
                 Ok(SemTypeContext::string_const(StringLitOrFormat::Tpl(tpl.clone())).into())
             }
-            JsonSchema::Object { vs, rest } => {
+            JsonSchema::Object {
+                vs,
+                rest,
+                pattern_vs,
+            } => {
                 let vs = vs
                     .iter()
                     .map(|(k, v)| match v {
-                        Optionality::Optional(v) => self
+                        Optionality::Optional(v) | Optionality::Default(v, _) => self
                             .convert_to_sem_type(v, builder)
                             .map(|v| (k.clone(), SemTypeContext::optional(v))),
                         Optionality::Required(v) => {
@@ -171,10 +218,7 @@ impl<'a> ToSemTypeConverter<'a> {
                         }
                     })
                     .collect::<Result<_>>()?;
-                let rest = match rest {
-                    Some(r) => self.convert_to_sem_type(r, builder)?,
-                    None => SemTypeContext::unknown().into(),
-                };
+                let rest = self.convert_rest_to_sem_type(rest, pattern_vs, builder)?;
                 Ok(builder.mapping_definition(Rc::new(vs), rest).into())
             }
             JsonSchema::Array(items) => {
@@ -183,17 +227,20 @@ impl<'a> ToSemTypeConverter<'a> {
             }
             JsonSchema::Tuple {
                 prefix_items,
+                optional_items,
                 items,
+                suffix_items,
             } => {
                 let items = match items {
                     Some(items) => Some(self.convert_to_sem_type(items, builder)?),
                     None => None,
                 };
-                let prefix_items: Vec<Rc<ComplexSemType>> = prefix_items
-                    .iter()
-                    .map(|v| self.convert_to_sem_type(v, builder))
-                    .collect::<Result<_>>()?;
-                Ok(builder.tuple(prefix_items, items).into())
+                let prefix_items = self.convert_all_to_sem_type(prefix_items, builder)?;
+                let optional_items = self.convert_all_to_sem_type(optional_items, builder)?;
+                let suffix_items = self.convert_all_to_sem_type(suffix_items, builder)?;
+                Ok(builder
+                    .tuple(prefix_items, optional_items, items, suffix_items)
+                    .into())
             }
             JsonSchema::Const(cons) => match cons {
                 JsonSchemaConst::Null => Ok(SemTypeContext::null().into()),
@@ -201,7 +248,9 @@ impl<'a> ToSemTypeConverter<'a> {
                 JsonSchemaConst::String(s) => {
                     Ok(SemTypeContext::string_const(StringLitOrFormat::Lit(s.clone())).into())
                 }
-                JsonSchemaConst::Number(n) => Ok(SemTypeContext::number_const(n.clone()).into()),
+                JsonSchemaConst::Number(n) => {
+                    Ok(SemTypeContext::number_const(NumberLitOrFormat::Lit(n.clone())).into())
+                }
             },
             JsonSchema::AnyArrayLike => {
                 self.convert_to_sem_type(&JsonSchema::Array(JsonSchema::Any.into()), builder)
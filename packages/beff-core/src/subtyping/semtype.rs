@@ -6,7 +6,7 @@ use super::{
     },
     evidence::{EvidenceResult, ProperSubtypeEvidenceResult},
     subtype::{
-        BasicTypeBitSet, BasicTypeCode, NumberRepresentation, ProperSubtype, ProperSubtypeOps,
+        BasicTypeBitSet, BasicTypeCode, NumberLitOrFormat, ProperSubtype, ProperSubtypeOps,
         StringLitOrFormat, SubType, SubTypeTag, VAL,
     },
 };
@@ -371,7 +371,7 @@ impl SemTypeContext {
             list_json_schema_ref_memo: BTreeMap::new(),
         }
     }
-    pub fn number_const(value: NumberRepresentation) -> SemType {
+    pub fn number_const(value: NumberLitOrFormat) -> SemType {
         SemType::new_complex(
             0x0,
             vec![ProperSubtype::Number {
@@ -425,15 +425,25 @@ impl SemTypeContext {
     pub fn array(&mut self, v: Rc<SemType>) -> SemType {
         let atom = ListAtomic {
             prefix_items: vec![],
+            optional_items: vec![],
             items: v,
+            suffix_items: vec![],
         };
         self.list_definition(Rc::new(atom))
     }
-    pub fn tuple(&mut self, prefix_items: Vec<Rc<SemType>>, items: Option<Rc<SemType>>) -> SemType {
+    pub fn tuple(
+        &mut self,
+        prefix_items: Vec<Rc<SemType>>,
+        optional_items: Vec<Rc<SemType>>,
+        items: Option<Rc<SemType>>,
+        suffix_items: Vec<Rc<SemType>>,
+    ) -> SemType {
         let atom = ListAtomic {
             prefix_items,
+            optional_items,
             // todo: should be unknown?
             items: items.unwrap_or(Self::never().into()),
+            suffix_items,
         };
         self.list_definition(Rc::new(atom))
     }
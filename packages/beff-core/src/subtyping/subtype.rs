@@ -53,7 +53,7 @@ pub enum SubType {
 }
 
 impl SubType {
-    fn number_subtype(allowed: bool, values: Vec<NumberRepresentation>) -> SubType {
+    fn number_subtype(allowed: bool, values: Vec<NumberLitOrFormat>) -> SubType {
         if values.is_empty() {
             if allowed {
                 return SubType::False(SubTypeTag::Number);
@@ -73,6 +73,12 @@ impl SubType {
     }
 }
 
+#[derive(PartialEq, Eq, Hash, Debug, Ord, PartialOrd, Clone)]
+pub enum NumberLitOrFormat {
+    Lit(NumberRepresentation),
+    Format(String),
+}
+
 #[derive(PartialEq, Eq, Hash, Debug, Ord, PartialOrd, Clone)]
 pub enum StringLitOrFormat {
     Lit(String),
@@ -86,7 +92,7 @@ pub enum ProperSubtype {
     Boolean(bool),
     Number {
         allowed: bool,
-        values: Vec<NumberRepresentation>,
+        values: Vec<NumberLitOrFormat>,
     },
     String {
         allowed: bool,
@@ -13,7 +13,7 @@ use crate::{
 use super::{
     bdd::{Atom, Bdd, ListAtomic},
     semtype::{MappingAtomicType, SemType, SemTypeContext, SemTypeOps},
-    subtype::{ProperSubtype, StringLitOrFormat, SubTypeTag},
+    subtype::{NumberLitOrFormat, ProperSubtype, StringLitOrFormat, SubTypeTag},
 };
 
 pub enum SchemaMemo {
@@ -46,18 +46,60 @@ impl<'a> SemTypeResolverContext<'a> {
             prefix_items.push(acc);
         }
 
+        let optional_len = it
+            .iter()
+            .map(|it| it.optional_items.len())
+            .max()
+            .unwrap_or(0);
+        let mut optional_items: Vec<Rc<SemType>> = vec![];
+        for i in 0..optional_len {
+            let mut acc = Rc::new(SemType::new_unknown());
+
+            for atom in &it {
+                if i < atom.optional_items.len() {
+                    acc = acc.intersect(&atom.optional_items[i]);
+                }
+            }
+
+            optional_items.push(acc);
+        }
+
+        // suffix items are aligned at the end of the list
+        let suffix_len = it.iter().map(|it| it.suffix_items.len()).max().unwrap_or(0);
+        let mut suffix_items: Vec<Rc<SemType>> = vec![];
+        for i in 0..suffix_len {
+            let mut acc = Rc::new(SemType::new_unknown());
+
+            for atom in &it {
+                let offset = suffix_len - atom.suffix_items.len();
+                if i >= offset {
+                    acc = acc.intersect(&atom.suffix_items[i - offset]);
+                } else {
+                    acc = acc.intersect(&atom.items);
+                }
+            }
+
+            suffix_items.push(acc);
+        }
+
         Rc::new(ListAtomic {
             items,
             prefix_items,
+            optional_items,
+            suffix_items,
         })
     }
 
     fn list_atomic_complement(it: Rc<ListAtomic>) -> Rc<ListAtomic> {
         let items = it.items.complement();
         let prefix_items = it.prefix_items.iter().map(|it| it.complement()).collect();
+        let optional_items = it.optional_items.iter().map(|it| it.complement()).collect();
+        let suffix_items = it.suffix_items.iter().map(|it| it.complement()).collect();
         Rc::new(ListAtomic {
             items,
             prefix_items,
+            optional_items,
+            suffix_items,
         })
     }
 
@@ -137,7 +179,9 @@ impl<'a> SemTypeResolverContext<'a> {
             }
             Bdd::False => Ok(vec![Rc::new(ListAtomic {
                 prefix_items: vec![],
+                optional_items: vec![],
                 items: Rc::new(SemType::new_never()),
+                suffix_items: vec![],
             })]),
             Bdd::Node {
                 atom,
@@ -195,6 +239,7 @@ impl<'a, 'b> SchemerContext<'a, 'b> {
         Ok(JsonSchema::Object {
             vs: BTreeMap::from_iter(acc),
             rest,
+            pattern_vs: BTreeMap::new(),
         })
     }
 
@@ -291,7 +336,8 @@ impl<'a, 'b> SchemerContext<'a, 'b> {
     }
 
     fn list_atom_schema(&mut self, mt: &Rc<ListAtomic>) -> anyhow::Result<JsonSchema> {
-        if mt.prefix_items.is_empty() {
+        if mt.prefix_items.is_empty() && mt.optional_items.is_empty() && mt.suffix_items.is_empty()
+        {
             if mt.items.is_any() {
                 return Ok(JsonSchema::AnyArrayLike);
             }
@@ -300,11 +346,9 @@ impl<'a, 'b> SchemerContext<'a, 'b> {
             )));
         }
 
-        let prefix_items = mt
-            .prefix_items
-            .iter()
-            .map(|it| self.convert_to_schema(it, None))
-            .collect::<anyhow::Result<Vec<_>>>()?;
+        let prefix_items = self.convert_all_to_schema(&mt.prefix_items)?;
+        let optional_items = self.convert_all_to_schema(&mt.optional_items)?;
+        let suffix_items = self.convert_all_to_schema(&mt.suffix_items)?;
 
         let items = if mt.items.is_never() {
             None
@@ -313,10 +357,18 @@ impl<'a, 'b> SchemerContext<'a, 'b> {
         };
         Ok(JsonSchema::Tuple {
             prefix_items,
+            optional_items,
             items,
+            suffix_items,
         })
     }
 
+    fn convert_all_to_schema(&mut self, vs: &[Rc<SemType>]) -> anyhow::Result<Vec<JsonSchema>> {
+        vs.iter()
+            .map(|it| self.convert_to_schema(it, None))
+            .collect()
+    }
+
     // fn to_schema_list(&mut self, bdd: &Rc<Bdd>) -> JsonSchema {
     //     let vs = self.ctx.to_schema_list_bdd_vec(bdd);
     //     let vs = vs
@@ -451,10 +503,20 @@ impl<'a, 'b> SchemerContext<'a, 'b> {
                 }
                 ProperSubtype::Number { allowed, values } => {
                     for h in values {
-                        acc.insert(maybe_not(
-                            JsonSchema::Const(JsonSchemaConst::Number(h.clone())),
-                            !allowed,
-                        ));
+                        match h {
+                            NumberLitOrFormat::Lit(n) => {
+                                acc.insert(maybe_not(
+                                    JsonSchema::Const(JsonSchemaConst::Number(n.clone())),
+                                    !allowed,
+                                ));
+                            }
+                            NumberLitOrFormat::Format(fmt) => {
+                                acc.insert(maybe_not(
+                                    JsonSchema::NumberWithFormat(fmt.clone()),
+                                    !allowed,
+                                ));
+                            }
+                        }
                     }
                 }
                 ProperSubtype::String { allowed, values } => {
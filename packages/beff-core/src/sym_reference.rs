@@ -1,7 +1,10 @@
 use std::rc::Rc;
 
+use swc_atoms::JsWord;
 use swc_common::Span;
-use swc_ecma_ast::{Expr, Ident, TsEnumDecl, TsInterfaceDecl, TsType, TsTypeParamDecl};
+use swc_ecma_ast::{
+    Expr, Ident, TsEnumDecl, TsInterfaceDecl, TsType, TsTypeParamDecl, VarDeclKind,
+};
 
 use crate::{
     diag::{Diagnostic, DiagnosticInfoMessage, Location},
@@ -21,19 +24,58 @@ pub enum TsBuiltIn {
     TsPartial(Span),
     TsPick(Span),
     TsExclude(Span),
+    TsParameters(Span),
+    TsReturnType(Span),
+    TsAwaited(Span),
+    TsStringIntrinsic(Span, StringIntrinsic),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum StringIntrinsic {
+    Capitalize,
+    Uncapitalize,
+    Uppercase,
+    Lowercase,
+}
+
+impl StringIntrinsic {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StringIntrinsic::Capitalize => "Capitalize",
+            StringIntrinsic::Uncapitalize => "Uncapitalize",
+            StringIntrinsic::Uppercase => "Uppercase",
+            StringIntrinsic::Lowercase => "Lowercase",
+        }
+    }
+
+    pub fn apply(&self, s: &str) -> String {
+        let mut chars = s.chars();
+        match self {
+            StringIntrinsic::Uppercase => s.to_uppercase(),
+            StringIntrinsic::Lowercase => s.to_lowercase(),
+            StringIntrinsic::Capitalize => match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            },
+            StringIntrinsic::Uncapitalize => match chars.next() {
+                Some(first) => first.to_lowercase().chain(chars).collect(),
+                None => String::new(),
+            },
+        }
+    }
 }
 
 pub enum ResolvedLocalSymbol {
     TsType(Option<Rc<TsTypeParamDecl>>, Rc<TsType>),
     TsInterfaceDecl(Rc<TsInterfaceDecl>),
     TsEnumDecl(Rc<TsEnumDecl>),
-    Expr(Rc<Expr>),
+    Expr(Rc<Expr>, Option<VarDeclKind>),
     NamedImport {
         exported: Rc<SymbolExport>,
         from_file: Rc<ImportReference>,
     },
     SymbolExportDefault(Rc<SymbolExportDefault>),
-    Star(BffFileName), // ExportDefault(Rc<Expr>),
+    Star(Rc<ImportReference>), // ExportDefault(Rc<Expr>),
     TsBuiltin(TsBuiltIn),
 }
 pub struct ResolvedNamespaceSymbol {
@@ -53,6 +95,34 @@ impl<'a, R: FileManager> TypeResolver<'a, R> {
             .get_or_fetch_file(&self.current_file)
             .expect("should have been parsed")
     }
+    // the namespaces whose blocks contain the identifier, innermost first
+    fn enclosing_namespaces(&mut self, i: &Ident) -> Vec<Rc<ImportReference>> {
+        let file = self.get_current_file();
+        let mut scopes: Vec<&(Span, Rc<ImportReference>)> = file
+            .locals
+            .namespace_scopes
+            .iter()
+            .filter(|(span, _)| span.lo <= i.span.lo && i.span.hi <= span.hi)
+            .collect();
+        scopes.sort_by_key(|(span, _)| span.hi.0 - span.lo.0);
+        scopes.into_iter().map(|(_, it)| it.clone()).collect()
+    }
+    fn resolve_namespace_member(
+        &mut self,
+        i: &Ident,
+        is_type: bool,
+    ) -> Option<(Rc<SymbolExport>, Rc<ImportReference>)> {
+        self.enclosing_namespaces(i)
+            .into_iter()
+            .find_map(|namespace| {
+                let exported = if is_type {
+                    namespace.get_type_export(&i.sym, self.files)
+                } else {
+                    namespace.get_value_export(&i.sym, self.files)
+                };
+                exported.map(|it| (it, namespace))
+            })
+    }
     fn resolve_export(
         &mut self,
         i: &Ident,
@@ -128,10 +198,19 @@ impl<'a, R: FileManager> TypeResolver<'a, R> {
     ) -> Res<ResolvedNamespaceSymbol> {
         let k = &(i.sym.clone(), i.span.ctxt);
 
+        if let Some(namespace) = self.get_current_file().locals.namespaces.get(k) {
+            return Ok(ResolvedNamespaceSymbol {
+                from_file: namespace.clone(),
+            });
+        }
+        if let Some((export, namespace)) = self.resolve_namespace_member(i, is_type) {
+            return self.resolve_export(i, &export, is_type, namespace.file_name().clone());
+        }
+
         if let Some(imported) = self.get_current_file().imports.get(k) {
             match &**imported {
                 ImportReference::Default { .. } => {}
-                ImportReference::Star { .. } => {
+                ImportReference::Star { .. } | ImportReference::Namespace { .. } => {
                     return Ok(ResolvedNamespaceSymbol {
                         from_file: imported.clone(),
                     })
@@ -153,6 +232,21 @@ impl<'a, R: FileManager> TypeResolver<'a, R> {
                 }
             }
         }
+        match self.resolve_ambient_import(i) {
+            Some((module, None)) => return Ok(ResolvedNamespaceSymbol { from_file: module }),
+            Some((module, Some(orig))) => {
+                let exported = if is_type {
+                    module.get_type_export(&orig, self.files)
+                } else {
+                    module.get_value_export(&orig, self.files)
+                };
+                if let Some(export) = exported {
+                    let file_name = module.file_name().clone();
+                    return self.resolve_export(i, &export, is_type, file_name);
+                }
+            }
+            None => {}
+        }
 
         Err(self
             .make_err(
@@ -168,6 +262,18 @@ impl<'a, R: FileManager> TypeResolver<'a, R> {
             .to_info(info_msg)
             .to_diag(None)
     }
+    // an import of a `declare module` block of another file: the block, and the imported name
+    // unless it is a star import
+    fn resolve_ambient_import(
+        &mut self,
+        i: &Ident,
+    ) -> Option<(Rc<ImportReference>, Option<JsWord>)> {
+        let k = &(i.sym.clone(), i.span.ctxt);
+        let file = self.get_current_file();
+        let import = file.ambient_imports.get(k)?;
+        let module = self.files.get_ambient_module(&import.module_specifier)?;
+        Some((module, import.orig.clone()))
+    }
     fn resolve_local_import(&mut self, i: &Ident, is_type: bool) -> Res<ResolvedLocalSymbol> {
         let k = &(i.sym.clone(), i.span.ctxt);
         if let Some(imported) = self.get_current_file().imports.get(k) {
@@ -191,7 +297,19 @@ impl<'a, R: FileManager> TypeResolver<'a, R> {
                     }
                 }
                 ImportReference::Star { file_name, .. } => {
-                    return Ok(ResolvedLocalSymbol::Star(file_name.clone()));
+                    let file = self.files.get_or_fetch_file(file_name);
+                    if let Some(exported) =
+                        file.and_then(|it| it.symbol_exports.get_assigned(is_type))
+                    {
+                        return Ok(ResolvedLocalSymbol::NamedImport {
+                            exported,
+                            from_file: imported.clone(),
+                        });
+                    }
+                    return Ok(ResolvedLocalSymbol::Star(imported.clone()));
+                }
+                ImportReference::Namespace { .. } => {
+                    return Ok(ResolvedLocalSymbol::Star(imported.clone()));
                 }
                 ImportReference::Default { file_name } => {
                     let file = self.files.get_or_fetch_file(file_name);
@@ -207,6 +325,23 @@ impl<'a, R: FileManager> TypeResolver<'a, R> {
                 }
             }
         }
+        match self.resolve_ambient_import(i) {
+            Some((module, None)) => return Ok(ResolvedLocalSymbol::Star(module)),
+            Some((module, Some(orig))) => {
+                let exported = if is_type {
+                    module.get_type_export(&orig, self.files)
+                } else {
+                    module.get_value_export(&orig, self.files)
+                };
+                if let Some(exported) = exported {
+                    return Ok(ResolvedLocalSymbol::NamedImport {
+                        exported,
+                        from_file: module,
+                    });
+                }
+            }
+            None => {}
+        }
 
         match i.sym.as_ref() {
             "Record" => {
@@ -232,6 +367,30 @@ impl<'a, R: FileManager> TypeResolver<'a, R> {
             "Partial" => {
                 return Ok(ResolvedLocalSymbol::TsBuiltin(TsBuiltIn::TsPartial(i.span)));
             }
+            "Parameters" => {
+                return Ok(ResolvedLocalSymbol::TsBuiltin(TsBuiltIn::TsParameters(
+                    i.span,
+                )));
+            }
+            "ReturnType" => {
+                return Ok(ResolvedLocalSymbol::TsBuiltin(TsBuiltIn::TsReturnType(
+                    i.span,
+                )));
+            }
+            "Awaited" => {
+                return Ok(ResolvedLocalSymbol::TsBuiltin(TsBuiltIn::TsAwaited(i.span)));
+            }
+            "Capitalize" | "Uncapitalize" | "Uppercase" | "Lowercase" => {
+                let intrinsic = match i.sym.as_ref() {
+                    "Capitalize" => StringIntrinsic::Capitalize,
+                    "Uncapitalize" => StringIntrinsic::Uncapitalize,
+                    "Uppercase" => StringIntrinsic::Uppercase,
+                    _ => StringIntrinsic::Lowercase,
+                };
+                return Ok(ResolvedLocalSymbol::TsBuiltin(
+                    TsBuiltIn::TsStringIntrinsic(i.span, intrinsic),
+                ));
+            }
             _ => {}
         }
 
@@ -245,8 +404,20 @@ impl<'a, R: FileManager> TypeResolver<'a, R> {
     pub fn resolve_local_value(&mut self, i: &Ident) -> Res<ResolvedLocalSymbol> {
         let k = &(i.sym.clone(), i.span.ctxt);
 
-        if let Some(alias) = self.get_current_file().locals.exprs.get(k) {
-            return Ok(ResolvedLocalSymbol::Expr(alias.clone()));
+        if let Some((alias, kind)) = self.get_current_file().locals.exprs.get(k) {
+            return Ok(ResolvedLocalSymbol::Expr(alias.clone(), *kind));
+        }
+        if let Some(enum_) = self.get_current_file().locals.enums.get(k) {
+            return Ok(ResolvedLocalSymbol::TsEnumDecl(enum_.clone()));
+        }
+        if let Some(namespace) = self.get_current_file().locals.namespaces.get(k) {
+            return Ok(ResolvedLocalSymbol::Star(namespace.clone()));
+        }
+        if let Some((exported, from_file)) = self.resolve_namespace_member(i, false) {
+            return Ok(ResolvedLocalSymbol::NamedImport {
+                exported,
+                from_file,
+            });
         }
 
         if let Some(exported) = self
@@ -256,8 +427,8 @@ impl<'a, R: FileManager> TypeResolver<'a, R> {
             .get(&i.sym)
         {
             match exported.as_ref() {
-                SymbolExport::ValueExpr { expr, .. } => {
-                    return Ok(ResolvedLocalSymbol::Expr(expr.clone()));
+                SymbolExport::ValueExpr { expr, kind, .. } => {
+                    return Ok(ResolvedLocalSymbol::Expr(expr.clone(), *kind));
                 }
                 SymbolExport::TsType { .. }
                 | SymbolExport::TsEnumDecl { .. }
@@ -282,6 +453,12 @@ impl<'a, R: FileManager> TypeResolver<'a, R> {
         if let Some(enum_) = self.get_current_file().locals.enums.get(k) {
             return Ok(ResolvedLocalSymbol::TsEnumDecl(enum_.clone()));
         }
+        if let Some((exported, from_file)) = self.resolve_namespace_member(i, true) {
+            return Ok(ResolvedLocalSymbol::NamedImport {
+                exported,
+                from_file,
+            });
+        }
 
         self.resolve_local_import(i, true)
     }
@@ -1,29 +1,40 @@
+use crate::ast::json::Json;
 use crate::ast::json_schema::{
-    CodecName, JsonSchema, JsonSchemaConst, Optionality, TplLitTypeItem,
+    built_in_string_format, CodecName, JsonSchema, JsonSchemaConst, Optionality, TplLitTypeItem,
 };
 use crate::diag::{
     Diagnostic, DiagnosticInfoMessage, DiagnosticInformation, DiagnosticParentMessage, Location,
 };
+use crate::print::printer::collect_refs;
+use crate::qualified_name::qualified_type_name;
 use crate::subtyping::semtype::{SemType, SemTypeContext, SemTypeOps};
 use crate::subtyping::subtype::StringLitOrFormat;
 use crate::subtyping::to_schema::to_validators;
 use crate::subtyping::ToSemType;
-use crate::sym_reference::{ResolvedLocalSymbol, TsBuiltIn, TypeResolver};
+use crate::sym_reference::{ResolvedLocalSymbol, StringIntrinsic, TsBuiltIn, TypeResolver};
+use crate::validate::{
+    validate_with_options, CustomFormatValidator, CustomNumberFormatValidator, ValidateOptions,
+};
 use crate::Validator;
-use crate::{BeffUserSettings, BffFileName, FileManager, ImportReference, SymbolExport};
-use std::collections::{BTreeMap, HashMap};
+use crate::{
+    BeffUserSettings, BffFileName, FileManager, ImportReference, SymbolExport, SymbolsExportsModule,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::rc::Rc;
 use swc_atoms::JsWord;
-use swc_common::{Span, Spanned};
+use swc_common::comments::{CommentKind, Comments};
+use swc_common::{EqIgnoreSpan, Span, Spanned};
 use swc_ecma_ast::{
-    Expr, Ident, Lit, MemberProp, Prop, PropName, PropOrSpread, Str, TruePlusMinus, TsArrayType,
-    TsConditionalType, TsConstructorType, TsEntityName, TsEnumDecl, TsEnumMemberId,
-    TsExprWithTypeArgs, TsFnOrConstructorType, TsFnType, TsImportType, TsIndexedAccessType,
-    TsInferType, TsInterfaceDecl, TsIntersectionType, TsKeywordType, TsKeywordTypeKind, TsLit,
-    TsLitType, TsMappedType, TsOptionalType, TsParenthesizedType, TsQualifiedName, TsRestType,
-    TsThisType, TsTplLitType, TsTupleType, TsType, TsTypeElement, TsTypeLit, TsTypeOperator,
-    TsTypeOperatorOp, TsTypeParam, TsTypeParamDecl, TsTypeParamInstantiation, TsTypePredicate,
-    TsTypeQuery, TsTypeQueryExpr, TsTypeRef, TsUnionOrIntersectionType, TsUnionType,
+    BinaryOp, BindingIdent, CallExpr, Callee, Expr, Ident, Lit, MemberExpr, MemberProp, Pat, Prop,
+    PropName, PropOrSpread, Str, TruePlusMinus, TsArrayType, TsConditionalType, TsConstructorType,
+    TsEntityName, TsEnumDecl, TsEnumMember, TsEnumMemberId, TsExprWithTypeArgs,
+    TsFnOrConstructorType, TsFnParam, TsFnType, TsImportType, TsIndexSignature,
+    TsIndexedAccessType, TsInferType, TsInterfaceDecl, TsIntersectionType, TsKeywordType,
+    TsKeywordTypeKind, TsLit, TsLitType, TsMappedType, TsOptionalType, TsParenthesizedType,
+    TsQualifiedName, TsRestType, TsThisType, TsTplLitType, TsTupleElement, TsTupleType, TsType,
+    TsTypeElement, TsTypeLit, TsTypeOperator, TsTypeOperatorOp, TsTypeParam, TsTypeParamDecl,
+    TsTypeParamInstantiation, TsTypePredicate, TsTypeQuery, TsTypeQueryExpr, TsTypeRef,
+    TsUnionOrIntersectionType, TsUnionType, UnaryOp, VarDeclKind,
 };
 
 pub struct TypeToSchema<'a, 'b, R: FileManager> {
@@ -32,10 +43,55 @@ pub struct TypeToSchema<'a, 'b, R: FileManager> {
     pub components: HashMap<String, Option<Validator>>,
     pub ref_stack: Vec<DiagnosticInformation>,
     pub type_param_stack: Vec<BTreeMap<String, JsonSchema>>,
+    pub alias_stack: Vec<String>,
     pub settings: &'a BeffUserSettings,
     pub counter: &'b mut usize,
 }
 
+// the name in `StringFormat<"name">` or `NumberFormat<"name">`
+fn format_type_param(type_params: &Option<Box<TsTypeParamInstantiation>>) -> Option<String> {
+    match type_params.as_ref().map(|it| &it.params[..]) {
+        Some([head]) => match &**head {
+            TsType::TsLitType(TsLitType {
+                lit: TsLit::Str(Str { value, .. }),
+                ..
+            }) => Some(value.to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// the value of a literal type, ie: `20`, `["a"]` or `{ a: true }`
+fn literal_json(it: &JsonSchema) -> Option<Json> {
+    match it {
+        JsonSchema::Const(it) => Some(it.clone().to_json()),
+        JsonSchema::Tuple {
+            prefix_items,
+            optional_items,
+            items: None,
+            ..
+        } if optional_items.is_empty() => prefix_items
+            .iter()
+            .map(literal_json)
+            .collect::<Option<_>>()
+            .map(Json::Array),
+        JsonSchema::Object {
+            vs,
+            rest: None,
+            pattern_vs,
+        } if pattern_vs.is_empty() => vs
+            .iter()
+            .map(|(k, v)| match v {
+                Optionality::Required(v) => literal_json(v).map(|v| (k.clone(), v)),
+                _ => None,
+            })
+            .collect::<Option<_>>()
+            .map(Json::Object),
+        _ => None,
+    }
+}
+
 fn extract_items_from_array(it: JsonSchema) -> JsonSchema {
     match it {
         JsonSchema::Array(items) => *items,
@@ -45,6 +101,22 @@ fn extract_items_from_array(it: JsonSchema) -> JsonSchema {
 
 type Res<T> = Result<T, Box<Diagnostic>>;
 
+struct FnSignatureParam {
+    ty: Option<Rc<TsType>>,
+    default: Option<Rc<Expr>>,
+    optional: bool,
+    rest: bool,
+    span: Span,
+}
+
+struct FnSignature {
+    params: Vec<FnSignatureParam>,
+    return_type: Option<Rc<TsType>>,
+    // the file the parameter and return types are declared in
+    file_name: BffFileName,
+    span: Span,
+}
+
 impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
     pub fn new(
         files: &'a mut R,
@@ -58,6 +130,7 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
             components: HashMap::new(),
             ref_stack: vec![],
             type_param_stack: vec![],
+            alias_stack: vec![],
             settings,
             counter,
         }
@@ -97,6 +170,23 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
         match prop {
             TsTypeElement::TsPropertySignature(prop) => {
                 let key = match &*prop.key {
+                    key if prop.computed => match self.typeof_expr(key, true)? {
+                        JsonSchema::Const(c) => match Self::const_to_key(&c) {
+                            Some(key) => key,
+                            None => {
+                                return self.error(
+                                    &prop.span,
+                                    DiagnosticInfoMessage::ComputedPropKeyShouldBeConst,
+                                )
+                            }
+                        },
+                        _ => {
+                            return self.error(
+                                &prop.span,
+                                DiagnosticInfoMessage::ComputedPropKeyShouldBeConst,
+                            )
+                        }
+                    },
                     Expr::Ident(ident) => ident.sym.to_string(),
                     Expr::Lit(Lit::Str(st)) => st.value.to_string(),
                     _ => {
@@ -105,24 +195,38 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
                 };
                 match &prop.type_ann.as_ref() {
                     Some(val) => {
-                        let value = self.convert_ts_type(&val.type_ann)?;
-                        let value = if prop.optional {
-                            value.optional()
-                        } else {
-                            value.required()
+                        let (value, default) = match self.get_default_marker(&val.type_ann)? {
+                            Some((value, default)) => (value, Some(default)),
+                            None => {
+                                let value = self.convert_ts_type(&val.type_ann)?;
+                                let default = self.get_jsdoc_default(&value, &prop.span)?;
+                                (value, default)
+                            }
+                        };
+                        let value = match default {
+                            Some(default) => Optionality::Default(value, default),
+                            None if prop.optional => value.optional(),
+                            None => value.required(),
                         };
                         Ok((key, value))
                     }
-                    None => self.error(
-                        &prop.span,
-                        DiagnosticInfoMessage::PropShouldHaveTypeAnnotation,
-                    ),
+                    // a class field without annotation, widened unless it is a readonly literal
+                    None => match &prop.init {
+                        Some(init) => {
+                            let as_const = prop.readonly && Self::is_literal_expr(init);
+                            let value = self.typeof_expr(init, as_const)?;
+                            Ok((key, value.required()))
+                        }
+                        None => self.error(
+                            &prop.span,
+                            DiagnosticInfoMessage::PropShouldHaveTypeAnnotation,
+                        ),
+                    },
                 }
             }
-            TsTypeElement::TsIndexSignature(_) => self.cannot_serialize_error(
-                &prop.span(),
-                DiagnosticInfoMessage::IndexSignatureNonSerializableToJsonSchema,
-            ),
+            TsTypeElement::TsIndexSignature(_) => {
+                unreachable!("index signatures are handled by convert_ts_type_elements")
+            }
             TsTypeElement::TsGetterSignature(_)
             | TsTypeElement::TsSetterSignature(_)
             | TsTypeElement::TsMethodSignature(_)
@@ -133,6 +237,117 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
             ),
         }
     }
+    fn convert_index_signature(&mut self, sig: &TsIndexSignature) -> Res<JsonSchema> {
+        let key = match sig.params.as_slice() {
+            [TsFnParam::Ident(BindingIdent {
+                type_ann: Some(key),
+                ..
+            })] => self.convert_ts_type(&key.type_ann)?,
+            _ => {
+                return self.cannot_serialize_error(
+                    &sig.span,
+                    DiagnosticInfoMessage::IndexSignatureNonSerializableToJsonSchema,
+                )
+            }
+        };
+        let value = match &sig.type_ann {
+            Some(value) => self.convert_ts_type(&value.type_ann)?,
+            None => {
+                return self.error(
+                    &sig.span,
+                    DiagnosticInfoMessage::PropShouldHaveTypeAnnotation,
+                )
+            }
+        };
+        self.convert_record(key, value, &sig.span)
+    }
+
+    fn convert_ts_type_elements(&mut self, members: &[TsTypeElement]) -> Res<JsonSchema> {
+        let mut vs = BTreeMap::new();
+        let mut rest: Option<JsonSchema> = None;
+        let mut pattern_vs: BTreeMap<Vec<TplLitTypeItem>, JsonSchema> = BTreeMap::new();
+
+        for prop in members {
+            match prop {
+                TsTypeElement::TsIndexSignature(sig) => {
+                    if let JsonSchema::Object {
+                        vs: sig_vs,
+                        rest: sig_rest,
+                        pattern_vs: sig_pattern_vs,
+                    } = self.convert_index_signature(sig)?
+                    {
+                        vs.extend(sig_vs);
+                        if let Some(sig_rest) = sig_rest {
+                            rest = Some(match rest {
+                                Some(prev) => JsonSchema::any_of(vec![prev, *sig_rest]),
+                                None => *sig_rest,
+                            });
+                        }
+                        for (k, v) in sig_pattern_vs {
+                            let v = match pattern_vs.remove(&k) {
+                                Some(prev) => JsonSchema::any_of(vec![prev, v]),
+                                None => v,
+                            };
+                            pattern_vs.insert(k, v);
+                        }
+                    }
+                }
+                _ => {
+                    let (k, v) = self.convert_ts_type_element(prop)?;
+                    vs.insert(k, v);
+                }
+            }
+        }
+
+        Ok(JsonSchema::Object {
+            vs,
+            rest: rest.map(Box::new),
+            pattern_vs,
+        })
+    }
+
+    fn convert_record(
+        &mut self,
+        key: JsonSchema,
+        value: JsonSchema,
+        span: &Span,
+    ) -> Res<JsonSchema> {
+        let mut vs = BTreeMap::new();
+        let mut rest = None;
+        let mut pattern_vs = BTreeMap::new();
+
+        for k in self.extract_union(key)? {
+            match k {
+                JsonSchema::String => rest = Some(Box::new(value.clone())),
+                // object keys are always strings, numbers are matched by their representation
+                JsonSchema::Number => {
+                    pattern_vs.insert(vec![TplLitTypeItem::Number], value.clone());
+                }
+                JsonSchema::Const(JsonSchemaConst::String(str)) => {
+                    vs.insert(str, value.clone().required());
+                }
+                JsonSchema::Const(JsonSchemaConst::Number(n)) => {
+                    vs.insert(n.to_serde().to_string(), value.clone().required());
+                }
+                JsonSchema::TplLitType(items) => match TplLitTypeItem::const_vec(&items) {
+                    Some(str) => {
+                        vs.insert(str, value.clone().required());
+                    }
+                    None => {
+                        pattern_vs.insert(items, value.clone());
+                    }
+                },
+                _ => return self.error(span, DiagnosticInfoMessage::RecordKeyShouldBeString),
+            }
+        }
+
+        Ok(JsonSchema::Object {
+            vs,
+            rest,
+            pattern_vs,
+        })
+    }
+
     fn convert_pick_keys(
         obj: &BTreeMap<String, Optionality<JsonSchema>>,
         keys: Vec<String>,
@@ -254,15 +469,113 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
             _ => self.error(&span, DiagnosticInfoMessage::ExpectedArray),
         }
     }
+    // the tuple or array a rest element spreads, through aliases
+    fn spread_target(&self, schema: JsonSchema) -> JsonSchema {
+        match schema {
+            JsonSchema::Ref(n) => match self.components.get(&n).and_then(|it| it.as_ref()) {
+                Some(Validator { schema, .. })
+                    if matches!(
+                        schema,
+                        JsonSchema::Tuple { .. } | JsonSchema::Array(_) | JsonSchema::Ref(_)
+                    ) =>
+                {
+                    self.spread_target(schema.clone())
+                }
+                _ => JsonSchema::Ref(n),
+            },
+            _ => schema,
+        }
+    }
+    fn convert_tuple(&mut self, elem_types: &[TsTupleElement]) -> Res<JsonSchema> {
+        // (span, type, is_optional, is_rest), spread tuples are flattened into their items
+        let mut elems = vec![];
+        for it in elem_types {
+            // named elements have the `?` and `...` on the label, ie: `[a?: string, ...b: number[]]`
+            let (ty, is_optional, is_rest) = match (&it.label, &*it.ty) {
+                (_, TsType::TsRestType(TsRestType { type_ann, .. })) => (type_ann, false, true),
+                (_, TsType::TsOptionalType(TsOptionalType { type_ann, .. })) => {
+                    (type_ann, true, false)
+                }
+                (Some(Pat::Rest(_)), _) => (&it.ty, false, true),
+                (Some(Pat::Ident(BindingIdent { id, .. })), _) => (&it.ty, id.optional, false),
+                _ => (&it.ty, false, false),
+            };
+            let ty = self.convert_ts_type(ty)?;
+            if !is_rest {
+                elems.push((it.span, ty, is_optional, false));
+                continue;
+            }
+            match self.spread_target(ty) {
+                JsonSchema::Tuple {
+                    prefix_items,
+                    optional_items,
+                    items,
+                    suffix_items,
+                } => {
+                    elems.extend(
+                        prefix_items
+                            .into_iter()
+                            .map(|ty| (it.span, ty, false, false)),
+                    );
+                    elems.extend(
+                        optional_items
+                            .into_iter()
+                            .map(|ty| (it.span, ty, true, false)),
+                    );
+                    elems.extend(items.map(|ty| (it.span, *ty, false, true)));
+                    elems.extend(
+                        suffix_items
+                            .into_iter()
+                            .map(|ty| (it.span, ty, false, false)),
+                    );
+                }
+                ty => elems.push((it.span, extract_items_from_array(ty), false, true)),
+            }
+        }
+
+        let mut prefix_items = vec![];
+        let mut optional_items = vec![];
+        let mut items = None;
+        let mut suffix_items = vec![];
+        for (span, ty, is_optional, is_rest) in elems {
+            if is_rest {
+                if items.is_some() {
+                    return self.cannot_serialize_error(
+                        &span,
+                        DiagnosticInfoMessage::DuplicatedRestNonSerializableToJsonSchema,
+                    );
+                }
+                items = Some(ty.into());
+                continue;
+            }
+            match (is_optional, &items) {
+                (true, None) => optional_items.push(ty),
+                (false, None) if optional_items.is_empty() => prefix_items.push(ty),
+                (false, Some(_)) if optional_items.is_empty() => suffix_items.push(ty),
+                _ => return self.error(&span, DiagnosticInfoMessage::InvalidOptionalTupleElement),
+            }
+        }
+        Ok(JsonSchema::Tuple {
+            prefix_items,
+            optional_items,
+            items,
+            suffix_items,
+        })
+    }
+
     fn extract_tuple(&mut self, arr: JsonSchema, span: Span) -> Res<Vec<JsonSchema>> {
         match arr {
             JsonSchema::Tuple {
                 mut prefix_items,
+                optional_items,
                 items,
+                suffix_items,
             } => {
+                prefix_items.extend(optional_items);
                 if let Some(r) = items {
                     prefix_items.push(*r);
                 }
+                prefix_items.extend(suffix_items);
                 Ok(prefix_items)
             }
             JsonSchema::Ref(n) => {
@@ -282,8 +595,15 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
         span: &Span,
     ) -> Res<BTreeMap<String, Optionality<JsonSchema>>> {
         match obj {
-            JsonSchema::Object { vs, rest } => match rest {
+            JsonSchema::Object {
+                vs,
+                rest,
+                pattern_vs,
+            } => match rest {
                 Some(_) => self.error(span, DiagnosticInfoMessage::RestFoundOnExtractObject),
+                None if !pattern_vs.is_empty() => {
+                    self.error(span, DiagnosticInfoMessage::RestFoundOnExtractObject)
+                }
                 None => Ok(vs.clone()),
             },
             JsonSchema::Ref(r) => {
@@ -302,10 +622,7 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
         type_args: &Option<Box<TsTypeParamInstantiation>>,
     ) -> Res<JsonSchema> {
         match typ {
-            TsBuiltIn::TsObject(_) => Ok(JsonSchema::Object {
-                vs: BTreeMap::new(),
-                rest: Some(Box::new(JsonSchema::Any)),
-            }),
+            TsBuiltIn::TsObject(_) => Ok(JsonSchema::object(vec![], Some(JsonSchema::Any.into()))),
 
             TsBuiltIn::TsRecord(span) => match type_args {
                 Some(vs) => {
@@ -322,48 +639,7 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
                         );
                     }
 
-                    let key = Box::new(items[0].clone());
-
-                    match key.as_ref() {
-                        JsonSchema::String => {
-                            let value = items[1].clone();
-                            Ok(JsonSchema::Object {
-                                vs: BTreeMap::new(),
-                                rest: Some(Box::new(value)),
-                            })
-                        }
-                        _ => {
-                            let union = self.extract_union(*key);
-                            if let Ok(vs) = union {
-                                let mut string_keys = vec![];
-                                for v in vs {
-                                    match v {
-                                        JsonSchema::Const(JsonSchemaConst::String(str)) => {
-                                            string_keys.push(str);
-                                        }
-                                        _ => {
-                                            return self.error(
-                                                span,
-                                                DiagnosticInfoMessage::RecordKeyShouldBeString,
-                                            );
-                                        }
-                                    }
-                                }
-
-                                let value = items[1].clone();
-
-                                Ok(JsonSchema::Object {
-                                    vs: string_keys
-                                        .into_iter()
-                                        .map(|it| (it, value.clone().required()))
-                                        .collect(),
-                                    rest: None,
-                                })
-                            } else {
-                                self.error(span, DiagnosticInfoMessage::RecordKeyShouldBeString)
-                            }
-                        }
-                    }
+                    self.convert_record(items[0].clone(), items[1].clone(), span)
                 }
                 None => self
                     .cannot_serialize_error(span, DiagnosticInfoMessage::MissingArgumentsOnRecord),
@@ -498,38 +774,586 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
                 None => self
                     .cannot_serialize_error(span, DiagnosticInfoMessage::MissingArgumentsOnExclude),
             },
+            TsBuiltIn::TsParameters(span) => match type_args {
+                Some(vs) => match vs.params.as_slice() {
+                    [fn_ty] => {
+                        let sig = self.fn_signature(fn_ty, span)?;
+                        self.convert_fn_parameters(&sig)
+                    }
+                    _ => self.error(
+                        span,
+                        DiagnosticInfoMessage::ParametersShouldHaveOneTypeArgument,
+                    ),
+                },
+                None => self.cannot_serialize_error(
+                    span,
+                    DiagnosticInfoMessage::MissingArgumentsOnParameters,
+                ),
+            },
+            TsBuiltIn::TsReturnType(span) => match type_args {
+                Some(vs) => match vs.params.as_slice() {
+                    [fn_ty] => {
+                        let sig = self.fn_signature(fn_ty, span)?;
+                        self.convert_fn_return_type(&sig, false)
+                    }
+                    _ => self.error(
+                        span,
+                        DiagnosticInfoMessage::ReturnTypeShouldHaveOneTypeArgument,
+                    ),
+                },
+                None => self.cannot_serialize_error(
+                    span,
+                    DiagnosticInfoMessage::MissingArgumentsOnReturnType,
+                ),
+            },
+            TsBuiltIn::TsAwaited(span) => match type_args {
+                Some(vs) => match vs.params.as_slice() {
+                    [ty] => self.convert_awaited(ty),
+                    _ => self.error(
+                        span,
+                        DiagnosticInfoMessage::AwaitedShouldHaveOneTypeArgument,
+                    ),
+                },
+                None => self
+                    .cannot_serialize_error(span, DiagnosticInfoMessage::MissingArgumentsOnAwaited),
+            },
+            TsBuiltIn::TsStringIntrinsic(span, intrinsic) => {
+                match type_args.as_ref().map(|it| it.params.as_slice()) {
+                    Some([ty]) => self.convert_string_intrinsic(span, *intrinsic, ty),
+                    _ => self.error(
+                        span,
+                        DiagnosticInfoMessage::StringIntrinsicShouldHaveOneTypeArgument(
+                            intrinsic.name().to_string(),
+                        ),
+                    ),
+                }
+            }
         }
     }
-    fn convert_enum_decl(&mut self, typ: &TsEnumDecl) -> Res<JsonSchema> {
-        let mut values = vec![];
 
-        for member in &typ.members {
-            match &member.init {
-                Some(init) => {
-                    let expr_ty = self.typeof_expr(init, true)?;
-                    values.push(expr_ty);
-                }
+    fn convert_string_intrinsic(
+        &mut self,
+        span: &Span,
+        intrinsic: StringIntrinsic,
+        ty: &TsType,
+    ) -> Res<JsonSchema> {
+        let schema = self.convert_ts_type(ty)?;
+        let mut acc = vec![];
+        for v in self.extract_union(schema)? {
+            let lit = match v {
+                JsonSchema::Const(JsonSchemaConst::String(s)) => Some(s),
+                JsonSchema::TplLitType(items) => TplLitTypeItem::const_vec(&items),
+                _ => None,
+            };
+            match lit {
+                Some(s) => acc.push(JsonSchema::Const(JsonSchemaConst::String(
+                    intrinsic.apply(&s),
+                ))),
                 None => {
-                    self.cannot_serialize_error(
-                        &typ.span,
-                        DiagnosticInfoMessage::EnumMemberNoInit,
-                    )?;
-                } // None => match &member.id {
-                  //     TsEnumMemberId::Ident(ident) => {
-                  //         let cons = JsonSchemaConst::String(ident.sym.to_string());
-                  //         let cons = JsonSchema::Const(cons);
-                  //         values.push(cons);
-                  //     }
-                  //     TsEnumMemberId::Str(str) => {
-                  //         let cons = JsonSchemaConst::String(str.value.to_string());
-                  //         let cons = JsonSchema::Const(cons);
-                  //         values.push(cons);
-                  //     }
-                  // },
+                    return self.error(
+                        span,
+                        DiagnosticInfoMessage::StringIntrinsicShouldHaveStringLiteralArgument(
+                            intrinsic.name().to_string(),
+                        ),
+                    )
+                }
+            }
+        }
+        Ok(JsonSchema::any_of(acc))
+    }
+
+    fn pat_signature_param(pat: &Pat) -> Option<FnSignatureParam> {
+        let (type_ann, optional, rest, span) = match pat {
+            // `this` parameters are not part of the arguments
+            Pat::Ident(BindingIdent { id, .. }) if id.sym == *"this" => return None,
+            Pat::Ident(BindingIdent { id, type_ann }) => (type_ann, id.optional, false, id.span),
+            Pat::Array(a) => (&a.type_ann, a.optional, false, a.span),
+            Pat::Object(o) => (&o.type_ann, o.optional, false, o.span),
+            Pat::Rest(r) => (&r.type_ann, false, true, r.span),
+            Pat::Assign(a) => {
+                // parameters with a default value are optional
+                let param = Self::pat_signature_param(&a.left)?;
+                return Some(FnSignatureParam {
+                    optional: true,
+                    default: Some(Rc::new(*a.right.clone())),
+                    ..param
+                });
+            }
+            Pat::Invalid(_) | Pat::Expr(_) => (&None, false, false, pat.span()),
+        };
+        Some(FnSignatureParam {
+            ty: type_ann.as_ref().map(|it| Rc::new(*it.type_ann.clone())),
+            default: None,
+            optional,
+            rest,
+            span,
+        })
+    }
+
+    fn ts_fn_type_signature(f: &TsFnType, file_name: BffFileName) -> FnSignature {
+        let params = f
+            .params
+            .iter()
+            .filter_map(|it| match it {
+                TsFnParam::Ident(id) => Self::pat_signature_param(&Pat::Ident(id.clone())),
+                TsFnParam::Array(a) => Self::pat_signature_param(&Pat::Array(a.clone())),
+                TsFnParam::Rest(r) => Self::pat_signature_param(&Pat::Rest(r.clone())),
+                TsFnParam::Object(o) => Self::pat_signature_param(&Pat::Object(o.clone())),
+            })
+            .collect();
+        FnSignature {
+            params,
+            return_type: Some(Rc::new(*f.type_ann.type_ann.clone())),
+            file_name,
+            span: f.span,
+        }
+    }
+
+    fn fn_expr_signature(e: &Expr, file_name: BffFileName) -> Option<FnSignature> {
+        match e {
+            Expr::Fn(f) => Some(FnSignature {
+                params: f
+                    .function
+                    .params
+                    .iter()
+                    .filter_map(|it| Self::pat_signature_param(&it.pat))
+                    .collect(),
+                return_type: Self::fn_expr_return_type(e).map(|it| Rc::new(it.clone())),
+                file_name,
+                span: f.function.span,
+            }),
+            Expr::Arrow(a) => Some(FnSignature {
+                params: a
+                    .params
+                    .iter()
+                    .filter_map(Self::pat_signature_param)
+                    .collect(),
+                return_type: Self::fn_expr_return_type(e).map(|it| Rc::new(it.clone())),
+                file_name,
+                span: a.span,
+            }),
+            Expr::Paren(p) => Self::fn_expr_signature(&p.expr, file_name),
+            _ => None,
+        }
+    }
+
+    fn export_fn_signature(&mut self, exported: &SymbolExport, span: &Span) -> Res<FnSignature> {
+        match exported {
+            SymbolExport::ValueExpr {
+                expr,
+                original_file,
+                ..
+            } => match Self::fn_expr_signature(expr, original_file.clone()) {
+                Some(sig) => Ok(sig),
+                None => self.error(span, DiagnosticInfoMessage::ExpectedFunctionType),
+            },
+            SymbolExport::ExprDecl {
+                ty, original_file, ..
+            } => {
+                let store_current_file = self.current_file.clone();
+                self.current_file = original_file.clone();
+                let sig = self.fn_signature(ty, span);
+                self.current_file = store_current_file;
+                sig
+            }
+            _ => self.error(span, DiagnosticInfoMessage::ExpectedFunctionType),
+        }
+    }
+
+    fn value_fn_signature(&mut self, i: &Ident) -> Res<FnSignature> {
+        if let Some(f) = self.files.get_or_fetch_file(&self.current_file) {
+            let k = &(i.sym.clone(), i.span.ctxt);
+            if let Some(expr_decl) = f.locals.exprs_decls.get(k) {
+                return self.fn_signature(expr_decl, &i.span);
             }
         }
+        let s = TypeResolver::new(self.files, &self.current_file).resolve_local_value(i)?;
+        match s {
+            ResolvedLocalSymbol::Expr(e, _) => {
+                match Self::fn_expr_signature(&e, self.current_file.clone()) {
+                    Some(sig) => Ok(sig),
+                    None => self.error(&i.span, DiagnosticInfoMessage::ExpectedFunctionType),
+                }
+            }
+            ResolvedLocalSymbol::NamedImport { exported, .. } => {
+                self.export_fn_signature(&exported, &i.span)
+            }
+            _ => self.error(&i.span, DiagnosticInfoMessage::ExpectedFunctionType),
+        }
+    }
 
-        Ok(JsonSchema::any_of(values))
+    fn qualified_value_fn_signature(&mut self, q: &TsQualifiedName) -> Res<FnSignature> {
+        let reference = match &q.left {
+            TsEntityName::Ident(left) => {
+                match TypeResolver::new(self.files, &self.current_file).resolve_local_value(left)? {
+                    ResolvedLocalSymbol::Star(reference) => Some(reference),
+                    _ => None,
+                }
+            }
+            TsEntityName::TsQualifiedName(_) => None,
+        };
+        let exported =
+            reference.and_then(|reference| reference.get_value_export(&q.right.sym, self.files));
+        match exported {
+            Some(exported) => self.export_fn_signature(&exported, &q.span()),
+            None => self.error(&q.span(), DiagnosticInfoMessage::ExpectedFunctionType),
+        }
+    }
+
+    // resolves the signature used by `Parameters` and `ReturnType`
+    fn fn_signature(&mut self, ty: &TsType, span: &Span) -> Res<FnSignature> {
+        match ty {
+            TsType::TsFnOrConstructorType(TsFnOrConstructorType::TsFnType(f)) => {
+                Ok(Self::ts_fn_type_signature(f, self.current_file.clone()))
+            }
+            TsType::TsParenthesizedType(TsParenthesizedType { type_ann, .. }) => {
+                self.fn_signature(type_ann, span)
+            }
+            TsType::TsTypeQuery(TsTypeQuery {
+                expr_name: TsTypeQueryExpr::TsEntityName(name),
+                type_args: None,
+                ..
+            }) => match name {
+                TsEntityName::Ident(i) => self.value_fn_signature(i),
+                TsEntityName::TsQualifiedName(q) => self.qualified_value_fn_signature(q),
+            },
+            TsType::TsTypeRef(TsTypeRef {
+                type_name: TsEntityName::Ident(i),
+                type_params: None,
+                ..
+            }) => match TypeResolver::new(self.files, &self.current_file).resolve_local_type(i)? {
+                ResolvedLocalSymbol::TsType(None, alias) => self.fn_signature(&alias, span),
+                ResolvedLocalSymbol::NamedImport { exported, .. } => match exported.as_ref() {
+                    SymbolExport::TsType {
+                        ty,
+                        params: None,
+                        original_file,
+                        ..
+                    } => {
+                        let store_current_file = self.current_file.clone();
+                        self.current_file = original_file.clone();
+                        let sig = self.fn_signature(ty, span);
+                        self.current_file = store_current_file;
+                        sig
+                    }
+                    _ => self.error(span, DiagnosticInfoMessage::ExpectedFunctionType),
+                },
+                _ => self.error(span, DiagnosticInfoMessage::ExpectedFunctionType),
+            },
+            _ => self.error(span, DiagnosticInfoMessage::ExpectedFunctionType),
+        }
+    }
+
+    fn convert_fn_parameters(&mut self, sig: &FnSignature) -> Res<JsonSchema> {
+        let store_current_file = self.current_file.clone();
+        self.current_file = sig.file_name.clone();
+        let mut prefix_items = vec![];
+        let mut optional_items: Vec<JsonSchema> = vec![];
+        let mut items = None;
+        let mut res = Ok(());
+        for param in &sig.params {
+            let ty = match (&param.ty, &param.default) {
+                (Some(ty), _) => self.convert_ts_type(ty),
+                // the type is inferred from the default value
+                (None, Some(default)) => self.typeof_expr(default, false),
+                (None, None) => self.error(
+                    &param.span,
+                    DiagnosticInfoMessage::FunctionParamShouldHaveTypeAnnotation,
+                ),
+            };
+            match ty {
+                Ok(ty) if param.rest => items = Some(extract_items_from_array(ty).into()),
+                // optional arguments can be omitted
+                Ok(ty) if param.optional => optional_items.push(ty),
+                Ok(ty) => {
+                    // a defaulted argument before a required one must be passed, possibly as null
+                    prefix_items.extend(
+                        optional_items
+                            .drain(..)
+                            .map(|it| JsonSchema::any_of(vec![it, JsonSchema::Null])),
+                    );
+                    prefix_items.push(ty)
+                }
+                Err(e) => {
+                    res = Err(e);
+                    break;
+                }
+            }
+        }
+        self.current_file = store_current_file;
+        res.map(|_| JsonSchema::Tuple {
+            prefix_items,
+            optional_items,
+            items,
+            suffix_items: vec![],
+        })
+    }
+
+    fn convert_fn_return_type(&mut self, sig: &FnSignature, awaited: bool) -> Res<JsonSchema> {
+        let return_type = match &sig.return_type {
+            Some(return_type) => return_type.clone(),
+            None => {
+                return self.error(
+                    &sig.span,
+                    DiagnosticInfoMessage::FunctionShouldDeclareReturnType,
+                )
+            }
+        };
+        let store_current_file = self.current_file.clone();
+        self.current_file = sig.file_name.clone();
+        let res = if awaited {
+            self.convert_awaited(&return_type)
+        } else {
+            self.convert_ts_type(&return_type)
+        };
+        self.current_file = store_current_file;
+        res
+    }
+
+    fn is_user_defined_type(&mut self, i: &Ident) -> bool {
+        self.type_param_stack
+            .iter()
+            .any(|map| map.contains_key(&i.sym.to_string()))
+            || TypeResolver::new(self.files, &self.current_file)
+                .resolve_local_type(i)
+                .is_ok_and(|it| !matches!(it, ResolvedLocalSymbol::TsBuiltin(_)))
+    }
+
+    // `Awaited` unwraps promises, also when they are the return type of a function
+    fn convert_awaited(&mut self, ty: &TsType) -> Res<JsonSchema> {
+        match ty {
+            TsType::TsParenthesizedType(TsParenthesizedType { type_ann, .. }) => {
+                self.convert_awaited(type_ann)
+            }
+            TsType::TsTypeRef(TsTypeRef {
+                type_name: TsEntityName::Ident(i),
+                type_params: Some(args),
+                span,
+            }) if !self.is_user_defined_type(i) => match (i.sym.as_ref(), args.params.as_slice()) {
+                ("Promise" | "PromiseLike", [inner]) => self.convert_awaited(inner),
+                ("ReturnType", [fn_ty]) => {
+                    let sig = self.fn_signature(fn_ty, span)?;
+                    self.convert_fn_return_type(&sig, true)
+                }
+                ("Awaited", [inner]) => self.convert_awaited(inner),
+                _ => self.convert_ts_type(ty),
+            },
+            TsType::TsTypeRef(TsTypeRef {
+                type_name: TsEntityName::Ident(i),
+                type_params,
+                ..
+            }) => self.convert_awaited_alias(i, type_params, ty),
+            _ => self.convert_ts_type(ty),
+        }
+    }
+
+    // `type P = Promise<X>` is unwrapped through the alias, in the file that declares it
+    fn convert_awaited_alias(
+        &mut self,
+        i: &Ident,
+        type_args: &Option<Box<TsTypeParamInstantiation>>,
+        ty: &TsType,
+    ) -> Res<JsonSchema> {
+        let is_type_param = self
+            .type_param_stack
+            .iter()
+            .any(|map| map.contains_key(&i.sym.to_string()));
+        if is_type_param {
+            return self.convert_ts_type(ty);
+        }
+        let (params, alias, file) =
+            match TypeResolver::new(self.files, &self.current_file).resolve_local_type(i)? {
+                ResolvedLocalSymbol::TsType(params, alias) => {
+                    (params, alias, self.current_file.clone())
+                }
+                ResolvedLocalSymbol::NamedImport { exported, .. } => match exported.as_ref() {
+                    SymbolExport::TsType {
+                        ty: alias,
+                        params,
+                        original_file,
+                        ..
+                    } => (params.clone(), alias.clone(), original_file.clone()),
+                    _ => return self.convert_ts_type(ty),
+                },
+                _ => return self.convert_ts_type(ty),
+            };
+        let mut body = alias.as_ref();
+        while let TsType::TsParenthesizedType(TsParenthesizedType { type_ann, .. }) = body {
+            body = type_ann;
+        }
+        // aliases of anything else keep their name
+        if !matches!(body, TsType::TsTypeRef(_)) {
+            return self.convert_ts_type(ty);
+        }
+        let type_args = type_args
+            .as_ref()
+            .map(|it| {
+                it.params
+                    .iter()
+                    .map(|it| self.convert_ts_type(it))
+                    .collect::<Res<Vec<_>>>()
+            })
+            .transpose()?;
+        let map =
+            self.get_type_params_stack_map(type_args, params.as_ref().map(|it| &it.params))?;
+        let store_current_file = self.current_file.clone();
+        self.current_file = file;
+        self.type_param_stack.push(map);
+        let res = self.convert_awaited(&alias);
+        self.type_param_stack.pop();
+        self.current_file = store_current_file;
+        res
+    }
+    fn enum_member_name(member: &TsEnumMember) -> String {
+        match &member.id {
+            TsEnumMemberId::Ident(i) => i.sym.to_string(),
+            TsEnumMemberId::Str(s) => s.value.to_string(),
+        }
+    }
+
+    fn enum_number_op(op: BinaryOp, l: f64, r: f64) -> Option<f64> {
+        let (li, ri) = (l as i64 as i32, r as i64 as i32);
+        let v = match op {
+            BinaryOp::Add => l + r,
+            BinaryOp::Sub => l - r,
+            BinaryOp::Mul => l * r,
+            BinaryOp::Div => l / r,
+            BinaryOp::Mod => l % r,
+            BinaryOp::Exp => l.powf(r),
+            BinaryOp::BitOr => (li | ri) as f64,
+            BinaryOp::BitAnd => (li & ri) as f64,
+            BinaryOp::BitXor => (li ^ ri) as f64,
+            BinaryOp::LShift => li.wrapping_shl(ri as u32) as f64,
+            BinaryOp::RShift => li.wrapping_shr(ri as u32) as f64,
+            BinaryOp::ZeroFillRShift => (li as u32).wrapping_shr(ri as u32) as f64,
+            _ => return None,
+        };
+        Some(v)
+    }
+
+    fn enum_member_init(
+        &mut self,
+        decl: &TsEnumDecl,
+        init: &Expr,
+        prev: &[(String, JsonSchema)],
+    ) -> Res<JsonSchema> {
+        let prev_value = |name: &str| prev.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone());
+        match init {
+            Expr::Ident(i) => {
+                if let Some(v) = prev_value(&i.sym) {
+                    return Ok(v);
+                }
+            }
+            Expr::Member(MemberExpr {
+                obj,
+                prop: MemberProp::Ident(prop),
+                ..
+            }) => {
+                if let Expr::Ident(obj) = obj.as_ref() {
+                    if obj.sym == decl.id.sym {
+                        if let Some(v) = prev_value(&prop.sym) {
+                            return Ok(v);
+                        }
+                    }
+                }
+            }
+            Expr::Paren(p) => return self.enum_member_init(decl, &p.expr, prev),
+            Expr::Unary(u) => {
+                let arg = self.enum_member_init(decl, &u.arg, prev)?;
+                if let JsonSchema::Const(JsonSchemaConst::Number(n)) = arg {
+                    let n = n.to_f64();
+                    let v = match u.op {
+                        UnaryOp::Minus => Some(-n),
+                        UnaryOp::Plus => Some(n),
+                        UnaryOp::Tilde => Some(!(n as i64 as i32) as f64),
+                        _ => None,
+                    };
+                    if let Some(v) = v {
+                        return Ok(JsonSchema::Const(JsonSchemaConst::parse_f64(v)));
+                    }
+                }
+            }
+            Expr::Bin(b) => {
+                let left = self.enum_member_init(decl, &b.left, prev)?;
+                let right = self.enum_member_init(decl, &b.right, prev)?;
+                match (left, right) {
+                    (
+                        JsonSchema::Const(JsonSchemaConst::Number(l)),
+                        JsonSchema::Const(JsonSchemaConst::Number(r)),
+                    ) => {
+                        if let Some(v) = Self::enum_number_op(b.op, l.to_f64(), r.to_f64()) {
+                            return Ok(JsonSchema::Const(JsonSchemaConst::parse_f64(v)));
+                        }
+                    }
+                    (
+                        JsonSchema::Const(JsonSchemaConst::String(l)),
+                        JsonSchema::Const(JsonSchemaConst::String(r)),
+                    ) if b.op == BinaryOp::Add => {
+                        return Ok(JsonSchema::Const(JsonSchemaConst::String(l + r.as_str())));
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        self.typeof_expr(init, true)
+    }
+
+    fn enum_member_values(&mut self, decl: &TsEnumDecl) -> Res<Vec<(String, JsonSchema)>> {
+        let mut values: Vec<(String, JsonSchema)> = vec![];
+
+        for member in &decl.members {
+            let value = match &member.init {
+                Some(init) => self.enum_member_init(decl, init, &values)?,
+                // members without initializer auto-increment the previous numeric member
+                None => match values.last() {
+                    None => JsonSchema::Const(JsonSchemaConst::parse_f64(0.0)),
+                    Some((_, JsonSchema::Const(JsonSchemaConst::Number(n)))) => {
+                        JsonSchema::Const(JsonSchemaConst::parse_f64(n.to_f64() + 1.0))
+                    }
+                    Some(_) => {
+                        return self.cannot_serialize_error(
+                            &member.span,
+                            DiagnosticInfoMessage::EnumMemberNoInit,
+                        )
+                    }
+                },
+            };
+            values.push((Self::enum_member_name(member), value));
+        }
+
+        Ok(values)
+    }
+
+    fn enum_member_value(
+        &mut self,
+        decl: &TsEnumDecl,
+        name: &JsWord,
+        span: &Span,
+    ) -> Res<JsonSchema> {
+        let values = self.enum_member_values(decl)?;
+        match values.into_iter().find(|(k, _)| k == name.as_ref()) {
+            Some((_, v)) => Ok(v),
+            None => self.error(
+                span,
+                DiagnosticInfoMessage::EnumMemberNotFound(name.to_string()),
+            ),
+        }
+    }
+
+    fn convert_enum_decl(&mut self, typ: &TsEnumDecl) -> Res<JsonSchema> {
+        let values = self.enum_member_values(typ)?;
+        Ok(JsonSchema::any_of(
+            values.into_iter().map(|(_, v)| v).collect(),
+        ))
+    }
+
+    fn typeof_enum_decl(&mut self, typ: &TsEnumDecl) -> Res<JsonSchema> {
+        let values = self.enum_member_values(typ)?;
+        Ok(JsonSchema::object(
+            values.into_iter().map(|(k, v)| (k, v.required())).collect(),
+            None,
+        ))
     }
 
     fn convert_interface_extends(&mut self, typ: &Vec<TsExprWithTypeArgs>) -> Res<Vec<JsonSchema>> {
@@ -567,14 +1391,7 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
 
         self.type_param_stack.push(map);
 
-        let r = Ok(JsonSchema::object(
-            typ.body
-                .body
-                .iter()
-                .map(|x| self.convert_ts_type_element(x))
-                .collect::<Res<_>>()?,
-            None,
-        ));
+        let r = self.convert_ts_type_elements(&typ.body.body);
         self.type_param_stack.pop();
 
         if typ.extends.is_empty() {
@@ -743,6 +1560,17 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
                     .collect::<Res<Vec<_>>>()
             })
             .transpose()?;
+        self.alias_stack.push(i.sym.to_string());
+        let res = self.get_type_ref_of_resolved_identifier(i, type_args);
+        self.alias_stack.pop();
+        res
+    }
+
+    fn get_type_ref_of_resolved_identifier(
+        &mut self,
+        i: &Ident,
+        type_args: Option<Vec<JsonSchema>>,
+    ) -> Res<JsonSchema> {
         match TypeResolver::new(self.files, &self.current_file).resolve_local_type(i)? {
             ResolvedLocalSymbol::TsType(decl, ty) => self.apply_type_params(type_args, &decl, &ty),
             ResolvedLocalSymbol::TsInterfaceDecl(int) => {
@@ -751,15 +1579,9 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
             ResolvedLocalSymbol::NamedImport {
                 exported,
                 from_file,
-            } => {
-                return self.convert_type_export(
-                    exported.as_ref(),
-                    from_file.file_name(),
-                    type_args,
-                )
-            }
+            } => self.convert_type_export(exported.as_ref(), from_file.file_name(), type_args),
             ResolvedLocalSymbol::Star(_)
-            | ResolvedLocalSymbol::Expr(_)
+            | ResolvedLocalSymbol::Expr(..)
             | ResolvedLocalSymbol::SymbolExportDefault(_) => {
                 self.error(&i.span, DiagnosticInfoMessage::FoundValueExpectedType)
             }
@@ -782,28 +1604,125 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
         Ok(JsonSchema::Ref(name))
     }
 
+    // `Default<T, V>`, a property of type `T` that is `V` when absent
+    fn get_default_marker(&mut self, ty: &TsType) -> Res<Option<(JsonSchema, Json)>> {
+        let TsType::TsTypeRef(TsTypeRef {
+            type_name: TsEntityName::Ident(i),
+            type_params,
+            span,
+        }) = ty
+        else {
+            return Ok(None);
+        };
+        if i.sym != *"Default" {
+            return Ok(None);
+        }
+        match type_params.as_ref().map(|it| &it.params[..]) {
+            Some([ty, value]) => {
+                let ty = self.convert_ts_type(ty)?;
+                let value = self.convert_ts_type(value)?;
+                match literal_json(&value) {
+                    Some(value) => {
+                        self.check_default(&ty, &value, span)?;
+                        Ok(Some((ty, value)))
+                    }
+                    None => self.error(span, DiagnosticInfoMessage::InvalidDefaultType),
+                }
+            }
+            _ => self.error(span, DiagnosticInfoMessage::InvalidDefaultType),
+        }
+    }
+
+    // The default is decoded like the wire value, it must be accepted by its type. Types that are
+    // still being converted cannot be checked yet.
+    fn check_default(&mut self, ty: &JsonSchema, value: &Json, span: &Span) -> Res<()> {
+        let validators: Vec<Validator> = self.components.values().flatten().cloned().collect();
+        let mut refs = vec![];
+        collect_refs(ty, &mut refs);
+        let mut seen = BTreeSet::new();
+        while let Some(r) = refs.pop() {
+            if !seen.insert(r.clone()) {
+                continue;
+            }
+            match validators.iter().find(|it| it.name == r) {
+                Some(v) => collect_refs(&v.schema, &mut refs),
+                None => return Ok(()),
+            }
+        }
+        // custom formats are checked by the functions registered at runtime
+        let options = ValidateOptions {
+            custom_formats: self
+                .settings
+                .custom_formats
+                .iter()
+                .map(|it| {
+                    (
+                        it.clone(),
+                        Box::new(|_: &str| true) as CustomFormatValidator,
+                    )
+                })
+                .collect(),
+            custom_number_formats: self
+                .settings
+                .custom_number_formats
+                .iter()
+                .map(|it| {
+                    (
+                        it.clone(),
+                        Box::new(|_: f64| true) as CustomNumberFormatValidator,
+                    )
+                })
+                .collect(),
+            ..ValidateOptions::default()
+        };
+        match validate_with_options(ty, &validators, &value.to_serde(), &options) {
+            Ok(()) => Ok(()),
+            Err(_) => self.error(span, DiagnosticInfoMessage::InvalidDefaultType),
+        }
+    }
+
+    // the `@default` tag of the JSDoc comment of a property
+    fn get_jsdoc_default(&mut self, ty: &JsonSchema, span: &Span) -> Res<Option<Json>> {
+        let comments = self
+            .files
+            .get_existing_file(&self.current_file)
+            .and_then(|file| file.comments.get_leading(span.lo))
+            .unwrap_or_default();
+        let tag = comments
+            .iter()
+            .filter(|it| it.kind == CommentKind::Block && it.text.starts_with('*'))
+            .flat_map(|it| it.text.lines())
+            .find_map(|line| {
+                let line = line.trim().trim_start_matches('*').trim();
+                line.strip_prefix("@default")
+                    .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+                    .map(|rest| rest.trim().to_string())
+            });
+        match tag {
+            Some(text) => match serde_json::from_str(&text) {
+                Ok(value) => {
+                    let value = Json::from_serde(&value);
+                    self.check_default(ty, &value, span)?;
+                    Ok(Some(value))
+                }
+                Err(_) => self.error(span, DiagnosticInfoMessage::InvalidJsDocDefault(text)),
+            },
+            None => Ok(None),
+        }
+    }
+
     fn get_string_with_format(
         &mut self,
         type_params: &Option<Box<TsTypeParamInstantiation>>,
         span: &Span,
     ) -> Res<JsonSchema> {
-        let r = type_params.as_ref().and_then(|it| it.params.split_first());
-
-        if let Some((head, rest)) = r {
-            if rest.is_empty() {
-                if let TsType::TsLitType(TsLitType {
-                    lit: TsLit::Str(Str { value, .. }),
-                    ..
-                }) = &**head
-                {
-                    let val_str = value.to_string();
-                    if self.settings.custom_formats.contains(&val_str) {
-                        return Ok(JsonSchema::StringWithFormat(val_str));
-                    } else {
-                        return self
-                            .error(span, DiagnosticInfoMessage::CustomFormatIsNotRegistered);
-                    }
-                }
+        if let Some(val_str) = format_type_param(type_params) {
+            if self.settings.custom_formats.contains(&val_str)
+                || built_in_string_format(&val_str).is_some()
+            {
+                return Ok(JsonSchema::StringWithFormat(val_str));
+            } else {
+                return self.error(span, DiagnosticInfoMessage::CustomFormatIsNotRegistered);
             }
         }
         self.error(
@@ -812,6 +1731,27 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
         )
     }
 
+    fn get_number_with_format(
+        &mut self,
+        type_params: &Option<Box<TsTypeParamInstantiation>>,
+        span: &Span,
+    ) -> Res<JsonSchema> {
+        if let Some(val_str) = format_type_param(type_params) {
+            if self.settings.custom_number_formats.contains(&val_str) {
+                return Ok(JsonSchema::NumberWithFormat(val_str));
+            } else {
+                return self.error(
+                    span,
+                    DiagnosticInfoMessage::CustomNumberFormatIsNotRegistered,
+                );
+            }
+        }
+        self.error(
+            span,
+            DiagnosticInfoMessage::InvalidUsageOfNumberFormatTypeParameter,
+        )
+    }
+
     fn get_type_ref(
         &mut self,
         i: &Ident,
@@ -828,6 +1768,11 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
 
         match i.sym.to_string().as_str() {
             "Date" => return Ok(JsonSchema::Codec(CodecName::ISO8061)),
+            "Uint8Array" => return Ok(JsonSchema::Codec(CodecName::Base64)),
+            "Base64Url" => return Ok(JsonSchema::Codec(CodecName::Base64Url)),
+            "PlainDate" => return Ok(JsonSchema::Codec(CodecName::PlainDate)),
+            "PlainTime" => return Ok(JsonSchema::Codec(CodecName::PlainTime)),
+            "IsoDuration" => return Ok(JsonSchema::Codec(CodecName::Duration)),
             "Array" => {
                 let type_params = type_params.as_ref().and_then(|it| it.params.split_first());
                 if let Some((ty, [])) = type_params {
@@ -837,37 +1782,114 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
                 return Ok(JsonSchema::Array(JsonSchema::Any.into()));
             }
             "StringFormat" => return self.get_string_with_format(type_params, &i.span),
+            "NumberFormat" => return self.get_number_with_format(type_params, &i.span),
+            "Brand" => {
+                if let Some([ty, brand]) = type_params.as_ref().map(|it| &it.params[..]) {
+                    if let TsType::TsLitType(TsLitType {
+                        lit: TsLit::Str(Str { value, .. }),
+                        ..
+                    }) = &**brand
+                    {
+                        let ty = self.convert_ts_type(ty)?;
+                        return Ok(JsonSchema::Brand(ty.into(), value.to_string()));
+                    }
+                }
+            }
+            // outside of a property the default value does not apply
+            "Default" => {
+                if let Some([ty, _]) = type_params.as_ref().map(|it| &it.params[..]) {
+                    return self.convert_ts_type(ty);
+                }
+            }
             _ => {}
         }
-        if let ResolvedLocalSymbol::TsBuiltin(bt) =
-            TypeResolver::new(self.files, &self.current_file).resolve_local_type(i)?
-        {
-            return self.convert_ts_built_in(&bt, type_params);
-        }
+        let resolved = TypeResolver::new(self.files, &self.current_file).resolve_local_type(i)?;
+        let name = match resolved {
+            ResolvedLocalSymbol::TsBuiltin(bt) => {
+                return self.convert_ts_built_in(&bt, type_params)
+            }
+            ResolvedLocalSymbol::TsType(_, ty) => {
+                qualified_type_name(&i.sym, &self.current_file, ty.span())
+            }
+            ResolvedLocalSymbol::TsInterfaceDecl(decl) => {
+                qualified_type_name(&decl.id.sym, &self.current_file, decl.id.span)
+            }
+            ResolvedLocalSymbol::TsEnumDecl(decl) => {
+                qualified_type_name(&decl.id.sym, &self.current_file, decl.id.span)
+            }
+            ResolvedLocalSymbol::NamedImport { exported, .. } => self
+                .type_export_name(&exported)
+                .unwrap_or_else(|| i.sym.to_string()),
+            ResolvedLocalSymbol::Expr(..)
+            | ResolvedLocalSymbol::SymbolExportDefault(_)
+            | ResolvedLocalSymbol::Star(_) => i.sym.to_string(),
+        };
 
-        let found = self.components.get(&(i.sym.to_string()));
+        let found = self.components.get(&name);
         if let Some(_found) = found {
-            return Ok(JsonSchema::Ref(i.sym.to_string()));
+            return Ok(JsonSchema::Ref(name));
         }
-        self.components.insert(i.sym.to_string(), None);
+        self.components.insert(name.clone(), None);
 
         let ty = self.get_type_ref_of_user_identifier(i, type_params);
         match ty {
             Ok(ty) => {
                 if type_params.is_some() {
-                    self.components.remove(&i.sym.to_string());
+                    self.components.remove(&name);
                     Ok(ty)
                 } else {
-                    self.insert_definition(i.sym.to_string(), ty)
+                    self.insert_definition(name, ty)
                 }
             }
             Err(e) => {
-                self.insert_definition(i.sym.to_string(), JsonSchema::Any)?;
+                self.insert_definition(name, JsonSchema::Any)?;
                 Err(e)
             }
         }
     }
 
+    // the name of the component of an exported type, qualified by the declaration it refers to
+    fn type_export_name(&mut self, exported: &SymbolExport) -> Option<String> {
+        match exported {
+            SymbolExport::TsType {
+                name,
+                ty,
+                original_file,
+                ..
+            } => Some(qualified_type_name(name, original_file, ty.span())),
+            SymbolExport::TsInterfaceDecl {
+                decl,
+                original_file,
+                ..
+            } => Some(qualified_type_name(
+                &decl.id.sym,
+                original_file,
+                decl.id.span,
+            )),
+            SymbolExport::TsEnumDecl {
+                decl,
+                original_file,
+                ..
+            } => Some(qualified_type_name(
+                &decl.id.sym,
+                original_file,
+                decl.id.span,
+            )),
+            SymbolExport::SomethingOfOtherFile {
+                something, file, ..
+            } => {
+                let exported = self
+                    .files
+                    .get_or_fetch_file(file)
+                    .and_then(|module| module.symbol_exports.get_type(something, self.files))?;
+                self.type_export_name(&exported)
+            }
+            SymbolExport::StarOfOtherFile { .. }
+            | SymbolExport::ValueExpr { .. }
+            | SymbolExport::ExprDecl { .. } => None,
+        }
+    }
+
     fn union(&mut self, types: &[Box<TsType>]) -> Res<JsonSchema> {
         let vs: Vec<JsonSchema> = types
             .iter()
@@ -876,13 +1898,79 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
         Ok(JsonSchema::any_of(vs))
     }
 
+    // `{ __brand: "UserId" }`, `{ readonly __brand: unique symbol }` or `{ [userIdBrand]: true }`,
+    // members of an intersection that only exist at compile time
+    // `declare const key: unique symbol`, local or imported
+    fn is_unique_symbol(&mut self, i: &Ident) -> bool {
+        let mut ty = None;
+        if let Some(f) = self.files.get_or_fetch_file(&self.current_file) {
+            let k = &(i.sym.clone(), i.span.ctxt);
+            ty = f.locals.exprs_decls.get(k).cloned();
+        }
+        if ty.is_none() {
+            if let Ok(ResolvedLocalSymbol::NamedImport { exported, .. }) =
+                TypeResolver::new(self.files, &self.current_file).resolve_local_value(i)
+            {
+                if let SymbolExport::ExprDecl { ty: decl, .. } = exported.as_ref() {
+                    ty = Some(decl.clone());
+                }
+            }
+        }
+        matches!(
+            ty.as_deref(),
+            Some(TsType::TsTypeOperator(TsTypeOperator {
+                op: TsTypeOperatorOp::Unique,
+                ..
+            }))
+        )
+    }
+
+    fn get_brand_marker(&mut self, ty: &TsType) -> Option<String> {
+        let TsType::TsTypeLit(TsTypeLit { members, .. }) = ty else {
+            return None;
+        };
+        let [TsTypeElement::TsPropertySignature(prop)] = &members[..] else {
+            return None;
+        };
+        let ty = prop.type_ann.as_ref().map(|it| &*it.type_ann);
+        let literal = match ty {
+            Some(TsType::TsLitType(TsLitType {
+                lit: TsLit::Str(Str { value, .. }),
+                ..
+            })) => Some(value.to_string()),
+            _ => None,
+        };
+        match &*prop.key {
+            Expr::Ident(key) if prop.computed && self.is_unique_symbol(key) => {
+                Some(literal.unwrap_or_else(|| key.sym.to_string()))
+            }
+            Expr::Ident(key) if key.sym == *"__brand" => match ty {
+                // the symbol has no name, the brand is named after the type alias
+                Some(TsType::TsTypeOperator(TsTypeOperator {
+                    op: TsTypeOperatorOp::Unique,
+                    ..
+                })) => self.alias_stack.last().cloned(),
+                _ => literal,
+            },
+            _ => None,
+        }
+    }
+
     fn intersection(&mut self, types: &[Box<TsType>], _span: &Span) -> Res<JsonSchema> {
-        let vs: Vec<JsonSchema> = types
-            .iter()
-            .map(|it| self.convert_ts_type(it))
-            .collect::<Res<_>>()?;
+        let mut brands = vec![];
+        let mut vs = vec![];
+        for it in types {
+            match self.get_brand_marker(it) {
+                Some(brand) => brands.push(brand),
+                None => vs.push(self.convert_ts_type(it)?),
+            }
+        }
 
-        Ok(JsonSchema::all_of(vs))
+        Ok(brands
+            .into_iter()
+            .fold(JsonSchema::all_of(vs), |acc, brand| {
+                JsonSchema::Brand(acc.into(), brand)
+            }))
     }
 
     fn cannot_serialize_error<T>(&mut self, span: &Span, msg: DiagnosticInfoMessage) -> Res<T> {
@@ -936,10 +2024,7 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
         right: &JsWord,
         span: &Span,
     ) -> Res<(Rc<SymbolExport>, Rc<ImportReference>, String)> {
-        let exported = self
-            .files
-            .get_or_fetch_file(from_file.file_name())
-            .and_then(|module| module.symbol_exports.get_type(right, self.files));
+        let exported = from_file.get_type_export(right, self.files);
         match exported {
             Some(exported) => {
                 let name = match &*exported {
@@ -1039,7 +2124,23 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
         type_args: &Option<Box<TsTypeParamInstantiation>>,
     ) -> Res<JsonSchema> {
         match &q.left {
-            TsEntityName::TsQualifiedName(_) => {}
+            TsEntityName::TsQualifiedName(left) => {
+                // members of enums declared in namespaces
+                if let Ok((exported, _, _)) = self.__convert_ts_type_qual_inner(left) {
+                    if let SymbolExport::TsEnumDecl {
+                        decl,
+                        span,
+                        original_file,
+                    } = exported.as_ref()
+                    {
+                        let store_current_file = self.current_file.clone();
+                        self.current_file = original_file.clone();
+                        let out = self.enum_member_value(decl, &q.right.sym, span);
+                        self.current_file = store_current_file;
+                        return out;
+                    }
+                }
+            }
             TsEntityName::Ident(i) => {
                 let ns = TypeResolver::new(self.files, &self.current_file)
                     .resolve_namespace_symbol(i, true);
@@ -1060,50 +2161,36 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
                                     original_file,
                                 } = symbol_export.as_ref()
                                 {
-                                    let found = decl.members.iter().find(|it| match &it.id {
-                                        TsEnumMemberId::Ident(i2) => i2.sym == q.right.sym,
-                                        TsEnumMemberId::Str(_) => unreachable!(),
-                                    });
-                                    return match found {
-                                        Some(item) => match &item.init {
-                                            Some(init) => {
-                                                let expr_ty = {
-                                                    let store_current_file =
-                                                        self.current_file.clone();
-                                                    self.current_file = original_file.clone();
-                                                    let out = self.typeof_expr(init, true);
-                                                    self.current_file = store_current_file;
-                                                    out?
-                                                };
-                                                Ok(expr_ty)
-                                            }
-                                            None => self.cannot_serialize_error(
-                                                span,
-                                                DiagnosticInfoMessage::EnumMemberNoInit,
-                                            ),
-                                        },
-                                        None => self.cannot_serialize_error(
-                                            span,
-                                            DiagnosticInfoMessage::EnumMemberNoInit,
-                                        ),
-                                    };
+                                    let store_current_file = self.current_file.clone();
+                                    self.current_file = original_file.clone();
+                                    let out = self.enum_member_value(decl, &q.right.sym, span);
+                                    self.current_file = store_current_file;
+                                    return out;
                                 }
                             }
                         }
-                        ImportReference::Star { .. } => {}
-                        ImportReference::Default { .. } => {}
+                        ImportReference::Star { .. }
+                        | ImportReference::Default { .. }
+                        | ImportReference::Namespace { .. } => {}
                     }
                 }
             }
         }
 
-        let found = self.components.get(&(q.right.sym.to_string()));
-        if let Some(_found) = found {
-            return Ok(JsonSchema::Ref(q.right.sym.to_string()));
-        }
-
         let (exported, from_file, name) = self.__convert_ts_type_qual_inner(q)?;
-
+        let name = self.type_export_name(&exported).unwrap_or(name);
+        if type_args.is_none() && self.components.contains_key(&name) {
+            return Ok(JsonSchema::Ref(name));
+        }
+        self.convert_qualified_type_export(exported, from_file, name, type_args)
+    }
+    fn convert_qualified_type_export(
+        &mut self,
+        exported: Rc<SymbolExport>,
+        from_file: Rc<ImportReference>,
+        name: String,
+        type_args: &Option<Box<TsTypeParamInstantiation>>,
+    ) -> Res<JsonSchema> {
         let type_args_schema = type_args
             .as_ref()
             .map(|it| {
@@ -1137,21 +2224,7 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
                     .get_existing_file(&self.current_file)
                     .and_then(|current_file| current_file.locals.enums.get(k).cloned());
                 if let Some(local) = local_enum {
-                    let found = local.members.iter().find(|it| match &it.id {
-                        TsEnumMemberId::Ident(i2) => i2.sym == q.right.sym,
-                        TsEnumMemberId::Str(_) => unreachable!(),
-                    });
-
-                    return match &found.as_ref().and_then(|it| it.init.clone()) {
-                        Some(init) => {
-                            let expr_ty = self.typeof_expr(init, true)?;
-                            Ok(expr_ty)
-                        }
-                        None => self.cannot_serialize_error(
-                            &q.right.span,
-                            DiagnosticInfoMessage::EnumMemberNoInit,
-                        ),
-                    };
+                    return self.enum_member_value(&local, &q.right.sym, &q.right.span);
                 }
             }
         };
@@ -1242,47 +2315,35 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
                         }
                     }
                 }
-                Ok(JsonSchema::Tuple {
-                    prefix_items,
-                    items: None,
-                })
+                Ok(JsonSchema::tuple(prefix_items, None))
             }
             Expr::Object(lit) => {
                 let mut vs = BTreeMap::new();
 
                 for it in &lit.props {
                     match it {
-                        PropOrSpread::Spread(_) => {
-                            return self.error(
-                                &it.span(),
-                                DiagnosticInfoMessage::TypeofObjectUnsupportedSpread,
-                            )
+                        PropOrSpread::Spread(spread) => {
+                            match self.typeof_expr(&spread.expr, as_const)? {
+                                JsonSchema::Object { vs: spread_vs, .. } => vs.extend(spread_vs),
+                                _ => {
+                                    return self.error(
+                                        &it.span(),
+                                        DiagnosticInfoMessage::TypeofObjectUnsupportedSpread,
+                                    )
+                                }
+                            }
                         }
                         PropOrSpread::Prop(p) => match p.as_ref() {
                             Prop::KeyValue(p) => {
-                                let key: String = match &p.key {
-                                    PropName::Ident(id) => id.sym.to_string(),
-                                    PropName::Str(st) => st.value.to_string(),
-                                    PropName::Num(_) => {
-                                        return self.error(
-                                            &p.key.span(),
-                                            DiagnosticInfoMessage::TypeofObjectUnsupportedPropNum,
-                                        )
-                                    }
-                                    PropName::Computed(_) => return self.error(
-                                        &p.key.span(),
-                                        DiagnosticInfoMessage::TypeofObjectUnsupportedPropComputed,
-                                    ),
-                                    PropName::BigInt(_) => return self.error(
-                                        &p.key.span(),
-                                        DiagnosticInfoMessage::TypeofObjectUnsupportedPropBigInt,
-                                    ),
-                                };
+                                let key = self.typeof_prop_name(&p.key)?;
                                 let value = self.typeof_expr(&p.value, as_const)?;
                                 vs.insert(key, value.required());
                             }
-                            Prop::Shorthand(_)
-                            | Prop::Assign(_)
+                            Prop::Shorthand(i) => {
+                                let value = self.typeof_expr(&Expr::Ident(i.clone()), as_const)?;
+                                vs.insert(i.sym.to_string(), value.required());
+                            }
+                            Prop::Assign(_)
                             | Prop::Getter(_)
                             | Prop::Setter(_)
                             | Prop::Method(_) => {
@@ -1295,8 +2356,55 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
                     }
                 }
 
-                Ok(JsonSchema::Object { vs, rest: None })
+                Ok(JsonSchema::Object {
+                    vs,
+                    rest: None,
+                    pattern_vs: BTreeMap::new(),
+                })
+            }
+            Expr::Tpl(tpl) => {
+                if !as_const {
+                    return Ok(JsonSchema::String);
+                }
+                let mut acc: Vec<TplLitTypeItem> = vec![];
+                for (idx, quasis) in tpl.quasis.iter().enumerate() {
+                    let cooked = quasis.cooked.as_ref().unwrap_or(&quasis.raw);
+                    acc.push(TplLitTypeItem::Quasis(cooked.to_string()));
+                    if let Some(expr) = tpl.exprs.get(idx) {
+                        let item = match self.typeof_expr(expr, true)? {
+                            JsonSchema::Const(JsonSchemaConst::Bool(b)) => {
+                                TplLitTypeItem::StringConst(b.to_string())
+                            }
+                            JsonSchema::Const(JsonSchemaConst::Null) | JsonSchema::Null => {
+                                TplLitTypeItem::StringConst("null".to_string())
+                            }
+                            JsonSchema::Const(c) => match Self::const_to_key(&c) {
+                                Some(str) => TplLitTypeItem::StringConst(str),
+                                None => self
+                                    .json_schema_to_tpl_lit(&expr.span(), &JsonSchema::Const(c))?,
+                            },
+                            ty => self.json_schema_to_tpl_lit(&expr.span(), &ty)?,
+                        };
+                        acc.push(item);
+                    }
+                }
+                match TplLitTypeItem::const_vec(&acc) {
+                    Some(str) => Ok(JsonSchema::Const(JsonSchemaConst::String(str))),
+                    None => Ok(JsonSchema::TplLitType(acc)),
+                }
             }
+            Expr::Paren(p) => self.typeof_expr(&p.expr, as_const),
+            Expr::Unary(u) if u.op == UnaryOp::Minus => match u.arg.as_ref() {
+                Expr::Lit(Lit::Num(n)) if as_const => {
+                    Ok(JsonSchema::Const(JsonSchemaConst::parse_f64(-n.value)))
+                }
+                Expr::Lit(Lit::Num(_)) => Ok(JsonSchema::Number),
+                _ => self.error(&e.span(), DiagnosticInfoMessage::CannotConvertExprToSchema),
+            },
+            Expr::TsSatisfies(s) => self.typeof_expr(&s.expr, as_const),
+            Expr::TsNonNull(n) => self.typeof_expr(&n.expr, as_const),
+            Expr::TsAs(a) => self.convert_ts_type(&a.type_ann),
+            Expr::Call(c) => self.typeof_call(c),
             Expr::Ident(i) => {
                 let s = TypeResolver::new(self.files, &self.current_file).resolve_local_value(i)?;
                 self.typeof_symbol(s, &i.span)
@@ -1305,7 +2413,7 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
             Expr::Member(m) => {
                 let mut ctx = SemTypeContext::new();
                 let obj = self.typeof_expr(&m.obj, as_const)?;
-                if let JsonSchema::Object { vs, rest: _ } = &obj {
+                if let JsonSchema::Object { vs, .. } = &obj {
                     // try to do it syntatically to preserve aliases
                     if let Some(key) = match &m.prop {
                         MemberProp::Ident(i) => Some(i.sym.to_string()),
@@ -1356,25 +2464,145 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
         }
     }
 
+    // a const initialized with a primitive literal keeps its literal type,
+    // `let` and `var` are widened
+    fn is_literal_const(e: &Expr, kind: Option<VarDeclKind>) -> bool {
+        kind == Some(VarDeclKind::Const) && Self::is_literal_expr(e)
+    }
+
+    fn is_literal_expr(e: &Expr) -> bool {
+        match e {
+            Expr::Lit(Lit::Str(_) | Lit::Num(_) | Lit::Bool(_) | Lit::Null(_)) => true,
+            Expr::Tpl(tpl) => tpl.exprs.is_empty(),
+            Expr::Unary(u) => {
+                u.op == UnaryOp::Minus && matches!(u.arg.as_ref(), Expr::Lit(Lit::Num(_)))
+            }
+            Expr::Paren(p) => Self::is_literal_expr(&p.expr),
+            _ => false,
+        }
+    }
+
+    fn const_to_key(c: &JsonSchemaConst) -> Option<String> {
+        match c {
+            JsonSchemaConst::String(s) => Some(s.clone()),
+            JsonSchemaConst::Number(n) => Some(n.to_serde().to_string()),
+            JsonSchemaConst::Null | JsonSchemaConst::Bool(_) => None,
+        }
+    }
+
+    fn typeof_prop_name(&mut self, key: &PropName) -> Res<String> {
+        match key {
+            PropName::Ident(id) => Ok(id.sym.to_string()),
+            PropName::Str(st) => Ok(st.value.to_string()),
+            PropName::Num(n) => match JsonSchemaConst::parse_f64(n.value) {
+                JsonSchemaConst::Number(n) => Ok(n.to_serde().to_string()),
+                _ => self.error(
+                    &key.span(),
+                    DiagnosticInfoMessage::TypeofObjectUnsupportedPropNum,
+                ),
+            },
+            PropName::Computed(c) => match self.typeof_expr(&c.expr, true)? {
+                JsonSchema::Const(c) => match Self::const_to_key(&c) {
+                    Some(key) => Ok(key),
+                    None => self.error(
+                        &key.span(),
+                        DiagnosticInfoMessage::TypeofObjectUnsupportedPropComputed,
+                    ),
+                },
+                _ => self.error(
+                    &key.span(),
+                    DiagnosticInfoMessage::TypeofObjectUnsupportedPropComputed,
+                ),
+            },
+            PropName::BigInt(_) => self.error(
+                &key.span(),
+                DiagnosticInfoMessage::TypeofObjectUnsupportedPropBigInt,
+            ),
+        }
+    }
+
+    fn fn_expr_return_type(e: &Expr) -> Option<&TsType> {
+        match e {
+            Expr::Fn(f) => f
+                .function
+                .return_type
+                .as_ref()
+                .map(|it| it.type_ann.as_ref()),
+            Expr::Arrow(a) => a.return_type.as_ref().map(|it| it.type_ann.as_ref()),
+            Expr::Paren(p) => Self::fn_expr_return_type(&p.expr),
+            _ => None,
+        }
+    }
+
+    fn typeof_fn_expr_call(&mut self, e: &Expr, span: &Span) -> Res<JsonSchema> {
+        match e {
+            Expr::Fn(_) | Expr::Arrow(_) | Expr::Paren(_) => match Self::fn_expr_return_type(e) {
+                Some(ty) => self.convert_ts_type(ty),
+                None => self.error(span, DiagnosticInfoMessage::TypeofCallWithoutReturnType),
+            },
+            _ => self.error(span, DiagnosticInfoMessage::TypeofCallOfNonFunction),
+        }
+    }
+
+    fn typeof_call(&mut self, c: &CallExpr) -> Res<JsonSchema> {
+        let callee = match &c.callee {
+            Callee::Expr(e) => e.as_ref(),
+            Callee::Super(_) | Callee::Import(_) => {
+                return self.error(&c.span, DiagnosticInfoMessage::TypeofCallOfNonFunction)
+            }
+        };
+        match callee {
+            Expr::Ident(i) => {
+                let s = TypeResolver::new(self.files, &self.current_file).resolve_local_value(i)?;
+                match s {
+                    ResolvedLocalSymbol::Expr(e, _) => self.typeof_fn_expr_call(&e, &c.span),
+                    ResolvedLocalSymbol::NamedImport {
+                        exported,
+                        from_file,
+                    } => match exported.as_ref() {
+                        SymbolExport::ValueExpr { expr, .. } => {
+                            let old_file = self.current_file.clone();
+                            self.current_file = from_file.file_name().clone();
+                            let ty = self.typeof_fn_expr_call(expr, &c.span);
+                            self.current_file = old_file;
+                            ty
+                        }
+                        _ => self.error(&c.span, DiagnosticInfoMessage::TypeofCallOfNonFunction),
+                    },
+                    _ => self.error(&c.span, DiagnosticInfoMessage::TypeofCallOfNonFunction),
+                }
+            }
+            _ => self.typeof_fn_expr_call(callee, &c.span),
+        }
+    }
+
     fn typeof_symbol_export(
         &mut self,
         exported: Rc<SymbolExport>,
         from_file: Rc<ImportReference>,
     ) -> Res<JsonSchema> {
         let old_file = self.current_file.clone();
-        self.current_file = from_file.file_name().clone();
+        // re-exported symbols are declared in another file than the one they are imported from
+        self.current_file = match exported.as_ref() {
+            SymbolExport::TsEnumDecl { original_file, .. }
+            | SymbolExport::ValueExpr { original_file, .. }
+            | SymbolExport::ExprDecl { original_file, .. } => original_file.clone(),
+            _ => from_file.file_name().clone(),
+        };
         let ty = match exported.as_ref() {
-            SymbolExport::TsEnumDecl { .. }
-            | SymbolExport::TsType { .. }
-            | SymbolExport::TsInterfaceDecl { .. } => self.error(
+            SymbolExport::TsEnumDecl { decl, .. } => self.typeof_enum_decl(decl),
+            SymbolExport::TsType { .. } | SymbolExport::TsInterfaceDecl { .. } => self.error(
                 &exported.span(),
                 DiagnosticInfoMessage::FoundTypeExpectedValueInSymbolExport,
             ),
-            SymbolExport::ValueExpr { expr, .. } => self.typeof_expr(expr, false),
-            SymbolExport::StarOfOtherFile { .. } => self.error(
-                &exported.span(),
-                DiagnosticInfoMessage::TypeOfStarNotSupported,
-            ),
+            SymbolExport::ValueExpr { expr, kind, .. } => {
+                self.typeof_expr(expr, Self::is_literal_const(expr, *kind))
+            }
+            SymbolExport::StarOfOtherFile { reference, .. } => {
+                let mut acc = vec![];
+                self.collect_reference_value_exports(reference, &mut acc)
+                    .map(|_| JsonSchema::object(acc, None))
+            }
             SymbolExport::SomethingOfOtherFile { .. } => self.error(
                 &exported.span(),
                 DiagnosticInfoMessage::TypeOfSomethingOfOtherFileNotSupported,
@@ -1393,73 +2621,98 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
         let store_current_file = self.current_file.clone();
         self.current_file = file_name.clone();
         // We must not use "?" if doing current file update, otherwise we get lost on errors
-        let out = self.collect_value_exports_no_curr_file_update____(file_name, acc);
+        let out = match self.files.get_or_fetch_file(file_name) {
+            Some(pm) => self.collect_value_exports_no_curr_file_update____(&pm.symbol_exports, acc),
+            None => Ok(()),
+        };
         self.current_file = store_current_file;
         out
     }
+    fn collect_reference_value_exports(
+        &mut self,
+        reference: &ImportReference,
+        acc: &mut Vec<(String, Optionality<JsonSchema>)>,
+    ) -> Res<()> {
+        match reference {
+            ImportReference::Namespace {
+                file_name, exports, ..
+            } => {
+                let store_current_file = self.current_file.clone();
+                self.current_file = file_name.clone();
+                let out = self.collect_value_exports_no_curr_file_update____(exports, acc);
+                self.current_file = store_current_file;
+                out
+            }
+            ImportReference::Named { .. }
+            | ImportReference::Star { .. }
+            | ImportReference::Default { .. } => {
+                self.collect_value_exports(reference.file_name(), acc)
+            }
+        }
+    }
     fn collect_value_exports_no_curr_file_update____(
         &mut self,
-        file_name: &BffFileName,
+        symbol_exports: &SymbolsExportsModule,
         acc: &mut Vec<(String, Optionality<JsonSchema>)>,
     ) -> Res<()> {
-        let file = self.files.get_or_fetch_file(file_name);
-        if let Some(pm) = file {
-            for (k, v) in &pm.symbol_exports.named_values {
-                match v.as_ref() {
-                    SymbolExport::TsEnumDecl { .. }
-                    | SymbolExport::TsType { .. }
-                    | SymbolExport::TsInterfaceDecl { .. } => {}
-                    SymbolExport::ValueExpr { expr, name: _, .. } => {
-                        let ty = self.typeof_expr(expr, false)?;
-                        acc.push((k.to_string(), ty.required()));
+        for (k, v) in &symbol_exports.named_values {
+            match v.as_ref() {
+                SymbolExport::TsType { .. } | SymbolExport::TsInterfaceDecl { .. } => {}
+                SymbolExport::TsEnumDecl { decl, .. } => {
+                    let ty = self.typeof_enum_decl(decl)?;
+                    acc.push((k.to_string(), ty.required()));
+                }
+                // functions are not values that can be validated
+                SymbolExport::ValueExpr { expr, .. }
+                    if matches!(expr.as_ref(), Expr::Fn(_) | Expr::Arrow(_)) => {}
+                SymbolExport::ValueExpr { expr, name: _, .. } => {
+                    let ty = self.typeof_expr(expr, false)?;
+                    acc.push((k.to_string(), ty.required()));
+                }
+                SymbolExport::StarOfOtherFile { reference, .. } => match reference.as_ref() {
+                    ImportReference::Named { .. } => {
+                        return self.error(&v.span(), DiagnosticInfoMessage::CannotUseNamedAsStar)
                     }
-                    SymbolExport::StarOfOtherFile { reference, .. } => match reference.as_ref() {
-                        ImportReference::Named { .. } => {
-                            return self
-                                .error(&v.span(), DiagnosticInfoMessage::CannotUseNamedAsStar)
-                        }
-                        ImportReference::Star { file_name, .. } => {
-                            let mut acc2 = vec![];
-                            self.collect_value_exports(file_name, &mut acc2)?;
-                            let v = JsonSchema::object(acc2, None);
-                            acc.push((k.to_string(), v.required()));
-                        }
-                        ImportReference::Default { .. } => {
-                            return self
-                                .error(&v.span(), DiagnosticInfoMessage::CannotUseDefaultAsStar)
-                        }
-                    },
-                    SymbolExport::SomethingOfOtherFile {
-                        file,
-                        something,
-                        span,
-                    } => {
+                    ImportReference::Star { .. } | ImportReference::Namespace { .. } => {
                         let mut acc2 = vec![];
-                        self.collect_value_exports(file, &mut acc2)?;
-                        let found = acc2.iter().find(|(k, _)| k == &something.to_string());
-                        match found {
-                            Some((_, found)) => {
-                                acc.push((k.to_string(), found.clone()));
-                            }
-                            None => {
-                                return self.error(
-                                    span,
-                                    DiagnosticInfoMessage::CouldNotFindSomethingOfOtherFile(
-                                        something.to_string(),
-                                    ),
-                                )
-                            }
-                        }
+                        self.collect_reference_value_exports(reference, &mut acc2)?;
+                        let v = JsonSchema::object(acc2, None);
+                        acc.push((k.to_string(), v.required()));
+                    }
+                    ImportReference::Default { .. } => {
+                        return self.error(&v.span(), DiagnosticInfoMessage::CannotUseDefaultAsStar)
                     }
-                    SymbolExport::ExprDecl { name, ty, .. } => {
-                        acc.push((name.to_string(), self.convert_ts_type(ty)?.required()));
+                },
+                SymbolExport::SomethingOfOtherFile {
+                    file,
+                    something,
+                    span,
+                } => {
+                    let mut acc2 = vec![];
+                    self.collect_value_exports(file, &mut acc2)?;
+                    let found = acc2.iter().find(|(k, _)| k == &something.to_string());
+                    match found {
+                        Some((_, found)) => {
+                            acc.push((k.to_string(), found.clone()));
+                        }
+                        None => {
+                            return self.error(
+                                span,
+                                DiagnosticInfoMessage::CouldNotFindSomethingOfOtherFile(
+                                    something.to_string(),
+                                ),
+                            )
+                        }
                     }
                 }
-            }
-            for f in &pm.symbol_exports.extends {
-                self.collect_value_exports(f, acc)?;
+                SymbolExport::ExprDecl { name, ty, .. } => {
+                    acc.push((name.to_string(), self.convert_ts_type(ty)?.required()));
+                }
             }
         }
+        for f in &symbol_exports.extends {
+            self.collect_value_exports(f, acc)?;
+        }
 
         Ok(())
     }
@@ -1469,27 +2722,27 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
             ResolvedLocalSymbol::TsType(_, _) | ResolvedLocalSymbol::TsInterfaceDecl(_) => {
                 self.error(span, DiagnosticInfoMessage::FoundTypeExpectedValue)
             }
-            ResolvedLocalSymbol::Expr(e) => self.typeof_expr(&e, false),
+            ResolvedLocalSymbol::Expr(e, kind) => {
+                self.typeof_expr(&e, Self::is_literal_const(&e, kind))
+            }
             ResolvedLocalSymbol::NamedImport {
                 exported,
                 from_file,
             } => self.typeof_symbol_export(exported, from_file),
             ResolvedLocalSymbol::SymbolExportDefault(e) => self.typeof_expr(&e.symbol_export, true),
-            ResolvedLocalSymbol::Star(file_name) => {
+            ResolvedLocalSymbol::Star(reference) => {
                 let mut acc = vec![];
-                self.collect_value_exports(&file_name, &mut acc)?;
+                self.collect_reference_value_exports(&reference, &mut acc)?;
                 Ok(JsonSchema::object(acc, None))
             }
-            ResolvedLocalSymbol::TsEnumDecl(_) => {
-                self.error(span, DiagnosticInfoMessage::TypeofTsEnumNotSupported)
-            }
+            ResolvedLocalSymbol::TsEnumDecl(decl) => self.typeof_enum_decl(&decl),
             ResolvedLocalSymbol::TsBuiltin(_) => {
                 self.error(span, DiagnosticInfoMessage::TypeOfTsBuiltinNotSupported)
             }
         }
     }
     fn get_kv_from_schema(&mut self, schema: JsonSchema, key: &str, span: Span) -> Res<JsonSchema> {
-        if let JsonSchema::Object { vs: kvs, rest: _ } = schema {
+        if let JsonSchema::Object { vs: kvs, .. } = schema {
             if let Some(Optionality::Required(v)) = kvs.get(key) {
                 return Ok(v.clone());
             }
@@ -1501,8 +2754,8 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
     }
     fn convert_type_query_qualified(&mut self, q: &TsQualifiedName) -> Res<JsonSchema> {
         match &q.left {
-            TsEntityName::TsQualifiedName(q) => {
-                let t = self.convert_type_query_qualified(q)?;
+            TsEntityName::TsQualifiedName(left) => {
+                let t = self.convert_type_query_qualified(left)?;
                 self.get_kv_from_schema(t, q.right.sym.as_ref(), q.right.span())
             }
             TsEntityName::Ident(id) => {
@@ -1514,14 +2767,114 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
         }
     }
 
+    fn resolve_import_type_file(&mut self, imp: &TsImportType) -> Res<BffFileName> {
+        let file_name = self
+            .files
+            .get_or_fetch_file(&self.current_file)
+            .and_then(|module| module.import_types.get(&imp.arg.value).cloned());
+        match file_name {
+            Some(file_name) => Ok(file_name),
+            None => self.error(
+                &imp.arg.span,
+                DiagnosticInfoMessage::CannotResolveImportType(imp.arg.value.to_string()),
+            ),
+        }
+    }
+    fn import_type_qualifier_export(
+        &mut self,
+        module: &Rc<ImportReference>,
+        qualifier: &TsEntityName,
+    ) -> Res<(Rc<SymbolExport>, Rc<ImportReference>, String)> {
+        match qualifier {
+            TsEntityName::Ident(i) => self.get_qualified_type_from_file(module, &i.sym, &i.span),
+            TsEntityName::TsQualifiedName(q) => {
+                let (exported, _from_file, _name) =
+                    self.import_type_qualifier_export(module, &q.left)?;
+                self.recursively_get_qualified_type_export(exported, &q.right)
+            }
+        }
+    }
+    fn convert_ts_import_type(&mut self, imp: &TsImportType) -> Res<JsonSchema> {
+        let qualifier = match &imp.qualifier {
+            Some(qualifier) => qualifier,
+            None => {
+                return self.cannot_serialize_error(
+                    &imp.span,
+                    DiagnosticInfoMessage::TsImportTypeNonSerializableToJsonSchema,
+                )
+            }
+        };
+        let file_name = self.resolve_import_type_file(imp)?;
+        let module = Rc::new(ImportReference::Star {
+            file_name,
+            span: imp.span,
+        });
+        let (exported, from_file, name) = self.import_type_qualifier_export(&module, qualifier)?;
+        let name = self.type_export_name(&exported).unwrap_or(name);
+        if imp.type_args.is_none() && self.components.contains_key(&name) {
+            return Ok(JsonSchema::Ref(name));
+        }
+        self.convert_qualified_type_export(exported, from_file, name, &imp.type_args)
+    }
+    fn typeof_import_qualifier(
+        &mut self,
+        file_name: &BffFileName,
+        qualifier: &TsEntityName,
+    ) -> Res<JsonSchema> {
+        match qualifier {
+            TsEntityName::Ident(i) => {
+                let exported = self
+                    .files
+                    .get_or_fetch_file(file_name)
+                    .and_then(|module| module.symbol_exports.get_value(&i.sym, self.files));
+                match exported {
+                    Some(exported)
+                        if matches!(
+                            exported.as_ref(),
+                            SymbolExport::ValueExpr { .. }
+                                | SymbolExport::ExprDecl { .. }
+                                | SymbolExport::TsEnumDecl { .. }
+                        ) =>
+                    {
+                        let module = Rc::new(ImportReference::Star {
+                            file_name: file_name.clone(),
+                            span: i.span,
+                        });
+                        self.typeof_symbol_export(exported, module)
+                    }
+                    _ => {
+                        // re-exports are resolved by collecting the whole module
+                        let mut acc = vec![];
+                        self.collect_value_exports(file_name, &mut acc)?;
+                        let module = JsonSchema::object(acc, None);
+                        self.get_kv_from_schema(module, i.sym.as_ref(), i.span)
+                    }
+                }
+            }
+            TsEntityName::TsQualifiedName(q) => {
+                let t = self.typeof_import_qualifier(file_name, &q.left)?;
+                self.get_kv_from_schema(t, q.right.sym.as_ref(), q.right.span)
+            }
+        }
+    }
+    fn typeof_import_type(&mut self, imp: &TsImportType) -> Res<JsonSchema> {
+        let file_name = self.resolve_import_type_file(imp)?;
+        match &imp.qualifier {
+            Some(qualifier) => self.typeof_import_qualifier(&file_name, qualifier),
+            None => {
+                let mut acc = vec![];
+                self.collect_value_exports(&file_name, &mut acc)?;
+                Ok(JsonSchema::object(acc, None))
+            }
+        }
+    }
+
     pub fn convert_type_query(&mut self, q: &TsTypeQuery) -> Res<JsonSchema> {
         if q.type_args.is_some() {
             return self.error(&q.span, DiagnosticInfoMessage::TypeQueryArgsNotSupported);
         }
         match q.expr_name {
-            TsTypeQueryExpr::Import(ref imp) => {
-                self.error(&imp.span, DiagnosticInfoMessage::TypeofImportNotSupported)
-            }
+            TsTypeQueryExpr::Import(ref imp) => self.typeof_import_type(imp),
             TsTypeQueryExpr::TsEntityName(ref n) => match n {
                 TsEntityName::TsQualifiedName(q) => self.convert_type_query_qualified(q),
                 TsEntityName::Ident(n) => {
@@ -1587,7 +2940,7 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
                     return self.convert_indexed_access_syntatically(&v.schema, index);
                 }
             }
-            (JsonSchema::Object { vs, rest: _ }, JsonSchema::Const(JsonSchemaConst::String(s))) => {
+            (JsonSchema::Object { vs, .. }, JsonSchema::Const(JsonSchemaConst::String(s))) => {
                 let v = vs.get(s);
                 if let Some(Optionality::Required(v)) = v {
                     return Ok(Some(v.clone()));
@@ -1664,6 +3017,142 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
         }
     }
 
+    fn mapped_type_source_object(
+        &mut self,
+        constraint: &TsType,
+    ) -> Res<Option<BTreeMap<String, Optionality<JsonSchema>>>> {
+        // `[K in keyof T]` is homomorphic, it keeps the modifiers of `T`
+        let mut source = match constraint {
+            TsType::TsTypeOperator(TsTypeOperator {
+                op: TsTypeOperatorOp::KeyOf,
+                type_ann,
+                ..
+            }) => self.convert_ts_type(type_ann)?,
+            _ => return Ok(None),
+        };
+        loop {
+            match source {
+                JsonSchema::Object { vs, .. } => return Ok(Some(vs)),
+                JsonSchema::Ref(r) => match self.components.get(&r).and_then(|it| it.clone()) {
+                    Some(v) => source = v.schema,
+                    None => return Ok(None),
+                },
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    // `T[K]` in `{ [K in keyof T]: T[K] }`, the only template that reads the source property
+    fn is_source_indexed_access(constraint: &TsType, param: &str, type_ann: &TsType) -> bool {
+        let TsType::TsTypeOperator(TsTypeOperator {
+            op: TsTypeOperatorOp::KeyOf,
+            type_ann: source,
+            ..
+        }) = constraint
+        else {
+            return false;
+        };
+        match type_ann {
+            TsType::TsIndexedAccessType(TsIndexedAccessType {
+                obj_type,
+                index_type,
+                ..
+            }) => {
+                let index_is_param = matches!(
+                    &**index_type,
+                    TsType::TsTypeRef(TsTypeRef {
+                        type_name: TsEntityName::Ident(i),
+                        type_params: None,
+                        ..
+                    }) if i.sym == *param
+                );
+                index_is_param && obj_type.eq_ignore_span(source)
+            }
+            TsType::TsParenthesizedType(TsParenthesizedType { type_ann, .. }) => {
+                Self::is_source_indexed_access(constraint, param, type_ann)
+            }
+            _ => false,
+        }
+    }
+
+    fn strip_optional_null(value: JsonSchema, source: &JsonSchema) -> JsonSchema {
+        // indexing an optional property adds `undefined`, which is already covered by the modifier
+        let source_is_nullable = match source {
+            JsonSchema::Null => true,
+            JsonSchema::AnyOf(vs) => vs.contains(&JsonSchema::Null),
+            _ => false,
+        };
+        match value {
+            JsonSchema::AnyOf(vs) if !source_is_nullable && vs.contains(&JsonSchema::Null) => {
+                JsonSchema::any_of(
+                    vs.into_iter()
+                        .filter(|it| it != &JsonSchema::Null)
+                        .collect(),
+                )
+            }
+            _ => value,
+        }
+    }
+
+    fn convert_mapped_type_key(&mut self, span: &Span, key: &TsType) -> Res<Vec<String>> {
+        match key {
+            TsType::TsKeywordType(TsKeywordType {
+                kind: TsKeywordTypeKind::TsNeverKeyword,
+                ..
+            }) => Ok(vec![]),
+            TsType::TsParenthesizedType(TsParenthesizedType { type_ann, .. }) => {
+                self.convert_mapped_type_key(span, type_ann)
+            }
+            TsType::TsConditionalType(t) => {
+                let branch = self.conditional_type_branch(t)?;
+                self.convert_mapped_type_key(span, branch)
+            }
+            TsType::TsUnionOrIntersectionType(TsUnionOrIntersectionType::TsUnionType(
+                TsUnionType { types, .. },
+            )) => {
+                let mut acc = vec![];
+                for it in types {
+                    acc.extend(self.convert_mapped_type_key(span, it)?);
+                }
+                Ok(acc)
+            }
+            _ => {
+                let schema = match self.convert_ts_type(key) {
+                    Ok(schema) => schema,
+                    // `Exclude<K, ...>` and friends evaluate to `never` for filtered keys
+                    Err(e)
+                        if matches!(
+                            e.cause.message,
+                            DiagnosticInfoMessage::NeverCannotBeConvertedToJsonSchema
+                        ) =>
+                    {
+                        return Ok(vec![])
+                    }
+                    Err(e) => return Err(e),
+                };
+                let mut acc = vec![];
+                for v in self.extract_union(schema)? {
+                    match v {
+                        JsonSchema::Const(JsonSchemaConst::String(s)) => acc.push(s),
+                        JsonSchema::StNever => {}
+                        JsonSchema::TplLitType(items) => match TplLitTypeItem::const_vec(&items) {
+                            Some(s) => acc.push(s),
+                            None => {
+                                return self
+                                    .error(span, DiagnosticInfoMessage::NonStringKeyInMappedType)
+                            }
+                        },
+                        _ => {
+                            return self
+                                .error(span, DiagnosticInfoMessage::NonStringKeyInMappedType)
+                        }
+                    }
+                }
+                Ok(acc)
+            }
+        }
+    }
+
     fn convert_mapped_type(&mut self, k: &TsMappedType) -> Res<JsonSchema> {
         let name = k.type_param.name.sym.to_string();
         let constraint = match k.type_param.constraint {
@@ -1675,6 +3164,7 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
                 )
             }
         };
+        let source = self.mapped_type_source_object(constraint)?;
         let constraint_schema = self.convert_ts_type(constraint)?;
         let values = self.extract_union(constraint_schema)?;
 
@@ -1696,27 +3186,54 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
             }
         };
 
+        let reads_source = Self::is_source_indexed_access(constraint, &name, type_ann);
+
+        // `readonly` modifiers do not change the runtime shape, so they are ignored
         let mut vs = vec![];
         for key in string_keys.into_iter() {
             self.type_param_stack.push(BTreeMap::from_iter(vec![(
                 name.clone(),
                 JsonSchema::Const(JsonSchemaConst::String(key.clone())),
             )]));
-            let ty = self.convert_ts_type(type_ann)?;
+            let new_keys = match &k.name_type {
+                Some(name_type) => self.convert_mapped_type_key(&k.span, name_type),
+                None => Ok(vec![key.clone()]),
+            };
+            let ty = new_keys.and_then(|new_keys| {
+                if new_keys.is_empty() {
+                    Ok(None)
+                } else {
+                    self.convert_ts_type(type_ann)
+                        .map(|ty| Some((new_keys, ty)))
+                }
+            });
             self.type_param_stack.pop();
 
+            let (new_keys, ty) = match ty? {
+                Some(it) => it,
+                None => continue,
+            };
+
+            let source_prop = source.as_ref().and_then(|it| it.get(&key));
+            let ty = match source_prop {
+                Some(Optionality::Optional(source_ty) | Optionality::Default(source_ty, _))
+                    if reads_source =>
+                {
+                    Self::strip_optional_null(ty, source_ty)
+                }
+                _ => ty,
+            };
             let ty = match k.optional {
-                Some(opt) => match opt {
-                    TruePlusMinus::True => Optionality::Optional(ty),
-                    TruePlusMinus::Plus => Optionality::Required(ty),
-                    TruePlusMinus::Minus => {
-                        return self
-                            .error(&k.span, DiagnosticInfoMessage::MappedTypeMinusNotSupported)
-                    }
+                Some(TruePlusMinus::True | TruePlusMinus::Plus) => Optionality::Optional(ty),
+                Some(TruePlusMinus::Minus) => Optionality::Required(ty),
+                None => match source_prop {
+                    Some(it) if !it.is_required() => Optionality::Optional(ty),
+                    _ => Optionality::Required(ty),
                 },
-                None => Optionality::Required(ty),
             };
-            vs.push((key, ty));
+            for new_key in new_keys {
+                vs.push((new_key, ty.clone()));
+            }
         }
 
         Ok(JsonSchema::object(vs, None))
@@ -1808,13 +3325,7 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
                 TsEntityName::Ident(i) => self.convert_ts_type_ident(i, type_params),
                 TsEntityName::TsQualifiedName(q) => self.convert_ts_type_qual(q, type_params),
             },
-            TsType::TsTypeLit(TsTypeLit { members, .. }) => Ok(JsonSchema::object(
-                members
-                    .iter()
-                    .map(|prop| self.convert_ts_type_element(prop))
-                    .collect::<Res<_>>()?,
-                None,
-            )),
+            TsType::TsTypeLit(TsTypeLit { members, .. }) => self.convert_ts_type_elements(members),
             TsType::TsArrayType(TsArrayType { elem_type, .. }) => {
                 Ok(JsonSchema::Array(self.convert_ts_type(elem_type)?.into()))
             }
@@ -1829,29 +3340,7 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
                     ..
                 }) => self.intersection(types, span),
             },
-            TsType::TsTupleType(TsTupleType { elem_types, .. }) => {
-                let mut prefix_items = vec![];
-                let mut items = None;
-                for it in elem_types {
-                    if let TsType::TsRestType(TsRestType { type_ann, .. }) = &*it.ty {
-                        if items.is_some() {
-                            return self.cannot_serialize_error(
-                                &it.span,
-                                DiagnosticInfoMessage::DuplicatedRestNonSerializableToJsonSchema,
-                            );
-                        }
-                        let ann = extract_items_from_array(self.convert_ts_type(type_ann)?);
-                        items = Some(ann.into());
-                    } else {
-                        let ty_schema = self.convert_ts_type(&it.ty)?;
-                        prefix_items.push(ty_schema);
-                    }
-                }
-                Ok(JsonSchema::Tuple {
-                    prefix_items,
-                    items,
-                })
-            }
+            TsType::TsTupleType(TsTupleType { elem_types, .. }) => self.convert_tuple(elem_types),
             TsType::TsLitType(TsLitType { lit, .. }) => match lit {
                 TsLit::Number(n) => Ok(JsonSchema::Const(JsonSchemaConst::parse_f64(n.value))),
                 TsLit::Str(s) => Ok(JsonSchema::Const(JsonSchemaConst::String(
@@ -1898,14 +3387,11 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
                 span,
                 DiagnosticInfoMessage::TsTypePredicateNonSerializableToJsonSchema,
             ),
-            TsType::TsImportType(TsImportType { span, .. }) => self.cannot_serialize_error(
-                span,
-                DiagnosticInfoMessage::TsImportTypeNonSerializableToJsonSchema,
-            ),
+            TsType::TsImportType(imp) => self.convert_ts_import_type(imp),
         }
     }
 
-    fn convert_conditional_type(&mut self, t: &TsConditionalType) -> Res<JsonSchema> {
+    fn conditional_type_branch<'c>(&mut self, t: &'c TsConditionalType) -> Res<&'c TsType> {
         let check_type_schema = self.convert_ts_type(&t.check_type)?;
         let extends_type_schema = self.convert_ts_type(&t.extends_type)?;
 
@@ -1932,9 +3418,14 @@ impl<'a, 'b, R: FileManager> TypeToSchema<'a, 'b, R> {
         let is_true = check_type_st.is_subtype(&extends_type_st, &mut ctx);
 
         if is_true {
-            self.convert_ts_type(&t.true_type)
+            Ok(&t.true_type)
         } else {
-            self.convert_ts_type(&t.false_type)
+            Ok(&t.false_type)
         }
     }
+
+    fn convert_conditional_type(&mut self, t: &TsConditionalType) -> Res<JsonSchema> {
+        let branch = self.conditional_type_branch(t)?;
+        self.convert_ts_type(branch)
+    }
 }
@@ -0,0 +1,876 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::ast::json_schema::{
+    built_in_string_format, CodecName, JsonSchema, JsonSchemaConst, Optionality, TplLitTypeItem,
+};
+use crate::{UnknownKeys, Validator};
+
+// Mirrors the errors built by the generated JavaScript decoders.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeError {
+    pub message: String,
+    pub path: Vec<String>,
+    pub received: Value,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_union_error: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<DecodeError>,
+}
+
+pub type CustomFormatValidator = Box<dyn Fn(&str) -> bool>;
+pub type CustomNumberFormatValidator = Box<dyn Fn(f64) -> bool>;
+
+#[derive(Default)]
+pub struct ValidateOptions {
+    pub custom_formats: BTreeMap<String, CustomFormatValidator>,
+    pub custom_number_formats: BTreeMap<String, CustomNumberFormatValidator>,
+    // only `strict` reports extra properties, the others do not change the value
+    pub unknown_keys: UnknownKeys,
+}
+
+// The keys the caller of the next object declares, like `allowedExtraProperties__`.
+#[derive(Clone, Default)]
+enum AllowedKeys {
+    #[default]
+    None,
+    Keys(Vec<String>),
+    // an `allOf` member, the merged object is checked instead
+    All,
+}
+
+impl AllowedKeys {
+    fn allows(&self, key: &str) -> bool {
+        match self {
+            AllowedKeys::None => false,
+            AllowedKeys::Keys(keys) => keys.iter().any(|it| it == key),
+            AllowedKeys::All => true,
+        }
+    }
+
+    fn with(&self, key: String) -> AllowedKeys {
+        match self {
+            AllowedKeys::None => AllowedKeys::Keys(vec![key]),
+            AllowedKeys::Keys(keys) => AllowedKeys::Keys([keys.clone(), vec![key]].concat()),
+            AllowedKeys::All => AllowedKeys::All,
+        }
+    }
+}
+
+struct ValidatorCtx<'a> {
+    validators: &'a [Validator],
+    options: &'a ValidateOptions,
+    allowed_extra_properties: AllowedKeys,
+    // the keys of the last object that was validated, the ones a decoder would return
+    object_keys: Option<BTreeSet<String>>,
+    paths: Vec<String>,
+    errors: Vec<DecodeError>,
+    regexes: HashMap<String, Regex>,
+}
+
+fn is_nullish(input: Option<&Value>) -> bool {
+    matches!(input, None | Some(Value::Null))
+}
+
+fn received(input: Option<&Value>) -> Value {
+    input.cloned().unwrap_or(Value::Null)
+}
+
+fn stringify(it: &Value) -> String {
+    serde_json::to_string(it).expect("should be able to serialize json")
+}
+
+// `===` of two JSON values
+fn strict_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        _ => a == b,
+    }
+}
+
+fn to_js_number(it: &Value) -> Option<f64> {
+    match it {
+        Value::Number(n) => n.as_f64(),
+        Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        Value::String(s) => {
+            let s = s.trim();
+            if s.is_empty() {
+                Some(0.0)
+            } else {
+                s.parse().ok()
+            }
+        }
+        _ => None,
+    }
+}
+
+// `==` of an input against a constant, the constants are never objects or arrays
+fn loose_eq(input: Option<&Value>, c: &Value) -> bool {
+    match (input, c) {
+        (None | Some(Value::Null), Value::Null) => true,
+        (None | Some(Value::Null), _) | (_, Value::Null) => false,
+        (Some(Value::String(a)), Value::String(b)) => a == b,
+        (Some(Value::Array(_) | Value::Object(_)), _) => false,
+        (Some(a), b) => match (to_js_number(a), to_js_number(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        },
+    }
+}
+
+// the key a value is converted to when indexing a JavaScript object
+fn property_key(it: &Value) -> Option<String> {
+    match it {
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(_) | Value::Number(_) => Some(it.to_string()),
+        _ => None,
+    }
+}
+
+fn take_digits(s: &[u8], pos: &mut usize, count: usize) -> Option<u32> {
+    let digits = s.get(*pos..*pos + count)?;
+    if !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    *pos += count;
+    std::str::from_utf8(digits).ok()?.parse().ok()
+}
+
+fn take_byte(s: &[u8], pos: &mut usize, options: &[u8]) -> bool {
+    match s.get(*pos) {
+        Some(c) if options.contains(c) => {
+            *pos += 1;
+            true
+        }
+        _ => false,
+    }
+}
+
+// ISO 8601 date-time strings, as accepted by `Date.parse`
+fn is_iso_date(s: &str) -> bool {
+    let s = s.as_bytes();
+    let mut pos = 0;
+    let year_digits = if take_byte(s, &mut pos, b"+-") { 6 } else { 4 };
+    if take_digits(s, &mut pos, year_digits).is_none() {
+        return false;
+    }
+    if take_byte(s, &mut pos, b"-") {
+        if !matches!(take_digits(s, &mut pos, 2), Some(1..=12)) {
+            return false;
+        }
+        if take_byte(s, &mut pos, b"-") && !matches!(take_digits(s, &mut pos, 2), Some(1..=31)) {
+            return false;
+        }
+    }
+    if take_byte(s, &mut pos, b"T ") {
+        if !matches!(take_digits(s, &mut pos, 2), Some(0..=24)) || !take_byte(s, &mut pos, b":") {
+            return false;
+        }
+        if !matches!(take_digits(s, &mut pos, 2), Some(0..=59)) {
+            return false;
+        }
+        if take_byte(s, &mut pos, b":") {
+            if !matches!(take_digits(s, &mut pos, 2), Some(0..=59)) {
+                return false;
+            }
+            if take_byte(s, &mut pos, b".") {
+                let start = pos;
+                while s.get(pos).is_some_and(u8::is_ascii_digit) {
+                    pos += 1;
+                }
+                if pos == start {
+                    return false;
+                }
+            }
+        }
+        if take_byte(s, &mut pos, b"+-")
+            && (!matches!(take_digits(s, &mut pos, 2), Some(0..=23))
+                || !take_byte(s, &mut pos, b":")
+                || !matches!(take_digits(s, &mut pos, 2), Some(0..=59)))
+        {
+            return false;
+        }
+        take_byte(s, &mut pos, b"Z");
+    }
+    pos == s.len()
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    let is_leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    match month {
+        2 if is_leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// calendar dates, YYYY-MM-DD
+fn is_plain_date(s: &str) -> bool {
+    let s = s.as_bytes();
+    let mut pos = 0;
+    let Some(year) = take_digits(s, &mut pos, 4) else {
+        return false;
+    };
+    if !take_byte(s, &mut pos, b"-") {
+        return false;
+    }
+    let Some(month @ 1..=12) = take_digits(s, &mut pos, 2) else {
+        return false;
+    };
+    if !take_byte(s, &mut pos, b"-") {
+        return false;
+    }
+    let Some(day) = take_digits(s, &mut pos, 2) else {
+        return false;
+    };
+    pos == s.len() && (1..=days_in_month(year, month)).contains(&day)
+}
+
+// times of day without an offset, HH:MM:SS with optional fractional seconds
+fn is_plain_time(s: &str) -> bool {
+    let s = s.as_bytes();
+    let mut pos = 0;
+    if !matches!(take_digits(s, &mut pos, 2), Some(0..=23)) || !take_byte(s, &mut pos, b":") {
+        return false;
+    }
+    if !matches!(take_digits(s, &mut pos, 2), Some(0..=59)) || !take_byte(s, &mut pos, b":") {
+        return false;
+    }
+    if !matches!(take_digits(s, &mut pos, 2), Some(0..=59)) {
+        return false;
+    }
+    if take_byte(s, &mut pos, b".") {
+        let start = pos;
+        while s.get(pos).is_some_and(u8::is_ascii_digit) {
+            pos += 1;
+        }
+        if pos == start {
+            return false;
+        }
+    }
+    pos == s.len()
+}
+
+// ISO 8601 durations, ie: P1Y2M3DT4H5M6.5S
+fn is_duration(s: &str) -> bool {
+    let Some(rest) = s.strip_prefix('P') else {
+        return false;
+    };
+    let (date, time) = match rest.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (rest, None),
+    };
+    !rest.is_empty()
+        && duration_components(date, "YMWD")
+        && time.is_none_or(|t| !t.is_empty() && duration_components(t, "HMS"))
+}
+
+// numbers followed by their unit, each unit at most once and in order
+fn duration_components(s: &str, units: &str) -> bool {
+    let mut units = units.chars();
+    let mut rest = s;
+    while !rest.is_empty() {
+        let Some(end) = rest.find(|c: char| !c.is_ascii_digit() && c != '.') else {
+            return false;
+        };
+        let (n, tail) = rest.split_at(end);
+        let (int, fract) = n.split_once('.').unwrap_or((n, "0"));
+        let is_number = [int, fract]
+            .iter()
+            .all(|it| !it.is_empty() && it.chars().all(|c| c.is_ascii_digit()));
+        let Some(unit) = tail.chars().next() else {
+            return false;
+        };
+        if !is_number || !units.any(|it| it == unit) {
+            return false;
+        }
+        rest = &tail[unit.len_utf8()..];
+    }
+    true
+}
+
+// standard and url safe alphabets, padding is optional
+fn is_base64(s: &str) -> bool {
+    let data = s.trim_end_matches('=');
+    let padding = s.len() - data.len();
+    data.bytes()
+        .all(|c| c.is_ascii_alphanumeric() || b"+/-_".contains(&c))
+        && data.len() % 4 != 1
+        && (padding == 0 || (padding <= 2 && s.len().is_multiple_of(4)))
+}
+
+fn is_bigint_string(s: &str) -> bool {
+    let s = s.trim();
+    if s.is_empty() {
+        return true;
+    }
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        return !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+    let digits = s.strip_prefix(['+', '-']).unwrap_or(s);
+    !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+impl<'a> ValidatorCtx<'a> {
+    fn new(validators: &'a [Validator], options: &'a ValidateOptions) -> Self {
+        ValidatorCtx {
+            validators,
+            options,
+            allowed_extra_properties: AllowedKeys::None,
+            object_keys: None,
+            paths: vec![],
+            errors: vec![],
+            regexes: HashMap::new(),
+        }
+    }
+
+    fn build_error(&mut self, input: Option<&Value>, message: String) {
+        self.errors.push(DecodeError {
+            message,
+            path: self.paths.clone(),
+            received: received(input),
+            is_union_error: false,
+            errors: vec![],
+        });
+    }
+
+    fn regex_test(&mut self, exp: &str, input: &str) -> bool {
+        let regex = self.regexes.entry(exp.to_string()).or_insert_with(|| {
+            Regex::new(exp).expect("template literals and formats should produce valid regexes")
+        });
+        regex.is_match(input)
+    }
+
+    fn get_validator(&self, name: &str) -> &'a Validator {
+        self.validators
+            .iter()
+            .find(|it| it.name == name)
+            .expect("everything should be resolved by now")
+    }
+
+    fn extract_union(&self, it: &JsonSchema) -> Vec<JsonSchema> {
+        match it {
+            JsonSchema::AnyOf(vs) => vs.iter().flat_map(|it| self.extract_union(it)).collect(),
+            JsonSchema::Ref(r) => self.extract_union(&self.get_validator(r).schema),
+            _ => vec![it.clone()],
+        }
+    }
+
+    // the same discriminated union detection as the decoder generator
+    fn discriminated_cases(
+        &self,
+        flat_values: &BTreeSet<JsonSchema>,
+    ) -> Option<(String, BTreeMap<String, JsonSchema>)> {
+        let all_objects_without_rest = flat_values.iter().all(|it| {
+            matches!(it, JsonSchema::Object { rest: None, pattern_vs, .. } if pattern_vs.is_empty())
+        });
+        if !all_objects_without_rest {
+            return None;
+        }
+        let object_vs = flat_values
+            .iter()
+            .filter_map(|it| match it {
+                JsonSchema::Object { vs, .. } => Some(vs),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let keys = object_vs.iter().flat_map(|vs| vs.keys().cloned());
+
+        for discriminator in keys {
+            let values = object_vs
+                .iter()
+                .map(|it| it.get(&discriminator))
+                .collect::<Option<BTreeSet<_>>>();
+            let Some(values) = values else {
+                continue;
+            };
+            let required_values = values
+                .into_iter()
+                .map(|it| match it {
+                    Optionality::Required(schema) => Some(schema),
+                    Optionality::Optional(_) | Optionality::Default(_, _) => None,
+                })
+                .collect::<Option<Vec<_>>>();
+            let Some(required_values) = required_values else {
+                continue;
+            };
+            let discriminator_strings = required_values
+                .into_iter()
+                .flat_map(|it| self.extract_union(it))
+                .map(|it| match it {
+                    JsonSchema::Const(JsonSchemaConst::String(s)) => Some(s),
+                    _ => None,
+                })
+                .collect::<Option<BTreeSet<_>>>();
+            let Some(discriminator_strings) = discriminator_strings else {
+                continue;
+            };
+
+            let mut acc = BTreeMap::new();
+            for current_key in discriminator_strings {
+                let mut cases = vec![];
+                for vs in object_vs.iter() {
+                    let value = vs
+                        .get(&discriminator)
+                        .expect("we already checked the discriminator exists")
+                        .inner();
+                    for s in self.extract_union(value) {
+                        if s == JsonSchema::Const(JsonSchemaConst::String(current_key.clone())) {
+                            let new_obj_vs: Vec<(String, Optionality<JsonSchema>)> = vs
+                                .iter()
+                                .filter(|it| it.0 != &discriminator)
+                                .map(|it| (it.0.clone(), it.1.clone()))
+                                .collect();
+                            cases.push(JsonSchema::object(new_obj_vs, None));
+                        }
+                    }
+                }
+                acc.insert(current_key, JsonSchema::any_of(cases));
+            }
+            return Some((discriminator, acc));
+        }
+        None
+    }
+
+    fn validate_any_of(
+        &mut self,
+        vs: &BTreeSet<JsonSchema>,
+        input: Option<&Value>,
+        required: bool,
+    ) {
+        let flat_values = vs
+            .iter()
+            .flat_map(|it| self.extract_union(it))
+            .collect::<BTreeSet<_>>();
+
+        let consts = flat_values
+            .iter()
+            .map(|it| match it {
+                JsonSchema::Const(c) => Some(c.clone().to_json().to_serde()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        if let Some(consts) = consts {
+            let input_value = received(input);
+            if !consts.iter().any(|c| strict_eq(&input_value, c)) {
+                let expected = consts.iter().map(stringify).collect::<Vec<_>>().join(", ");
+                self.build_error(input, format!("expected one of {expected}"));
+            }
+            return;
+        }
+
+        if let Some((discriminator, mapping)) = self.discriminated_cases(&flat_values) {
+            let d = match input {
+                Some(Value::Object(obj)) => obj.get(&discriminator),
+                _ => None,
+            };
+            if is_nullish(d) {
+                let message = format!(
+                    "expected discriminator key {}",
+                    stringify(&Value::String(discriminator))
+                );
+                return self.build_error(input, message);
+            }
+            let case = d.and_then(property_key).and_then(|key| mapping.get(&key));
+            match case {
+                Some(case) => {
+                    let prev = self.allowed_extra_properties.clone();
+                    self.allowed_extra_properties = prev.with(discriminator.clone());
+                    self.validate_schema(case, input, true);
+                    self.allowed_extra_properties = prev;
+                    if let Some(keys) = &mut self.object_keys {
+                        keys.insert(discriminator);
+                    }
+                }
+                None => {
+                    let expected = mapping
+                        .keys()
+                        .map(|it| stringify(&Value::String(it.clone())))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    self.paths.push(discriminator);
+                    self.build_error(d, format!("expected one of {expected}"));
+                    self.paths.pop();
+                }
+            }
+            return;
+        }
+
+        let allowed = std::mem::take(&mut self.allowed_extra_properties);
+        let mut acc_errors = vec![];
+        for v in vs {
+            let mut ctx = ValidatorCtx::new(self.validators, self.options);
+            ctx.allowed_extra_properties = allowed.clone();
+            ctx.validate_schema(v, input, required);
+            if ctx.errors.is_empty() {
+                self.object_keys = ctx.object_keys;
+                return;
+            }
+            acc_errors.extend(ctx.errors);
+        }
+        self.errors.push(DecodeError {
+            message: "expected one of".into(),
+            path: self.paths.clone(),
+            received: received(input),
+            is_union_error: true,
+            errors: acc_errors,
+        });
+    }
+
+    fn validate_object(
+        &mut self,
+        vs: &BTreeMap<String, Optionality<JsonSchema>>,
+        rest: &Option<Box<JsonSchema>>,
+        pattern_vs: &BTreeMap<Vec<TplLitTypeItem>, JsonSchema>,
+        input: Option<&Value>,
+    ) {
+        // keys declared by the caller, they do not apply to nested objects
+        let allowed = std::mem::take(&mut self.allowed_extra_properties);
+        let Some(Value::Object(obj)) = input else {
+            return self.build_error(input, "expected object".into());
+        };
+        for (k, v) in vs {
+            self.paths.push(k.clone());
+            self.validate_schema(v.inner(), obj.get(k), v.is_required());
+            self.paths.pop();
+        }
+
+        let mut validated_extra = BTreeSet::new();
+        if rest.is_some() || !pattern_vs.is_empty() {
+            for (k, v) in obj {
+                if vs.contains_key(k) {
+                    continue;
+                }
+                let pattern = pattern_vs
+                    .iter()
+                    .find(|(pattern, _)| self.regex_test(&TplLitTypeItem::pattern_vec(pattern), k));
+                let validator = pattern.map(|it| it.1).or(rest.as_deref());
+                if let Some(validator) = validator {
+                    self.paths.push(k.clone());
+                    self.validate_schema(validator, Some(v), false);
+                    self.paths.pop();
+                    validated_extra.insert(k);
+                }
+            }
+        }
+
+        let keys: BTreeSet<String> = vs.keys().chain(validated_extra).cloned().collect();
+        if self.options.unknown_keys == UnknownKeys::Strict {
+            self.report_extra_properties(obj, &keys, &allowed);
+        }
+        self.object_keys = Some(keys);
+    }
+
+    fn report_extra_properties(
+        &mut self,
+        obj: &serde_json::Map<String, Value>,
+        keys: &BTreeSet<String>,
+        allowed: &AllowedKeys,
+    ) {
+        for (k, v) in obj {
+            if !keys.contains(k) && !allowed.allows(k) {
+                self.paths.push(k.clone());
+                self.build_error(Some(v), "extra property".into());
+                self.paths.pop();
+            }
+        }
+    }
+
+    fn validate_all_of(
+        &mut self,
+        vs: &BTreeSet<JsonSchema>,
+        input: Option<&Value>,
+        required: bool,
+    ) {
+        // each member only knows its own keys, extra keys are checked once all of them are merged
+        let allowed = std::mem::take(&mut self.allowed_extra_properties);
+        // `None` once a member is not an object, it keeps every key
+        let mut keys = Some(BTreeSet::new());
+        for v in vs {
+            self.allowed_extra_properties = AllowedKeys::All;
+            self.object_keys = None;
+            self.validate_schema(v, input, required);
+            match (self.object_keys.take(), &mut keys) {
+                (Some(member_keys), Some(keys)) => keys.extend(member_keys),
+                _ => keys = None,
+            }
+        }
+        self.allowed_extra_properties = AllowedKeys::None;
+        if let (Some(Value::Object(obj)), Some(keys)) = (input, keys) {
+            if self.options.unknown_keys == UnknownKeys::Strict {
+                self.report_extra_properties(obj, &keys, &allowed);
+            }
+            self.object_keys = Some(keys);
+        }
+    }
+
+    fn validate_tuple(
+        &mut self,
+        prefix_items: &[JsonSchema],
+        optional_items: &[JsonSchema],
+        items: &Option<Box<JsonSchema>>,
+        suffix_items: &[JsonSchema],
+        input: Option<&Value>,
+    ) {
+        let Some(Value::Array(arr)) = input else {
+            return self.build_error(input, "expected tuple".into());
+        };
+        // the suffix is read from the end, a short tuple reports its missing items
+        let suffix_start = arr
+            .len()
+            .saturating_sub(suffix_items.len())
+            .max(prefix_items.len());
+        for (idx, v) in prefix_items.iter().enumerate() {
+            self.paths.push(format!("[{idx}]"));
+            self.validate_schema(v, arr.get(idx), true);
+            self.paths.pop();
+        }
+        for (idx, v) in optional_items.iter().enumerate() {
+            let idx = prefix_items.len() + idx;
+            self.paths.push(format!("[{idx}]"));
+            self.validate_schema(v, arr.get(idx), false);
+            self.paths.pop();
+        }
+        let positional = prefix_items.len() + optional_items.len();
+        match items {
+            Some(items) => {
+                for (idx, v) in arr.iter().enumerate().take(suffix_start).skip(positional) {
+                    self.paths.push(format!("[{idx}]"));
+                    self.validate_schema(items, Some(v), true);
+                    self.paths.pop();
+                }
+            }
+            None if arr.len() > positional => {
+                self.build_error(input, "tuple has too many items".into())
+            }
+            None => {}
+        }
+        for (idx, v) in suffix_items.iter().enumerate() {
+            let idx = suffix_start + idx;
+            self.paths.push(format!("[{idx}]"));
+            self.validate_schema(v, arr.get(idx), true);
+            self.paths.pop();
+        }
+    }
+
+    fn validate_codec(&mut self, codec: &CodecName, input: Option<&Value>) {
+        match codec {
+            CodecName::ISO8061 => {
+                let is_valid = match input {
+                    Some(Value::Number(n)) => n.as_f64().is_some_and(|it| it.abs() <= 8.64e15),
+                    Some(Value::String(s)) => is_iso_date(s),
+                    Some(Value::Bool(_) | Value::Null) | None => true,
+                    Some(Value::Array(_) | Value::Object(_)) => false,
+                };
+                if !is_valid {
+                    self.build_error(input, "expected ISO8061 date".into());
+                }
+            }
+            CodecName::BigInt => {
+                let is_valid = match input {
+                    Some(Value::Number(n)) => n.as_f64().is_some_and(|it| it.fract() == 0.0),
+                    Some(Value::String(s)) => is_bigint_string(s),
+                    _ => false,
+                };
+                if !is_valid {
+                    self.build_error(input, "expected bigint".into());
+                }
+            }
+            CodecName::PlainDate => self.validate_codec_string(input, is_plain_date, "date"),
+            CodecName::PlainTime => self.validate_codec_string(input, is_plain_time, "time"),
+            CodecName::Duration => {
+                self.validate_codec_string(input, is_duration, "ISO8601 duration");
+            }
+            CodecName::Base64 => self.validate_codec_string(input, is_base64, "base64"),
+            CodecName::Base64Url => self.validate_codec_string(input, is_base64, "base64url"),
+        }
+    }
+
+    fn validate_codec_string(
+        &mut self,
+        input: Option<&Value>,
+        is_valid: fn(&str) -> bool,
+        expected: &str,
+    ) {
+        if !matches!(input, Some(Value::String(s)) if is_valid(s)) {
+            self.build_error(input, format!("expected {expected}"));
+        }
+    }
+
+    fn validate_schema(&mut self, schema: &JsonSchema, input: Option<&Value>, required: bool) {
+        if !required && is_nullish(input) {
+            return;
+        }
+        match schema {
+            JsonSchema::StNever | JsonSchema::StNot(_) => {
+                unreachable!("should not validate semantic types")
+            }
+            JsonSchema::Any => {}
+            JsonSchema::AnyArrayLike => {
+                self.validate_schema(&JsonSchema::Array(JsonSchema::Any.into()), input, required)
+            }
+            JsonSchema::Null => {
+                let is_null = match input {
+                    None | Some(Value::Null) => true,
+                    Some(Value::String(s)) => s == "null" || s == "undefined",
+                    _ => false,
+                };
+                if !is_null {
+                    self.build_error(input, "expected null".into());
+                }
+            }
+            JsonSchema::Boolean => {
+                if !matches!(input, Some(Value::Bool(_))) {
+                    self.build_error(input, "expected boolean".into());
+                }
+            }
+            JsonSchema::String => {
+                if !matches!(input, Some(Value::String(_))) {
+                    self.build_error(input, "expected string".into());
+                }
+            }
+            JsonSchema::Number => {
+                let is_number = match input {
+                    Some(Value::Number(_)) => true,
+                    Some(Value::String(s)) => s.to_lowercase() == "nan",
+                    _ => false,
+                };
+                if !is_number {
+                    self.build_error(input, "expected number".into());
+                }
+            }
+            JsonSchema::StringWithFormat(format) => {
+                let expected = format!(
+                    "expected string with format {}",
+                    stringify(&Value::String(format.clone()))
+                );
+                let Some(Value::String(s)) = input else {
+                    return self.build_error(input, expected);
+                };
+                let is_ok = match (
+                    self.options.custom_formats.get(format),
+                    built_in_string_format(format),
+                ) {
+                    (Some(validator), _) => validator(s),
+                    (None, Some(built_in)) => self.regex_test(&built_in.regex, s),
+                    (None, None) => {
+                        let message = format!(
+                            "format {} not implemented",
+                            stringify(&Value::String(format.clone()))
+                        );
+                        return self.build_error(input, message);
+                    }
+                };
+                if !is_ok {
+                    self.build_error(input, expected);
+                }
+            }
+            JsonSchema::Ref(r) => {
+                let validator = self.get_validator(r);
+                self.validate_schema(&validator.schema, input, required)
+            }
+            JsonSchema::NumberWithFormat(format) => {
+                let expected = format!(
+                    "expected number with format {}",
+                    stringify(&Value::String(format.clone()))
+                );
+                let Some(n) = input.and_then(Value::as_f64) else {
+                    return self.build_error(input, expected);
+                };
+                match self.options.custom_number_formats.get(format) {
+                    Some(validator) => {
+                        if !validator(n) {
+                            self.build_error(input, expected);
+                        }
+                    }
+                    None => {
+                        let message = format!(
+                            "format {} not implemented",
+                            stringify(&Value::String(format.clone()))
+                        );
+                        self.build_error(input, message);
+                    }
+                }
+            }
+            JsonSchema::Brand(ty, _) => self.validate_schema(ty, input, required),
+            JsonSchema::Object {
+                vs,
+                rest,
+                pattern_vs,
+            } => self.validate_object(vs, rest, pattern_vs, input),
+            JsonSchema::Array(ty) => {
+                let Some(Value::Array(arr)) = input else {
+                    return self.build_error(input, "expected array".into());
+                };
+                for (idx, v) in arr.iter().enumerate() {
+                    self.paths.push(format!("[{idx}]"));
+                    self.validate_schema(ty, Some(v), true);
+                    self.paths.pop();
+                }
+            }
+            JsonSchema::Tuple {
+                prefix_items,
+                optional_items,
+                items,
+                suffix_items,
+            } => self.validate_tuple(prefix_items, optional_items, items, suffix_items, input),
+            JsonSchema::AnyOf(vs) => {
+                if vs.is_empty() {
+                    panic!("empty anyOf is not allowed")
+                }
+                self.validate_any_of(vs, input, required)
+            }
+            JsonSchema::AllOf(vs) => self.validate_all_of(vs, input, required),
+            JsonSchema::Const(c) => {
+                let c = c.clone().to_json().to_serde();
+                if !loose_eq(input, &c) {
+                    self.build_error(input, format!("expected {}", stringify(&c)));
+                }
+            }
+            JsonSchema::Codec(codec) => self.validate_codec(codec, input),
+            JsonSchema::TplLitType(items) => {
+                let exp = items.iter().map(|it| it.regex_expr()).collect::<String>();
+                let matches = match input {
+                    Some(Value::String(s)) => self.regex_test(&exp, s),
+                    _ => false,
+                };
+                if !matches {
+                    let message = format!(
+                        "expected string matching {}",
+                        TplLitTypeItem::describe_vec(items)
+                    );
+                    self.build_error(input, message);
+                }
+            }
+        }
+    }
+}
+
+pub fn validate_with_options(
+    schema: &JsonSchema,
+    validators: &[Validator],
+    value: &Value,
+    options: &ValidateOptions,
+) -> Result<(), Vec<DecodeError>> {
+    let mut ctx = ValidatorCtx::new(validators, options);
+    ctx.validate_schema(schema, Some(value), true);
+    if ctx.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ctx.errors)
+    }
+}
+
+// Checks a value the same way the generated decoders do, without a JavaScript runtime.
+pub fn validate(
+    schema: &JsonSchema,
+    validators: &[Validator],
+    value: &Value,
+) -> Result<(), Vec<DecodeError>> {
+    validate_with_options(schema, validators, value, &ValidateOptions::default())
+}
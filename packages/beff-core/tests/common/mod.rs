@@ -0,0 +1,120 @@
+// Each test file uses its own part of these helpers.
+#![allow(dead_code)]
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    rc::Rc,
+};
+
+use beff_core::{
+    ast::json_schema::JsonSchema,
+    find_ambient_module,
+    import_resolver::{parse_and_bind, FsModuleResolver},
+    BeffUserSettings, BffFileName, EntryPoints, ExtractResult, FileManager, ImportReference,
+    ParsedModule, UnknownKeys, Validator,
+};
+use swc_atoms::JsWord;
+use swc_common::{Globals, GLOBALS};
+
+pub struct TestFileManager {
+    pub files: HashMap<BffFileName, Rc<ParsedModule>>,
+}
+
+impl FileManager for TestFileManager {
+    fn get_or_fetch_file(&mut self, name: &BffFileName) -> Option<Rc<ParsedModule>> {
+        self.files.get(name).cloned()
+    }
+
+    fn get_existing_file(&self, name: &BffFileName) -> Option<Rc<ParsedModule>> {
+        self.files.get(name).cloned()
+    }
+
+    fn get_ambient_module(&self, module_specifier: &JsWord) -> Option<Rc<ImportReference>> {
+        find_ambient_module(self.files.iter(), module_specifier)
+    }
+}
+
+// Resolves `./name` to `name.ts`, `name.d.ts` or `node_modules/name/index.d.ts`.
+pub struct TestResolver {
+    pub known_files: Vec<String>,
+}
+impl FsModuleResolver for TestResolver {
+    fn resolve_import(&mut self, module_specifier: &str) -> Option<BffFileName> {
+        let specifier = module_specifier.trim_start_matches("./");
+        [
+            format!("{specifier}.ts"),
+            format!("{specifier}.d.ts"),
+            format!("node_modules/{specifier}/index.d.ts"),
+        ]
+        .into_iter()
+        .find(|name| self.known_files.contains(name))
+        .map(BffFileName::new)
+    }
+}
+
+pub fn parse_files(files: &[(&str, &str)]) -> HashMap<BffFileName, Rc<ParsedModule>> {
+    let known_files = files.iter().map(|(name, _)| name.to_string()).collect();
+    let mut resolver = TestResolver { known_files };
+    GLOBALS.set(&Globals::new(), || {
+        files
+            .iter()
+            .map(|(name, content)| {
+                let file_name = BffFileName::new(name.to_string());
+                let res = parse_and_bind(&mut resolver, &file_name, content);
+                (file_name, res.expect("failed to parse"))
+            })
+            .collect()
+    })
+}
+
+pub fn settings(unknown_keys: UnknownKeys) -> BeffUserSettings {
+    BeffUserSettings {
+        custom_formats: BTreeSet::from(["password".to_string()]),
+        custom_number_formats: BTreeSet::from(["Cents".to_string(), "Percentage".to_string()]),
+        unknown_keys,
+    }
+}
+
+pub enum Entry {
+    Parser,
+    Schema,
+}
+
+// Extracts `file.ts` as the parser or schema entry point, it must not have errors.
+pub fn extract(from: &str, entry: Entry, unknown_keys: UnknownKeys) -> ExtractResult {
+    let file_name = BffFileName::new("file.ts".into());
+    let mut man = TestFileManager {
+        files: parse_files(&[("file.ts", from)]),
+    };
+    let (parser_entry_points, schema_entry_point) = match entry {
+        Entry::Parser => (vec![file_name], None),
+        Entry::Schema => (vec![], Some(file_name)),
+    };
+    let entry = EntryPoints {
+        parser_entry_points,
+        schema_entry_point,
+        routes_entry_point: None,
+        settings: settings(unknown_keys),
+    };
+    let res = beff_core::extract(&mut man, entry);
+    assert!(res.errors().is_empty(), "errors: {:?}", res.errors());
+    res
+}
+
+pub struct Parsers {
+    pub validators: Vec<Validator>,
+    pub schemas: BTreeMap<String, JsonSchema>,
+}
+
+pub fn parsers(from: &str) -> Parsers {
+    let res = extract(from, Entry::Parser, UnknownKeys::default());
+    let parser = res.parser.expect("should have parsers");
+    Parsers {
+        schemas: parser
+            .exported_decoders()
+            .into_iter()
+            .map(|(name, it)| (name, it.schema.clone()))
+            .collect(),
+        validators: parser.validators,
+    }
+}
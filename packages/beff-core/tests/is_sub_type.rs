@@ -2,7 +2,7 @@
 mod tests {
 
     use beff_core::{
-        ast::json_schema::{JsonSchema, JsonSchemaConst},
+        ast::json_schema::{CodecName, JsonSchema, JsonSchemaConst},
         subtyping::{
             semtype::{SemTypeContext, SemTypeOps},
             ToSemType,
@@ -286,13 +286,56 @@ mod tests {
         assert!(res);
     }
     #[test]
+    fn mapping_indexed_access() {
+        let definitions = vec![];
+        let mut ctx = SemTypeContext::new();
+        let obj = JsonSchema::object(
+            vec![
+                ("a".into(), JsonSchema::String.required()),
+                ("b".into(), JsonSchema::Number.required()),
+            ],
+            Some(JsonSchema::Boolean.into()),
+        )
+        .to_sem_type(&definitions, &mut ctx)
+        .expect("should work");
+
+        let cases = [
+            // a declared key does not include the rest
+            (vec!["a"], JsonSchema::String),
+            (
+                vec!["a", "b"],
+                JsonSchema::any_of(vec![JsonSchema::String, JsonSchema::Number]),
+            ),
+            // undeclared keys are looked up in the rest
+            (vec!["c"], JsonSchema::Boolean),
+            (
+                vec!["a", "c"],
+                JsonSchema::any_of(vec![JsonSchema::String, JsonSchema::Boolean]),
+            ),
+        ];
+        for (keys, expected) in cases {
+            let idx = JsonSchema::any_of(
+                keys.iter()
+                    .map(|it| JsonSchema::Const(JsonSchemaConst::String(it.to_string())))
+                    .collect(),
+            )
+            .to_sem_type(&definitions, &mut ctx)
+            .expect("should work");
+            let res = ctx
+                .indexed_access(obj.clone(), idx)
+                .expect("should be able to index");
+            let expected = expected
+                .to_sem_type(&definitions, &mut ctx)
+                .expect("should work");
+            assert!(res.is_subtype(&expected, &mut ctx), "{keys:?}");
+            assert!(expected.is_subtype(&res, &mut ctx), "{keys:?}");
+        }
+    }
+    #[test]
     fn array_and_tuple() {
         let definitions = vec![];
 
-        let t1 = JsonSchema::Tuple {
-            prefix_items: vec![JsonSchema::String],
-            items: None,
-        };
+        let t1 = JsonSchema::tuple(vec![JsonSchema::String], None);
         let t2 = JsonSchema::Array(JsonSchema::String.into());
 
         let res = schema_is_sub_type(&t1, &t2, &definitions, &definitions);
@@ -301,6 +344,202 @@ mod tests {
         assert!(!res);
     }
     #[test]
+    fn tuple_optional_items() {
+        let definitions = vec![];
+
+        // [string, number?]
+        let t1 = JsonSchema::Tuple {
+            prefix_items: vec![JsonSchema::String],
+            optional_items: vec![JsonSchema::Number],
+            items: None,
+            suffix_items: vec![],
+        };
+        // [string] | [string, number]
+        let t2 = JsonSchema::any_of(vec![
+            JsonSchema::tuple(vec![JsonSchema::String], None),
+            JsonSchema::tuple(vec![JsonSchema::String, JsonSchema::Number], None),
+        ]);
+
+        assert!(schema_is_sub_type(&t1, &t2, &definitions, &definitions));
+        assert!(schema_is_sub_type(&t2, &t1, &definitions, &definitions));
+
+        let t3 = JsonSchema::tuple(vec![JsonSchema::String], None);
+        assert!(schema_is_sub_type(&t3, &t1, &definitions, &definitions));
+        assert!(!schema_is_sub_type(&t1, &t3, &definitions, &definitions));
+    }
+    #[test]
+    fn tuple_suffix_items() {
+        let definitions = vec![];
+
+        // [string, ...number[], boolean]
+        let t1 = JsonSchema::Tuple {
+            prefix_items: vec![JsonSchema::String],
+            optional_items: vec![],
+            items: Some(JsonSchema::Number.into()),
+            suffix_items: vec![JsonSchema::Boolean],
+        };
+        let any_item = JsonSchema::Array(
+            JsonSchema::any_of(vec![
+                JsonSchema::String,
+                JsonSchema::Number,
+                JsonSchema::Boolean,
+            ])
+            .into(),
+        );
+        assert!(schema_is_sub_type(
+            &t1,
+            &any_item,
+            &definitions,
+            &definitions
+        ));
+        assert!(!schema_is_sub_type(
+            &any_item,
+            &t1,
+            &definitions,
+            &definitions
+        ));
+
+        let no_suffix =
+            JsonSchema::tuple(vec![JsonSchema::String], Some(JsonSchema::Number.into()));
+        assert!(!schema_is_sub_type(
+            &t1,
+            &no_suffix,
+            &definitions,
+            &definitions
+        ));
+        assert!(!schema_is_sub_type(
+            &no_suffix,
+            &t1,
+            &definitions,
+            &definitions
+        ));
+
+        let fixed = JsonSchema::tuple(
+            vec![JsonSchema::String, JsonSchema::Number, JsonSchema::Boolean],
+            None,
+        );
+        assert!(schema_is_sub_type(&fixed, &t1, &definitions, &definitions));
+        assert!(!schema_is_sub_type(&t1, &fixed, &definitions, &definitions));
+
+        // [string, ...(number | boolean)[]] contains every [string, ...number[], boolean]
+        let wider = JsonSchema::tuple(
+            vec![JsonSchema::String],
+            Some(JsonSchema::any_of(vec![JsonSchema::Number, JsonSchema::Boolean]).into()),
+        );
+        assert!(schema_is_sub_type(&t1, &wider, &definitions, &definitions));
+        // [string, ...number[], boolean] | [string, ...number[]] is the wider type
+        let union = JsonSchema::any_of(vec![t1.clone(), no_suffix.clone()]);
+        assert!(schema_is_sub_type(
+            &union,
+            &wider,
+            &definitions,
+            &definitions
+        ));
+        assert!(!schema_is_sub_type(
+            &wider,
+            &union,
+            &definitions,
+            &definitions
+        ));
+    }
+    #[test]
+    fn string_formats() {
+        let definitions = vec![];
+
+        let date_time = JsonSchema::StringWithFormat("date-time".into());
+        let date = JsonSchema::StringWithFormat("date".into());
+
+        assert!(schema_is_sub_type(
+            &date_time,
+            &JsonSchema::String,
+            &definitions,
+            &definitions
+        ));
+        assert!(!schema_is_sub_type(
+            &JsonSchema::String,
+            &date_time,
+            &definitions,
+            &definitions
+        ));
+        assert!(!schema_is_sub_type(
+            &date,
+            &date_time,
+            &definitions,
+            &definitions
+        ));
+    }
+    #[test]
+    fn number_formats() {
+        let definitions = vec![];
+
+        let cents = JsonSchema::NumberWithFormat("Cents".into());
+        let percentage = JsonSchema::NumberWithFormat("Percentage".into());
+
+        assert!(schema_is_sub_type(
+            &cents,
+            &JsonSchema::Number,
+            &definitions,
+            &definitions
+        ));
+        assert!(!schema_is_sub_type(
+            &JsonSchema::Number,
+            &cents,
+            &definitions,
+            &definitions
+        ));
+        assert!(!schema_is_sub_type(
+            &cents,
+            &percentage,
+            &definitions,
+            &definitions
+        ));
+        assert!(!schema_is_sub_type(
+            &JsonSchema::Const(JsonSchemaConst::parse_int(1)),
+            &cents,
+            &definitions,
+            &definitions
+        ));
+    }
+    #[test]
+    fn codecs() {
+        let definitions = vec![];
+
+        let plain_date = JsonSchema::Codec(CodecName::PlainDate);
+        let date_time = JsonSchema::Codec(CodecName::ISO8061);
+        let base64 = JsonSchema::Codec(CodecName::Base64);
+
+        assert!(schema_is_sub_type(
+            &plain_date,
+            &JsonSchema::String,
+            &definitions,
+            &definitions
+        ));
+        assert!(!schema_is_sub_type(
+            &JsonSchema::String,
+            &plain_date,
+            &definitions,
+            &definitions
+        ));
+        assert!(!schema_is_sub_type(
+            &plain_date,
+            &date_time,
+            &definitions,
+            &definitions
+        ));
+        assert!(!schema_is_sub_type(
+            &JsonSchema::Codec(CodecName::PlainTime),
+            &JsonSchema::Codec(CodecName::Duration),
+            &definitions,
+            &definitions
+        ));
+        assert!(schema_is_sub_type(
+            &base64,
+            &JsonSchema::any_of(vec![base64.clone(), plain_date.clone()]),
+            &definitions,
+            &definitions
+        ));
+    }
+    #[test]
     fn it_works() {
         let definitions = vec![];
 
@@ -0,0 +1,436 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{extract, Entry};
+    use beff_core::{
+        mock::MockOptions,
+        print::printer::{ToWritableModules, WritableModules},
+        UnknownKeys,
+    };
+    use jsonschema::{Draft, Resource};
+    use regex::Regex;
+    use serde_json::{json, Map, Value};
+
+    fn json_schemas(from: &str) -> Map<String, Value> {
+        json_schemas_with(from, UnknownKeys::default())
+    }
+
+    fn json_schemas_with(from: &str, unknown_keys: UnknownKeys) -> Map<String, Value> {
+        let res = extract(from, Entry::Schema, unknown_keys);
+        to_map(res.to_module().expect("should be able to print"))
+    }
+
+    fn to_map(module: WritableModules) -> Map<String, Value> {
+        let json_schema = module.json_schema.expect("should have a json schema");
+        match serde_json::from_str(&json_schema).expect("should be valid json") {
+            Value::Object(it) => it,
+            _ => panic!("json schema should be an object"),
+        }
+    }
+
+    // The official draft 2020-12 meta-schema, with the vocabularies it is made of.
+    const META_SCHEMA: &str = include_str!("fixtures/draft2020-12/schema.json");
+    const VOCABULARIES: [(&str, &str); 7] = [
+        ("core", include_str!("fixtures/draft2020-12/meta/core.json")),
+        (
+            "applicator",
+            include_str!("fixtures/draft2020-12/meta/applicator.json"),
+        ),
+        (
+            "unevaluated",
+            include_str!("fixtures/draft2020-12/meta/unevaluated.json"),
+        ),
+        (
+            "validation",
+            include_str!("fixtures/draft2020-12/meta/validation.json"),
+        ),
+        (
+            "meta-data",
+            include_str!("fixtures/draft2020-12/meta/meta-data.json"),
+        ),
+        (
+            "format-annotation",
+            include_str!("fixtures/draft2020-12/meta/format-annotation.json"),
+        ),
+        (
+            "content",
+            include_str!("fixtures/draft2020-12/meta/content.json"),
+        ),
+    ];
+
+    fn parse(content: &str) -> Value {
+        serde_json::from_str(content).expect("should be valid json")
+    }
+
+    fn meta_validator(meta_schema: &Value) -> jsonschema::Validator {
+        let official = (
+            "https://json-schema.org/draft/2020-12/schema".to_string(),
+            Resource::from_contents(parse(META_SCHEMA)),
+        );
+        jsonschema::options()
+            .with_draft(Draft::Draft202012)
+            .with_resources(
+                VOCABULARIES
+                    .into_iter()
+                    .map(|(name, content)| {
+                        (
+                            format!("https://json-schema.org/draft/2020-12/meta/{name}"),
+                            Resource::from_contents(parse(content)),
+                        )
+                    })
+                    .chain([official]),
+            )
+            .build(meta_schema)
+            .expect("the meta-schema should compile")
+    }
+
+    // The official meta-schema, extended to reject unknown keywords like ajv's strict mode does.
+    // Its dynamic anchor makes every subschema go through the extension too.
+    fn strict_meta_validator() -> jsonschema::Validator {
+        meta_validator(&json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://beff.dev/strict-schema",
+            "$dynamicAnchor": "meta",
+            "$ref": "https://json-schema.org/draft/2020-12/schema",
+            "unevaluatedProperties": false
+        }))
+    }
+
+    // The validator of one of our schemas, which knows the custom formats of the project. It
+    // does not compile when a format is unknown or a pattern is not a valid regex.
+    fn schema_validator(
+        schema: &Value,
+    ) -> Result<jsonschema::Validator, jsonschema::ValidationError<'static>> {
+        jsonschema::options()
+            .with_draft(Draft::Draft202012)
+            .should_validate_formats(true)
+            .should_ignore_unknown_formats(false)
+            .with_format("password", |_| true)
+            .with_format("Percentage", |_| true)
+            .build(schema)
+    }
+
+    fn conformance_errors(strict: &jsonschema::Validator, schema: &Value) -> Vec<String> {
+        let mut errors: Vec<String> = strict.iter_errors(schema).map(|e| e.to_string()).collect();
+        if let Err(e) = schema_validator(schema) {
+            errors.push(e.to_string());
+        }
+        errors
+    }
+
+    // Every schema is valid under the strict meta-schema, compiles, and accepts its own examples.
+    fn assert_conforms(schemas: &Map<String, Value>) {
+        let strict = strict_meta_validator();
+        for (name, schema) in schemas {
+            let errors = conformance_errors(&strict, schema);
+            assert!(errors.is_empty(), "{name}: {errors:#?}\n{schema:#}");
+            if let Some(Value::Array(examples)) = schema.get("examples") {
+                let validator = schema_validator(schema).expect("the schema should compile");
+                for example in examples {
+                    assert!(
+                        validator.is_valid(example),
+                        "{name}: {example} is not valid"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_schema_conforms() {
+        let schemas = json_schemas(
+            r#"
+        type User = {
+            id: `user_${number}`;
+            name: string;
+            age?: number;
+            active: boolean;
+            nickname: string | null;
+            role: "admin" | "user";
+            level: 1 | 2 | "max";
+            tags: string[];
+            friends: User[];
+            meta: unknown;
+        };
+        type Shape =
+            | { kind: "circle"; radius: number }
+            | { kind: "square"; side: number };
+        type Named = { name: string } & { id: number };
+        type Scores = Record<string, number>;
+        type Prefixed = Record<`x-${string}`, string>;
+        type Route = `/users/${number}/${"posts" | "comments"}`;
+        export default buildSchemas<{
+            User: User;
+            Shape: Shape;
+            Named: Named;
+            Scores: Scores;
+            Prefixed: Prefixed;
+            Route: Route;
+            Pair: [string, number];
+            Rest: [boolean, ...number[]];
+            Range: [from: number, to?: number];
+            Path: [string, ...string[], boolean];
+            Password: StringFormat<"password">;
+            Date: Date;
+            Big: bigint;
+            Version: 1;
+        }>();
+      "#,
+        );
+        assert_conforms(&schemas);
+    }
+
+    #[test]
+    fn tuple_lengths() {
+        let schemas = json_schemas(
+            r#"
+        export default buildSchemas<{
+            Pair: [string, number];
+            Range: [from: number, to?: number];
+            Rest: [boolean, ...number[]];
+            Path: [string, ...string[], boolean];
+        }>();
+      "#,
+        );
+        assert_conforms(&schemas);
+        let lengths = |name: &str| {
+            (
+                schemas[name]["prefixItems"].as_array().map(Vec::len),
+                schemas[name]["minItems"].as_u64(),
+                schemas[name]["maxItems"].as_u64(),
+            )
+        };
+        assert_eq!(lengths("Pair"), (Some(2), Some(2), Some(2)));
+        assert_eq!(lengths("Range"), (Some(2), Some(1), Some(2)));
+        assert_eq!(lengths("Rest"), (Some(1), Some(1), None));
+        assert_eq!(lengths("Path"), (Some(1), Some(2), None));
+    }
+
+    #[test]
+    fn template_literals_are_anchored_patterns() {
+        let schemas = json_schemas(
+            r#"
+        type Route = `/users/${number}/${"posts" | "comments"}`;
+        type Flag = `${boolean}.${string}`;
+        export default buildSchemas<{ Route: Route; Flag: Flag }>();
+      "#,
+        );
+        assert_conforms(&schemas);
+        let matches = |name: &str, it: &str| {
+            let pattern = schemas[name]["pattern"]
+                .as_str()
+                .expect("should be a pattern");
+            Regex::new(pattern).unwrap().is_match(it)
+        };
+        assert!(matches("Route", "/users/12/posts"));
+        assert!(matches("Route", "/users/1.5/comments"));
+        assert!(!matches("Route", "/users/12/likes"));
+        assert!(!matches("Route", "/api/users/12/posts"));
+        assert!(!matches("Route", "/users/12/posts/1"));
+        assert!(matches("Flag", "true.anything"));
+        assert!(!matches("Flag", "yes.anything"));
+        assert_eq!(schemas["Route"]["type"], json!("string"));
+        assert_eq!(schemas["Route"].get("format"), None);
+    }
+
+    #[test]
+    fn codecs() {
+        let schemas = json_schemas(
+            r#"
+        export default buildSchemas<{
+            Date: Date;
+            Big: bigint;
+            Day: PlainDate;
+            Time: PlainTime;
+            Duration: IsoDuration;
+            Bytes: Uint8Array;
+            Token: Base64Url;
+        }>();
+      "#,
+        );
+        assert_conforms(&schemas);
+        assert_eq!(
+            schemas["Date"],
+            json!({ "type": "string", "format": "date-time" })
+        );
+        assert_eq!(
+            schemas["Big"],
+            json!({ "type": "string", "pattern": "^-?[0-9]+$" })
+        );
+        assert_eq!(
+            schemas["Day"],
+            json!({ "type": "string", "format": "date" })
+        );
+        assert_eq!(
+            schemas["Time"],
+            json!({
+                "type": "string",
+                "pattern": "^([01][0-9]|2[0-3]):[0-5][0-9]:[0-5][0-9](\\.[0-9]+)?$"
+            })
+        );
+        assert_eq!(
+            schemas["Duration"],
+            json!({ "type": "string", "format": "duration" })
+        );
+        assert_eq!(
+            schemas["Bytes"],
+            json!({ "type": "string", "contentEncoding": "base64" })
+        );
+        assert_eq!(
+            schemas["Token"],
+            json!({ "type": "string", "contentEncoding": "base64url" })
+        );
+    }
+
+    #[test]
+    fn built_in_string_formats() {
+        let schemas = json_schemas(
+            r#"
+        type Contact = { email: StringFormat<"email">; website?: StringFormat<"uri"> };
+        export default buildSchemas<{ Contact: Contact; Id: StringFormat<"uuid"> }>();
+      "#,
+        );
+        assert_conforms(&schemas);
+        assert_eq!(
+            schemas["Contact"]["properties"]["email"],
+            json!({ "type": "string", "format": "email" })
+        );
+        assert_eq!(schemas["Id"], json!({ "type": "string", "format": "uuid" }));
+    }
+
+    #[test]
+    fn number_formats() {
+        let schemas = json_schemas(
+            r#"
+        type Percentage = NumberFormat<"Percentage">;
+        export default buildSchemas<{ Percentage: Percentage }>();
+      "#,
+        );
+        assert_conforms(&schemas);
+        assert_eq!(
+            schemas["Percentage"],
+            json!({ "type": "number", "format": "Percentage" })
+        );
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let from = r#"
+        type User = { name: string; meta: Record<string, number> };
+        export default buildSchemas<{ User: User }>();
+      "#;
+        for unknown_keys in [UnknownKeys::Strict, UnknownKeys::Strip] {
+            let schemas = json_schemas_with(from, unknown_keys);
+            assert_conforms(&schemas);
+            assert_eq!(schemas["User"]["additionalProperties"], json!(false));
+        }
+        let schemas = json_schemas_with(from, UnknownKeys::Passthrough);
+        assert_conforms(&schemas);
+        assert_eq!(schemas["User"].get("additionalProperties"), None);
+        assert_eq!(
+            schemas["User"]["properties"]["meta"]["additionalProperties"],
+            json!({ "type": "number" })
+        );
+    }
+
+    #[test]
+    fn defaults() {
+        let schemas = json_schemas(
+            r#"
+        type Page = {
+            /** @default 20 */
+            pageSize?: number;
+            sort?: Default<"asc" | "desc", "asc">;
+            createdAfter?: Default<Date, "2020-01-01T00:00:00.000Z">;
+        };
+        export default buildSchemas<{ Page: Page }>();
+      "#,
+        );
+        assert_conforms(&schemas);
+        assert_eq!(schemas["Page"]["required"], json!([]));
+        let properties = &schemas["Page"]["properties"];
+        assert_eq!(
+            properties["pageSize"],
+            json!({ "type": "number", "default": 20 })
+        );
+        assert_eq!(properties["sort"]["default"], json!("asc"));
+        assert_eq!(
+            properties["createdAfter"],
+            json!({ "type": "string", "format": "date-time", "default": "2020-01-01T00:00:00.000Z" })
+        );
+    }
+
+    #[test]
+    fn brands() {
+        let schemas = json_schemas(
+            r#"
+        type UserId = string & { __brand: "UserId" };
+        type Cents = Brand<number, "Cents">;
+        type User = { id: UserId; balance: Cents } & { readonly __brand: "User" };
+        export default buildSchemas<{ UserId: UserId; User: User }>();
+      "#,
+        );
+        assert_conforms(&schemas);
+        assert_eq!(schemas["UserId"], json!({ "type": "string" }));
+        assert_eq!(
+            schemas["User"]["properties"]["balance"],
+            json!({ "type": "number" })
+        );
+        assert_eq!(schemas["User"]["required"], json!(["balance", "id"]));
+    }
+
+    #[test]
+    fn examples() {
+        let res = extract(
+            r#"
+        type User = { name: string; role: "admin" | "user" };
+        export default buildSchemas<{ User: User; Ok: "ok" }>();
+      "#,
+            Entry::Schema,
+            UnknownKeys::default(),
+        );
+        let schemas = to_map(
+            res.to_module_with_examples(&MockOptions::default())
+                .expect("should be able to print"),
+        );
+        assert_conforms(&schemas);
+        assert_eq!(schemas["Ok"]["examples"], json!(["ok"]));
+        let examples = schemas["User"]["examples"].as_array().unwrap();
+        assert_eq!(examples.len(), 1);
+        assert!(examples[0]["name"].is_string());
+        assert!(["admin", "user"].contains(&examples[0]["role"].as_str().unwrap()));
+
+        let schemas = json_schemas(
+            r#"
+        export default buildSchemas<{ Ok: "ok" }>();
+      "#,
+        );
+        assert!(schemas["Ok"].get("examples").is_none());
+    }
+
+    #[test]
+    fn the_checker_rejects_bad_schemas() {
+        let bad = json!({
+            "TplDescription": { "type": "string", "format": "/users/${number}" },
+            "Codec": { "type": "string", "format": "Codec::ISO8061" },
+            "BadPattern": { "type": "string", "pattern": "(unclosed" },
+            "UnknownType": { "type": "text" },
+            "UnknownKeyword": { "type": "string", "description2": "x" },
+            "DuplicateRequired": { "type": "object", "required": ["a", "a"] },
+            "BadPatternProperty": {
+                "type": "object",
+                "patternProperties": { "[": { "type": "string" } }
+            },
+        });
+        let strict = strict_meta_validator();
+        for (name, schema) in bad.as_object().unwrap() {
+            let errors = conformance_errors(&strict, schema);
+            assert!(!errors.is_empty(), "{name} should not conform");
+        }
+        let official = meta_validator(&parse(META_SCHEMA));
+        assert!(!official.is_valid(&bad["UnknownType"]));
+        assert!(!official.is_valid(&bad["DuplicateRequired"]));
+        assert!(official.is_valid(&bad["UnknownKeyword"]));
+    }
+}
@@ -0,0 +1,340 @@
+#[cfg(test)]
+mod tests {
+    use beff_core::{
+        ast::{
+            json::Json,
+            json_schema::{CodecName, JsonSchema, Optionality},
+        },
+        json_schema_import::{import_json_schema, JsonSchemaImportResult},
+        print::printer::ToWritableModules,
+        validate, BffFileName, UnknownKeys,
+    };
+    use serde_json::{json, Value};
+
+    fn import(schema: Value) -> JsonSchemaImportResult {
+        import_json_schema(
+            &BffFileName::new("partner.json".into()),
+            "Partner",
+            &schema.to_string(),
+        )
+    }
+
+    fn ok(schema: Value) -> JsonSchemaImportResult {
+        let res = import(schema);
+        let errors: Vec<_> = res
+            .errors
+            .iter()
+            .map(|it| it.cause.message.to_string())
+            .collect();
+        assert!(errors.is_empty(), "errors: {errors:#?}");
+        res
+    }
+
+    fn errors(schema: Value) -> Vec<String> {
+        import(schema)
+            .errors
+            .iter()
+            .map(|it| it.cause.message.to_string())
+            .collect()
+    }
+
+    fn is_valid(res: &JsonSchemaImportResult, name: &str, value: Value) -> bool {
+        let validator = res
+            .validators
+            .iter()
+            .find(|it| it.name == name)
+            .expect("should have imported the type");
+        validate(&validator.schema, &res.validators, &value).is_ok()
+    }
+
+    #[test]
+    fn ok_draft_2020_12() {
+        let res = ok(json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "An order",
+            "type": "object",
+            "properties": {
+                "id": { "type": "string", "format": "uuid" },
+                "createdAt": { "type": "string", "format": "date-time" },
+                "total": { "type": "number" },
+                "status": { "enum": ["open", "paid"] },
+                "note": { "type": ["string", "null"] },
+                "customer": { "$ref": "#/$defs/customer" },
+                "lines": { "type": "array", "items": { "$ref": "#/$defs/line" } },
+                "meta": { "type": "object", "additionalProperties": { "type": "number" } }
+            },
+            "required": ["id", "createdAt", "total", "status", "customer", "lines"],
+            "$defs": {
+                "customer": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "referredBy": { "$ref": "#/$defs/customer" }
+                    },
+                    "required": ["name"]
+                },
+                "line": {
+                    "type": "array",
+                    "prefixItems": [{ "type": "string" }, { "type": "number" }],
+                    "items": false,
+                    "minItems": 2,
+                    "maxItems": 2
+                }
+            }
+        }));
+        insta::assert_snapshot!(res.to_ts_types());
+    }
+
+    #[test]
+    fn ok_draft_07() {
+        let res = ok(json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "definitions": {
+                "point": {
+                    "type": "array",
+                    "items": [{ "type": "number" }, { "type": "number" }],
+                    "additionalItems": false
+                },
+                "shape": {
+                    "oneOf": [
+                        {
+                            "properties": { "kind": { "const": "circle" }, "radius": { "type": "number" } },
+                            "required": ["kind", "radius"]
+                        },
+                        {
+                            "properties": {
+                                "kind": { "const": "polygon" },
+                                "points": { "type": "array", "items": { "$ref": "#/definitions/point" } }
+                            },
+                            "required": ["kind", "points"]
+                        }
+                    ]
+                },
+                "tagged": {
+                    "allOf": [
+                        { "$ref": "#/definitions/shape" },
+                        { "properties": { "tag": { "type": "string" } }, "required": ["tag"] }
+                    ]
+                }
+            }
+        }));
+        insta::assert_snapshot!(res.to_ts_types());
+    }
+
+    #[test]
+    fn decoders() {
+        let res = ok(json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "age": { "type": "number", "nullable": true },
+                "pair": { "type": "array", "prefixItems": [{ "type": "string" }, { "type": "boolean" }], "items": false },
+                "big": { "type": "string", "pattern": "^-?[0-9]+$" }
+            },
+            "required": ["name", "pair"]
+        }));
+        assert!(is_valid(
+            &res,
+            "Partner",
+            json!({ "name": "a", "pair": ["x", true] })
+        ));
+        assert!(is_valid(
+            &res,
+            "Partner",
+            json!({ "name": "a", "age": null, "pair": ["x", false], "big": "-12" })
+        ));
+        assert!(!is_valid(
+            &res,
+            "Partner",
+            json!({ "name": "a", "pair": ["x"] })
+        ));
+        assert!(!is_valid(
+            &res,
+            "Partner",
+            json!({ "name": "a", "pair": ["x", true, 1] })
+        ));
+        assert!(!is_valid(
+            &res,
+            "Partner",
+            json!({ "name": 1, "pair": ["x", true] })
+        ));
+        assert!(!is_valid(
+            &res,
+            "Partner",
+            json!({ "name": "a", "pair": ["x", true], "big": "1.5" })
+        ));
+
+        let module = res.to_module().expect("should print the decoders");
+        assert!(module.js_validators.contains("function DecodePartner("));
+        assert!(module
+            .js_built_parsers
+            .expect("should build parsers")
+            .contains("Partner"));
+    }
+
+    #[test]
+    fn codecs() {
+        let res = ok(json!({
+            "type": "object",
+            "properties": {
+                "day": { "type": "string", "format": "date" },
+                "at": { "type": "string", "pattern": "^([01][0-9]|2[0-3]):[0-5][0-9]:[0-5][0-9](\\.[0-9]+)?$" },
+                "length": { "type": "string", "format": "duration" },
+                "payload": { "contentEncoding": "base64" },
+                "token": { "contentEncoding": "base64url" }
+            },
+            "required": ["day", "at", "length", "payload", "token"]
+        }));
+        let JsonSchema::Object { vs, .. } = &res.validators[0].schema else {
+            panic!("should be an object");
+        };
+        let codecs = [
+            ("day", CodecName::PlainDate),
+            ("at", CodecName::PlainTime),
+            ("length", CodecName::Duration),
+            ("payload", CodecName::Base64),
+            ("token", CodecName::Base64Url),
+        ];
+        for (key, codec) in codecs {
+            assert_eq!(vs[key], Optionality::Required(JsonSchema::Codec(codec)));
+        }
+        assert_eq!(
+            errors(json!({ "type": "string", "contentEncoding": "base32" })),
+            vec!["Keyword 'contentEncoding' at '#' cannot be represented as a type"]
+        );
+    }
+
+    #[test]
+    fn optional_tuple_items() {
+        let res = ok(json!({
+            "type": "array",
+            "prefixItems": [{ "type": "number" }, { "type": "number" }],
+            "items": false,
+            "minItems": 1
+        }));
+        assert_eq!(
+            res.validators[0].schema,
+            JsonSchema::Tuple {
+                prefix_items: vec![JsonSchema::Number],
+                optional_items: vec![JsonSchema::Number],
+                items: None,
+                suffix_items: vec![],
+            }
+        );
+        assert!(is_valid(&res, "Partner", json!([1])));
+        assert!(is_valid(&res, "Partner", json!([1, 2])));
+        assert!(!is_valid(&res, "Partner", json!([])));
+    }
+
+    #[test]
+    fn defaults() {
+        let res = ok(json!({
+            "type": "object",
+            "properties": {
+                "pageSize": { "type": "number", "default": 20 },
+                "cursor": { "type": "string", "default": "" }
+            },
+            "required": ["cursor"]
+        }));
+        let JsonSchema::Object { vs, .. } = &res.validators[0].schema else {
+            panic!("should be an object");
+        };
+        assert_eq!(
+            vs["pageSize"],
+            Optionality::Default(JsonSchema::Number, Json::parse_int(20))
+        );
+        assert_eq!(vs["cursor"], Optionality::Required(JsonSchema::String));
+    }
+
+    #[test]
+    fn only_definitions() {
+        let res = ok(json!({
+            "$defs": {
+                "user-id": { "type": "string" },
+                "Partner": { "type": "number" }
+            }
+        }));
+        let names: Vec<_> = res.validators.iter().map(|it| it.name.as_str()).collect();
+        assert_eq!(names, vec!["Partner$1", "user_id"]);
+    }
+
+    #[test]
+    fn unsupported_keywords() {
+        assert_eq!(
+            errors(json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "minLength": 1 },
+                    "code": { "type": "string", "pattern": "^[A-Z]{3}$" },
+                    "tags": { "type": "array", "items": { "type": "string" }, "minItems": 1 },
+                    "other": { "$ref": "other.json#/$defs/thing" },
+                    "never": false
+                },
+                "if": { "required": ["name"] },
+                "then": { "required": ["code"] }
+            })),
+            vec![
+                "Keyword 'pattern' at '#/properties/code' cannot be represented as a type",
+                "Keyword 'minLength' at '#/properties/name' cannot be represented as a type",
+                "Keyword 'false' at '#/properties/never' cannot be represented as a type",
+                "Cannot resolve JSON schema reference 'other.json#/$defs/thing'",
+                "Keyword 'minItems' at '#/properties/tags' cannot be represented as a type",
+                "Keyword 'if' at '#' cannot be represented as a type",
+                "Keyword 'then' at '#' cannot be represented as a type",
+            ]
+        );
+        assert_eq!(
+            errors(json!({ "type": "object", "properties": { "n": { "type": "integer" } } })),
+            vec![
+                "Type 'integer' at '#/properties/n' cannot be represented as a type, use 'number'"
+            ]
+        );
+        assert_eq!(
+            errors(json!({ "type": "text" })),
+            vec!["Invalid JSON schema at '#/type': unknown type 'text'"]
+        );
+        assert_eq!(
+            import_json_schema(&BffFileName::new("partner.json".into()), "Partner", "{")
+                .errors
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn closed_objects() {
+        let res = ok(json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "address": {
+                    "type": "object",
+                    "properties": { "city": { "type": "string" } },
+                    "additionalProperties": false
+                },
+                "meta": { "type": "object", "additionalProperties": { "type": "string" } }
+            },
+            "additionalProperties": false
+        }));
+        assert_eq!(res.unknown_keys, UnknownKeys::Strict);
+        let module = res.to_module().expect("should print the decoders");
+        assert!(module
+            .js_built_parsers
+            .expect("should build parsers")
+            .contains("\"strict\""));
+
+        let res = ok(json!({ "type": "object", "additionalProperties": true }));
+        assert_eq!(res.unknown_keys, UnknownKeys::Strip);
+
+        assert_eq!(
+            errors(json!({
+                "type": "object",
+                "properties": {
+                    "address": { "type": "object", "properties": { "city": { "type": "string" } } }
+                },
+                "additionalProperties": false
+            })),
+            vec!["'additionalProperties: false' at '#' requires every object of the document to forbid additional properties, the decoders share one unknown keys policy"]
+        );
+    }
+}
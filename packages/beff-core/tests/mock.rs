@@ -0,0 +1,135 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::common::{parsers, Parsers};
+    use beff_core::{
+        ast::json::Json,
+        mock,
+        mock::MockOptions,
+        validate::{validate_with_options, ValidateOptions},
+        UnknownKeys,
+    };
+    use serde_json::json;
+
+    impl Parsers {
+        fn mock(&self, name: &str, seed: u64, max_depth: usize) -> Json {
+            let mut options = MockOptions {
+                seed,
+                max_depth,
+                ..MockOptions::default()
+            };
+            options
+                .custom_formats
+                .insert("password".into(), "hunter22".into());
+            mock(&self.schemas[name], &self.validators, &options).expect("should build a value")
+        }
+
+        // every seed builds a value the decoders accept
+        fn assert_valid(&self, seeds: u64, max_depth: usize) {
+            let mut options = ValidateOptions {
+                unknown_keys: UnknownKeys::Strict,
+                ..ValidateOptions::default()
+            };
+            options
+                .custom_formats
+                .insert("password".into(), Box::new(|it: &str| it.len() >= 8));
+            for (name, schema) in &self.schemas {
+                for seed in 0..seeds {
+                    let value = self.mock(name, seed, max_depth).to_serde();
+                    let res = validate_with_options(schema, &self.validators, &value, &options);
+                    assert_eq!(res, Ok(()), "{name} with seed {seed}: {value}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn values_are_valid() {
+        let p = parsers(
+            r#"
+        type User = { name: string; age?: number; tags: string[]; role: "admin" | "user" };
+        type Shape =
+            | { kind: "circle"; radius: number }
+            | { kind: "square" | "rect"; side: number };
+        type Named = { name: string } & { id: number };
+        type Route = `/users/${number}/${"posts" | "comments"}`;
+        parse.buildParsers<{
+            User: User;
+            Shape: Shape;
+            Named: Named;
+            Route: Route;
+            Pair: [string, number];
+            Rest: [boolean, ...number[]];
+            Range: [from: number, to?: number];
+            Path: [string, ...string[], boolean];
+            Args: [string, number?, ...boolean[]];
+            Scores: Record<string, number>;
+            Password: StringFormat<"password">;
+            Email: StringFormat<"email">;
+            Id: StringFormat<"uuid">;
+            Address: StringFormat<"ipv6">;
+            Date: Date;
+            Big: bigint;
+            Day: PlainDate;
+            Time: PlainTime;
+            Duration: IsoDuration;
+            Bytes: Uint8Array;
+            Token: Base64Url;
+            Version: 1;
+            Anything: unknown;
+        }>();
+      "#,
+        );
+        p.assert_valid(32, 3);
+    }
+
+    #[test]
+    fn same_seed_same_value() {
+        let p = parsers(
+            r#"
+        type User = { name: string; age?: number; friends: User[]; role: "admin" | "user" };
+        parse.buildParsers<{ User: User }>();
+      "#,
+        );
+        assert_eq!(p.mock("User", 7, 3), p.mock("User", 7, 3));
+        let values: BTreeSet<String> = (0..8).map(|it| p.mock("User", it, 3).to_string()).collect();
+        assert!(values.len() > 1);
+    }
+
+    #[test]
+    fn recursion_is_bounded() {
+        let p = parsers(
+            r#"
+        type Tree = { value: number; children?: Tree[] };
+        type List = { value: string; next: List | null };
+        type Expr = { op: "add"; left: Expr; right: Expr } | { op: "lit"; value: number };
+        parse.buildParsers<{ Tree: Tree; List: List; Expr: Expr }>();
+      "#,
+        );
+        for max_depth in 0..4 {
+            p.assert_valid(16, max_depth);
+        }
+        assert_eq!(
+            p.mock("List", 0, 0).to_serde(),
+            json!({ "value": "echo", "next": null })
+        );
+    }
+
+    #[test]
+    fn infinite_types_fail() {
+        let p = parsers(
+            r#"
+        type Loop = { next: Loop };
+        parse.buildParsers<{ Loop: Loop }>();
+      "#,
+        );
+        let res = mock(&p.schemas["Loop"], &p.validators, &MockOptions::default());
+        assert_eq!(
+            res.map_err(|it| it.to_string()),
+            Err("type 'Loop' has no finite value".to_string())
+        );
+    }
+}
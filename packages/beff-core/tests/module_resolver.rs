@@ -0,0 +1,395 @@
+#[cfg(test)]
+mod tests {
+    use beff_core::{
+        import_resolver::FsModuleResolver,
+        module_resolver::{
+            CompilerOptions, InMemoryHost, ModuleResolutionKind, TsFileModuleResolver,
+            TsModuleResolver,
+        },
+        BffFileName,
+    };
+    use std::rc::Rc;
+
+    fn resolver(files: Vec<(&str, &str)>) -> TsModuleResolver<InMemoryHost> {
+        TsModuleResolver::new(InMemoryHost::new(files), CompilerOptions::default())
+    }
+
+    fn resolver_with_tsconfig(files: Vec<(&str, &str)>) -> TsModuleResolver<InMemoryHost> {
+        TsModuleResolver::from_tsconfig(InMemoryHost::new(files), "/app/tsconfig.json")
+            .expect("tsconfig should load")
+    }
+
+    #[test]
+    fn relative_imports() {
+        let r = resolver(vec![
+            ("/app/src/a.ts", ""),
+            ("/app/src/b.tsx", ""),
+            ("/app/src/c.d.ts", ""),
+            ("/app/src/dir/index.ts", ""),
+            ("/app/lib/d.mts", ""),
+        ]);
+        let from = "/app/src/main.ts";
+        assert_eq!(r.resolve(from, "./a"), Some("/app/src/a.ts".into()));
+        assert_eq!(r.resolve(from, "./b"), Some("/app/src/b.tsx".into()));
+        assert_eq!(r.resolve(from, "./c"), Some("/app/src/c.d.ts".into()));
+        assert_eq!(
+            r.resolve(from, "./dir"),
+            Some("/app/src/dir/index.ts".into())
+        );
+        assert_eq!(r.resolve(from, "./a.ts"), Some("/app/src/a.ts".into()));
+        assert_eq!(r.resolve(from, "./missing"), None);
+    }
+
+    #[test]
+    fn js_extension_substitution() {
+        let r = resolver(vec![
+            ("/app/src/a.ts", ""),
+            ("/app/src/b.tsx", ""),
+            ("/app/src/c.d.ts", ""),
+            ("/app/lib/d.mts", ""),
+            ("/app/lib/e.d.cts", ""),
+        ]);
+        let from = "/app/src/main.ts";
+        assert_eq!(r.resolve(from, "./a.js"), Some("/app/src/a.ts".into()));
+        assert_eq!(r.resolve(from, "./b.js"), Some("/app/src/b.tsx".into()));
+        assert_eq!(r.resolve(from, "./b.jsx"), Some("/app/src/b.tsx".into()));
+        assert_eq!(r.resolve(from, "./c.js"), Some("/app/src/c.d.ts".into()));
+        assert_eq!(
+            r.resolve(from, "../lib/d.mjs"),
+            Some("/app/lib/d.mts".into())
+        );
+        assert_eq!(
+            r.resolve(from, "../lib/e.cjs"),
+            Some("/app/lib/e.d.cts".into())
+        );
+    }
+
+    #[test]
+    fn tsconfig_paths_and_base_url() {
+        let r = resolver_with_tsconfig(vec![
+            (
+                "/app/tsconfig.json",
+                r#"{
+                    // comments and trailing commas are allowed
+                    "compilerOptions": {
+                        "baseUrl": "./src",
+                        "paths": {
+                            "@/*": ["./*"],
+                            "@/models/*": ["../models/*", "./models/*"],
+                            "config": ["./config/index.ts"],
+                        },
+                    },
+                }"#,
+            ),
+            ("/app/src/utils/date.ts", ""),
+            ("/app/src/models/user.ts", ""),
+            ("/app/models/order.ts", ""),
+            ("/app/src/config/index.ts", ""),
+            ("/app/src/shared.ts", ""),
+        ]);
+        let from = "/app/src/pages/home.ts";
+        assert_eq!(
+            r.resolve(from, "@/utils/date"),
+            Some("/app/src/utils/date.ts".into())
+        );
+        assert_eq!(
+            r.resolve(from, "@/models/order"),
+            Some("/app/models/order.ts".into())
+        );
+        assert_eq!(
+            r.resolve(from, "@/models/user"),
+            Some("/app/src/models/user.ts".into())
+        );
+        assert_eq!(
+            r.resolve(from, "config"),
+            Some("/app/src/config/index.ts".into())
+        );
+        assert_eq!(r.resolve(from, "shared"), Some("/app/src/shared.ts".into()));
+    }
+
+    #[test]
+    fn tsconfig_extends() {
+        let r = resolver_with_tsconfig(vec![
+            (
+                "/app/tsconfig.json",
+                r#"{ "extends": ["./configs/base", "@acme/tsconfig"] }"#,
+            ),
+            (
+                "/app/configs/base.json",
+                r#"{ "compilerOptions": { "paths": { "~/*": ["../src/*"] } } }"#,
+            ),
+            (
+                "/app/node_modules/@acme/tsconfig/tsconfig.json",
+                r#"{ "compilerOptions": { "moduleResolution": "NodeNext" } }"#,
+            ),
+            ("/app/src/a.ts", ""),
+        ]);
+        assert_eq!(r.options.module_resolution, ModuleResolutionKind::Node16);
+        assert_eq!(
+            r.resolve("/app/src/b.ts", "~/a"),
+            Some("/app/src/a.ts".into())
+        );
+    }
+
+    #[test]
+    fn tsconfig_inherited_base_url() {
+        let r = resolver_with_tsconfig(vec![
+            (
+                "/app/tsconfig.json",
+                r#"{ "extends": "./configs/base.json", "compilerOptions": { "paths": { "~/*": ["lib/*"] } } }"#,
+            ),
+            (
+                "/app/configs/base.json",
+                r#"{ "compilerOptions": { "baseUrl": "../src" } }"#,
+            ),
+            ("/app/src/lib/a.ts", ""),
+            ("/app/lib/a.ts", ""),
+        ]);
+        assert_eq!(
+            r.resolve("/app/src/b.ts", "~/a"),
+            Some("/app/src/lib/a.ts".into())
+        );
+    }
+
+    #[test]
+    fn package_types_and_index() {
+        let r = resolver(vec![
+            (
+                "/app/node_modules/typed/package.json",
+                r#"{ "types": "./dist/main.d.ts" }"#,
+            ),
+            ("/app/node_modules/typed/dist/main.d.ts", ""),
+            ("/app/node_modules/typed/dist/extra.d.ts", ""),
+            (
+                "/app/node_modules/main-only/package.json",
+                r#"{ "main": "./lib/index.js" }"#,
+            ),
+            ("/app/node_modules/main-only/lib/index.d.ts", ""),
+            ("/app/node_modules/bare/index.d.ts", ""),
+            ("/node_modules/hoisted/index.d.ts", ""),
+            ("/app/node_modules/@types/untyped/index.d.ts", ""),
+            ("/app/node_modules/@types/acme__scoped/index.d.ts", ""),
+        ]);
+        let from = "/app/src/main.ts";
+        assert_eq!(
+            r.resolve(from, "typed"),
+            Some("/app/node_modules/typed/dist/main.d.ts".into())
+        );
+        assert_eq!(
+            r.resolve(from, "typed/dist/extra"),
+            Some("/app/node_modules/typed/dist/extra.d.ts".into())
+        );
+        assert_eq!(
+            r.resolve(from, "main-only"),
+            Some("/app/node_modules/main-only/lib/index.d.ts".into())
+        );
+        assert_eq!(
+            r.resolve(from, "bare"),
+            Some("/app/node_modules/bare/index.d.ts".into())
+        );
+        assert_eq!(
+            r.resolve(from, "hoisted"),
+            Some("/node_modules/hoisted/index.d.ts".into())
+        );
+        assert_eq!(
+            r.resolve(from, "untyped"),
+            Some("/app/node_modules/@types/untyped/index.d.ts".into())
+        );
+        assert_eq!(
+            r.resolve(from, "@acme/scoped"),
+            Some("/app/node_modules/@types/acme__scoped/index.d.ts".into())
+        );
+        assert_eq!(r.resolve(from, "missing"), None);
+    }
+
+    #[test]
+    fn package_exports() {
+        let files = vec![
+            (
+                "/app/node_modules/pkg/package.json",
+                r#"{
+                    "exports": {
+                        ".": { "types": "./types/index.d.ts", "default": "./index.js" },
+                        "./feature/*": { "import": "./esm/feature/*.js", "require": "./cjs/feature/*.js" },
+                        "./cjs-only": { "require": "./cjs/only.js" }
+                    }
+                }"#,
+            ),
+            ("/app/node_modules/pkg/types/index.d.ts", ""),
+            ("/app/node_modules/pkg/esm/feature/x.d.ts", ""),
+            ("/app/node_modules/pkg/cjs/only.d.ts", ""),
+            ("/app/node_modules/pkg/hidden.d.ts", ""),
+            (
+                "/app/node_modules/sugar/package.json",
+                r#"{ "exports": "./main.js" }"#,
+            ),
+            ("/app/node_modules/sugar/main.d.ts", ""),
+        ];
+        let from = "/app/src/main.ts";
+        let bundler = resolver(files.clone());
+        assert_eq!(
+            bundler.resolve(from, "pkg"),
+            Some("/app/node_modules/pkg/types/index.d.ts".into())
+        );
+        assert_eq!(
+            bundler.resolve(from, "pkg/feature/x"),
+            Some("/app/node_modules/pkg/esm/feature/x.d.ts".into())
+        );
+        assert_eq!(bundler.resolve(from, "pkg/hidden"), None);
+        assert_eq!(bundler.resolve(from, "pkg/cjs-only"), None);
+        assert_eq!(
+            bundler.resolve(from, "sugar"),
+            Some("/app/node_modules/sugar/main.d.ts".into())
+        );
+
+        let node16 = TsModuleResolver::new(
+            InMemoryHost::new(files),
+            CompilerOptions {
+                module_resolution: ModuleResolutionKind::Node16,
+                ..CompilerOptions::default()
+            },
+        );
+        assert_eq!(
+            node16.resolve(from, "pkg/cjs-only"),
+            Some("/app/node_modules/pkg/cjs/only.d.ts".into())
+        );
+    }
+
+    #[test]
+    fn package_exports_condition_order() {
+        let r = resolver(vec![
+            (
+                "/app/node_modules/ordered/package.json",
+                r#"{
+                    "exports": {
+                        ".": { "default": "./fallback.js", "types": "./index.d.ts" },
+                        "./sub": { "import": "./esm/sub.js", "types": "./types/sub.d.ts" }
+                    }
+                }"#,
+            ),
+            ("/app/node_modules/ordered/fallback.d.ts", ""),
+            ("/app/node_modules/ordered/index.d.ts", ""),
+            ("/app/node_modules/ordered/esm/sub.d.ts", ""),
+            ("/app/node_modules/ordered/types/sub.d.ts", ""),
+        ]);
+        let from = "/app/src/main.ts";
+        assert_eq!(
+            r.resolve(from, "ordered"),
+            Some("/app/node_modules/ordered/fallback.d.ts".into())
+        );
+        assert_eq!(
+            r.resolve(from, "ordered/sub"),
+            Some("/app/node_modules/ordered/esm/sub.d.ts".into())
+        );
+    }
+
+    #[test]
+    fn node16_importer_module_format() {
+        let r = TsModuleResolver::new(
+            InMemoryHost::new(vec![
+                (
+                    "/app/node_modules/dual/package.json",
+                    r#"{ "exports": { "import": "./esm/index.js", "require": "./cjs/index.js" } }"#,
+                ),
+                ("/app/node_modules/dual/esm/index.d.ts", ""),
+                ("/app/node_modules/dual/cjs/index.d.ts", ""),
+                ("/app/esm/package.json", r#"{ "type": "module" }"#),
+                ("/app/cjs/package.json", r#"{ "type": "commonjs" }"#),
+            ]),
+            CompilerOptions {
+                module_resolution: ModuleResolutionKind::Node16,
+                ..CompilerOptions::default()
+            },
+        );
+        let esm = Some("/app/node_modules/dual/esm/index.d.ts".to_string());
+        let cjs = Some("/app/node_modules/dual/cjs/index.d.ts".to_string());
+        assert_eq!(r.resolve("/app/src/main.ts", "dual"), cjs);
+        assert_eq!(r.resolve("/app/src/main.mts", "dual"), esm);
+        assert_eq!(r.resolve("/app/esm/main.ts", "dual"), esm);
+        assert_eq!(r.resolve("/app/esm/main.cts", "dual"), cjs);
+        assert_eq!(r.resolve("/app/cjs/main.ts", "dual"), cjs);
+
+        let bundler = resolver(vec![
+            (
+                "/app/node_modules/dual/package.json",
+                r#"{ "exports": { "require": "./cjs/index.js", "import": "./esm/index.js" } }"#,
+            ),
+            ("/app/node_modules/dual/esm/index.d.ts", ""),
+            ("/app/node_modules/dual/cjs/index.d.ts", ""),
+        ]);
+        assert_eq!(bundler.resolve("/app/src/main.cts", "dual"), esm);
+    }
+
+    #[test]
+    fn package_types_versions() {
+        let r = resolver(vec![
+            (
+                "/app/node_modules/versioned/package.json",
+                r#"{
+                    "types": "./index.d.ts",
+                    "typesVersions": { ">=4.0": { "*": ["ts4/*"] } }
+                }"#,
+            ),
+            ("/app/node_modules/versioned/ts4/index.d.ts", ""),
+            ("/app/node_modules/versioned/ts4/sub.d.ts", ""),
+            ("/app/node_modules/versioned/index.d.ts", ""),
+        ]);
+        let from = "/app/src/main.ts";
+        assert_eq!(
+            r.resolve(from, "versioned/sub"),
+            Some("/app/node_modules/versioned/ts4/sub.d.ts".into())
+        );
+        assert_eq!(
+            r.resolve(from, "versioned"),
+            Some("/app/node_modules/versioned/ts4/index.d.ts".into())
+        );
+    }
+
+    #[test]
+    fn package_types_versions_ranges() {
+        let r = resolver(vec![
+            (
+                "/app/node_modules/old/package.json",
+                r#"{
+                    "types": "./index.d.ts",
+                    "typesVersions": {
+                        "<4.0": { "*": ["ts3/*"] },
+                        ">=4.5 <5 || ~5.4": { "*": ["ts5/*"] },
+                        "*": { "*": ["any/*"] }
+                    }
+                }"#,
+            ),
+            ("/app/node_modules/old/ts3/sub.d.ts", ""),
+            ("/app/node_modules/old/ts5/sub.d.ts", ""),
+            ("/app/node_modules/old/any/sub.d.ts", ""),
+            (
+                "/app/node_modules/legacy/package.json",
+                r#"{ "typesVersions": { "<3.0": { "*": ["ts2/*"] } } }"#,
+            ),
+            ("/app/node_modules/legacy/ts2/sub.d.ts", ""),
+            ("/app/node_modules/legacy/sub.d.ts", ""),
+        ]);
+        let from = "/app/src/main.ts";
+        assert_eq!(
+            r.resolve(from, "old/sub"),
+            Some("/app/node_modules/old/ts5/sub.d.ts".into())
+        );
+        assert_eq!(
+            r.resolve(from, "legacy/sub"),
+            Some("/app/node_modules/legacy/sub.d.ts".into())
+        );
+    }
+
+    #[test]
+    fn fs_module_resolver_adapter() {
+        let r = Rc::new(resolver(vec![("/app/src/a.ts", "")]));
+        let mut file_resolver = TsFileModuleResolver {
+            resolver: r,
+            current_file: BffFileName::new("/app/src/main.ts".into()),
+        };
+        assert_eq!(
+            file_resolver.resolve_import("./a.js"),
+            Some(BffFileName::new("/app/src/a.ts".into()))
+        );
+    }
+}
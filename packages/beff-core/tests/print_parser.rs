@@ -1,57 +1,48 @@
+mod common;
+
 #[cfg(test)]
 mod tests {
-    use std::{collections::BTreeSet, rc::Rc};
-
+    use crate::common::{parse_files, settings, TestFileManager};
     use beff_core::{
-        import_resolver::{parse_and_bind, FsModuleResolver},
-        parser_extractor::BuiltDecoder,
-        print::printer::ToWritableModules,
-        schema_changes::print_ts_types,
-        BeffUserSettings, BffFileName, EntryPoints, ExtractResult, FileManager, ParsedModule,
+        parser_extractor::BuiltDecoder, print::printer::ToWritableModules,
+        schema_changes::print_ts_types, BffFileName, EntryPoints, ExtractResult, UnknownKeys,
         Validator,
     };
-    use swc_common::{Globals, GLOBALS};
     use swc_ecma_ast::TsType;
-    struct TestFileManager {
-        pub f: Rc<ParsedModule>,
-    }
 
-    impl FileManager for TestFileManager {
-        fn get_or_fetch_file(&mut self, _name: &BffFileName) -> Option<Rc<ParsedModule>> {
-            Some(self.f.clone())
-        }
-
-        fn get_existing_file(&self, _name: &BffFileName) -> Option<Rc<ParsedModule>> {
-            Some(self.f.clone())
-        }
+    fn parse_api_files(files: &[(&str, &str)]) -> ExtractResult {
+        parse_api_entries(files, &[files[0].0])
     }
-
-    struct TestResolver {}
-    impl FsModuleResolver for TestResolver {
-        fn resolve_import(&mut self, _module_specifier: &str) -> Option<BffFileName> {
-            None
-        }
-    }
-    fn parse_str(content: &str) -> Rc<ParsedModule> {
-        let mut resolver = TestResolver {};
-        let file_name = BffFileName::new("file.ts".into());
-        GLOBALS.set(&Globals::new(), || {
-            let res = parse_and_bind(&mut resolver, &file_name, content);
-            res.expect("failed to parse")
-        })
+    fn parse_api_entries(files: &[(&str, &str)], entries: &[&str]) -> ExtractResult {
+        let mut man = TestFileManager {
+            files: parse_files(files),
+        };
+        let entry = EntryPoints {
+            parser_entry_points: entries
+                .iter()
+                .map(|it| BffFileName::new(it.to_string()))
+                .collect(),
+            schema_entry_point: None,
+            routes_entry_point: None,
+            settings: settings(UnknownKeys::default()),
+        };
+        beff_core::extract(&mut man, entry)
     }
-    fn parse_api(it: &str) -> ExtractResult {
-        let f = parse_str(it);
-        let mut man = TestFileManager { f };
+    fn parse_routes_files(files: &[(&str, &str)]) -> ExtractResult {
+        let mut man = TestFileManager {
+            files: parse_files(files),
+        };
         let entry = EntryPoints {
-            parser_entry_point: Some(BffFileName::new("file.ts".into())),
+            parser_entry_points: vec![],
             schema_entry_point: None,
-            settings: BeffUserSettings {
-                custom_formats: BTreeSet::from_iter(vec!["password".to_string()]),
-            },
+            routes_entry_point: Some(BffFileName::new(files[0].0.into())),
+            settings: settings(UnknownKeys::default()),
         };
         beff_core::extract(&mut man, entry)
     }
+    fn parse_api(it: &str) -> ExtractResult {
+        parse_api_files(&[("file.ts", it)])
+    }
     fn as_typescript_string_(validators: &[&Validator], built_decoders: &[BuiltDecoder]) -> String {
         let mut vs: Vec<(String, TsType)> = vec![];
 
@@ -72,21 +63,77 @@ mod tests {
         print_ts_types(vs)
     }
     fn ok(from: &str) -> String {
-        let p = parse_api(from);
+        ok_files(&[("file.ts", from)])
+    }
+    fn ok_files(files: &[(&str, &str)]) -> String {
+        let p = parse_api_files(files);
         let errors = p.errors();
 
         if !errors.is_empty() {
             panic!("errors: {:?}", errors);
         }
         match p.parser {
-            Some(v) => as_typescript_string_(
-                &v.validators.iter().collect::<Vec<_>>(),
-                v.built_decoders.as_ref().unwrap_or(&vec![]),
-            ),
+            Some(v) => {
+                let built_decoders = v
+                    .built_parsers
+                    .into_iter()
+                    .flat_map(|it| it.built_decoders)
+                    .collect::<Vec<_>>();
+                as_typescript_string_(&v.validators.iter().collect::<Vec<_>>(), &built_decoders)
+            }
             None => panic!(),
         }
     }
 
+    fn built_parsers(files: &[(&str, &str)], entries: &[&str]) -> String {
+        let p = parse_api_entries(files, entries);
+        let errors = p.errors();
+
+        if !errors.is_empty() {
+            panic!("errors: {:?}", errors);
+        }
+        let m = p.to_module().expect("should be able to emit module");
+        format!(
+            "{}\n\n{}",
+            m.js_validators,
+            m.js_built_parsers.expect("should emit parsers")
+        )
+    }
+    fn declarations(from: &str) -> String {
+        let p = parse_api(from);
+        let errors = p.errors();
+
+        if !errors.is_empty() {
+            panic!("errors: {:?}", errors);
+        }
+        let m = p.to_module().expect("should be able to emit module");
+        m.ts_declarations.expect("should emit declarations")
+    }
+    fn routes(from: &str) -> String {
+        let p = parse_routes_files(&[("file.ts", from)]);
+        let errors = p.errors();
+
+        if !errors.is_empty() {
+            panic!("errors: {:?}", errors);
+        }
+        let m = p.to_module().expect("should be able to emit module");
+        let openapi: serde_json::Value =
+            serde_json::from_str(&m.openapi_paths.expect("should emit openapi paths"))
+                .expect("should be valid json");
+        format!(
+            "{}\n\n{}",
+            m.js_built_routes.expect("should emit routes"),
+            serde_json::to_string_pretty(&openapi).expect("should print json")
+        )
+    }
+    fn routes_errors(from: &str) -> Vec<String> {
+        let p = parse_routes_files(&[("file.ts", from)]);
+        p.errors()
+            .iter()
+            .map(|it| it.cause.message.to_string())
+            .collect()
+    }
+
     fn decoder(from: &str) -> String {
         let p = parse_api(from);
         let errors = p.errors();
@@ -99,6 +146,7 @@ mod tests {
                 let res = ExtractResult {
                     parser: Some(v),
                     schema: None,
+                    routes: None,
                 };
                 let m = res.to_module().expect("should be able to emit module");
                 m.js_validators
@@ -292,6 +340,45 @@ mod tests {
       "#));
     }
     #[test]
+    fn ok_mapped_type_modifiers() {
+        insta::assert_snapshot!(ok(r#"
+        type Obj = { a: string; b?: number; c: string | null };
+        export type Opt = { +readonly [K in keyof Obj]+?: Obj[K] };
+        export type Req = { -readonly [K in keyof Obj]-?: Obj[K] };
+        export type Same = { [K in keyof Obj]: Obj[K] };
+        parse.buildParsers<{ Opt: Opt; Req: Req; Same: Same }>();
+      "#));
+    }
+    #[test]
+    fn ok_mapped_type_template_keeps_null() {
+        insta::assert_snapshot!(ok(r#"
+        type Obj = { a: string; b?: number };
+        export type Nullable = { [K in keyof Obj]: string | null };
+        export type Wrapped = { [K in keyof Obj]: Obj[K] | null };
+        parse.buildParsers<{ Nullable: Nullable; Wrapped: Wrapped }>();
+      "#));
+    }
+    #[test]
+    fn ok_mapped_type_as() {
+        insta::assert_snapshot!(ok(r#"
+        type Obj = { a: string; b: number; c: boolean };
+        export type Renamed = { [K in keyof Obj as `get_${K}`]: Obj[K] };
+        export type Filtered = { [K in keyof Obj as K extends "b" ? never : K]: Obj[K] };
+        export type Excluded = { [K in keyof Obj as Exclude<K, "a">]: Obj[K] };
+        parse.buildParsers<{ Renamed: Renamed; Filtered: Filtered; Excluded: Excluded }>();
+      "#));
+    }
+    #[test]
+    fn ok_mapped_type_string_intrinsics() {
+        insta::assert_snapshot!(ok(r#"
+        type Obj = { name: string; age: number };
+        export type Getters = { [K in keyof Obj as `get${Capitalize<K>}`]: Obj[K] };
+        export type Upper = { [K in keyof Obj as Uppercase<K>]: Obj[K] };
+        export type Cases = Lowercase<"A" | "B"> | Uncapitalize<"Cd">;
+        parse.buildParsers<{ Getters: Getters; Upper: Upper; Cases: Cases }>();
+      "#));
+    }
+    #[test]
     fn ok_mapped_type_repro() {
         insta::assert_snapshot!(ok(r#"
         type Obj = { a: string } & {d: string}
@@ -317,6 +404,36 @@ mod tests {
       "#));
     }
     #[test]
+    fn ok_record_number_and_tpl() {
+        insta::assert_snapshot!(ok(r#"
+        export type ByIndex = Record<number, string>;
+        export type Headers = Record<`x-${string}`, string>;
+        export type Mixed = Record<"a" | 1 | `b-${number}`, boolean>;
+        parse.buildParsers<{ ByIndex: ByIndex; Headers: Headers; Mixed: Mixed }>();
+      "#));
+    }
+    #[test]
+    fn ok_index_signature() {
+        insta::assert_snapshot!(ok(r#"
+        export type A = { a: string; [key: string]: string };
+        export interface B {
+            b: number;
+            [key: `data-${string}`]: string;
+            [key: number]: boolean;
+        }
+        parse.buildParsers<{ A: A; B: B }>();
+      "#));
+    }
+    #[test]
+    fn ok_index_signature_decoder() {
+        insta::assert_snapshot!(decoder(
+            r#"
+        export type Headers = { id: string; [key: `x-${string}`]: string };
+        parse.buildParsers<{ Headers: Headers }>();
+      "#
+        ));
+    }
+    #[test]
     fn ok_array_spread() {
         insta::assert_snapshot!(ok(r#"
         const Arr1 = ["a", "b"] as const
@@ -367,6 +484,613 @@ mod tests {
       "#));
     }
     #[test]
+    fn ok_typeof_object_spread_and_keys() {
+        insta::assert_snapshot!(ok(r#"
+        const KEY = "computed"
+        const BASE = { a: 1, b: "b" } as const
+        const CONFIG = {
+            ...BASE,
+            b: true,
+            [KEY]: "c",
+            ["literal"]: 2,
+            10: "ten",
+            KEY,
+        } satisfies Record<string, unknown>
+        const ROLES = { list: ["admin", "user"] as const }
+        const PREFIX = "app"
+        const TOPIC = `${PREFIX}-${ROLES.list[0]}` as const
+        const TOPICS = [`${PREFIX}-a`, `${PREFIX}-${KEY}`, `v${1}`] as const
+        type Config = typeof CONFIG
+        type Role = typeof ROLES.list[number]
+        type Topic = typeof TOPIC
+        type Topics = typeof TOPICS[number]
+        parse.buildParsers<{ Config: Config; Role: Role; Topic: Topic; Topics: Topics }>();
+      "#));
+    }
+    #[test]
+    fn ok_typeof_let_is_widened() {
+        insta::assert_snapshot!(ok(r#"
+        let greeting = "hello"
+        var count = 1
+        const LITERAL = "hello"
+        export let exported = true
+        type Greeting = typeof greeting
+        type Count = typeof count
+        type Literal = typeof LITERAL
+        type Exported = typeof exported
+        type Topic = `${typeof greeting}-a`
+        parse.buildParsers<{
+            Greeting: Greeting;
+            Count: Count;
+            Literal: Literal;
+            Exported: Exported;
+            Topic: Topic;
+        }>();
+      "#));
+    }
+    #[test]
+    fn ok_typeof_call() {
+        insta::assert_snapshot!(ok(r#"
+        type User = { id: string };
+        function getUser(): User {
+            return { id: "a" };
+        }
+        const getIds = (): string[] => [];
+        const USER = getUser();
+        const CONFIG = { ids: getIds(), count: 1 as number };
+        type U = typeof USER
+        type C = typeof CONFIG
+        parse.buildParsers<{ U: U; C: C }>();
+      "#));
+    }
+    #[test]
+    fn ok_import_type() {
+        insta::assert_snapshot!(ok_files(&[
+            (
+                "file.ts",
+                r#"
+        type Inline = import("./models").User;
+        type Nested = import("./models").Api.Page<string>;
+        type Roles = typeof import("./models").ROLES[number];
+        type Models = typeof import("./models");
+        export type Direct = import("./api").Page<number>;
+        parse.buildParsers<{
+            Inline: Inline;
+            Nested: Nested;
+            Roles: Roles;
+            Models: Models;
+            Direct: Direct;
+        }>();
+      "#
+            ),
+            (
+                "models.ts",
+                r#"
+        export * as Api from "./api";
+        export type User = { id: string };
+        export const ROLES = ["admin", "user"] as const;
+      "#
+            ),
+            (
+                "api.ts",
+                r#"
+        export type Page<T> = { items: T[] };
+      "#
+            ),
+        ]));
+    }
+    #[test]
+    fn ok_namespace() {
+        insta::assert_snapshot!(ok(r#"
+        namespace Api {
+            export type Id = string;
+            export namespace Users {
+                export type User = { id: Id; role: Role };
+            }
+            export enum Role {
+                Admin = "admin",
+                User = "user",
+            }
+            export const LIMITS = { max: 10 } as const;
+        }
+        namespace Api {
+            export type Page<T> = { items: T[] };
+        }
+        namespace Deep.Inner {
+            export type Flag = boolean;
+        }
+        declare namespace Ambient {
+            type Implicit = number;
+        }
+        declare module "ext" {
+            export type External = { ext: string };
+        }
+        import * as Ext from "ext";
+        import { External } from "ext";
+        type ApiUser = Api.Users.User;
+        type Admin = Api.Role.Admin;
+        type Users = Api.Page<ApiUser>;
+        type Max = typeof Api.LIMITS.max;
+        parse.buildParsers<{
+            ApiUser: ApiUser;
+            Admin: Admin;
+            Users: Users;
+            Max: Max;
+            Flag: Deep.Inner.Flag;
+            Implicit: Ambient.Implicit;
+            Ext: Ext.External;
+            External: External;
+        }>();
+      "#));
+    }
+    #[test]
+    fn ok_namespace_merged_references() {
+        insta::assert_snapshot!(ok(r#"
+        namespace Api {
+            export type User = { id: string };
+            export const ROLE = "admin";
+        }
+        namespace Api {
+            export type Extra = { u: User; role: typeof ROLE };
+            export namespace Inner {
+                export type Wrapped = { extra: Extra; user: User };
+            }
+        }
+        namespace Api {
+            type Local = { inner: Inner.Wrapped };
+            export type Page = { items: Local[] };
+        }
+        parse.buildParsers<{ Extra: Api.Extra; Page: Api.Page }>();
+      "#));
+    }
+    #[test]
+    fn ok_namespace_import() {
+        insta::assert_snapshot!(ok_files(&[
+            (
+                "file.ts",
+                r#"
+        import { Models } from "./models";
+        type Config = typeof Models.CONFIG;
+        parse.buildParsers<{ Account: Models.Account; Config: Config }>();
+      "#
+            ),
+            (
+                "models.ts",
+                r#"
+        namespace Models {
+            export type Account = { owner: string };
+            export const CONFIG = { retries: 3 };
+        }
+        export { Models };
+      "#
+            ),
+        ]));
+    }
+    #[test]
+    fn ok_same_name_in_different_files() {
+        insta::assert_snapshot!(ok_files(&[
+            (
+                "file.ts",
+                r#"
+        import { Order } from "./orders/types";
+        import { User } from "./users/types";
+        type Status = "draft" | "published";
+        parse.buildParsers<{ Order: Order; User: User; Status: Status }>();
+      "#
+            ),
+            (
+                "orders/types.ts",
+                r#"
+        type Status = "pending" | "shipped";
+        export type Order = { status: Status };
+      "#
+            ),
+            (
+                "users/types.ts",
+                r#"
+        type Status = "active" | "banned";
+        export type User = { status: Status };
+      "#
+            ),
+        ]));
+    }
+    #[test]
+    fn ok_same_name_in_namespaces() {
+        insta::assert_snapshot!(ok(r#"
+        namespace Api {
+            export namespace Users {
+                export type User = { id: string };
+            }
+            export namespace Admins {
+                export type User = { id: string; admin: true };
+            }
+        }
+        type User = Api.Users.User;
+        parse.buildParsers<{ User: User; Admin: Api.Admins.User }>();
+      "#));
+    }
+    #[test]
+    fn ok_declaration_files() {
+        insta::assert_snapshot!(ok_files(&[
+            (
+                "file.ts",
+                r#"
+        import type { Money, Currency } from "@acme/types";
+        import Legacy = require("legacy");
+        import Config = require("legacy-config");
+        import Client = require("legacy-client");
+        type Default = typeof import("@acme/types").DEFAULT_CURRENCY;
+        parse.buildParsers<{
+            Money: Money;
+            Currency: Currency;
+            Default: Default;
+            Options: Legacy.Options;
+            Config: Config;
+            Client: Client;
+        }>();
+      "#
+            ),
+            (
+                "node_modules/@acme/types/index.d.ts",
+                r#"
+        export * from "acme-money";
+      "#
+            ),
+            (
+                "node_modules/acme-money/index.d.ts",
+                r#"
+        export interface Money {
+            amount: number;
+        }
+        export interface Money {
+            currency: Currency;
+        }
+        export type Currency = "EUR" | "USD";
+        export declare const DEFAULT_CURRENCY: Currency;
+      "#
+            ),
+            (
+                "node_modules/legacy/index.d.ts",
+                r#"
+        declare namespace Legacy {
+            interface Options {
+                verbose: boolean;
+            }
+        }
+        export = Legacy;
+      "#
+            ),
+            (
+                "node_modules/legacy-config/index.d.ts",
+                r#"
+        type Level = "debug" | "info";
+        interface Config {
+            level: Level;
+        }
+        export = Config;
+      "#
+            ),
+            (
+                "node_modules/legacy-client/index.d.ts",
+                r#"
+        declare class Client {
+            url: string;
+            retries?: number;
+        }
+        export = Client;
+      "#
+            ),
+        ]));
+    }
+    #[test]
+    fn ok_ambient_module_in_other_file() {
+        insta::assert_snapshot!(ok_files(&[
+            (
+                "file.ts",
+                r#"
+        import type { User } from "pkg";
+        import * as Pkg from "pkg";
+        parse.buildParsers<{
+            User: User;
+            Role: Pkg.Role;
+        }>();
+      "#
+            ),
+            (
+                "types/pkg.d.ts",
+                r#"
+        declare module "pkg" {
+            export type Role = "admin" | "user";
+            export interface User {
+                name: string;
+                role: Role;
+            }
+        }
+      "#
+            ),
+        ]));
+    }
+    #[test]
+    fn fail_export_assignment_hides_other_declarations() {
+        let errors = parse_api_files(&[
+            (
+                "file.ts",
+                r#"
+        import type { Level } from "legacy-config";
+        parse.buildParsers<{ Level: Level }>();
+      "#,
+            ),
+            (
+                "node_modules/legacy-config/index.d.ts",
+                r#"
+        type Level = "debug" | "info";
+        interface Config {
+            level: Level;
+        }
+        export = Config;
+      "#,
+            ),
+        ])
+        .errors()
+        .iter()
+        .map(|it| it.cause.message.to_string())
+        .collect::<Vec<_>>();
+        assert_eq!(errors, vec!["Cannot find symbol 'Level'"]);
+    }
+    #[test]
+    fn ok_class_declarations() {
+        insta::assert_snapshot!(ok_files(&[
+            (
+                "file.ts",
+                r#"
+        import { UserDto } from "./dto";
+        class Page<T> {
+            items: T[];
+            total: number;
+        }
+        parse.buildParsers<{ UserDto: UserDto; UserPage: Page<UserDto> }>();
+      "#
+            ),
+            (
+                "dto.ts",
+                r#"
+        class BaseDto {
+            readonly id: string;
+            protected internal: string;
+        }
+        export class UserDto extends BaseDto {
+            static table: string;
+            name?: string;
+            private secret: string;
+            #hidden: string;
+            constructor(public email: string, private password: string) {
+                super();
+            }
+            greet(): string {
+                return this.name ?? "";
+            }
+            get upper(): string {
+                return this.email.toUpperCase();
+            }
+        }
+      "#
+            ),
+        ]));
+    }
+    #[test]
+    fn ok_class_field_initializers() {
+        insta::assert_snapshot!(ok(r#"
+        class Counter {
+            count = 0;
+            label = "counter";
+            enabled = false;
+            readonly kind = "counter";
+            constructor(public step = 1) {}
+        }
+        parse.buildParsers<{ Counter: Counter }>();
+      "#));
+    }
+    #[test]
+    fn ok_function_optional_params() {
+        insta::assert_snapshot!(ok(r#"
+        type Optional = (a: string, b?: number) => void;
+        function withDefault(a: string, b = 1, ...rest: boolean[]): void {}
+        function defaultFirst(a = "", b: number): void {}
+        parse.buildParsers<{
+            OptionalArgs: Parameters<Optional>;
+            WithDefaultArgs: Parameters<typeof withDefault>;
+            DefaultFirstArgs: Parameters<typeof defaultFirst>;
+        }>();
+      "#));
+    }
+    #[test]
+    fn ok_function_signatures() {
+        insta::assert_snapshot!(ok_files(&[
+            (
+                "file.ts",
+                r#"
+        import { getUser } from "./handlers";
+        import * as H from "./handlers";
+        type Handler = (id: string, ...tags: number[]) => boolean;
+        function search(this: Window, query: string, page = 1, limit?: number): string[] {
+            return [];
+        }
+        const remove = ({ id }: { id: string }): void => {};
+        parse.buildParsers<{
+            GetUserArgs: Parameters<typeof getUser>;
+            GetUserResult: Awaited<ReturnType<typeof getUser>>;
+            CreateArgs: Parameters<typeof H.createUser>;
+            Created: ReturnType<typeof H.createUser>;
+            SearchArgs: Parameters<typeof search>;
+            SearchResult: ReturnType<typeof search>;
+            RemoveArgs: Parameters<typeof remove>;
+            HandlerArgs: Parameters<Handler>;
+            HandlerResult: ReturnType<Handler>;
+            Wrapped: Awaited<Promise<Promise<number>>>;
+        }>();
+      "#
+            ),
+            (
+                "handlers.ts",
+                r#"
+        type User = { id: string; name: string };
+        export async function getUser(id: string): Promise<User> {
+            return { id, name: "" };
+        }
+        export const createUser = (name: string): User => ({ id: "", name });
+      "#
+            ),
+        ]));
+    }
+    #[test]
+    fn ok_awaited_promise_aliases() {
+        insta::assert_snapshot!(ok_files(&[
+            (
+                "file.ts",
+                r#"
+        import { Pending } from "./pending";
+        type User = { id: string };
+        type P = Promise<User>;
+        type Async<T> = Promise<T>;
+        type Nested = (P);
+        parse.buildParsers<{
+            Aliased: Awaited<P>;
+            Generic: Awaited<Async<number>>;
+            Nested: Awaited<Nested>;
+            Imported: Awaited<Pending>;
+            Plain: Awaited<User>;
+        }>();
+      "#
+            ),
+            (
+                "pending.ts",
+                r#"
+        type Status = "pending" | "done";
+        export type Pending = Promise<Status[]>;
+      "#
+            ),
+        ]));
+    }
+    #[test]
+    fn ok_routes() {
+        insta::assert_snapshot!(routes(
+            r#"
+        type UserId = string;
+        type User = { id: UserId; name: string };
+        type Role = "admin" | "user";
+        routes.buildRoutes<{
+            "GET /users/{id}": { params: { id: UserId }; response: User };
+            "GET /users": {
+                query: { role?: Role; page: number; tags: string[] };
+                response: User[];
+            };
+            "POST /users": { body: Omit<User, "id">; response: User; status: 201 };
+            "DELETE /users/{id}": { params: { id: string }; response: null };
+        }>();
+      "#
+        ));
+    }
+    #[test]
+    fn fail_routes() {
+        assert_eq!(
+            routes_errors(
+                r#"
+        type User = { id: string };
+        routes.buildRoutes<{
+            "GET /users/{id}/{slug}/{page}": {
+                params: { id: User; extra: string; page?: number };
+                response: User;
+            };
+            "FETCH /users": { response: User };
+            "/users": { response: User };
+            "PUT /users": { query: { filter: { name: string } }; body: User };
+            "PATCH /users": { body: User; response: User; status: "created" };
+        }>();
+      "#
+            ),
+            vec![
+                "Path parameter 'slug' is not declared in params",
+                "Param 'extra' is not in the route path",
+                "Parameter 'id' should be a string, number or boolean",
+                "Path parameter 'page' cannot be optional",
+                "Invalid HTTP method 'FETCH'",
+                "Route key should be a string with a method and a path, ie: \"GET /users/{id}\"",
+                "Parameter 'filter' should be a string, number or boolean",
+                "Route must have a response",
+                "Route status should be a number literal between 100 and 599, ie: 201",
+            ]
+        );
+    }
+    #[test]
+    fn fail_multiple_parser_entries() {
+        let p = parse_api_entries(
+            &[
+                (
+                    "users.ts",
+                    r#"
+        export const A = parse.buildParsers<{ Id: string }>({ id: "users" });
+        export const B = parse.buildParsers<{ Id: number }>();
+      "#,
+                ),
+                (
+                    "orders.ts",
+                    r#"
+        export const C = parse.buildParsers<{ Id: number }>({ id: "users" });
+        export const D = parse.buildParsers<{ Other: string }>({ id: 1, unknownKeys: "reject" });
+      "#,
+                ),
+            ],
+            &["users.ts", "orders.ts"],
+        );
+        let errors: Vec<_> = p
+            .errors()
+            .iter()
+            .map(|it| it.cause.message.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "unknownKeys must be \"strict\", \"strip\" or \"passthrough\"",
+                "The id of a buildParsers call must be a string literal",
+                "buildParsers is called more than once, each call should have an id",
+                "Another buildParsers call has the id 'users'",
+                "buildParsers is called more than once, each call should have an id",
+            ]
+        );
+    }
+    #[test]
+    fn ok_multiple_parser_entries() {
+        insta::assert_snapshot!(built_parsers(
+            &[
+                (
+                    "users.ts",
+                    r#"
+        import { User } from "./shared";
+        export const UserParsers = parse.buildParsers<{ User: User; Users: User[] }>({ id: "users" });
+        export const IdParsers = parse.buildParsers<{ Id: User["id"] }>({ id: "userIds" });
+      "#
+                ),
+                (
+                    "orders.ts",
+                    r#"
+        import { User } from "./shared";
+        type Order = { id: string; buyer: User };
+        export const OrderParsers = parse.buildParsers<{ Order: Order; User: User; Id: number }>({
+            id: "orders",
+        });
+      "#
+                ),
+                (
+                    "shared.ts",
+                    r#"
+        export type User = { id: string; name: string };
+      "#
+                ),
+            ],
+            &["users.ts", "orders.ts"]
+        ));
+    }
+    #[test]
     fn ok_enum_member() {
         insta::assert_snapshot!(ok(r#"
         export enum Enum {
@@ -391,6 +1115,43 @@ mod tests {
       "#));
     }
     #[test]
+    fn ok_enum_numeric() {
+        insta::assert_snapshot!(ok(r#"
+        enum Auto {
+            A,
+            B,
+            C = 10,
+            D,
+        }
+        const enum Flags {
+            None = 0,
+            Read = 1 << 0,
+            Write = 1 << 1,
+            ReadWrite = Read | Write,
+            Prefixed = "f-" + "x",
+        }
+        type Second = Auto.B
+        type RW = Flags.ReadWrite
+        parse.buildParsers<{ Auto: Auto; Flags: Flags; Second: Second; RW: RW }>();
+      "#));
+    }
+    #[test]
+    fn ok_typeof_enum() {
+        insta::assert_snapshot!(ok(r#"
+        enum Color {
+            Red = "red",
+            Green = "green",
+        }
+        enum Level {
+            Low,
+            High,
+        }
+        type ColorObj = typeof Color
+        type LevelKey = keyof typeof Level
+        parse.buildParsers<{ ColorObj: ColorObj; LevelKey: LevelKey }>();
+      "#));
+    }
+    #[test]
     fn ok_discriminated_union() {
         insta::assert_snapshot!(decoder(
             r#"
@@ -506,4 +1267,301 @@ mod tests {
         parse.buildParsers<{ IX: IX }>();
       "#));
     }
+    #[test]
+    fn ok_declarations_resolve_type_operators() {
+        insta::assert_snapshot!(declarations(
+            r#"
+        type User = { id: number; name: string; email?: string; friends: User[]; createdAt: Date };
+        type Keys = "a" | "b";
+        type Flags = { [K in Keys]: boolean };
+        type IsString<T> = T extends string ? "yes" : "no";
+        parse.buildParsers<{
+            User: User;
+            Preview: Pick<User, "id" | "name">;
+            Patch: Partial<Omit<User, "id">>;
+            Flags: Flags;
+            Answer: IsString<"x">;
+        }>();
+      "#
+        ));
+    }
+    #[test]
+    fn ok_declarations_name_conflict() {
+        insta::assert_snapshot!(declarations(
+            r#"
+        type Item = { id: number };
+        type Order = { items: Item[] };
+        parse.buildParsers<{ Item: string; Order: Order }>();
+      "#
+        ));
+    }
+    #[test]
+    fn ok_declarations_same_name_in_calls() {
+        insta::assert_snapshot!(declarations(
+            r#"
+        type User = { name: string };
+        export const A = parse.buildParsers<{ Id: string; User: User }>({ id: "a" });
+        export const B = parse.buildParsers<{ Id: number; User: User }>({ id: "b" });
+      "#
+        ));
+    }
+    #[test]
+    fn ok_unknown_keys_per_call() {
+        insta::assert_snapshot!(built_parsers(
+            &[(
+                "file.ts",
+                r#"
+        type User = { name: string };
+        export const Inbound = parse.buildParsers<{ User: User }>({ id: "in", unknownKeys: "strict" });
+        export const Outbound = parse.buildParsers<{ User: User }>({ id: "out" });
+        export const Raw = parse.buildParsers<{ User: User }>({ id: "raw", "unknownKeys": "passthrough" });
+      "#,
+            )],
+            &["file.ts"],
+        ));
+    }
+    #[test]
+    fn ok_defaults() {
+        insta::assert_snapshot!(decoder(
+            r#"
+        type Page = {
+            /**
+             * The number of items
+             * @default 20
+             */
+            pageSize?: number;
+            sort?: Default<"asc" | "desc", "asc">;
+            /** @default ["id"] */
+            fields?: string[];
+            filter?: Default<{ archived: boolean }, { archived: false }>;
+            cursor?: string;
+        };
+        export const P = parse.buildParsers<{ Page: Page }>();
+      "#
+        ));
+    }
+    #[test]
+    fn fail_defaults() {
+        let errors = |from: &str| -> Vec<String> {
+            parse_api(from)
+                .errors()
+                .iter()
+                .map(|it| it.cause.message.to_string())
+                .collect()
+        };
+        assert_eq!(
+            errors(
+                r#"
+        type Page = {
+            /** @default twenty */
+            pageSize?: number;
+        };
+        export const P = parse.buildParsers<{ Page: Page }>();
+      "#
+            ),
+            vec!["Cannot read the @default value 'twenty' as JSON"]
+        );
+        assert_eq!(
+            errors(
+                r#"
+        type Page = { sort?: Default<string, string> };
+        export const P = parse.buildParsers<{ Page: Page }>();
+      "#
+            ),
+            vec!["Default should have a type and a literal type, ie: Default<number, 20>"]
+        );
+        let wrong_defaults = [
+            "size?: Default<number, \"20\">",
+            "sort?: Default<Sort, \"up\">",
+            "/** @default \"twenty\" */ pageSize?: number",
+            "/** @default { \"archived\": 1 } */ filter?: { archived: boolean }",
+        ];
+        for prop in wrong_defaults {
+            let from = format!(
+                r#"
+        type Sort = "asc" | "desc";
+        type Page = {{
+            {prop};
+        }};
+        export const P = parse.buildParsers<{{ Page: Page }}>();
+      "#
+            );
+            assert_eq!(
+                errors(&from),
+                vec!["Default should have a type and a literal type, ie: Default<number, 20>"],
+                "{prop}"
+            );
+        }
+    }
+    #[test]
+    fn ok_brands() {
+        insta::assert_snapshot!(decoder(
+            r#"
+        declare const orderIdBrand: unique symbol;
+        type UserId = string & { __brand: "UserId" };
+        type OrderId = string & { readonly [orderIdBrand]: true };
+        type Cents = number & { readonly __brand: unique symbol };
+        type Email = Brand<string, "Email">;
+        type User = { id: UserId; email: Email } & { readonly __brand: "User" };
+        export const P = parse.buildParsers<{
+            UserId: UserId;
+            OrderId: OrderId;
+            Cents: Cents;
+            User: User;
+        }>();
+      "#
+        ));
+    }
+    #[test]
+    fn ok_brands_declarations() {
+        insta::assert_snapshot!(declarations(
+            r#"
+        type UserId = string & { __brand: "UserId" };
+        type User = { id: UserId; friends: UserId[] };
+        export const P = parse.buildParsers<{ User: User }>();
+      "#
+        ));
+    }
+    #[test]
+    fn ok_formats_declarations() {
+        insta::assert_snapshot!(declarations(
+            r#"
+        type Password = StringFormat<"password">;
+        type Cents = NumberFormat<"Cents">;
+        type Account = { password: Password; balance: Cents };
+        export const P = parse.buildParsers<{ Account: Account }>();
+      "#
+        ));
+    }
+    #[test]
+    fn ok_number_formats() {
+        insta::assert_snapshot!(built_parsers(
+            &[(
+                "file.ts",
+                r#"
+        type Cents = NumberFormat<"Cents">;
+        type Order = { total: Cents; discount?: Cents };
+        export const P = parse.buildParsers<{ Order: Order; Paid: Exclude<Cents | "free", "free"> }>();
+      "#,
+            )],
+            &["file.ts"],
+        ));
+    }
+    #[test]
+    fn fail_number_formats() {
+        let errors = |from: &str| -> Vec<String> {
+            parse_api(from)
+                .errors()
+                .iter()
+                .map(|it| it.cause.message.to_string())
+                .collect()
+        };
+        assert_eq!(
+            errors(
+                r#"
+        export const P = parse.buildParsers<{ Latitude: NumberFormat<"Latitude"> }>();
+      "#
+            ),
+            vec!["Custom number format is not registered"]
+        );
+        assert_eq!(
+            errors(
+                r#"
+        export const P = parse.buildParsers<{ Latitude: NumberFormat<1> }>();
+      "#
+            ),
+            vec!["Invalid usage of number format type parameter"]
+        );
+    }
+    #[test]
+    fn ok_built_in_string_formats() {
+        insta::assert_snapshot!(decoder(
+            r#"
+        type Contact = {
+            id: StringFormat<"uuid">;
+            email: StringFormat<"email">;
+            website?: StringFormat<"uri">;
+            birthday: StringFormat<"date">;
+            lastSeen: StringFormat<"date-time">;
+            wakeUp: StringFormat<"time">;
+            ip: StringFormat<"ipv4"> | StringFormat<"ipv6">;
+            host: StringFormat<"hostname">;
+        };
+        export const P = parse.buildParsers<{ Contact: Contact }>();
+      "#
+        ));
+    }
+
+    #[test]
+    fn ok_codecs() {
+        insta::assert_snapshot!(decoder(
+            r#"
+        type Event = {
+            day: PlainDate;
+            startsAt?: PlainTime;
+            length: IsoDuration;
+            attachment: Uint8Array | null;
+            createdAt: Date;
+        };
+        export const P = parse.buildParsers<{ Event: Event }>();
+      "#
+        ));
+    }
+    #[test]
+    fn ok_base64url_codec() {
+        insta::assert_snapshot!(decoder(
+            r#"
+        type Session = { token: Base64Url; signature: Uint8Array };
+        export const P = parse.buildParsers<{ Session: Session }>();
+      "#
+        ));
+    }
+    #[test]
+    fn ok_codecs_declarations() {
+        insta::assert_snapshot!(declarations(
+            r#"
+        type Event = { day: PlainDate; at: PlainTime; length: IsoDuration; attachment: Uint8Array };
+        export const P = parse.buildParsers<{ Event: Event }>();
+      "#
+        ));
+    }
+    #[test]
+    fn ok_tuple_optional_and_suffix() {
+        insta::assert_snapshot!(decoder(
+            r#"
+        type Range = [from: number, to?: number];
+        type Path = [string, ...string[], boolean];
+        type Args = [string, number?, ...boolean[]];
+        export const P = parse.buildParsers<{ Range: Range; Path: Path; Args: Args }>();
+      "#
+        ));
+    }
+    #[test]
+    fn ok_tuple_optional_and_suffix_declarations() {
+        insta::assert_snapshot!(declarations(
+            r#"
+        type Range = [from: number, to?: number];
+        type Path = [string, ...rest: string[], boolean];
+        export const P = parse.buildParsers<{ Range: Range; Path: Path }>();
+      "#
+        ));
+    }
+    #[test]
+    fn fails_named_tuple_suffix_after_optional() {
+        let p = parse_api(
+            r#"
+        type Bad = [a: string, b?: number, ...c: boolean[], d: string];
+        export const P = parse.buildParsers<{ Bad: Bad }>();
+      "#,
+        );
+        let errors: Vec<String> = p
+            .errors()
+            .iter()
+            .map(|it| it.cause.message.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            vec!["Optional tuple elements must follow the required elements and precede a rest element"]
+        );
+    }
 }
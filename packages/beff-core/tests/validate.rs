@@ -0,0 +1,516 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{parsers, Parsers};
+    use beff_core::{
+        validate,
+        validate::{validate_with_options, DecodeError, ValidateOptions},
+        UnknownKeys,
+    };
+    use serde_json::{json, Value};
+
+    impl Parsers {
+        fn validate(&self, name: &str, value: Value) -> Result<(), Vec<DecodeError>> {
+            validate(&self.schemas[name], &self.validators, &value)
+        }
+        fn messages(&self, name: &str, value: Value) -> Vec<(String, String)> {
+            self.validate(name, value)
+                .expect_err("should fail")
+                .into_iter()
+                .map(|it| (it.path.join("."), it.message))
+                .collect()
+        }
+    }
+
+    fn err(path: &[&str], message: &str) -> (String, String) {
+        (path.join("."), message.to_string())
+    }
+
+    #[test]
+    fn objects_and_arrays() {
+        let p = parsers(
+            r#"
+        type User = { name: string; age?: number; tags: string[]; friend?: User };
+        parse.buildParsers<{ User: User; Users: User[] }>();
+      "#,
+        );
+        assert_eq!(
+            p.validate("User", json!({ "name": "a", "tags": [] })),
+            Ok(())
+        );
+        assert_eq!(
+            p.validate(
+                "User",
+                json!({ "name": "a", "age": null, "tags": ["x"], "extra": 1 })
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            p.messages(
+                "Users",
+                json!([{ "name": 1, "tags": ["a", 2], "friend": { "tags": "no" } }, "str"])
+            ),
+            vec![
+                err(&["[0]", "friend", "name"], "expected string"),
+                err(&["[0]", "friend", "tags"], "expected array"),
+                err(&["[0]", "name"], "expected string"),
+                err(&["[0]", "tags", "[1]"], "expected string"),
+                err(&["[1]"], "expected object"),
+            ]
+        );
+        assert_eq!(
+            p.messages("User", Value::Null),
+            vec![err(&[], "expected object")]
+        );
+    }
+
+    #[test]
+    fn unions_and_consts() {
+        let p = parsers(
+            r#"
+        type Role = "admin" | "user";
+        type Id = string | number;
+        parse.buildParsers<{ Role: Role; Id: Id; Version: 1 }>();
+      "#,
+        );
+        assert_eq!(p.validate("Role", json!("admin")), Ok(()));
+        assert_eq!(
+            p.messages("Role", json!("root")),
+            vec![err(&[], "expected one of \"admin\", \"user\"")]
+        );
+        assert_eq!(p.validate("Id", json!(1)), Ok(()));
+        let errors = p.validate("Id", json!(true)).expect_err("should fail");
+        assert_eq!(
+            serde_json::to_value(&errors).unwrap(),
+            json!([{
+                "message": "expected one of",
+                "path": [],
+                "received": true,
+                "isUnionError": true,
+                "errors": [
+                    { "message": "expected string", "path": [], "received": true },
+                    { "message": "expected number", "path": [], "received": true },
+                ],
+            }])
+        );
+        assert_eq!(p.validate("Version", json!(1)), Ok(()));
+        assert_eq!(p.validate("Version", json!("1")), Ok(()));
+        assert_eq!(
+            p.messages("Version", json!(2)),
+            vec![err(&[], "expected 1")]
+        );
+    }
+
+    #[test]
+    fn discriminated_unions() {
+        let p = parsers(
+            r#"
+        type Shape =
+            | { kind: "circle"; radius: number }
+            | { kind: "square" | "rect"; side: number };
+        parse.buildParsers<{ Shape: Shape }>();
+      "#,
+        );
+        assert_eq!(
+            p.validate("Shape", json!({ "kind": "circle", "radius": 1 })),
+            Ok(())
+        );
+        assert_eq!(
+            p.validate("Shape", json!({ "kind": "rect", "side": 1 })),
+            Ok(())
+        );
+        assert_eq!(
+            p.messages("Shape", json!({ "radius": 1 })),
+            vec![err(&[], "expected discriminator key \"kind\"")]
+        );
+        assert_eq!(
+            p.messages("Shape", json!({ "kind": "triangle" })),
+            vec![err(
+                &["kind"],
+                "expected one of \"circle\", \"rect\", \"square\""
+            )]
+        );
+        assert_eq!(
+            p.messages("Shape", json!({ "kind": "circle", "radius": "1" })),
+            vec![err(&["radius"], "expected number")]
+        );
+    }
+
+    #[test]
+    fn tuples_formats_and_codecs() {
+        let p = parsers(
+            r#"
+        type Pair = [string, number];
+        type Rest = [string, ...number[]];
+        type Route = `/users/${number}`;
+        parse.buildParsers<{
+            Pair: Pair;
+            Rest: Rest;
+            Route: Route;
+            Password: StringFormat<"password">;
+            Date: Date;
+            Big: bigint;
+        }>();
+      "#,
+        );
+        assert_eq!(p.validate("Pair", json!(["a", 1])), Ok(()));
+        assert_eq!(
+            p.messages("Pair", json!(["a", 1, 2])),
+            vec![err(&[], "tuple has too many items")]
+        );
+        assert_eq!(
+            p.messages("Rest", json!(["a", 1, "b"])),
+            vec![err(&["[2]"], "expected number")]
+        );
+        assert_eq!(p.validate("Route", json!("/users/12")), Ok(()));
+        assert_eq!(
+            p.messages("Route", json!("/posts/1")),
+            vec![err(&[], "expected string matching /users/${number}")]
+        );
+        assert_eq!(
+            p.messages("Password", json!("secret")),
+            vec![err(&[], "format \"password\" not implemented")]
+        );
+        assert_eq!(
+            p.validate("Date", json!("2023-10-01T12:30:00.000Z")),
+            Ok(())
+        );
+        assert_eq!(p.validate("Date", json!("2023-10-01")), Ok(()));
+        assert_eq!(
+            p.messages("Date", json!("yesterday")),
+            vec![err(&[], "expected ISO8061 date")]
+        );
+        assert_eq!(p.validate("Big", json!("12345678901234567890")), Ok(()));
+        assert_eq!(
+            p.messages("Big", json!(1.5)),
+            vec![err(&[], "expected bigint")]
+        );
+    }
+
+    #[test]
+    fn built_in_string_formats() {
+        let p = parsers(
+            r#"
+        parse.buildParsers<{
+            email: StringFormat<"email">;
+            uuid: StringFormat<"uuid">;
+            uri: StringFormat<"uri">;
+            date: StringFormat<"date">;
+            date_time: StringFormat<"date-time">;
+            time: StringFormat<"time">;
+            ipv4: StringFormat<"ipv4">;
+            ipv6: StringFormat<"ipv6">;
+            hostname: StringFormat<"hostname">;
+        }>();
+      "#,
+        );
+        let cases = [
+            ("email", "jane.doe@example.co.uk", "jane.doe@example"),
+            (
+                "uuid",
+                "123e4567-E89B-12d3-a456-426614174000",
+                "123e4567e89b12d3a456426614174000",
+            ),
+            ("uri", "urn:isbn:0451450523", "example.com/path"),
+            ("date", "2024-02-29", "2024-13-01"),
+            (
+                "date_time",
+                "2024-02-29T23:59:60.5+01:00",
+                "2024-02-29T23:59:60",
+            ),
+            ("time", "23:59:59Z", "24:00:00Z"),
+            ("ipv4", "192.168.0.255", "192.168.0.256"),
+            ("ipv6", "2001:db8::ff00:42:8329", "2001:db8:::1"),
+            ("hostname", "api-1.example.com", "-api.example.com"),
+        ];
+        for (name, valid, invalid) in cases {
+            assert_eq!(p.validate(name, json!(valid)), Ok(()), "{name}: {valid}");
+            assert_eq!(
+                p.messages(name, json!(invalid)),
+                vec![err(
+                    &[],
+                    &format!(
+                        "expected string with format {}",
+                        json!(name.replace('_', "-"))
+                    )
+                )],
+                "{name}: {invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn optional_and_suffix_tuple_items() {
+        let p = parsers(
+            r#"
+        type Range = [from: number, to?: number];
+        type Path = [string, ...string[], boolean];
+        parse.buildParsers<{ Range: Range; Path: Path }>();
+      "#,
+        );
+        assert_eq!(p.validate("Range", json!([1])), Ok(()));
+        assert_eq!(p.validate("Range", json!([1, 2])), Ok(()));
+        assert_eq!(
+            p.messages("Range", json!([1, "2"])),
+            vec![err(&["[1]"], "expected number")]
+        );
+        assert_eq!(
+            p.messages("Range", json!([1, 2, 3])),
+            vec![err(&[], "tuple has too many items")]
+        );
+        assert_eq!(p.validate("Path", json!(["a", true])), Ok(()));
+        assert_eq!(p.validate("Path", json!(["a", "b", "c", false])), Ok(()));
+        assert_eq!(
+            p.messages("Path", json!(["a", "b", 1])),
+            vec![err(&["[2]"], "expected boolean")]
+        );
+        assert_eq!(
+            p.messages("Path", json!(["a"])),
+            vec![err(&["[1]"], "expected boolean")]
+        );
+    }
+
+    #[test]
+    fn spread_tuples() {
+        let p = parsers(
+            r#"
+        type Pair = [number, boolean];
+        type Names = string[];
+        type Inline = [string, ...[number, boolean]];
+        type Aliased = [...Pair, string];
+        type Nested = [...[string, ...Names], number];
+        parse.buildParsers<{ Inline: Inline; Aliased: Aliased; Nested: Nested }>();
+      "#,
+        );
+        assert_eq!(p.validate("Inline", json!(["a", 1, true])), Ok(()));
+        assert_eq!(
+            p.messages("Inline", json!(["a", [1, true]])),
+            vec![
+                err(&["[1]"], "expected number"),
+                err(&["[2]"], "expected boolean")
+            ]
+        );
+        assert_eq!(p.validate("Aliased", json!([1, false, "a"])), Ok(()));
+        assert_eq!(
+            p.messages("Aliased", json!([1, false, "a", "b"])),
+            vec![err(&[], "tuple has too many items")]
+        );
+        assert_eq!(p.validate("Nested", json!(["a", 1])), Ok(()));
+        assert_eq!(p.validate("Nested", json!(["a", "b", "c", 1])), Ok(()));
+        assert_eq!(
+            p.messages("Nested", json!(["a", ["b"], 1])),
+            vec![err(&["[1]"], "expected string")]
+        );
+    }
+
+    #[test]
+    fn numeric_keys() {
+        let p = parsers(
+            r#"
+        type ByIndex = Record<number, string>;
+        type Scaled = `x${number}`;
+        parse.buildParsers<{ ByIndex: ByIndex; Scaled: Scaled }>();
+      "#,
+        );
+        assert_eq!(
+            p.validate(
+                "ByIndex",
+                json!({ "1": "a", "-1": "b", "1e3": "c", "0.5": "d" })
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            p.messages("ByIndex", json!({ "-1": 1, "1e3": 2 })),
+            vec![
+                err(&["-1"], "expected string"),
+                err(&["1e3"], "expected string")
+            ]
+        );
+        for ok in ["x1", "x-1", "x+2.5", "x.5", "x1e-3"] {
+            assert_eq!(p.validate("Scaled", json!(ok)), Ok(()), "{ok}");
+        }
+        assert!(p.validate("Scaled", json!("x-")).is_err());
+        assert!(p.validate("Scaled", json!("x")).is_err());
+    }
+
+    #[test]
+    fn computed_keys_are_brands_only_for_unique_symbols() {
+        let p = parsers(
+            r#"
+        const NAME = "name";
+        declare const idBrand: unique symbol;
+        type Named = { id: number } & { [NAME]: string };
+        type Id = string & { readonly [idBrand]: true };
+        parse.buildParsers<{ Named: Named; Id: Id }>();
+      "#,
+        );
+        assert_eq!(p.validate("Named", json!({ "id": 1, "name": "a" })), Ok(()));
+        assert_eq!(
+            p.messages("Named", json!({ "id": 1, "name": 2 })),
+            vec![err(&["name"], "expected string")]
+        );
+        assert_eq!(p.validate("Id", json!("a")), Ok(()));
+    }
+
+    #[test]
+    fn codecs() {
+        let p = parsers(
+            r#"
+        parse.buildParsers<{
+            date: PlainDate;
+            time: PlainTime;
+            duration: IsoDuration;
+            base64: Uint8Array;
+            base64url: Base64Url;
+        }>();
+      "#,
+        );
+        let cases = [
+            ("date", "2024-02-29", "2023-02-29"),
+            ("date", "2024-12-31", "2024-12-31T00:00:00Z"),
+            ("time", "23:59:59.125", "24:00:00"),
+            ("time", "00:00:00", "12:00:00Z"),
+            ("duration", "P1Y2M3W4DT5H6M7.5S", "P1DT"),
+            ("duration", "PT36H", "P1M1Y"),
+            ("base64", "aGVsbG8=", "aGVsbG8=="),
+            ("base64", "-_8", "a"),
+            ("base64url", "-_8", "a=b"),
+        ];
+        for (name, valid, invalid) in cases {
+            assert_eq!(p.validate(name, json!(valid)), Ok(()), "{name}: {valid}");
+            let expected = match name {
+                "duration" => "expected ISO8601 duration".to_string(),
+                _ => format!("expected {name}"),
+            };
+            assert_eq!(
+                p.messages(name, json!(invalid)),
+                vec![err(&[], &expected)],
+                "{name}: {invalid}"
+            );
+        }
+        assert_eq!(
+            p.messages("date", json!(20240229)),
+            vec![err(&[], "expected date")]
+        );
+    }
+
+    #[test]
+    fn options() {
+        let p = parsers(
+            r#"
+        type Shape = { kind: "a"; a: string } | { kind: "b"; b: string };
+        type Account = {
+            name: string;
+            password: StringFormat<"password">;
+            balance?: NumberFormat<"Cents">;
+        };
+        parse.buildParsers<{ Shape: Shape; Account: Account }>();
+      "#,
+        );
+        let mut options = ValidateOptions {
+            unknown_keys: UnknownKeys::Strict,
+            ..ValidateOptions::default()
+        };
+        options
+            .custom_formats
+            .insert("password".into(), Box::new(|it: &str| it.len() >= 8));
+        options
+            .custom_number_formats
+            .insert("Cents".into(), Box::new(|it: f64| it.fract() == 0.0));
+        let check = |name: &str, value: Value| {
+            validate_with_options(&p.schemas[name], &p.validators, &value, &options).map_err(
+                |errors| {
+                    errors
+                        .into_iter()
+                        .map(|it| (it.path.join("."), it.message))
+                        .collect::<Vec<_>>()
+                },
+            )
+        };
+        assert_eq!(check("Shape", json!({ "kind": "a", "a": "x" })), Ok(()));
+        assert_eq!(
+            check("Shape", json!({ "kind": "a", "a": "x", "b": "y" })),
+            Err(vec![err(&["b"], "extra property")])
+        );
+        assert_eq!(
+            check("Account", json!({ "name": "n", "password": "12345678" })),
+            Ok(())
+        );
+        assert_eq!(
+            check("Account", json!({ "name": "n", "password": "1234" })),
+            Err(vec![err(
+                &["password"],
+                "expected string with format \"password\""
+            )])
+        );
+        assert_eq!(
+            check(
+                "Account",
+                json!({ "name": "n", "password": "12345678", "balance": 100 })
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            check(
+                "Account",
+                json!({ "name": "n", "password": "12345678", "balance": 1.5 })
+            ),
+            Err(vec![err(
+                &["balance"],
+                "expected number with format \"Cents\""
+            )])
+        );
+    }
+
+    #[test]
+    fn strict_unknown_keys() {
+        let p = parsers(
+            r#"
+        type A = { a: string };
+        type B = { b: number };
+        type Either = A | B;
+        type Both = A & B;
+        type Holder = { either: Either; both: Both; tagged: { kind: "t"; t: A } | { kind: "u" } };
+        parse.buildParsers<{ Either: Either; Both: Both; Holder: Holder }>();
+      "#,
+        );
+        let options = ValidateOptions {
+            unknown_keys: UnknownKeys::Strict,
+            ..ValidateOptions::default()
+        };
+        let check = |name: &str, value: Value| {
+            validate_with_options(&p.schemas[name], &p.validators, &value, &options).map_err(
+                |errors| {
+                    errors
+                        .into_iter()
+                        .map(|it| (it.path.join("."), it.message))
+                        .collect::<Vec<_>>()
+                },
+            )
+        };
+        assert_eq!(check("Either", json!({ "a": "x" })), Ok(()));
+        let union_error = check("Either", json!({ "a": "x", "c": 1 })).unwrap_err();
+        assert_eq!(union_error, vec![err(&[], "expected one of")]);
+        assert_eq!(check("Both", json!({ "a": "x", "b": 1 })), Ok(()));
+        assert_eq!(
+            check("Both", json!({ "a": "x", "b": 1, "c": true })),
+            Err(vec![err(&["c"], "extra property")])
+        );
+        let holder = json!({
+            "either": { "b": 1 },
+            "both": { "a": "x", "b": 1 },
+            "tagged": { "kind": "t", "t": { "a": "x" } }
+        });
+        assert_eq!(check("Holder", holder), Ok(()));
+        let holder = json!({
+            "either": { "b": 1 },
+            "both": { "a": "x", "b": 1 },
+            "tagged": { "kind": "t", "t": { "a": "x", "kind": "t" } }
+        });
+        assert_eq!(
+            check("Holder", holder),
+            Err(vec![err(&["tagged", "t", "kind"], "extra property")])
+        );
+    }
+}
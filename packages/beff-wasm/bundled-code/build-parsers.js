@@ -17,15 +17,44 @@ function buildParsers(args) {
     registerCustomFormatter(k, v);
   });
 
-
-  let decoders = {};
+  const customNumberFormats = args?.customNumberFormats ?? {}
   //@ts-ignore
-  Object.keys(buildParsersInput).forEach((k) => {
+  for (const k of RequiredCustomNumberFormats) {
+    if (customNumberFormats[k] == null) {
+      throw new Error(`Missing custom number format ${k}`);
+    }
+  }
+
+  Object.keys(customNumberFormats).forEach((k) => {
+    const v = customNumberFormats[k];
+    //@ts-ignore
+    registerCustomNumberFormatter(k, v);
+  });
+
+  const brands = args?.brands ?? {}
+  Object.keys(brands).forEach((k) => {
     //@ts-ignore
-    let v = buildParsersInput[k];
-    const safeParse = (input, options) => {
+    registerBrandRefinement(k, brands[k]);
+  });
+
+
+  // a call gets the parsers of its own call site, found by its `id` when there are several
+  //@ts-ignore
+  const callSites = Object.keys(buildParsersInput);
+  const callSite = args?.id ?? (callSites.length === 1 ? callSites[0] : undefined);
+  //@ts-ignore
+  const parsersInput = buildParsersInput[callSite];
+  if (parsersInput == null) {
+    throw new Error(`No buildParsers call with id ${JSON.stringify(callSite)}`);
+  }
+
+  let decoders = {};
+  Object.keys(parsersInput).forEach((k) => {
+    let v = parsersInput[k];
+    const run = (input, options, encode) => {
       const validatorCtx = {
-        disallowExtraProperties: options?.disallowExtraProperties ?? false,
+        encode,
+        unknownKeys: options?.unknownKeys ?? (options?.disallowExtraProperties ? "strict" : undefined),
       };
       const new_value = v(validatorCtx, input);
       const validation_result = validatorCtx.errors;
@@ -35,6 +64,7 @@ function buildParsers(args) {
       const errorsSlice = validation_result.slice(0, 10);
       return { success: false, errors: errorsSlice };
     };
+    const safeParse = (input, options) => run(input, options, false);
     const parse = (input, options) => {
       const safe = safeParse(input, options);
       if (safe.success) {
@@ -45,6 +75,18 @@ function buildParsers(args) {
       error.errors = safe.errors;
       throw error
     };
+    // from the parsed value back to JSON, ie: a Date becomes an ISO string
+    const safeEncode = (value, options) => run(value, options, true);
+    const encode = (value, options) => {
+      const safe = safeEncode(value, options);
+      if (safe.success) {
+        return safe.data;
+      }
+      const error = new Error(`Failed to encode ${k}`);
+      //@ts-ignore
+      error.errors = safe.errors;
+      throw error
+    };
     const zod = () => {
       //@ts-ignore
       return z.custom(data => safeParse(data).success, val => {
@@ -56,6 +98,8 @@ function buildParsers(args) {
     decoders[k] = {
       parse,
       safeParse,
+      encode,
+      safeEncode,
       zod,
     };
   });
@@ -0,0 +1,70 @@
+/* eslint-disable no-undef */
+//@ts-check
+
+function buildRoutes() {
+  const buildRouteParser = (routeKey, part, v) => {
+    const run = (input, options, encode) => {
+      const validatorCtx = {
+        encode,
+        unknownKeys: options?.unknownKeys ?? (options?.disallowExtraProperties ? "strict" : undefined),
+      };
+      const new_value = v(validatorCtx, input);
+      const validation_result = validatorCtx.errors;
+      if (validation_result == null) {
+        return { success: true, data: new_value };
+      }
+      const errorsSlice = validation_result.slice(0, 10);
+      return { success: false, errors: errorsSlice };
+    };
+    const safeParse = (input, options) => run(input, options, false);
+    const parse = (input, options) => {
+      const safe = safeParse(input, options);
+      if (safe.success) {
+        return safe.data;
+      }
+      const error = new Error(`Failed to parse ${part} of ${routeKey}`);
+      //@ts-ignore
+      error.errors = safe.errors;
+      throw error
+    };
+    // from the parsed value back to JSON, ie: a Date becomes an ISO string
+    const safeEncode = (value, options) => run(value, options, true);
+    const encode = (value, options) => {
+      const safe = safeEncode(value, options);
+      if (safe.success) {
+        return safe.data;
+      }
+      const error = new Error(`Failed to encode ${part} of ${routeKey}`);
+      //@ts-ignore
+      error.errors = safe.errors;
+      throw error
+    };
+    const zod = () => {
+      //@ts-ignore
+      return z.custom(data => safeParse(data).success, val => {
+        const errors = safeParse(val).errors;
+        //@ts-ignore
+        return printErrors(errors, [])
+      })
+    }
+    return {
+      parse,
+      safeParse,
+      encode,
+      safeEncode,
+      zod,
+    };
+  };
+
+  let routes = {};
+  //@ts-ignore
+  Object.keys(buildRoutesInput).forEach((routeKey) => {
+    //@ts-ignore
+    const parts = buildRoutesInput[routeKey];
+    routes[routeKey] = {};
+    Object.keys(parts).forEach((part) => {
+      routes[routeKey][part] = buildRouteParser(routeKey, part, parts[part]);
+    });
+  });
+  return routes;
+}
@@ -7,6 +7,18 @@ function registerCustomFormatter(name, validator) {
   customFormatters[name] = validator;
 }
 
+const customNumberFormatters = {}
+
+function registerCustomNumberFormatter(name, validator) {
+  customNumberFormatters[name] = validator;
+}
+
+const brandRefinements = {}
+
+function registerBrandRefinement(name, refinement) {
+  brandRefinements[name] = refinement;
+}
+
 function pushPath(ctx, path) {
   if (ctx.paths == null) {
     ctx.paths = [];
@@ -42,14 +54,23 @@ function buildUnionError(received, ctx, errors) {
   });
 }
 
-function decodeObject(ctx, input, required, data, additionalPropsValidator = null) {
+function decodeObject(
+  ctx,
+  input,
+  required,
+  data,
+  additionalPropsValidator = null,
+  patternPropsValidators = []
+) {
   if (!required && input == null) {
     return input;
   }
 
-  const disallowExtraProperties = ctx?.disallowExtraProperties ?? false;
+  const unknownKeys = ctx.unknownKeys ?? "strip";
 
-  const allowedExtraProperties = ctx.allowedExtraProperties__ ?? []
+  // keys declared by the caller, they do not apply to nested objects
+  const allowedExtraProperties = ctx.allowedExtraProperties__ ?? [];
+  ctx.allowedExtraProperties__ = undefined;
 
   if (typeof input === "object" && !Array.isArray(input) && input !== null) {
     const acc = {};
@@ -59,23 +80,28 @@ function decodeObject(ctx, input, required, data, additionalPropsValidator = nul
       popPath(ctx);
     }
 
-    if (additionalPropsValidator != null) {
+    if (additionalPropsValidator != null || patternPropsValidators.length > 0) {
       for (const [k, v] of Object.entries(input)) {
         if (acc[k] == null) {
-          pushPath(ctx, k);
-          //@ts-ignore
-          acc[k] = additionalPropsValidator(ctx, v);
-          popPath(ctx);
+          const patternValidator = patternPropsValidators.find(([regex]) => regex.test(k));
+          const validator = patternValidator?.[1] ?? additionalPropsValidator;
+          if (validator != null) {
+            pushPath(ctx, k);
+            //@ts-ignore
+            acc[k] = validator(ctx, v);
+            popPath(ctx);
+          }
         }
       }
     }
 
-    if (disallowExtraProperties) {
+    if (unknownKeys === "strict" && allowedExtraProperties !== true) {
+      reportExtraProperties(ctx, input, acc, allowedExtraProperties);
+    }
+    if (unknownKeys === "passthrough") {
       for (const k of Object.keys(input)) {
-        if (acc[k] == null && allowedExtraProperties.indexOf(k) == -1) {
-          pushPath(ctx, k);
-          buildError(input[k], ctx, "extra property");
-          popPath(ctx);
+        if (!(k in acc)) {
+          acc[k] = input[k];
         }
       }
     }
@@ -85,10 +111,32 @@ function decodeObject(ctx, input, required, data, additionalPropsValidator = nul
   return buildError(input, ctx, "expected object");
 }
 
+function reportExtraProperties(ctx, input, acc, allowedExtraProperties) {
+  for (const k of Object.keys(input)) {
+    if (!(k in acc) && allowedExtraProperties.indexOf(k) == -1) {
+      pushPath(ctx, k);
+      buildError(input[k], ctx, "extra property");
+      popPath(ctx);
+    }
+  }
+}
+
+function decodeDefault(ctx, input, required, defaultValue, decoder) {
+  // the default is in the wire format, the receiver fills it in
+  if (ctx.encode && input === undefined) {
+    return input;
+  }
+  return decoder(ctx, input === undefined ? defaultValue : input);
+}
+
 function decodeArray(ctx, input, required, data) {
   if (!required && input == null) {
     return input;
   }
+  // a query parameter given once is a single value
+  if (ctx.coerce && !Array.isArray(input)) {
+    input = [input];
+  }
   if (Array.isArray(input)) {
     const acc = [];
     for (let i = 0; i < input.length; i++) {
@@ -113,10 +161,22 @@ function decodeString(ctx, input, required) {
   return buildError(input, ctx, "expected string");
 }
 
+// path and query parameters are strings, their route decoders set `ctx.coerce`
+function coerceNumber(ctx, input) {
+  if (ctx.coerce && typeof input === "string" && input.trim() !== "") {
+    const n = Number(input);
+    if (!Number.isNaN(n)) {
+      return n;
+    }
+  }
+  return input;
+}
+
 function decodeNumber(ctx, input, required) {
   if (!required && input == null) {
     return input;
   }
+  input = coerceNumber(ctx, input);
   if (typeof input === "number") {
     return input;
   }
@@ -127,10 +187,204 @@ function decodeNumber(ctx, input, required) {
   return buildError(input, ctx, "expected number");
 }
 
+function daysInMonth(year, month) {
+  const isLeap = year % 4 === 0 && (year % 100 !== 0 || year % 400 === 0);
+  return [31, isLeap ? 29 : 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31][month - 1];
+}
+function isInRange(n, min, max) {
+  return typeof n === "number" && n >= min && n <= max;
+}
+function isPlainDate(d) {
+  return (
+    typeof d === "object" &&
+    d !== null &&
+    Number.isInteger(d.year) &&
+    Number.isInteger(d.month) &&
+    Number.isInteger(d.day) &&
+    isInRange(d.year, 0, 9999) &&
+    isInRange(d.month, 1, 12) &&
+    isInRange(d.day, 1, daysInMonth(d.year, d.month))
+  );
+}
+function isPlainTime(t) {
+  return (
+    typeof t === "object" &&
+    t !== null &&
+    Number.isInteger(t.hour) &&
+    Number.isInteger(t.minute) &&
+    isInRange(t.hour, 0, 23) &&
+    isInRange(t.minute, 0, 59) &&
+    typeof t.second === "number" &&
+    t.second >= 0 &&
+    t.second < 60
+  );
+}
+const DURATION_UNITS = [
+  ["years", "Y"],
+  ["months", "M"],
+  ["weeks", "W"],
+  ["days", "D"],
+  ["hours", "H"],
+  ["minutes", "M"],
+  ["seconds", "S"],
+];
+function isDuration(d) {
+  return (
+    typeof d === "object" &&
+    d !== null &&
+    DURATION_UNITS.every(([k]) => d[k] == null || (Number.isFinite(d[k]) && d[k] >= 0))
+  );
+}
+
+const PLAIN_DATE_REGEX = /^([0-9]{4})-([0-9]{2})-([0-9]{2})$/;
+const PLAIN_TIME_REGEX = /^([0-9]{2}):([0-9]{2}):([0-9]{2}(?:\.[0-9]+)?)$/;
+const DURATION_NUMBER = "([0-9]+(?:\\.[0-9]+)?)";
+const DURATION_REGEX = new RegExp(
+  `^P(?:${DURATION_NUMBER}Y)?(?:${DURATION_NUMBER}M)?(?:${DURATION_NUMBER}W)?(?:${DURATION_NUMBER}D)?` +
+    `(?:T(?:${DURATION_NUMBER}H)?(?:${DURATION_NUMBER}M)?(?:${DURATION_NUMBER}S)?)?$`
+);
+const BASE64_REGEX = /^[A-Za-z0-9+/_-]*={0,2}$/;
+
+function parsePlainDate(input) {
+  const m = PLAIN_DATE_REGEX.exec(input);
+  if (m == null) {
+    return null;
+  }
+  const d = { year: Number(m[1]), month: Number(m[2]), day: Number(m[3]) };
+  return isPlainDate(d) ? d : null;
+}
+function parsePlainTime(input) {
+  const m = PLAIN_TIME_REGEX.exec(input);
+  if (m == null) {
+    return null;
+  }
+  const t = { hour: Number(m[1]), minute: Number(m[2]), second: Number(m[3]) };
+  return isPlainTime(t) ? t : null;
+}
+function parseDuration(input) {
+  const m = DURATION_REGEX.exec(input);
+  // at least one component, and one after the time designator
+  if (m == null || input === "P" || input.endsWith("T")) {
+    return null;
+  }
+  const d = {};
+  DURATION_UNITS.forEach(([k], idx) => {
+    d[k] = Number(m[idx + 1] ?? 0);
+  });
+  return d;
+}
+// standard and url safe alphabets, padding is optional
+function parseBase64(input) {
+  const data = input.replace(/=+$/, "");
+  if (!BASE64_REGEX.test(input) || data.length % 4 === 1 || (data.length !== input.length && input.length % 4 !== 0)) {
+    return null;
+  }
+  const std = data.replace(/-/g, "+").replace(/_/g, "/");
+  const binary = atob(std + "==".slice(0, (4 - (std.length % 4)) % 4));
+  const bytes = new Uint8Array(binary.length);
+  for (let i = 0; i < binary.length; i++) {
+    bytes[i] = binary.charCodeAt(i);
+  }
+  return bytes;
+}
+
+function padNumber(n, length) {
+  return String(n).padStart(length, "0");
+}
+function formatDecimal(n) {
+  // toFixed always writes the decimal point, it goes away with the zeros when nothing else is left
+  return Number.isInteger(n) ? String(n) : n.toFixed(9).replace(/\.?0+$/, "");
+}
+function formatPlainTime(t) {
+  const second = formatDecimal(t.second);
+  return padNumber(t.hour, 2) + ":" + padNumber(t.minute, 2) + ":" + (t.second < 10 ? "0" : "") + second;
+}
+function formatDuration(d) {
+  let date = "";
+  let time = "";
+  DURATION_UNITS.forEach(([k, unit], idx) => {
+    if (d[k] != null && d[k] !== 0) {
+      const part = formatDecimal(d[k]) + unit;
+      if (idx < 4) {
+        date += part;
+      } else {
+        time += part;
+      }
+    }
+  });
+  if (date === "" && time === "") {
+    return "PT0S";
+  }
+  return "P" + date + (time === "" ? "" : "T" + time);
+}
+function formatBase64(bytes) {
+  let binary = "";
+  for (let i = 0; i < bytes.length; i++) {
+    binary += String.fromCharCode(bytes[i]);
+  }
+  return btoa(binary);
+}
+// url safe alphabet, without padding
+function formatBase64Url(bytes) {
+  return formatBase64(bytes).replace(/\+/g, "-").replace(/\//g, "_").replace(/=+$/, "");
+}
+
+// the inverse of decodeCodec, from the runtime value to its wire format
+function encodeCodec(ctx, input, codec) {
+  switch (codec) {
+    case "Codec::ISO8061": {
+      if (input instanceof Date && !isNaN(input.getTime())) {
+        return input.toISOString();
+      }
+      return buildError(input, ctx, "expected Date");
+    }
+    case "Codec::BigInt": {
+      if (typeof input === "bigint") {
+        return input.toString();
+      }
+      return buildError(input, ctx, "expected bigint");
+    }
+    case "Codec::PlainDate": {
+      if (isPlainDate(input)) {
+        return padNumber(input.year, 4) + "-" + padNumber(input.month, 2) + "-" + padNumber(input.day, 2);
+      }
+      return buildError(input, ctx, "expected PlainDate");
+    }
+    case "Codec::PlainTime": {
+      if (isPlainTime(input)) {
+        return formatPlainTime(input);
+      }
+      return buildError(input, ctx, "expected PlainTime");
+    }
+    case "Codec::Duration": {
+      if (isDuration(input)) {
+        return formatDuration(input);
+      }
+      return buildError(input, ctx, "expected IsoDuration");
+    }
+    case "Codec::Base64": {
+      if (input instanceof Uint8Array) {
+        return formatBase64(input);
+      }
+      return buildError(input, ctx, "expected Uint8Array");
+    }
+    case "Codec::Base64Url": {
+      if (input instanceof Uint8Array) {
+        return formatBase64Url(input);
+      }
+      return buildError(input, ctx, "expected Uint8Array");
+    }
+  }
+  return buildError(input, ctx, "codec " + codec + " not implemented");
+}
+
 function decodeCodec(ctx, input, required, codec) {
   if (!required && input == null) {
     return input;
   }
+  if (ctx.encode) {
+    return encodeCodec(ctx, input, codec);
+  }
   switch (codec) {
     case "Codec::ISO8061": {
       const d = new Date(input);
@@ -155,11 +409,46 @@ function decodeCodec(ctx, input, required, codec) {
       }
       return buildError(input, ctx, "expected bigint");
     }
+    case "Codec::PlainDate": {
+      const d = typeof input === "string" ? parsePlainDate(input) : null;
+      if (d == null) {
+        return buildError(input, ctx, "expected date");
+      }
+      return d;
+    }
+    case "Codec::PlainTime": {
+      const t = typeof input === "string" ? parsePlainTime(input) : null;
+      if (t == null) {
+        return buildError(input, ctx, "expected time");
+      }
+      return t;
+    }
+    case "Codec::Duration": {
+      const d = typeof input === "string" ? parseDuration(input) : null;
+      if (d == null) {
+        return buildError(input, ctx, "expected ISO8601 duration");
+      }
+      return d;
+    }
+    case "Codec::Base64": {
+      const bytes = typeof input === "string" ? parseBase64(input) : null;
+      if (bytes == null) {
+        return buildError(input, ctx, "expected base64");
+      }
+      return bytes;
+    }
+    case "Codec::Base64Url": {
+      const bytes = typeof input === "string" ? parseBase64(input) : null;
+      if (bytes == null) {
+        return buildError(input, ctx, "expected base64url");
+      }
+      return bytes;
+    }
   }
   return buildError(input, ctx, "codec " + codec + " not implemented");
 }
 
-function decodeStringWithFormat(ctx, input, required, format) {
+function decodeStringWithFormat(ctx, input, required, format, builtInRegex = null) {
   if (!required && input == null) {
     return input;
   }
@@ -169,6 +458,28 @@ function decodeStringWithFormat(ctx, input, required, format) {
 
   const validator = customFormatters[format];
 
+  if (validator == null && builtInRegex == null) {
+    return buildError(input, ctx, "format " + JSON.stringify(format) + " not implemented");
+  }
+
+  const isOk = validator != null ? validator(input) : builtInRegex.test(input);
+  if (isOk) {
+    return input;
+  }
+  return buildError(input, ctx, "expected string with format " + JSON.stringify(format));
+}
+
+function decodeNumberWithFormat(ctx, input, required, format) {
+  if (!required && input == null) {
+    return input;
+  }
+  input = coerceNumber(ctx, input);
+  if (typeof input !== "number") {
+    return buildError(input, ctx, "expected number with format " + JSON.stringify(format));
+  }
+
+  const validator = customNumberFormatters[format];
+
   if (validator == null) {
     return buildError(input, ctx, "format " + JSON.stringify(format) + " not implemented");
   }
@@ -177,7 +488,25 @@ function decodeStringWithFormat(ctx, input, required, format) {
   if (isOk) {
     return input;
   }
-  return buildError(input, ctx, "expected string with format " + JSON.stringify(format));
+  return buildError(input, ctx, "expected number with format " + JSON.stringify(format));
+}
+
+function decodeBrand(ctx, input, required, brand, decoder) {
+  if (!required && input == null) {
+    return input;
+  }
+  const errorCount = ctx.errors?.length ?? 0;
+  const value = decoder(ctx, input);
+  const refinement = brandRefinements[brand];
+  // a brand without a refinement is checked at compile time only
+  if (refinement == null || (ctx.errors?.length ?? 0) > errorCount) {
+    return value;
+  }
+  // an encoded value is in the wire format, the refinement takes the runtime value
+  if (refinement(ctx.encode ? input : value)) {
+    return value;
+  }
+  return buildError(input, ctx, "expected " + JSON.stringify(brand));
 }
 
 function decodeAnyOfDiscriminated(ctx, input, required, discriminator, mapping) {
@@ -196,7 +525,7 @@ function decodeAnyOfDiscriminated(ctx, input, required, discriminator, mapping)
     return err;
   }
   const prevAllow = (ctx.allowedExtraProperties__ ?? []);
-  ctx.allowedExtraProperties__ = [...prevAllow, discriminator]
+  ctx.allowedExtraProperties__ = prevAllow === true ? true : [...prevAllow, discriminator]
   const out = v(ctx, input);
   ctx.allowedExtraProperties__ = prevAllow;
   return { ...out, [discriminator]: d };
@@ -207,7 +536,7 @@ function decodeAnyOfConsts(ctx, input, required, consts) {
     return input;
   }
   for (const c of consts) {
-    if (input === c) {
+    if (input === c || (ctx.coerce && typeof input === "string" && String(c) === input)) {
       return c;
     }
   }
@@ -220,7 +549,12 @@ function decodeAnyOf(ctx, input, required, vs) {
 
   let accErrors = [];
   for (const v of vs) {
-    const validatorCtx = {};
+    const validatorCtx = {
+      encode: ctx.encode,
+      coerce: ctx.coerce,
+      unknownKeys: ctx.unknownKeys,
+      allowedExtraProperties__: ctx.allowedExtraProperties__,
+    };
     const newValue = v(validatorCtx, input);
     if (validatorCtx.errors == null) {
       return newValue;
@@ -233,10 +567,14 @@ function decodeAllOf(ctx, input, required, vs) {
   if (!required && input == null) {
     return input;
   }
+  // each member only knows its own keys, extra keys are checked once all of them are merged
+  const strict = ctx.unknownKeys === "strict";
+  const allowedExtraProperties = ctx.allowedExtraProperties__ ?? [];
   let acc = {};
   let foundOneObject = false;
   let allObjects = true;
   for (const v of vs) {
+    ctx.allowedExtraProperties__ = strict ? true : allowedExtraProperties;
     const newValue = v(ctx, input);
     const isObj = typeof newValue === "object";
     allObjects = allObjects && isObj;
@@ -245,7 +583,11 @@ function decodeAllOf(ctx, input, required, vs) {
       acc = { ...acc, ...newValue };
     }
   }
+  ctx.allowedExtraProperties__ = undefined;
   if (foundOneObject && allObjects) {
+    if (strict && allowedExtraProperties !== true) {
+      reportExtraProperties(ctx, input, acc, allowedExtraProperties);
+    }
     return acc;
   }
   return input;
@@ -256,6 +598,9 @@ function decodeTuple(ctx, input, required, vs) {
   }
   if (Array.isArray(input)) {
     const acc = [];
+    const suffix = vs.suffix ?? [];
+    // the suffix is read from the end, a short tuple reports its missing items
+    const suffixStart = Math.max(input.length - suffix.length, vs.prefix.length);
     let idx = 0;
     for (const v of vs.prefix) {
       pushPath(ctx, "[" + idx + "]");
@@ -264,8 +609,16 @@ function decodeTuple(ctx, input, required, vs) {
       acc.push(newValue);
       idx++;
     }
+    for (const v of vs.optional ?? []) {
+      if (idx < input.length) {
+        pushPath(ctx, "[" + idx + "]");
+        acc.push(v(ctx, input[idx]));
+        popPath(ctx);
+      }
+      idx++;
+    }
     if (vs.items != null) {
-      for (let i = idx; i < input.length; i++) {
+      for (let i = idx; i < suffixStart; i++) {
         const v = input[i];
         pushPath(ctx, "[" + i + "]");
         acc.push(vs.items(ctx, v));
@@ -276,6 +629,12 @@ function decodeTuple(ctx, input, required, vs) {
         return buildError(input, ctx, "tuple has too many items");
       }
     }
+    for (let i = 0; i < suffix.length; i++) {
+      const suffixIdx = suffixStart + i;
+      pushPath(ctx, "[" + suffixIdx + "]");
+      acc.push(suffix[i](ctx, input[suffixIdx]));
+      popPath(ctx);
+    }
     return acc;
   }
   return buildError(input, ctx, "expected tuple");
@@ -287,6 +646,9 @@ function decodeBoolean(ctx, input, required) {
   if (typeof input === "boolean") {
     return input;
   }
+  if (ctx.coerce && (input === "true" || input === "false")) {
+    return input === "true";
+  }
   return buildError(input, ctx, "expected boolean");
 }
 function decodeAny(ctx, input, required) {
@@ -0,0 +1,8 @@
+import { BuildRoutesFunction } from "@beff/cli";
+
+declare const _exports: {
+  buildRoutes: BuildRoutesFunction;
+  openApiPaths: Record<string, unknown>;
+};
+
+export default _exports;
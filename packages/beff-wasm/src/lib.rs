@@ -8,6 +8,8 @@ use anyhow::anyhow;
 use anyhow::Result;
 use beff_core::diag::Diagnostic;
 use beff_core::import_resolver::parse_and_bind;
+use beff_core::json_schema_import::import_json_schema as import_json_schema_document;
+use beff_core::mock::MockOptions;
 use beff_core::print::printer::ToWritableModules;
 use beff_core::print::printer::WritableModules;
 use beff_core::wasm_diag::WasmDiagnostic;
@@ -15,12 +17,16 @@ use beff_core::BeffUserSettings;
 use beff_core::BffFileName;
 use beff_core::EntryPoints;
 use beff_core::ExtractResult;
+use beff_core::find_ambient_module;
 use beff_core::FileManager;
+use beff_core::ImportReference;
 use beff_core::ParsedModule;
 use log::Level;
 use module_resolver::WasmModuleResolver;
+use serde::Serialize;
 use std::rc::Rc;
 use std::{cell::RefCell, collections::HashMap};
+use swc_atoms::JsWord;
 use swc_common::{Globals, GLOBALS};
 use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen::JsValue;
@@ -67,15 +73,29 @@ extern "C" {
 }
 #[wasm_bindgen]
 pub fn bundle_to_string(
-    parser_entry_point: &str,
+    parser_entry_points: JsValue,
     schema_entry_point: &str,
+    routes_entry_point: &str,
     settings: JsValue,
+    with_examples: bool,
+    seed: u32,
+    max_depth: u32,
 ) -> JsValue {
-    match bundle_to_string_inner(parse_entrypoints(
-        parser_entry_point,
-        schema_entry_point,
-        settings,
-    )) {
+    // the JSON Schema gets the same examples as `bundle_to_examples`
+    let examples = with_examples.then(|| MockOptions {
+        seed: seed.into(),
+        max_depth: max_depth as usize,
+        ..MockOptions::default()
+    });
+    match bundle_to_string_inner(
+        parse_entrypoints(
+            parser_entry_points,
+            schema_entry_point,
+            routes_entry_point,
+            settings,
+        ),
+        examples.as_ref(),
+    ) {
         Ok(s) => serde_wasm_bindgen::to_value(&s).expect("should be able to serialize bundle"),
         Err(_) => JsValue::null(),
     }
@@ -83,41 +103,81 @@ pub fn bundle_to_string(
 
 #[wasm_bindgen]
 pub fn bundle_to_diagnostics(
-    parser_entry_point: &str,
+    parser_entry_points: JsValue,
     schema_entry_point: &str,
+    routes_entry_point: &str,
     settings: JsValue,
 ) -> JsValue {
     let v = bundle_to_diagnostics_inner(parse_entrypoints(
-        parser_entry_point,
+        parser_entry_points,
         schema_entry_point,
+        routes_entry_point,
         settings,
     ));
     serde_wasm_bindgen::to_value(&v).expect("should be able to serialize diagnostics")
 }
 #[wasm_bindgen]
+pub fn bundle_to_examples(
+    parser_entry_points: JsValue,
+    schema_entry_point: &str,
+    routes_entry_point: &str,
+    settings: JsValue,
+    seed: u32,
+    max_depth: u32,
+) -> JsValue {
+    let options = MockOptions {
+        seed: seed.into(),
+        max_depth: max_depth as usize,
+        ..MockOptions::default()
+    };
+    match bundle_to_examples_inner(
+        parse_entrypoints(
+            parser_entry_points,
+            schema_entry_point,
+            routes_entry_point,
+            settings,
+        ),
+        &options,
+    ) {
+        Ok(s) => JsValue::from_str(&s),
+        Err(_) => JsValue::null(),
+    }
+}
+#[wasm_bindgen]
+pub fn import_json_schema(file_name: &str, root_name: &str, content: &str) -> JsValue {
+    match import_json_schema_inner(file_name, root_name, content) {
+        Ok(it) => serde_wasm_bindgen::to_value(&it).expect("should be able to serialize import"),
+        Err(_) => JsValue::null(),
+    }
+}
+#[wasm_bindgen]
 pub fn update_file_content(file_name: &str, content: &str) {
     update_file_content_inner(file_name, content)
 }
+fn parse_entry_point(entry_point: &str) -> Option<BffFileName> {
+    if entry_point.is_empty() {
+        None
+    } else {
+        Some(BffFileName::new(entry_point.to_string()))
+    }
+}
 fn parse_entrypoints(
-    parser_entry_point: &str,
+    parser_entry_points: JsValue,
     schema_entry_point: &str,
+    routes_entry_point: &str,
     settings: JsValue,
 ) -> EntryPoints {
-    let parser_entry_point = if parser_entry_point.is_empty() {
-        None
-    } else {
-        Some(BffFileName::new(parser_entry_point.to_string()))
-    };
-    let schema_entry_point = if schema_entry_point.is_empty() {
-        None
-    } else {
-        Some(BffFileName::new(schema_entry_point.to_string()))
-    };
+    let parser_entry_points: Vec<String> = serde_wasm_bindgen::from_value(parser_entry_points)
+        .expect("should be able to parse parser entry points");
     let settings: BeffUserSettings =
         serde_wasm_bindgen::from_value(settings).expect("should be able to parse settings");
     EntryPoints {
-        parser_entry_point,
-        schema_entry_point,
+        parser_entry_points: parser_entry_points
+            .into_iter()
+            .map(BffFileName::new)
+            .collect(),
+        schema_entry_point: parse_entry_point(schema_entry_point),
+        routes_entry_point: parse_entry_point(routes_entry_point),
         settings,
     }
 }
@@ -149,6 +209,10 @@ impl<'a> FileManager for LazyFileManager<'a> {
     fn get_existing_file(&self, name: &BffFileName) -> Option<Rc<ParsedModule>> {
         self.files.get(name).cloned()
     }
+
+    fn get_ambient_module(&self, module_specifier: &JsWord) -> Option<Rc<ImportReference>> {
+        find_ambient_module(self.files.iter(), module_specifier)
+    }
 }
 
 fn run_extraction(entry: EntryPoints) -> ExtractResult {
@@ -170,19 +234,64 @@ fn print_errors(errors: Vec<&Diagnostic>) {
     emit_diagnostic(v)
 }
 
-fn bundle_to_string_inner(entry: EntryPoints) -> Result<WritableModules> {
+fn bundle_to_string_inner(
+    entry: EntryPoints,
+    examples: Option<&MockOptions>,
+) -> Result<WritableModules> {
     let res = run_extraction(entry);
     let errs = res.errors();
     if errs.is_empty() {
         // let v = WasmDiagnostic::from_diagnostics(vec![]);
         // let v = serde_wasm_bindgen::to_value(&v).expect("should be able to serialize");
         // emit_diagnostic(v);
-        return res.to_module();
+        return match examples {
+            Some(options) => res.to_module_with_examples(options),
+            None => res.to_module(),
+        };
     }
     print_errors(errs);
     Err(anyhow!("Failed to bundle"))
 }
 
+fn bundle_to_examples_inner(entry: EntryPoints, options: &MockOptions) -> Result<String> {
+    let res = run_extraction(entry);
+    let errs = res.errors();
+    if errs.is_empty() {
+        return match beff_core::mock::examples(&res, options) {
+            Ok(it) => Ok(it.to_string()),
+            Err(e) => {
+                log::error!("{:?}", e);
+                Err(e)
+            }
+        };
+    }
+    print_errors(errs);
+    Err(anyhow!("Failed to bundle"))
+}
+
+#[derive(Serialize)]
+struct ImportedJsonSchema {
+    ts_types: String,
+    modules: WritableModules,
+}
+
+fn import_json_schema_inner(
+    file_name: &str,
+    root_name: &str,
+    content: &str,
+) -> Result<ImportedJsonSchema> {
+    let file_name = BffFileName::new(file_name.to_string());
+    let res = import_json_schema_document(&file_name, root_name, content);
+    if res.errors.is_empty() {
+        return Ok(ImportedJsonSchema {
+            ts_types: res.to_ts_types(),
+            modules: res.to_module()?,
+        });
+    }
+    print_errors(res.errors.iter().collect());
+    Err(anyhow!("Failed to import JSON schema"))
+}
+
 fn bundle_to_diagnostics_inner(entry: EntryPoints) -> WasmDiagnostic {
     WasmDiagnostic::from_diagnostics(run_extraction(entry).errors())
 }
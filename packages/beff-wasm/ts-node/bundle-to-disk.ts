@@ -11,6 +11,7 @@ const decodersExported = [
   "decodeNumber",
   "decodeCodec",
   "decodeStringWithFormat",
+  "decodeNumberWithFormat",
   "decodeAnyOf",
   "decodeAllOf",
   "decodeBoolean",
@@ -19,6 +20,8 @@ const decodersExported = [
   "decodeNull",
   "decodeConst",
   "registerCustomFormatter",
+  "registerCustomNumberFormatter",
+  "registerBrandRefinement",
 ];
 
 const esmTag = (mod: ProjectModule) => {
@@ -57,22 +60,44 @@ const importValidators = (mod: ProjectModule) => {
   return [importRest].join("\n");
 };
 
-const finalizeParserFile = (wasmCode: WritableModules, mod: ProjectModule, customFormats: string[]) => {
+const finalizeParserFile = (
+  wasmCode: WritableModules,
+  mod: ProjectModule,
+  customFormats: string[],
+  customNumberFormats: string[]
+) => {
   const exportedItems = ["buildParsers"].join(", ");
   const exports = [exportCode(mod), `{ ${exportedItems} };`].join(" ");
 
   const customFormatsCode = `const RequiredCustomFormats = ${JSON.stringify(customFormats)};`;
+  const customNumberFormatsCode = `const RequiredCustomNumberFormats = ${JSON.stringify(customNumberFormats)};`;
   return [
     "//@ts-nocheck\n/* eslint-disable */\n",
     esmTag(mod),
     importValidators(mod),
     customFormatsCode,
+    customNumberFormatsCode,
     wasmCode.js_built_parsers,
     gen["build-parsers.js"],
     exports,
   ].join("\n");
 };
 
+const finalizeRoutesFile = (wasmCode: WritableModules, mod: ProjectModule) => {
+  const exportedItems = ["buildRoutes", "openApiPaths"].join(", ");
+  const exports = [exportCode(mod), `{ ${exportedItems} };`].join(" ");
+
+  return [
+    "//@ts-nocheck\n/* eslint-disable */\n",
+    esmTag(mod),
+    importValidators(mod),
+    wasmCode.js_built_routes,
+    `const openApiPaths = ${wasmCode.openapi_paths};`,
+    gen["build-routes.js"],
+    exports,
+  ].join("\n");
+};
+
 export const execProject = (
   bundler: Bundler,
   projectPath: string,
@@ -81,19 +106,29 @@ export const execProject = (
 ): "ok" | "failed" => {
   const mod = projectJson.module ?? "esm";
 
-  const parserEntryPoint = projectJson.parser
-    ? path.join(path.dirname(projectPath), projectJson.parser)
-    : undefined;
+  const parserEntryPoints = (projectJson.parser ?? []).map((it) =>
+    path.join(path.dirname(projectPath), it)
+  );
 
   const schemaEntryPoint = projectJson.schema
     ? path.join(path.dirname(projectPath), projectJson.schema)
     : undefined;
 
+  const routesEntryPoint = projectJson.routes
+    ? path.join(path.dirname(projectPath), projectJson.routes)
+    : undefined;
+
   if (verbose) {
     // eslint-disable-next-line no-console
-    console.log(`JS: Parser entry point ${parserEntryPoint}`);
+    console.log(`JS: Parser entry points ${parserEntryPoints.join(", ")}`);
   }
-  const outResult = bundler.bundle(parserEntryPoint, schemaEntryPoint, projectJson.settings);
+  const outResult = bundler.bundle(
+    parserEntryPoints,
+    schemaEntryPoint,
+    routesEntryPoint,
+    projectJson.settings,
+    projectJson.examples
+  );
   if (outResult == null) {
     return "failed";
   }
@@ -116,15 +151,44 @@ export const execProject = (
     );
   }
 
-  if (projectJson.parser) {
+  if (parserEntryPoints.length > 0) {
     fs.writeFileSync(
       path.join(outputDir, "parser.js"),
-      finalizeParserFile(outResult, mod, projectJson.settings.customFormats.map((it) => it.name) ?? [])
+      finalizeParserFile(
+        outResult,
+        mod,
+        projectJson.settings.customFormats.map((it) => it.name) ?? [],
+        projectJson.settings.customNumberFormats.map((it) => it.name) ?? []
+      )
     );
     fs.writeFileSync(
       path.join(outputDir, "parser.d.ts"),
       ["/* eslint-disable */\n", gen["parser.d.ts"]].join("\n")
     );
+    fs.writeFileSync(
+      path.join(outputDir, "types.d.ts"),
+      ["/* eslint-disable */\n", outResult.ts_declarations].join("\n")
+    );
+  }
+  if (projectJson.routes) {
+    fs.writeFileSync(path.join(outputDir, "routes.js"), finalizeRoutesFile(outResult, mod));
+    fs.writeFileSync(
+      path.join(outputDir, "routes.d.ts"),
+      ["/* eslint-disable */\n", gen["routes.d.ts"]].join("\n")
+    );
+  }
+  if (projectJson.examples) {
+    const examples = bundler.examples(
+      parserEntryPoints,
+      schemaEntryPoint,
+      routesEntryPoint,
+      projectJson.settings,
+      projectJson.examples
+    );
+    if (examples == null) {
+      return "failed";
+    }
+    fs.writeFileSync(path.join(outputDir, "examples.json"), examples);
   }
   return "ok";
 };
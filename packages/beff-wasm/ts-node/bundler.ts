@@ -12,7 +12,7 @@ import {
 } from "./tsc-slim/out";
 import { codeFrameColumns } from "@babel/code-frame";
 import * as chalk from "chalk";
-import { BeffUserSettings } from "./project";
+import { BeffExamplesSettings, BeffUserSettings } from "./project";
 interface ModuleResolutionHost {
   fileExists(fileName: string): boolean;
   readFile(fileName: string): string | undefined;
@@ -198,6 +198,14 @@ export type WritableModules = {
   js_server_meta: string | undefined;
   json_schema: string | undefined;
   js_built_parsers: string | undefined;
+  ts_declarations: string | undefined;
+  js_built_routes: string | undefined;
+  openapi_paths: string | undefined;
+};
+
+export type ImportedJsonSchema = {
+  ts_types: string;
+  modules: WritableModules;
 };
 
 export class Bundler {
@@ -229,29 +237,58 @@ export class Bundler {
   }
 
   public bundle(
-    parser_entrypoint: string | undefined,
+    parser_entrypoints: string[],
     schema_entrypoint: string | undefined,
-    settings: BeffUserSettings
+    routes_entrypoint: string | undefined,
+    settings: BeffUserSettings,
+    examples?: BeffExamplesSettings
   ): WritableModules | undefined {
     return wasm.bundle_to_string(
-      parser_entrypoint ?? "",
+      parser_entrypoints,
       schema_entrypoint ?? "",
-      serializeSettings(settings)
+      routes_entrypoint ?? "",
+      serializeSettings(settings),
+      examples != null,
+      examples?.seed ?? 0,
+      examples?.maxDepth ?? 0
+    );
+  }
+
+  public examples(
+    parser_entrypoints: string[],
+    schema_entrypoint: string | undefined,
+    routes_entrypoint: string | undefined,
+    settings: BeffUserSettings,
+    examples: BeffExamplesSettings
+  ): string | undefined {
+    return wasm.bundle_to_examples(
+      parser_entrypoints,
+      schema_entrypoint ?? "",
+      routes_entrypoint ?? "",
+      serializeSettings(settings),
+      examples.seed,
+      examples.maxDepth
     );
   }
 
   public diagnostics(
-    parser_entrypoint: string | undefined,
+    parser_entrypoints: string[],
     schema_entrypoint: string | undefined,
+    routes_entrypoint: string | undefined,
     settings: BeffUserSettings
   ): WasmDiagnostic | null {
     return wasm.bundle_to_diagnostics(
-      parser_entrypoint ?? "",
+      parser_entrypoints,
       schema_entrypoint ?? "",
+      routes_entrypoint ?? "",
       serializeSettings(settings)
     );
   }
 
+  public importJsonSchema(file_name: string, root_name: string, content: string): ImportedJsonSchema | undefined {
+    return wasm.import_json_schema(file_name, root_name, content);
+  }
+
   public updateFileContent(file_name: string, content: string) {
     return wasm.update_file_content(file_name, content);
   }
@@ -259,5 +296,7 @@ export class Bundler {
 function serializeSettings(settings: BeffUserSettings) {
   return {
     custom_formats: settings.customFormats.map((it) => it.name) ?? [],
+    custom_number_formats: settings.customNumberFormats.map((it) => it.name) ?? [],
+    unknown_keys: settings.unknownKeys,
   };
 }
@@ -1,7 +1,13 @@
 import { Command } from "commander";
 import * as fs from "fs";
 import * as path from "path";
-import { ProjectJson, parseUserSettings } from "./project";
+import {
+  ProjectJson,
+  isUnknownKeys,
+  parseExamplesSettings,
+  parseParserEntryPoints,
+  parseUserSettings,
+} from "./project";
 import * as chalk from "chalk";
 import { execProject } from "./bundle-to-disk";
 import { Bundler } from "./bundler";
@@ -28,16 +34,21 @@ const readProjectJson = (projectPath: string): ProjectJson => {
     throw bail(`Failed to parse bff.json: ${e}`);
   }
 
-  if (!projectJson.parser) {
-    throw bail(`Field or "parser" not found in bff.json`);
+  if (!projectJson.parser && !projectJson.routes) {
+    throw bail(`Field "parser" or "routes" not found in bff.json`);
   }
   if (!projectJson.outputDir) {
     throw bail(`Field "outputDir" not found in bff.json`);
   }
+  if (projectJson.unknownKeys != null && !isUnknownKeys(projectJson.unknownKeys)) {
+    throw bail(`Field "unknownKeys" must be "strict", "strip" or "passthrough"`);
+  }
 
   return {
-    parser: projectJson.parser == null ? projectJson.parser : String(projectJson.parser),
+    parser: parseParserEntryPoints(projectJson.parser),
     schema: projectJson.schema == null ? projectJson.schema : String(projectJson.schema),
+    routes: projectJson.routes == null ? projectJson.routes : String(projectJson.routes),
+    examples: parseExamplesSettings(projectJson.examples),
     outputDir: String(projectJson.outputDir),
     module: projectJson.module,
     settings: parseUserSettings(projectJson),
@@ -3,19 +3,20 @@ import * as vscode from "vscode";
 import * as path from "path";
 import * as fs from "fs";
 import { Bundler, WasmDiagnosticInformation } from "./bundler";
-import { BeffUserSettings, ProjectJson, parseUserSettings } from "./project";
+import { BeffUserSettings, ProjectJson, parseParserEntryPoints, parseUserSettings } from "./project";
 
 const readProjectJson = (
   projectPath: string
-): Pick<ProjectJson, "schema" | "module" | "parser" | "settings"> => {
+): Pick<ProjectJson, "schema" | "module" | "parser" | "routes" | "settings"> => {
   const projectJson = JSON.parse(fs.readFileSync(projectPath, "utf-8"));
 
-  if (!projectJson.router && !projectJson.parser) {
-    throw new Error(`Field "router" or "parser" not found in bff.json`);
+  if (!projectJson.routes && !projectJson.parser) {
+    throw new Error(`Field "routes" or "parser" not found in bff.json`);
   }
   return {
     schema: projectJson.schema == null ? projectJson.schema : String(projectJson.schema),
-    parser: projectJson.parser == null ? projectJson.parser : String(projectJson.parser),
+    parser: parseParserEntryPoints(projectJson.parser),
+    routes: projectJson.routes == null ? projectJson.routes : String(projectJson.routes),
     module: projectJson.module,
     settings: parseUserSettings(projectJson),
   };
@@ -55,14 +56,20 @@ export function activate(_context: vscode.ExtensionContext) {
   const projectJson = readProjectJson(projectPath);
 
   bundler = new Bundler(VERBOSE);
-  // const router = projectJson.router;
   const schema_entrypoint =
     projectJson.schema == null ? undefined : path.join(path.dirname(projectPath), projectJson.schema);
-  const parser_entrypoint =
-    projectJson.parser == null ? undefined : path.join(path.dirname(projectPath), projectJson.parser);
+  const parser_entrypoints = (projectJson.parser ?? []).map((it) => path.join(path.dirname(projectPath), it));
+  const routes_entrypoint =
+    projectJson.routes == null ? undefined : path.join(path.dirname(projectPath), projectJson.routes);
 
   const updateDiag = () =>
-    updateDiagnostics(schema_entrypoint, parser_entrypoint, projectJson.settings, collection);
+    updateDiagnostics(
+      schema_entrypoint,
+      parser_entrypoints,
+      routes_entrypoint,
+      projectJson.settings,
+      collection
+    );
   updateDiag();
   const watcher = vscode.workspace.createFileSystemWatcher(
     new vscode.RelativePattern(workspacePath, "**/*.ts")
@@ -129,12 +136,13 @@ const relatedInformation = (cause: WasmDiagnosticInformation): vscode.Diagnostic
 
 function updateDiagnostics(
   schema_entrypoint: string | undefined,
-  parser_entrypoint: string | undefined,
+  parser_entrypoints: string[],
+  routes_entrypoint: string | undefined,
   settings: BeffUserSettings,
   collection: vscode.DiagnosticCollection
 ): void {
   collection.clear();
-  const diags = bundler?.diagnostics(parser_entrypoint, schema_entrypoint, settings);
+  const diags = bundler?.diagnostics(parser_entrypoints, schema_entrypoint, routes_entrypoint, settings);
   const acc: Record<string, vscode.Diagnostic[]> = {};
   const pushDiag = (k: string, v: vscode.Diagnostic) => {
     if (acc[k] == null) {
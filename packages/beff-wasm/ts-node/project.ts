@@ -2,12 +2,23 @@ export type BeffCustomFormat = {
   name: string;
 };
 
+export type UnknownKeys = "strict" | "strip" | "passthrough";
+
 export type BeffUserSettings = {
   customFormats: BeffCustomFormat[];
+  customNumberFormats: BeffCustomFormat[];
+  unknownKeys: UnknownKeys;
+};
+export type BeffExamplesSettings = {
+  seed: number;
+  maxDepth: number;
 };
+
 export type ProjectJson = {
-  parser?: string;
+  parser?: string[];
   schema?: string;
+  routes?: string;
+  examples?: BeffExamplesSettings;
   outputDir: string;
   module: ProjectModule | undefined;
   settings: BeffUserSettings;
@@ -15,8 +26,33 @@ export type ProjectJson = {
 
 export type ProjectModule = "cjs" | "esm";
 
+export const parseParserEntryPoints = (parser: any): string[] | undefined => {
+  if (parser == null) {
+    return undefined;
+  }
+  if (Array.isArray(parser)) {
+    return parser.map(String);
+  }
+  return [String(parser)];
+};
+
+export const parseExamplesSettings = (examples: any): BeffExamplesSettings | undefined => {
+  if (examples == null || examples === false) {
+    return undefined;
+  }
+  return {
+    seed: Number(examples.seed ?? 0),
+    maxDepth: Number(examples.maxDepth ?? 3),
+  };
+};
+
+export const isUnknownKeys = (it: any): it is UnknownKeys =>
+  it === "strict" || it === "strip" || it === "passthrough";
+
 const EMPTY_SETTINGS: BeffUserSettings = {
   customFormats: [],
+  customNumberFormats: [],
+  unknownKeys: "strip",
 };
 export const parseUserSettings = (settings: any): BeffUserSettings => {
   if (settings == null) {
@@ -24,5 +60,7 @@ export const parseUserSettings = (settings: any): BeffUserSettings => {
   }
   return {
     customFormats: settings.customFormats ?? [],
+    customNumberFormats: settings.customNumberFormats ?? [],
+    unknownKeys: settings.unknownKeys ?? "strip",
   };
 };